@@ -1,20 +1,43 @@
-use std::io::Result;
+use std::{io::Result, path::PathBuf, rc::Rc};
+
+use crossterm::event::KeyEvent;
 
 use ratatui::{
     backend::Backend,
     buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     text::{Line, Span},
-    widgets::Widget,
+    widgets::{StatefulWidget, Widget},
     Terminal,
 };
 
 use crate::{
-    event_handler::key_board_handler,
-    theme::THEME,
+    backend::{export::ExportKind, key::Key, Gpg},
+    config::Config,
+    event_handler::{key_board_handler, keymap::Keymap},
+    theme::{set_theme, theme, Theme},
     ui::{
+        dialog::{
+            about::AboutDialog, add_subkey::AddSubkeyForm, add_user_id::AddUserIdForm,
+            backend_process::BackendProcessDialog, change_expiry::ChangeExpiryDialog,
+            check_update::CheckUpdateDialog, decrypt_verify::DecryptVerifyDialog,
+            delete_key::DeleteKeyDialog, encrypt_sign::EncryptSignDialog, export::ExportDialog,
+            general_settings::GeneralSettingsDialog, import::ImportDialog,
+            new_key_pair::NewKeyPairDialog, primary_user_id::PrimaryUserIdForm,
+            refresh_cert::RefreshCertDialog, revoke_subkey::RevokeSubkeyForm,
+            revoke_user_id::RevokeUserIdForm, search_on_server::SearchOnServerDialog,
+            server_settings::ServerSettingsDialog, theme_settings::ThemeSettingsDialog, Dialog,
+            DialogResult, KeyEditDialog,
+        },
         focus,
-        menu_bar::{self, init_menu_bar_state, render_menu_bar},
+        key_detail::{
+            self, init_key_detail_state, render_key_detail, DetailAction, DetailItem,
+            KeyDetailUiState,
+        },
+        key_list::{self, init_key_list_state, render_key_list},
+        menu_bar::{
+            self, init_menu_bar_state, menu_tree, render_menu_bar, Action, MenuContext, MenuNode,
+        },
         UiState,
     },
 };
@@ -22,18 +45,127 @@ use crate::{
 pub struct App {
     running_state: AppState,
     ui_state: UiState,
+    gpg: Gpg,
+    keys: Vec<Key>,
+    dialog: Option<Dialog>,
+    /// Where the focus goes back to when the dialog is closed
+    dialog_parent: focus::FocusOn,
+    /// Tabs and items of the menu bar
+    menu: Vec<MenuNode>,
+    /// Key bindings of the main panel, the key detail and the menus
+    keymap: Keymap,
+    config: Config,
+    /// Where `config` is saved, `None` if no config directory is known
+    config_path: Option<PathBuf>,
+    /// Shown in the hint bar until the next key press, e.g. config warnings
+    notice: Option<String>,
 }
 
 impl Default for App {
     fn default() -> Self {
-        Self {
+        Self::new(Config::default(), None)
+    }
+}
+
+impl App {
+    pub fn new(config: Config, config_path: Option<PathBuf>) -> Self {
+        let menu = menu_tree();
+        let keymap_path = config_path.as_deref().map(Keymap::path);
+        let (keymap, warnings) = Keymap::load(&config.keymap, keymap_path.as_deref());
+        let mut app = Self {
             running_state: Default::default(),
             // Initialize the UiState
             ui_state: UiState {
                 focus_on: focus::FocusOn::MainPanel,
-                menu_bar_state: init_menu_bar_state(),
+                menu_bar_state: init_menu_bar_state(&menu),
+                key_list_state: init_key_list_state(),
+                key_detail_state: init_key_detail_state(),
+                dialog_buttons: Rc::default(),
+                date_format: config.date_format.clone(),
+                expiry_warning_days: config.expiry_warning_days,
             },
+            gpg: config.gpg(),
+            keys: Vec::new(),
+            dialog: None,
+            dialog_parent: focus::FocusOn::MainPanel,
+            menu,
+            keymap,
+            config,
+            config_path,
+            notice: None,
+        };
+        if !warnings.is_empty() {
+            app.notice = Some(format!("keymap.toml: {}", warnings.join("; ")));
         }
+        app.load_theme();
+        app.update_menu();
+        app.reload_keys(None);
+        app
+    }
+
+    /// Show problems found while loading the config
+    pub fn show_config_warnings(&mut self, warnings: &[String]) {
+        if !warnings.is_empty() {
+            let notice = format!("config.toml: {}", warnings.join("; "));
+            self.notice = Some(match self.notice.take() {
+                Some(other_notice) => format!("{notice}; {other_notice}"),
+                None => notice,
+            });
+        }
+    }
+
+    /// Draw with the theme of the config, a bad theme file is shown as a notice
+    fn load_theme(&mut self) {
+        let dir = self.config_path.as_deref().map(Theme::dir);
+        let (theme, warnings) = Theme::load(&self.config.theme, dir.as_deref());
+        set_theme(theme);
+        if !warnings.is_empty() {
+            let notice = format!("theme {}: {}", self.config.theme, warnings.join("; "));
+            self.notice = Some(match self.notice.take() {
+                Some(other_notice) => format!("{other_notice}; {notice}"),
+                None => notice,
+            });
+        }
+    }
+
+    /// Use the settings saved in the General or Theme settings
+    fn apply_config(&mut self, config: Config) {
+        let selected = self.selected_key().map(|key| key.fingerprint().to_string());
+        self.gpg = config.gpg();
+        self.ui_state.date_format = config.date_format.clone();
+        self.ui_state.expiry_warning_days = config.expiry_warning_days;
+        if config.keymap != self.config.keymap {
+            let keymap_path = self.config_path.as_deref().map(Keymap::path);
+            let (keymap, warnings) = Keymap::load(&config.keymap, keymap_path.as_deref());
+            self.keymap = keymap;
+            if !warnings.is_empty() {
+                self.notice = Some(format!("keymap.toml: {}", warnings.join("; ")));
+            }
+        }
+        let theme_changed = config.theme != self.config.theme;
+        self.config = config;
+        if theme_changed {
+            self.load_theme();
+        }
+        self.update_menu();
+        self.reload_keys(selected.as_deref());
+    }
+
+    /// What the enabled predicates of the menu items look at
+    fn menu_context(&self) -> MenuContext<'_> {
+        MenuContext {
+            config: &self.config,
+            selected_key: self.selected_key(),
+        }
+    }
+
+    /// Enable the menu items which depend on the config or the selected key
+    fn update_menu(&self) {
+        menu_bar::update_enabled(
+            &self.ui_state.menu_bar_state,
+            &self.menu,
+            &self.menu_context(),
+        );
     }
 }
 
@@ -49,6 +181,7 @@ impl App {
         while self.running_state == AppState::Running {
             self.draw(terminal)?;
             self.handle_events();
+            self.tick();
         }
         Ok(())
     }
@@ -58,10 +191,291 @@ impl App {
         Ok(())
     }
 
+    /// Re-read the keyring, then select `fingerprint` if given
+    fn reload_keys(&mut self, fingerprint: Option<&str>) {
+        match self.gpg.list_keys() {
+            Ok(keys) => {
+                self.keys = keys;
+                self.ui_state.key_list_state.borrow_mut().message = None;
+            }
+            Err(err) => {
+                self.keys.clear();
+                self.ui_state.key_list_state.borrow_mut().message =
+                    Some(format!("Failed to list keys: {err}"));
+            }
+        }
+        key_list::select_key(&self.ui_state.key_list_state, &self.keys, fingerprint);
+        if self.dialog_parent == focus::FocusOn::KeyDetail {
+            self.reload_key_detail();
+        }
+    }
+
+    /// Load the key shown in the detail view again, keeping the selected item
+    fn reload_key_detail(&mut self) {
+        let mut key_detail_state = self.ui_state.key_detail_state.borrow_mut();
+        let Some(fingerprint) = key_detail_state
+            .key
+            .as_ref()
+            .map(|key| key.fingerprint().to_string())
+        else {
+            return;
+        };
+        match self.gpg.key_details(&fingerprint) {
+            Ok(key) => key_detail_state.key = Some(key),
+            Err(err) => {
+                key_detail_state.key = None;
+                key_detail_state.message = Some(format!("Failed to load key: {err}"));
+            }
+        }
+        let last = key_detail_state.items().len().saturating_sub(1);
+        key_detail_state.selected = key_detail_state.selected.min(last);
+    }
+
+    /// Show `dialog` on top of the main panel
+    fn open_dialog(&mut self, dialog: Dialog) {
+        self.dialog = Some(dialog);
+        self.dialog_parent = match self.ui_state.focus_on {
+            focus::FocusOn::KeyDetail => focus::FocusOn::KeyDetail,
+            _ => focus::FocusOn::MainPanel,
+        };
+        self.ui_state.focus_on = focus::FocusOn::Dialog;
+    }
+
+    fn close_dialog(&mut self) {
+        self.dialog = None;
+        self.ui_state.focus_on = self.dialog_parent;
+    }
+
+    fn apply_dialog_result(&mut self, result: DialogResult) {
+        match result {
+            DialogResult::Continue => (),
+            DialogResult::Close => self.close_dialog(),
+            DialogResult::Reload(fingerprint) => self.reload_keys(fingerprint.as_deref()),
+            DialogResult::CloseAndReload(fingerprint) => {
+                self.close_dialog();
+                self.reload_keys(fingerprint.as_deref());
+            }
+            DialogResult::ConfigChanged(config) => self.apply_config(config),
+        }
+    }
+
+    fn handle_dialog_key(&mut self, key: KeyEvent) {
+        if let Some(dialog) = self.dialog.as_mut() {
+            let result = dialog.handle_key(key, &self.gpg);
+            self.apply_dialog_result(result);
+        }
+    }
+
+    fn handle_dialog_paste(&mut self, text: &str) {
+        if let Some(dialog) = self.dialog.as_mut() {
+            let result = dialog.handle_paste(text);
+            self.apply_dialog_result(result);
+        }
+    }
+
+    /// Open the import dialog for data piped to the program at startup
+    pub fn import_stdin(&mut self, data: Vec<u8>) {
+        self.open_dialog(Dialog::new(ImportDialog::from_stdin(data)));
+    }
+
+    /// Poll background work of the open dialog
+    fn tick(&mut self) {
+        if let Some(dialog) = self.dialog.as_mut() {
+            let result = dialog.tick();
+            self.apply_dialog_result(result);
+        }
+    }
+
+    /// Execute the action of a chosen menu item
+    fn handle_menu_action(&mut self, action: Action) {
+        match action {
+            // File > New KeyPair
+            Action::NewKeyPair => self.open_dialog(Dialog::new(NewKeyPairDialog::default())),
+            // File > Import
+            Action::Import => self.open_dialog(Dialog::new(ImportDialog::default())),
+            // File > Search On Server
+            Action::SearchOnServer => self.open_dialog(Dialog::new(SearchOnServerDialog::new(
+                self.config.default_keyserver(),
+            ))),
+            // File > Decrypt/Verify
+            Action::DecryptVerify => self.open_dialog(Dialog::new(DecryptVerifyDialog::default())),
+            // File > Encrypt/Sign
+            Action::EncryptSign => self.open_dialog(Dialog::new(EncryptSignDialog::new(
+                self.keys.clone(),
+                self.config.armor,
+                self.config.default_signing_key.as_deref(),
+            ))),
+            // File > Quit
+            Action::Quit => self.running_state = AppState::Quitting,
+            // Tool > Refresh OpenPGP Cert
+            Action::RefreshCert => self.open_dialog(Dialog::new(RefreshCertDialog::new(
+                self.keys.clone(),
+                self.selected_key().cloned(),
+                self.config.default_keyserver(),
+            ))),
+            // Tool > Restart Backend Process
+            Action::BackendProcess => {
+                self.open_dialog(Dialog::new(BackendProcessDialog::new(&self.gpg)))
+            }
+            // Setting > General
+            Action::GeneralSettings => self.open_dialog(Dialog::new(GeneralSettingsDialog::new(
+                self.config.clone(),
+                self.config_path.clone(),
+                self.keys.clone(),
+            ))),
+            // Setting > Server
+            Action::ServerSettings => self.open_dialog(Dialog::new(ServerSettingsDialog::new(
+                &self.gpg,
+                self.config.clone(),
+                self.config_path.clone(),
+            ))),
+            // Setting > Theme
+            Action::ThemeSettings => self.open_dialog(Dialog::new(ThemeSettingsDialog::new(
+                self.config.clone(),
+                self.config_path.clone(),
+            ))),
+            // Help > Check Update
+            Action::CheckUpdate => {
+                self.open_dialog(Dialog::new(CheckUpdateDialog::new(&self.gpg, &self.config)))
+            }
+            // Help > About
+            Action::About => self.open_dialog(Dialog::new(AboutDialog::new(
+                &self.gpg,
+                self.config_path.as_deref(),
+            ))),
+            // File > Export > Public Key / Secret Key
+            Action::ExportPublic | Action::ExportSecret => {
+                let kind = match action {
+                    Action::ExportSecret => ExportKind::Secret,
+                    _ => ExportKind::Public,
+                };
+                self.open_dialog(Dialog::new(ExportDialog::new(
+                    self.keys.clone(),
+                    self.selected_key().cloned(),
+                    kind,
+                    self.config.armor,
+                )))
+            }
+            // File > Delete Key, only ever deletes after the dialog was confirmed
+            Action::DeleteKey => {
+                if let Some(key) = self.selected_key().cloned() {
+                    self.open_dialog(Dialog::new(DeleteKeyDialog::new(
+                        key,
+                        self.config.confirm_delete,
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Execute an action bound in the key detail view on the key shown there
+    fn handle_detail_action(&mut self, action: DetailAction) {
+        let key_detail_state = self.ui_state.key_detail_state.borrow();
+        let Some(key) = key_detail_state.key.clone() else {
+            return;
+        };
+        let selected = key_detail_state.selected_item();
+        drop(key_detail_state);
+        if !key.has_secret() {
+            self.notice = Some("Only keys with a secret key can be changed".to_string());
+            return;
+        }
+        match action {
+            DetailAction::Expire => {
+                self.open_dialog(Dialog::new(ChangeExpiryDialog::new(key, selected)))
+            }
+            DetailAction::AddSubkey => self.open_dialog(Dialog::new(KeyEditDialog::new(
+                AddSubkeyForm::new(key, selected),
+            ))),
+            DetailAction::AddUserId => {
+                self.open_dialog(Dialog::new(KeyEditDialog::new(AddUserIdForm::new(key))))
+            }
+            DetailAction::Revoke => match selected {
+                Some(DetailItem::UserId(index)) => self.open_dialog(Dialog::new(
+                    KeyEditDialog::new(RevokeUserIdForm::new(key, index)),
+                )),
+                Some(DetailItem::Subkey(index)) => self.open_dialog(Dialog::new(
+                    KeyEditDialog::new(RevokeSubkeyForm::new(key, index)),
+                )),
+                _ => self.notice = Some("Select a user ID or subkey to revoke".to_string()),
+            },
+            DetailAction::PrimaryUserId => match selected {
+                Some(DetailItem::UserId(index)) => self.open_dialog(Dialog::new(
+                    KeyEditDialog::new(PrimaryUserIdForm::new(key, index)),
+                )),
+                _ => self.notice = Some("Select the user ID to make primary".to_string()),
+            },
+        }
+    }
+
+    fn selected_key(&self) -> Option<&Key> {
+        let selected = self
+            .ui_state
+            .key_list_state
+            .borrow()
+            .table_state
+            .selected()?;
+        self.keys.get(selected)
+    }
+
+    /// Load the selected key including its signatures and show it
+    fn open_key_detail(&mut self) {
+        let Some(fingerprint) = self.selected_key().map(|key| key.fingerprint().to_string()) else {
+            return;
+        };
+        let mut key_detail_state = KeyDetailUiState::default();
+        match self.gpg.key_details(&fingerprint) {
+            Ok(key) => key_detail_state.key = Some(key),
+            Err(err) => key_detail_state.message = Some(format!("Failed to load key: {err}")),
+        }
+        *self.ui_state.key_detail_state.borrow_mut() = key_detail_state;
+        self.ui_state.focus_on = focus::FocusOn::KeyDetail;
+    }
+
+    /// Pass a click to the dialog, the menu or the key list, whatever is there
+    fn handle_click(&mut self, position: Position) {
+        // A click ends the search of the key list where it is
+        self.ui_state.key_list_state.borrow_mut().find = None;
+        match self.ui_state.focus_on {
+            focus::FocusOn::Dialog => {
+                if let Some(dialog) = self.dialog.as_mut() {
+                    let button_areas = self.ui_state.dialog_buttons.borrow().clone();
+                    let result = dialog.handle_click(position, &button_areas, &self.gpg);
+                    self.apply_dialog_result(result);
+                }
+            }
+            // A click beside an open menu closes it
+            focus_on
+                if matches!(focus_on, focus::FocusOn::MenuBar(_))
+                    || menu_bar::is_hit(focus_on, &self.ui_state.menu_bar_state, position) =>
+            {
+                self.update_menu();
+                let action = menu_bar::handle_event(
+                    &mut self.ui_state.focus_on,
+                    self.ui_state.menu_bar_state.clone(),
+                    &self.menu,
+                    key_board_handler::EventResult::MouseClick(position),
+                );
+                if let Some(action) = action {
+                    self.handle_menu_action(action);
+                }
+            }
+            focus::FocusOn::MainPanel => {
+                if key_list::handle_click(&self.ui_state.key_list_state, self.keys.len(), position)
+                {
+                    self.open_key_detail();
+                }
+            }
+            focus::FocusOn::KeyDetail | focus::FocusOn::MenuBar(_) => (),
+        }
+    }
+
     fn handle_events(&mut self) {
-        let result = key_board_handler::handle_events(&self.ui_state);
-        if result.is_ok() {
-            let result = result.unwrap();
+        let result = key_board_handler::handle_events(&self.ui_state, &mut self.keymap);
+        if let Ok(result) = result {
+            if result.0 {
+                self.notice = None;
+            }
             match result {
                 (true, key_board_handler::EventResult::Quit) => {
                     self.running_state = AppState::Quitting;
@@ -71,12 +485,69 @@ impl App {
                 | (true, key_board_handler::EventResult::MenuPrevItem)
                 | (true, key_board_handler::EventResult::MenuNextItem)
                 | (true, key_board_handler::EventResult::MenuChoose)
+                | (true, key_board_handler::EventResult::NextMenuLevel)
+                | (true, key_board_handler::EventResult::MenuAccelerator(_))
+                | (true, key_board_handler::EventResult::MenuHotkey(_))
                 | (true, key_board_handler::EventResult::PrevMenuLevel) => {
-                    menu_bar::handle_event(
+                    self.update_menu();
+                    let action = menu_bar::handle_event(
                         &mut self.ui_state.focus_on,
                         self.ui_state.menu_bar_state.clone(),
+                        &self.menu,
                         result.1,
                     );
+                    if let Some(action) = action {
+                        self.handle_menu_action(action);
+                    }
+                }
+                (true, key_board_handler::EventResult::KeyListPrevItem)
+                | (true, key_board_handler::EventResult::KeyListNextItem)
+                | (true, key_board_handler::EventResult::KeyListPrevPage)
+                | (true, key_board_handler::EventResult::KeyListNextPage)
+                | (true, key_board_handler::EventResult::KeyListFirstItem)
+                | (true, key_board_handler::EventResult::KeyListLastItem) => {
+                    key_list::handle_event(
+                        self.ui_state.key_list_state.clone(),
+                        self.keys.len(),
+                        result.1,
+                    );
+                }
+                (true, key_board_handler::EventResult::KeyListFind) => {
+                    key_list::start_find(&self.ui_state.key_list_state)
+                }
+                (true, key_board_handler::EventResult::KeyListFindKey(key)) => {
+                    key_list::handle_find_key(&self.ui_state.key_list_state, &self.keys, key)
+                }
+                (true, key_board_handler::EventResult::OpenKeyDetail) => self.open_key_detail(),
+                (true, key_board_handler::EventResult::MenuAction(action))
+                    if menu_bar::is_enabled(&self.menu, action, &self.menu_context()) =>
+                {
+                    self.handle_menu_action(action)
+                }
+                (true, key_board_handler::EventResult::MouseClick(position)) => {
+                    self.handle_click(position)
+                }
+                (true, key_board_handler::EventResult::DialogKey(key)) => {
+                    self.handle_dialog_key(key)
+                }
+                (true, key_board_handler::EventResult::DialogPaste(ref text)) => {
+                    self.handle_dialog_paste(text)
+                }
+                (true, key_board_handler::EventResult::CloseKeyDetail) => {
+                    self.ui_state.focus_on = focus::FocusOn::MainPanel;
+                }
+                (true, key_board_handler::EventResult::KeyDetailPrevItem)
+                | (true, key_board_handler::EventResult::KeyDetailNextItem)
+                | (true, key_board_handler::EventResult::KeyDetailFirstItem)
+                | (true, key_board_handler::EventResult::KeyDetailLastItem)
+                | (true, key_board_handler::EventResult::KeyDetailPrevPage)
+                | (true, key_board_handler::EventResult::KeyDetailNextPage)
+                | (true, key_board_handler::EventResult::KeyDetailScrollUp)
+                | (true, key_board_handler::EventResult::KeyDetailScrollDown) => {
+                    key_detail::handle_event(self.ui_state.key_detail_state.clone(), result.1);
+                }
+                (true, key_board_handler::EventResult::KeyDetailAction(action)) => {
+                    self.handle_detail_action(action)
                 }
                 _ => (),
             }
@@ -101,48 +572,79 @@ impl Widget for &App {
             ],
         )
         .split(area);
-        render_menu_bar(&self.ui_state, main_layout[0], buf);
-        render_bottom_bar(&self.ui_state, main_layout[2], buf);
+        // Dialogs and menus opened from the detail view stay on top of it
+        let shows_detail = match self.ui_state.focus_on {
+            focus::FocusOn::KeyDetail => true,
+            focus::FocusOn::MenuBar(_) => {
+                self.ui_state.menu_bar_state.borrow().return_focus == focus::FocusOn::KeyDetail
+            }
+            focus::FocusOn::Dialog => self.dialog_parent == focus::FocusOn::KeyDetail,
+            focus::FocusOn::MainPanel => false,
+        };
+        if shows_detail {
+            render_key_detail(&self.ui_state, main_layout[1], buf);
+        } else {
+            render_key_list(&self.ui_state, &self.keys, main_layout[1], buf);
+        }
+        if let Some(dialog) = &self.dialog {
+            StatefulWidget::render(
+                dialog,
+                main_layout[1],
+                buf,
+                &mut self.ui_state.dialog_buttons.borrow_mut(),
+            );
+        }
+        render_bottom_bar(
+            &self.ui_state,
+            &self.keymap,
+            self.dialog.as_ref(),
+            self.notice.as_deref(),
+            main_layout[2],
+            buf,
+        );
+        // The menu bar goes last, its drop downs overlap the other areas
+        render_menu_bar(&self.ui_state, &self.menu, main_layout[0], buf);
     }
 }
 
-fn render_bottom_bar(ui_state: &UiState, area: Rect, buf: &mut Buffer) {
-    let keys: Vec<(&str, &str)> = match ui_state.focus_on {
-        focus::FocusOn::MainPanel => [
-            ("Q", "Quit"),
-            ("Tab", "Menu"),
-            ("Up/Down", "Select"),
-            ("Enter", "Choose"),
-        ]
-        .to_vec(),
-        focus::FocusOn::MenuBar(focus::Menu::Tab) => [
-            ("L/R", "Select"),
-            ("Enter/Down", "Choose"),
-            ("Tab/Esc", "Quit Menu"),
-        ]
-        .to_vec(),
-        focus::FocusOn::MenuBar(focus::Menu::TabItem(0)) => [
-            ("Up/Down", "Select"),
-            ("Enter", "Choose"),
-            ("Esc", "Back"),
-            ("Tab", "Quit Menu"),
-        ]
-        .to_vec(),
-        _ => vec![],
+fn render_bottom_bar(
+    ui_state: &UiState,
+    keymap: &Keymap,
+    dialog: Option<&Dialog>,
+    notice: Option<&str>,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let keys: Vec<(String, &str)> = match ui_state.focus_on {
+        focus::FocusOn::Dialog => dialog
+            .map(Dialog::hints)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, description)| (key.to_string(), description))
+            .collect(),
+        focus::FocusOn::MainPanel if ui_state.key_list_state.borrow().find.is_some() => {
+            key_list::FIND_HINTS
+                .iter()
+                .map(|(key, description)| (key.to_string(), *description))
+                .collect()
+        }
+        focus_on => keymap.hints(focus_on),
     };
 
-    let mut spans: Vec<Span> = keys
-        .iter()
-        .flat_map(|(key, desc)| {
-            let key = Span::styled(format!(" {key} "), THEME.hint_bar.key);
-            let desc = Span::styled(format!(" {desc}  "), THEME.hint_bar.description);
-            [key, desc]
-        })
-        .collect();
-    spans.insert(0, Span::styled("  ", THEME.hint_bar.default_style));
+    let mut spans = vec![Span::styled("  ", theme().hint_bar.default_style)];
+    // The notice goes first, a narrow terminal cuts off the end of the line
+    if let Some(notice) = notice {
+        spans.push(Span::styled(format!(" {notice} "), theme().hint_bar.key));
+        spans.push(Span::styled("  ", theme().hint_bar.default_style));
+    }
+    spans.extend(keys.iter().flat_map(|(key, desc)| {
+        let key = Span::styled(format!(" {key} "), theme().hint_bar.key);
+        let desc = Span::styled(format!(" {desc}  "), theme().hint_bar.description);
+        [key, desc]
+    }));
 
     Line::from(spans)
         .left_aligned()
-        .style(THEME.hint_bar.default_style)
+        .style(theme().hint_bar.default_style)
         .render(area, buf);
 }
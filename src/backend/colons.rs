@@ -0,0 +1,252 @@
+//! Parser for the `--with-colons` output of GnuPG
+//!
+//! See `doc/DETAILS` in the GnuPG source tree for the format.
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+use super::key::{
+    Algorithm, Capabilities, Key, Signature, SignatureCheck, Subkey, UserId, Validity,
+};
+
+/// The record the following `sig`/`rev` records refer to
+enum Parent {
+    Key,
+    UserId,
+}
+
+/// Parse a `--list-keys` or `--list-secret-keys` colon listing
+///
+/// Records which are not understood (`tru`, `cfg`, ...) are skipped, as are
+/// records which appear before the first `pub`/`sec` record.
+pub fn parse_keys(output: &str) -> Vec<Key> {
+    let mut keys: Vec<Key> = Vec::new();
+    let mut parent = Parent::Key;
+
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields[0] {
+            "pub" | "sec" => {
+                let primary = parse_subkey(&fields);
+                keys.push(Key {
+                    primary,
+                    owner_trust: Validity::from_field(field(&fields, 9)),
+                    capabilities: Capabilities::from_field(
+                        &field(&fields, 12)
+                            .chars()
+                            .filter(char::is_ascii_uppercase)
+                            .collect::<String>(),
+                    ),
+                    user_ids: Vec::new(),
+                    subkeys: Vec::new(),
+                });
+                parent = Parent::Key;
+            }
+            "sub" | "ssb" => {
+                if let Some(key) = keys.last_mut() {
+                    key.subkeys.push(parse_subkey(&fields));
+                }
+                parent = Parent::Key;
+            }
+            "uid" | "uat" => {
+                if let Some(key) = keys.last_mut() {
+                    key.user_ids.push(parse_user_id(&fields));
+                }
+                parent = Parent::UserId;
+            }
+            "sig" | "rev" => {
+                let Some(key) = keys.last_mut() else {
+                    continue;
+                };
+                let signature = parse_signature(&fields);
+                match parent {
+                    Parent::Key => last_subkey(key).signatures.push(signature),
+                    Parent::UserId => {
+                        if let Some(uid) = key.user_ids.last_mut() {
+                            uid.signatures.push(signature);
+                        }
+                    }
+                }
+            }
+            "fpr" => {
+                if let Some(subkey) = keys.last_mut().map(last_subkey) {
+                    subkey.fingerprint = field(&fields, 10).to_string();
+                }
+            }
+            "grp" => {
+                if let Some(subkey) = keys.last_mut().map(last_subkey) {
+                    subkey.keygrip = Some(field(&fields, 10).to_string());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    keys
+}
+
+/// Mark keys of `public` whose secret counterpart is listed in `secret`
+pub fn merge_secret(public: &mut [Key], secret: &[Key]) {
+    for key in public.iter_mut() {
+        let Some(secret_key) = secret
+            .iter()
+            .find(|secret_key| secret_key.fingerprint() == key.fingerprint())
+        else {
+            continue;
+        };
+        copy_secret(&mut key.primary, &secret_key.primary);
+        for subkey in key.subkeys.iter_mut() {
+            if let Some(secret_sub) = secret_key
+                .subkeys
+                .iter()
+                .find(|secret_sub| secret_sub.fingerprint == subkey.fingerprint)
+            {
+                copy_secret(subkey, secret_sub);
+            }
+        }
+    }
+}
+
+fn copy_secret(public: &mut Subkey, secret: &Subkey) {
+    public.has_secret = secret.has_secret;
+    public.is_stub = secret.is_stub;
+    public.card_serial = secret.card_serial.clone();
+}
+
+/// Decode the C-style `\xHH` escapes GnuPG uses in user ID fields
+pub fn unescape(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let raw = field.as_bytes();
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'\\' && i + 3 < raw.len() && raw[i + 1] == b'x' {
+            let hex = std::str::from_utf8(&raw[i + 2..i + 4]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                bytes.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        bytes.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parse a date field, which is either seconds since epoch or ISO 8601
+pub fn parse_date(field: &str) -> Option<DateTime<Utc>> {
+    if field.is_empty() {
+        return None;
+    }
+    if let Ok(seconds) = field.parse::<i64>() {
+        return if seconds == 0 {
+            None
+        } else {
+            Utc.timestamp_opt(seconds, 0).single()
+        };
+    }
+    NaiveDateTime::parse_from_str(field, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+/// Get the 1-based field `index` of a record, or an empty string
+fn field<'a>(fields: &[&'a str], index: usize) -> &'a str {
+    fields.get(index - 1).copied().unwrap_or("")
+}
+
+/// The subkey the following `fpr`/`grp` records refer to
+fn last_subkey(key: &mut Key) -> &mut Subkey {
+    if key.subkeys.is_empty() {
+        &mut key.primary
+    } else {
+        key.subkeys.last_mut().unwrap()
+    }
+}
+
+fn parse_subkey(fields: &[&str]) -> Subkey {
+    let curve = field(fields, 17);
+    // Field 15 is only filled in secret key listings: `+` for a secret key in
+    // the keyring, `#` for a stub and the serial number for a smartcard
+    let is_secret = matches!(fields[0], "sec" | "ssb");
+    let token = field(fields, 15);
+    Subkey {
+        fingerprint: String::new(),
+        key_id: field(fields, 5).to_string(),
+        validity: Validity::from_field(field(fields, 2)),
+        algorithm: Algorithm::from_id(field(fields, 4).parse().unwrap_or(0)),
+        length: field(fields, 3).parse().unwrap_or(0),
+        curve: if curve.is_empty() {
+            None
+        } else {
+            Some(curve.to_string())
+        },
+        capabilities: Capabilities::from_field(
+            &field(fields, 12)
+                .chars()
+                .filter(char::is_ascii_lowercase)
+                .collect::<String>(),
+        ),
+        created: parse_date(field(fields, 6)),
+        expires: parse_date(field(fields, 7)),
+        keygrip: None,
+        has_secret: is_secret && !token.starts_with('#'),
+        is_stub: is_secret && token.starts_with('#'),
+        card_serial: if is_secret && !matches!(token, "" | "+" | "#") {
+            Some(token.to_string())
+        } else {
+            None
+        },
+        signatures: Vec::new(),
+    }
+}
+
+fn parse_user_id(fields: &[&str]) -> UserId {
+    let is_attribute = fields[0] == "uat";
+    let uid = if is_attribute {
+        // `uat` records carry "<count> <size>" instead of a user ID string
+        let size = field(fields, 10).split(' ').nth(1).unwrap_or("0");
+        format!("[user attribute of size {size}]")
+    } else {
+        unescape(field(fields, 10))
+    };
+    UserId {
+        validity: Validity::from_field(field(fields, 2)),
+        uid,
+        hash: field(fields, 8).to_string(),
+        created: parse_date(field(fields, 6)),
+        expires: parse_date(field(fields, 7)),
+        is_attribute,
+        signatures: Vec::new(),
+    }
+}
+
+fn parse_signature(fields: &[&str]) -> Signature {
+    // Field 11 looks like `13x` or, for revocations, `30x,20`
+    let (class, reason) = match field(fields, 11).split_once(',') {
+        Some((class, reason)) => (class, u8::from_str_radix(reason, 16).ok()),
+        None => (field(fields, 11), None),
+    };
+    let issuer_fingerprint = field(fields, 13);
+    let comment = field(fields, 21);
+    Signature {
+        is_revocation: fields[0] == "rev",
+        check: SignatureCheck::from_field(field(fields, 2)),
+        key_id: field(fields, 5).to_string(),
+        issuer_fingerprint: if issuer_fingerprint.is_empty() {
+            None
+        } else {
+            Some(issuer_fingerprint.to_string())
+        },
+        signer: unescape(field(fields, 10)),
+        created: parse_date(field(fields, 6)),
+        expires: parse_date(field(fields, 7)),
+        class: u8::from_str_radix(class.get(..2).unwrap_or(""), 16).unwrap_or(0),
+        exportable: !class.ends_with('l'),
+        revocation_reason: reason,
+        revocation_comment: if comment.is_empty() {
+            None
+        } else {
+            Some(unescape(comment))
+        },
+    }
+}
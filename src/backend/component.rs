@@ -0,0 +1,213 @@
+use std::{
+    io::{Error, Result},
+    path::PathBuf,
+    process::Command,
+};
+
+use super::{check_output, keyserver::url_decode, Gpg};
+
+/// Whether a GnuPG component is currently running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentState {
+    Running,
+    Stopped,
+    NotDaemon, // A command line tool like gpg itself, started per use
+    Unknown,   // The sockets can not be probed on this platform
+}
+
+impl ComponentState {
+    fn from_running(is_running: bool) -> Self {
+        if is_running {
+            ComponentState::Running
+        } else {
+            ComponentState::Stopped
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComponentState::Running => "running",
+            ComponentState::Stopped => "stopped",
+            ComponentState::NotDaemon => "-",
+            ComponentState::Unknown => "unknown",
+        }
+    }
+}
+
+/// A component listed by `gpgconf --list-components`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub description: String,
+    pub program: PathBuf,
+    pub state: ComponentState,
+    /// Sockets the daemon listens on, as `(name, path)`
+    pub sockets: Vec<(String, PathBuf)>,
+}
+
+impl Component {
+    /// Whether the component is a daemon which can be killed and launched
+    pub fn is_daemon(&self) -> bool {
+        self.state != ComponentState::NotDaemon
+    }
+}
+
+/// The `--list-dirs` entries holding the sockets of each daemon
+fn socket_names(component: &str) -> &'static [&'static str] {
+    match component {
+        "gpg-agent" => &[
+            "agent-socket",
+            "agent-extra-socket",
+            "agent-browser-socket",
+            "agent-ssh-socket",
+        ],
+        "dirmngr" => &["dirmngr-socket"],
+        "keyboxd" => &["keyboxd-socket"],
+        _ => &[],
+    }
+}
+
+/// Whether a daemon listens on the `--list-dirs` entry `socket`
+#[cfg(unix)]
+fn socket_state(dirs: &[(String, String)], socket: &str) -> ComponentState {
+    ComponentState::from_running(
+        dirs.iter()
+            .find(|(dir, _)| dir == socket)
+            .is_some_and(|(_, path)| std::os::unix::net::UnixStream::connect(path).is_ok()),
+    )
+}
+
+/// Sockets are emulated with files elsewhere, which tell nothing without a handshake
+#[cfg(not(unix))]
+fn socket_state(_dirs: &[(String, String)], _socket: &str) -> ComponentState {
+    ComponentState::Unknown
+}
+
+/// Daemons without a socket of their own, which gpg-agent starts on demand
+const AGENT_CHILDREN: [&str; 2] = ["scdaemon", "tpm2daemon"];
+
+/// Parse `name:value` lines of `gpgconf --list-dirs` or `--list-components`
+pub fn parse_gpgconf_list(output: &str) -> Vec<Vec<String>> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split(':').map(url_decode).collect())
+        .collect()
+}
+
+impl Gpg {
+    /// The `gpgconf` next to the `gpg` binary, with the home directory applied
+    pub fn gpgconf(&self) -> Command {
+        self.sibling_command("gpgconf")
+    }
+
+    fn sibling_command(&self, name: &str) -> Command {
+        let program = match self.program.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.join(name),
+            _ => PathBuf::from(name),
+        };
+        let mut command = Command::new(program);
+        if let Some(homedir) = &self.homedir {
+            command.arg("--homedir").arg(homedir);
+        }
+        command
+    }
+
+    /// Directories and sockets from `gpgconf --list-dirs`, as `(name, value)`
+    pub fn list_dirs(&self) -> Result<Vec<(String, String)>> {
+        let output = self.gpgconf().arg("--list-dirs").output()?;
+        check_output(&output)?;
+        Ok(parse_gpgconf_list(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter_map(|fields| Some((fields.first()?.clone(), fields.get(1)?.clone())))
+            .collect())
+    }
+
+    /// All components with their running state and sockets
+    pub fn list_components(&self) -> Result<Vec<Component>> {
+        let output = self.gpgconf().arg("--list-components").output()?;
+        check_output(&output)?;
+        let dirs = self.list_dirs()?;
+        let agent_state = socket_state(&dirs, "agent-socket");
+
+        Ok(parse_gpgconf_list(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|fields| fields.len() >= 3)
+            .map(|fields| {
+                let name = fields[0].clone();
+                let sockets: Vec<(String, PathBuf)> = socket_names(&name)
+                    .iter()
+                    .filter_map(|socket| {
+                        let (_, path) = dirs.iter().find(|(dir, _)| dir == socket)?;
+                        Some((socket.to_string(), PathBuf::from(path)))
+                    })
+                    .collect();
+                let state = if let Some((socket, _)) = sockets.first() {
+                    socket_state(&dirs, socket)
+                } else if AGENT_CHILDREN.contains(&name.as_str()) {
+                    match agent_state {
+                        ComponentState::Running => {
+                            ComponentState::from_running(self.is_agent_child_running(&name))
+                        }
+                        state => state,
+                    }
+                } else {
+                    ComponentState::NotDaemon
+                };
+                Component {
+                    name,
+                    description: fields[1].clone(),
+                    program: PathBuf::from(&fields[2]),
+                    state,
+                    sockets,
+                }
+            })
+            .collect())
+    }
+
+    /// Ask gpg-agent whether it has started `name`, without starting anything
+    fn is_agent_child_running(&self, name: &str) -> bool {
+        let query = match name {
+            "scdaemon" => "GETINFO scd_running",
+            _ => "GETINFO tpm2d_running",
+        };
+        self.sibling_command("gpg-connect-agent")
+            .args(["--no-autostart", query, "/bye"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).starts_with("OK"))
+    }
+
+    /// Stop the daemon `name`, or all daemons with `all`
+    pub fn kill_component(&self, name: &str) -> Result<()> {
+        check_output(&self.gpgconf().args(["--kill", name]).output()?)
+    }
+
+    /// Start the daemon `name` unless it is running already
+    pub fn launch_component(&self, name: &str) -> Result<()> {
+        if !AGENT_CHILDREN.contains(&name) {
+            return check_output(&self.gpgconf().args(["--launch", name]).output()?);
+        }
+        // gpgconf can not launch these, but gpg-agent starts them on first use
+        let command = match name {
+            "scdaemon" => "SCD GETINFO version",
+            _ => "TPM2D GETINFO version",
+        };
+        let output = self
+            .sibling_command("gpg-connect-agent")
+            .args([command, "/bye"])
+            .output()?;
+        check_output(&output)?;
+        let reply = String::from_utf8_lossy(&output.stdout);
+        match reply.lines().find(|line| line.starts_with("ERR")) {
+            Some(err) => Err(Error::other(format!("{name} could not be started: {err}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop all daemons and start gpg-agent and dirmngr again
+    pub fn restart_all(&self) -> Result<()> {
+        self.kill_component("all")?;
+        self.launch_component("gpg-agent")?;
+        self.launch_component("dirmngr")
+    }
+}
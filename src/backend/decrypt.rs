@@ -0,0 +1,296 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Result, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::{DateTime, Utc};
+
+use super::{colons::parse_date, keyserver::url_decode, status::StatusLine, Gpg};
+
+/// What gpg decrypts or verifies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    File(PathBuf),
+    Data(Vec<u8>), // Data(e.g. pasted armor)
+}
+
+/// Verdict on a single signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Good,
+    Bad,
+    Expired,       // The signature itself expired (EXPSIG)
+    ExpiredKey,    // Made by a key which expired since (EXPKEYSIG)
+    RevokedKey,    // Made by a revoked key (REVKEYSIG)
+    MissingKey,    // ERRSIG with code 9
+    Error(String), // Error(Reason)
+}
+
+impl SignatureStatus {
+    pub fn is_good(&self) -> bool {
+        *self == SignatureStatus::Good
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            SignatureStatus::Good => "Good signature".to_string(),
+            SignatureStatus::Bad => "BAD signature".to_string(),
+            SignatureStatus::Expired => "Expired signature".to_string(),
+            SignatureStatus::ExpiredKey => "Good signature by an expired key".to_string(),
+            SignatureStatus::RevokedKey => "Good signature by a REVOKED key".to_string(),
+            SignatureStatus::MissingKey => "Signature by an unknown key".to_string(),
+            SignatureStatus::Error(reason) => format!("Signature could not be checked: {reason}"),
+        }
+    }
+}
+
+/// A signature reported by `gpg --decrypt` or `gpg --verify`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub status: SignatureStatus,
+    pub key_id: String,
+    /// Primary user ID of the signer, if the key is known
+    pub user_id: String,
+    /// Fingerprint of the signing (sub)key (VALIDSIG)
+    pub fingerprint: Option<String>,
+    /// Fingerprint of the signer's primary key (VALIDSIG)
+    pub primary_fingerprint: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    /// Validity of the signer's key, e.g. `ultimate` (TRUST_*)
+    pub trust: Option<String>,
+}
+
+/// Whether the integrity of the encrypted data was verified
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrity {
+    #[default]
+    NotEncrypted,
+    Mdc,         // GOODMDC
+    Aead,        // AEAD mode, authenticated on decryption
+    Unprotected, // Neither MDC nor AEAD, the data may have been modified
+    Failed,      // BADMDC or DECRYPTION_FAILED
+}
+
+impl Integrity {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Integrity::NotEncrypted => "not encrypted",
+            Integrity::Mdc => "MDC check passed",
+            Integrity::Aead => "AEAD check passed",
+            Integrity::Unprotected => "NOT integrity protected",
+            Integrity::Failed => "FAILED, the data may have been modified",
+        }
+    }
+}
+
+/// Outcome of a decryption or verification
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct DecryptResult {
+    pub success: bool,
+    pub signatures: Vec<SignatureInfo>,
+    /// Long key IDs the message is encrypted to (ENC_TO)
+    pub recipients: Vec<String>,
+    /// Fingerprint of the subkey which decrypted the message
+    pub decryption_key: Option<String>,
+    /// Whether the message was encrypted with a passphrase only
+    pub symmetric: bool,
+    pub integrity: Integrity,
+    pub plaintext: Vec<u8>,
+    /// File name stored in the literal data packet
+    pub filename: Option<String>,
+    /// Human readable messages gpg printed, shown on failure
+    pub log: String,
+}
+
+impl DecryptResult {
+    pub fn is_encrypted(&self) -> bool {
+        self.integrity != Integrity::NotEncrypted
+    }
+
+    /// Whether the plaintext can be shown in a text view
+    pub fn is_text(&self) -> bool {
+        !self.plaintext.contains(&0) && std::str::from_utf8(&self.plaintext).is_ok()
+    }
+}
+
+fn errsig_reason(code: &str) -> SignatureStatus {
+    match code {
+        "9" => SignatureStatus::MissingKey,
+        "4" => SignatureStatus::Error("unsupported algorithm".to_string()),
+        code => SignatureStatus::Error(format!("error code {code}")),
+    }
+}
+
+/// Collect the decryption and signature status lines
+pub fn parse_decrypt(status: &[StatusLine]) -> DecryptResult {
+    let mut result = DecryptResult::default();
+    let mut aead = false;
+    let mut integrity = None;
+    for line in status {
+        let signature = |status: SignatureStatus| SignatureInfo {
+            status,
+            key_id: line.arg(0).to_string(),
+            user_id: line.rest(1),
+            fingerprint: None,
+            primary_fingerprint: None,
+            created: None,
+            trust: None,
+        };
+        match line.keyword.as_str() {
+            "GOODSIG" => result.signatures.push(signature(SignatureStatus::Good)),
+            "BADSIG" => result.signatures.push(signature(SignatureStatus::Bad)),
+            "EXPSIG" => result.signatures.push(signature(SignatureStatus::Expired)),
+            "EXPKEYSIG" => result
+                .signatures
+                .push(signature(SignatureStatus::ExpiredKey)),
+            "REVKEYSIG" => result
+                .signatures
+                .push(signature(SignatureStatus::RevokedKey)),
+            "ERRSIG" => result.signatures.push(SignatureInfo {
+                user_id: String::new(),
+                fingerprint: Some(line.arg(6).to_string()).filter(|fpr| !fpr.is_empty()),
+                created: parse_date(line.arg(4)),
+                ..signature(errsig_reason(line.arg(5)))
+            }),
+            "VALIDSIG" => {
+                if let Some(signature) = result.signatures.last_mut() {
+                    signature.fingerprint = Some(line.arg(0).to_string());
+                    signature.created = parse_date(line.arg(2));
+                    signature.primary_fingerprint =
+                        Some(line.arg(9).to_string()).filter(|fpr| !fpr.is_empty());
+                }
+            }
+            keyword if keyword.starts_with("TRUST_") => {
+                if let Some(signature) = result.signatures.last_mut() {
+                    signature.trust = Some(keyword["TRUST_".len()..].to_lowercase());
+                }
+            }
+            "ENC_TO" => result.recipients.push(line.arg(0).to_string()),
+            "DECRYPTION_KEY" => result.decryption_key = Some(line.arg(0).to_string()),
+            "NEED_PASSPHRASE_SYM" => result.symmetric = true,
+            "BEGIN_DECRYPTION" => integrity = integrity.or(Some(Integrity::Unprotected)),
+            "DECRYPTION_INFO" => aead = !matches!(line.arg(2), "" | "0"),
+            "GOODMDC" => integrity = Some(Integrity::Mdc),
+            "DECRYPTION_OKAY" if aead => integrity = Some(Integrity::Aead),
+            "BADMDC" | "DECRYPTION_FAILED" => integrity = Some(Integrity::Failed),
+            "PLAINTEXT" => {
+                // The file name is percent-escaped
+                result.filename = Some(url_decode(&line.rest(2))).filter(|name| !name.is_empty())
+            }
+            _ => (),
+        }
+    }
+    result.integrity = integrity.unwrap_or_default();
+    result
+}
+
+/// The detached signature belonging to `path`, as `(signature, data)`
+///
+/// Picking either `file` or `file.sig`/`file.asc` finds the pair, as long as
+/// the signature file really holds a signature and not e.g. an encrypted message.
+pub fn detached_pair(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    if matches!(extension, "sig" | "asc") {
+        let data = path.with_extension("");
+        if data.is_file() && is_signature_file(path) {
+            return Some((path.to_path_buf(), data));
+        }
+    }
+    ["sig", "asc"].into_iter().find_map(|extension| {
+        let mut signature = path.as_os_str().to_owned();
+        signature.push(format!(".{extension}"));
+        let signature = PathBuf::from(signature);
+        (signature.is_file() && is_signature_file(&signature))
+            .then(|| (signature, path.to_path_buf()))
+    })
+}
+
+fn is_signature_file(path: &Path) -> bool {
+    let mut head = [0u8; 64];
+    let Ok(len) = fs::File::open(path).and_then(|mut file| file.read(&mut head)) else {
+        return false;
+    };
+    let head = &head[..len];
+    if head.starts_with(b"-----BEGIN PGP SIGNATURE-----") {
+        return true;
+    }
+    // A binary `.sig` is a signature by convention
+    !head.starts_with(b"-----") && path.extension().is_some_and(|ext| ext == "sig")
+}
+
+/// A file in the temp directory which is removed again when dropped
+pub(crate) struct TempFile(pub PathBuf);
+
+impl TempFile {
+    pub fn with_data(data: &[u8]) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "gpg4terminal-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        // Keep it private to the user where the platform allows
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&path)?.write_all(data)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl Gpg {
+    /// Decrypt and/or verify `input`, returning the plaintext and signature status
+    ///
+    /// With a `passphrase` gpg is run in loopback pinentry mode, otherwise the
+    /// agent asks for it if needed. A failed decryption is reported in the
+    /// result, not as an error, so bad signatures and the log are still shown.
+    pub fn decrypt(&self, input: &Input, passphrase: Option<&str>) -> Result<DecryptResult> {
+        let mut args = vec!["--decrypt".to_string()];
+        let mut stdin = Vec::new();
+        let mut temp = None;
+        if let Some(passphrase) = passphrase {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"].map(String::from));
+            stdin = format!("{passphrase}\n").into_bytes();
+        }
+        match input {
+            Input::File(path) => args.push(path.to_string_lossy().into_owned()),
+            // stdin already carries the passphrase
+            Input::Data(data) if passphrase.is_some() => {
+                let file = TempFile::with_data(data)?;
+                args.push(file.0.to_string_lossy().into_owned());
+                temp = Some(file);
+            }
+            Input::Data(data) => stdin = data.clone(),
+        }
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.run_with_status(&args, &stdin, |_| ())?;
+        drop(temp);
+
+        let mut result = parse_decrypt(&output.status);
+        result.success = output.success;
+        result.plaintext = output.stdout;
+        result.log = output.log;
+        Ok(result)
+    }
+
+    /// Verify the detached `signature` over the file `data`
+    pub fn verify_detached(&self, signature: &Path, data: &Path) -> Result<DecryptResult> {
+        let signature = signature.to_string_lossy();
+        let data = data.to_string_lossy();
+        let output = self.run_with_status(&["--verify", &signature, &data], &[], |_| ())?;
+        let mut result = parse_decrypt(&output.status);
+        result.success = output.success;
+        result.log = output.log;
+        Ok(result)
+    }
+}
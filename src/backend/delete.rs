@@ -0,0 +1,55 @@
+use std::io::Result;
+
+use super::Gpg;
+
+/// What is deleted of a key
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteKind {
+    #[default]
+    Public, // `--delete-keys`, only works once there is no secret key left
+    Secret,          // `--delete-secret-keys`, the public key stays in the keyring
+    SecretAndPublic, // `--delete-secret-and-public-key`
+}
+
+impl DeleteKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeleteKind::Public => "Public key",
+            DeleteKind::Secret => "Secret key only",
+            DeleteKind::SecretAndPublic => "Secret and public key",
+        }
+    }
+
+    /// Whether secret key material is deleted
+    pub fn is_secret(&self) -> bool {
+        !matches!(self, DeleteKind::Public)
+    }
+
+    /// The kinds which apply to a key with or without a secret part
+    pub fn choices(has_secret: bool) -> &'static [DeleteKind] {
+        if has_secret {
+            &[DeleteKind::SecretAndPublic, DeleteKind::Secret]
+        } else {
+            &[DeleteKind::Public]
+        }
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            DeleteKind::Public => "--delete-keys",
+            DeleteKind::Secret => "--delete-secret-keys",
+            DeleteKind::SecretAndPublic => "--delete-secret-and-public-key",
+        }
+    }
+}
+
+impl Gpg {
+    /// Delete the key with `fingerprint` from the keyring, without asking
+    ///
+    /// Secret keys are only deleted by their fingerprint, so a key ID can not hit another key.
+    pub fn delete_key(&self, fingerprint: &str, kind: DeleteKind) -> Result<()> {
+        let args = ["--yes", kind.command(), fingerprint];
+        self.run_with_status(&args, &[], |_| ())?.check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    io::{BufRead, BufReader, Error, Result, Write},
+    process::Stdio,
+};
+
+use super::{key::RevocationReason, status::StatusLine, Gpg};
+
+/// Status keywords with which gpg asks for the next line on `--command-fd`
+const PROMPTS: [&str; 3] = ["GET_LINE", "GET_BOOL", "GET_HIDDEN"];
+
+/// Answers to the questions gpg asks about the reason of a revocation
+///
+/// gpg numbers its choices on its own, so they do not match the reason codes.
+pub fn revocation_answers(
+    reason: RevocationReason,
+    description: &str,
+) -> Vec<(&'static str, String)> {
+    let choice = match reason {
+        RevocationReason::Unspecified => "0",
+        RevocationReason::Compromised => "1",
+        RevocationReason::Superseded => "2",
+        RevocationReason::Retired => "3",
+        RevocationReason::UserIdInvalid => "4",
+    };
+    let mut answers = vec![("ask_revocation_reason.code", choice.to_string())];
+    // An empty line ends the description
+    answers.extend(
+        description
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| ("ask_revocation_reason.text", line.to_string())),
+    );
+    answers.push(("ask_revocation_reason.text", String::new()));
+    answers.push(("ask_revocation_reason.okay", "y".to_string()));
+    answers
+}
+
+impl Gpg {
+    /// Run `gpg --edit-key` on `fingerprint`, answering its prompts from `script`
+    ///
+    /// Each answer names the prompt it is meant for, e.g. `keyedit.prompt`. As
+    /// soon as gpg asks anything else it is stopped, before it saved a change.
+    pub fn edit_key(
+        &self,
+        fingerprint: &str,
+        script: &[(&str, String)],
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        let mut args = vec!["--status-fd", "2", "--command-fd", "0"];
+        if passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+        }
+        args.extend(["--edit-key", fingerprint]);
+        let mut child = self
+            .command()
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        // gpg reads the passphrase before any command
+        if let Some(passphrase) = passphrase {
+            writeln!(stdin, "{passphrase}")?;
+        }
+        let mut answers = script.iter();
+        let mut unexpected = None;
+        let mut log = String::new();
+        for line in BufReader::new(child.stderr.take().unwrap()).lines() {
+            let line = line?;
+            let Some(status) = StatusLine::parse(&line) else {
+                log.push_str(&line);
+                log.push('\n');
+                continue;
+            };
+            if !PROMPTS.contains(&status.keyword.as_str()) {
+                continue;
+            }
+            match answers.next() {
+                Some((prompt, answer)) if *prompt == status.arg(0) => {
+                    writeln!(stdin, "{answer}")?;
+                }
+                _ => {
+                    unexpected = Some(status.arg(0).to_string());
+                    break;
+                }
+            }
+        }
+
+        if let Some(prompt) = unexpected {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::other(format!(
+                "gpg asked for {prompt} unexpectedly: {}",
+                log.trim()
+            )));
+        }
+        drop(stdin);
+        if !child.wait()?.success() {
+            return Err(Error::other(format!("gpg failed: {}", log.trim())));
+        }
+        if let Some((prompt, _)) = answers.next() {
+            return Err(Error::other(format!(
+                "gpg stopped before asking for {prompt}: {}",
+                log.trim()
+            )));
+        }
+        Ok(())
+    }
+}
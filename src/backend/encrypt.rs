@@ -0,0 +1,236 @@
+use std::{
+    io::{Error, Result},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    key::{Key, Validity},
+    Gpg,
+};
+
+/// How the input is encrypted
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    #[default]
+    PublicKey,
+    Symmetric, // Passphrase only (`--symmetric`)
+    None,
+}
+
+impl Encryption {
+    pub const ALL: [Encryption; 3] = [
+        Encryption::PublicKey,
+        Encryption::Symmetric,
+        Encryption::None,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Encryption::PublicKey => "To recipients",
+            Encryption::Symmetric => "Passphrase only",
+            Encryption::None => "Do not encrypt",
+        }
+    }
+}
+
+/// How the input is signed
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signing {
+    #[default]
+    None,
+    Inline,    // `--sign`, the signature wraps the data
+    Detached,  // `--detach-sign`, the signature is written to its own file
+    Clearsign, // `--clearsign`, the text stays readable
+}
+
+impl Signing {
+    pub const ALL: [Signing; 4] = [
+        Signing::None,
+        Signing::Inline,
+        Signing::Detached,
+        Signing::Clearsign,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Signing::None => "Do not sign",
+            Signing::Inline => "Inline",
+            Signing::Detached => "Detached",
+            Signing::Clearsign => "Clear text",
+        }
+    }
+}
+
+/// Everything needed to encrypt and/or sign a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncryptOptions {
+    pub encryption: Encryption,
+    /// Fingerprints of the recipients
+    pub recipients: Vec<String>,
+    /// Also encrypt to `signer`, so the sender can read the message again
+    pub encrypt_to_self: bool,
+    pub signing: Signing,
+    /// Fingerprint of the signing key, gpg's default key if `None`
+    pub signer: Option<String>,
+    pub armor: bool,
+    /// Passphrase for symmetric encryption or the signing key
+    ///
+    /// Without one the agent asks for the signing key's passphrase if needed.
+    pub passphrase: Option<String>,
+    /// Encrypt to recipients whose keys are not fully valid (`--trust-model always`)
+    pub always_trust: bool,
+}
+
+impl EncryptOptions {
+    /// Check for combinations gpg does not support
+    pub fn validate(&self) -> Result<()> {
+        let is_encrypted = self.encryption != Encryption::None;
+        if !is_encrypted && self.signing == Signing::None {
+            return Err(Error::other("Choose to encrypt, to sign or both"));
+        }
+        if is_encrypted && matches!(self.signing, Signing::Detached | Signing::Clearsign) {
+            return Err(Error::other(
+                "Detached and clear text signatures can not be encrypted",
+            ));
+        }
+        if self.encryption == Encryption::PublicKey
+            && self.recipients.is_empty()
+            && !self.encrypt_to_self
+        {
+            return Err(Error::other("Choose at least one recipient"));
+        }
+        if self.encryption == Encryption::PublicKey && self.encrypt_to_self && self.signer.is_none()
+        {
+            return Err(Error::other("Choose your own key to encrypt to yourself"));
+        }
+        if self.encryption == Encryption::Symmetric && self.passphrase.is_none() {
+            return Err(Error::other("Enter a passphrase for the encryption"));
+        }
+        Ok(())
+    }
+
+    /// Where the result for `input` is written by default, e.g. `file.txt.asc`
+    pub fn output_path(&self, input: &Path) -> PathBuf {
+        let extension = match (self.signing, self.armor) {
+            (Signing::Clearsign, _) | (_, true) => "asc",
+            (Signing::Detached, false) => "sig",
+            _ => "gpg",
+        };
+        let mut output = input.as_os_str().to_owned();
+        output.push(format!(".{extension}"));
+        PathBuf::from(output)
+    }
+
+    fn args(&self, input: &Path, output: &Path) -> Vec<String> {
+        let mut args = vec!["--yes".to_string()];
+        if self.passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"].map(String::from));
+        }
+        if self.armor {
+            args.push("--armor".to_string());
+        }
+        if self.always_trust {
+            args.extend(["--trust-model", "always"].map(String::from));
+        }
+        match self.encryption {
+            Encryption::PublicKey => {
+                args.push("--encrypt".to_string());
+                let own_key = self.signer.iter().filter(|_| self.encrypt_to_self);
+                for recipient in self.recipients.iter().chain(own_key) {
+                    args.extend(["--recipient".to_string(), recipient.clone()]);
+                }
+            }
+            Encryption::Symmetric => args.push("--symmetric".to_string()),
+            Encryption::None => (),
+        }
+        match self.signing {
+            Signing::None => (),
+            Signing::Inline => args.push("--sign".to_string()),
+            Signing::Detached => args.push("--detach-sign".to_string()),
+            Signing::Clearsign => args.push("--clearsign".to_string()),
+        }
+        if let (Some(signer), true) = (&self.signer, self.signing != Signing::None) {
+            args.extend(["--local-user".to_string(), signer.clone()]);
+        }
+        args.extend([
+            "--output".to_string(),
+            output.to_string_lossy().into_owned(),
+            input.to_string_lossy().into_owned(),
+        ]);
+        args
+    }
+}
+
+/// Why a key should not (or can not) be used as a recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientIssue {
+    Revoked,
+    Expired,
+    Disabled,
+    Invalid,
+    NoEncryptionKey,
+    Untrusted(Validity), // Untrusted(Validity), gpg refuses it unless told otherwise
+}
+
+impl RecipientIssue {
+    pub fn describe(&self) -> String {
+        match self {
+            RecipientIssue::Revoked => "revoked".to_string(),
+            RecipientIssue::Expired => "expired".to_string(),
+            RecipientIssue::Disabled => "disabled".to_string(),
+            RecipientIssue::Invalid => "invalid".to_string(),
+            RecipientIssue::NoEncryptionKey => "no encryption key".to_string(),
+            RecipientIssue::Untrusted(validity) => format!("not trusted ({validity})"),
+        }
+    }
+
+    /// Whether gpg can not encrypt to the key at all
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, RecipientIssue::Untrusted(_))
+    }
+}
+
+/// Check whether `key` is fit to be a recipient
+pub fn recipient_issue(key: &Key) -> Option<RecipientIssue> {
+    match key.primary.validity {
+        Validity::Revoked => return Some(RecipientIssue::Revoked),
+        Validity::Disabled => return Some(RecipientIssue::Disabled),
+        Validity::Invalid => return Some(RecipientIssue::Invalid),
+        _ if key.primary.is_expired() => return Some(RecipientIssue::Expired),
+        _ => (),
+    }
+    if !key.capabilities.encrypt {
+        return Some(RecipientIssue::NoEncryptionKey);
+    }
+    match key.primary.validity {
+        Validity::Full | Validity::Ultimate => None,
+        validity => Some(RecipientIssue::Untrusted(validity)),
+    }
+}
+
+impl Gpg {
+    /// Encrypt and/or sign the file `input` to `output`, replacing it if it exists
+    pub fn encrypt_file(
+        &self,
+        input: &Path,
+        output: &Path,
+        options: &EncryptOptions,
+    ) -> Result<()> {
+        options.validate()?;
+        let args = options.args(input, output);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let stdin = options
+            .passphrase
+            .as_ref()
+            .map(|passphrase| format!("{passphrase}\n").into_bytes())
+            .unwrap_or_default();
+        let output = self.run_with_status(&args, &stdin, |_| ())?;
+        if let Some(line) = output.find("INV_RECP") {
+            return Err(Error::other(format!(
+                "gpg rejected the recipient {}",
+                line.rest(1)
+            )));
+        }
+        output.check().map(|_| ())
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::Result;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use super::Gpg;
+
+/// When an expiry from [`normalize_expiry`](super::generate::normalize_expiry) ends, `None` for `never`
+///
+/// Counts like gpg, a month is 30 days and a year 365 days. A date ends at noon UTC.
+pub fn expiry_date(expiry: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(expiry, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(12, 0, 0)?.and_utc());
+    }
+    let (count, unit) = expiry.split_at(expiry.len().checked_sub(1)?);
+    let days = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        "y" => 365,
+        _ => return None,
+    };
+    let count: i64 = count.parse().ok()?;
+    Some(now + TimeDelta::days(count * days))
+}
+
+impl Gpg {
+    /// Set the expiry of the key with `fingerprint`, or of its `subkeys` if any are given
+    ///
+    /// `expiry` is what [`normalize_expiry`](super::generate::normalize_expiry) returns.
+    pub fn set_expiry(
+        &self,
+        fingerprint: &str,
+        subkeys: &[String],
+        expiry: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        let mut args = Vec::new();
+        if passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+        }
+        args.extend(["--quick-set-expire", fingerprint, expiry]);
+        args.extend(subkeys.iter().map(String::as_str));
+        let stdin = passphrase
+            .map(|passphrase| format!("{passphrase}\n").into_bytes())
+            .unwrap_or_default();
+        self.run_with_status(&args, &stdin, |_| ())?.check()?;
+        Ok(())
+    }
+}
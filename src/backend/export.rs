@@ -0,0 +1,188 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Error, Result, Write},
+    path::Path,
+};
+
+use super::Gpg;
+
+/// What is exported of a key
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    #[default]
+    Public,
+    Secret,        // `--export-secret-keys`
+    SecretSubkeys, // `--export-secret-subkeys`, the primary key stays behind
+    Ssh,           // `--export-ssh-key`, the authentication key in OpenSSH format
+}
+
+impl ExportKind {
+    pub const ALL: [ExportKind; 4] = [
+        ExportKind::Public,
+        ExportKind::Secret,
+        ExportKind::SecretSubkeys,
+        ExportKind::Ssh,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportKind::Public => "Public key",
+            ExportKind::Secret => "Secret key",
+            ExportKind::SecretSubkeys => "Secret subkeys only",
+            ExportKind::Ssh => "SSH public key",
+        }
+    }
+
+    /// Whether secret key material is exported
+    pub fn is_secret(&self) -> bool {
+        matches!(self, ExportKind::Secret | ExportKind::SecretSubkeys)
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            ExportKind::Public => "--export",
+            ExportKind::Secret => "--export-secret-keys",
+            ExportKind::SecretSubkeys => "--export-secret-subkeys",
+            ExportKind::Ssh => "--export-ssh-key",
+        }
+    }
+}
+
+/// Everything needed to export keys
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub kind: ExportKind,
+    pub armor: bool,
+    /// Leave out all signatures but the most recent self-signatures (`export-minimal`)
+    pub minimal: bool,
+    /// Leave out signatures by keys not in the keyring (`export-clean`)
+    pub clean: bool,
+    /// Passphrase of a secret key
+    ///
+    /// Without one the agent asks for it.
+    pub passphrase: Option<String>,
+}
+
+impl ExportOptions {
+    /// Check for combinations gpg does not support, for exporting `key_count` keys
+    pub fn validate(&self, key_count: usize) -> Result<()> {
+        if key_count == 0 {
+            return Err(Error::other("There is no key to export"));
+        }
+        if self.kind == ExportKind::Ssh && key_count > 1 {
+            return Err(Error::other("SSH keys are exported one at a time"));
+        }
+        Ok(())
+    }
+
+    /// Whether the result is text, SSH keys are always text
+    pub fn is_text(&self) -> bool {
+        self.armor || self.kind == ExportKind::Ssh
+    }
+
+    /// File name for the export of `name`, e.g. `0x1234ABCD-secret.asc`
+    pub fn file_name(&self, name: &str) -> String {
+        let suffix = match self.kind {
+            ExportKind::Public => "",
+            ExportKind::Secret => "-secret",
+            ExportKind::SecretSubkeys => "-secret-subkeys",
+            ExportKind::Ssh => return format!("{name}-ssh.pub"),
+        };
+        let extension = if self.armor { "asc" } else { "gpg" };
+        format!("{name}{suffix}.{extension}")
+    }
+
+    fn args(&self, fingerprints: &[String]) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
+        if self.passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"].map(String::from));
+        }
+        if self.kind != ExportKind::Ssh {
+            if self.armor {
+                args.push("--armor".to_string());
+            }
+            let mut export_options = Vec::new();
+            if self.minimal {
+                export_options.push("export-minimal");
+            }
+            if self.clean {
+                export_options.push("export-clean");
+            }
+            if !export_options.is_empty() {
+                args.extend(["--export-options".to_string(), export_options.join(",")]);
+            }
+        }
+        args.push(self.kind.command().to_string());
+        // Not `<fpr>!`, which would leave out the subkeys
+        args.extend(fingerprints.iter().cloned());
+        args
+    }
+}
+
+impl Gpg {
+    /// Export the keys with `fingerprints`, returning what gpg wrote
+    pub fn export_keys(&self, fingerprints: &[String], options: &ExportOptions) -> Result<Vec<u8>> {
+        options.validate(fingerprints.len())?;
+        let args = options.args(fingerprints);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let stdin = options
+            .passphrase
+            .as_ref()
+            .map(|passphrase| format!("{passphrase}\n").into_bytes())
+            .unwrap_or_default();
+        let output = self.run_with_status(&args, &stdin, |_| ())?.check()?;
+        // gpg only warns when none of the keys had anything to export
+        if output.stdout.is_empty() {
+            return Err(Error::other(format!(
+                "Nothing exported: {}",
+                output.log.trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Export the keys with `fingerprints` to `path`, replacing it if it exists
+    ///
+    /// Secret keys are only made readable by the owner.
+    pub fn export_to_file(
+        &self,
+        fingerprints: &[String],
+        options: &ExportOptions,
+        path: &Path,
+    ) -> Result<()> {
+        let data = self.export_keys(fingerprints, options)?;
+        create_file(path, options.kind.is_secret())?.write_all(&data)
+    }
+}
+
+/// Create or truncate `path`, only readable by the owner if `private`
+#[cfg(unix)]
+fn create_file(path: &Path, private: bool) -> Result<File> {
+    use std::{
+        fs::Permissions,
+        os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    };
+
+    let mode = if private { 0o600 } else { 0o644 };
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)?;
+    // The mode only applies to new files, a replaced one may be readable by others
+    if private {
+        file.set_permissions(Permissions::from_mode(mode))?;
+    }
+    Ok(file)
+}
+
+/// Create or truncate `path`, which keeps the access rules of its directory here
+#[cfg(not(unix))]
+fn create_file(path: &Path, _private: bool) -> Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
@@ -0,0 +1,202 @@
+use std::io::{Error, Result};
+
+use chrono::NaiveDate;
+
+use super::{key::Subkey, status::StatusLine, Gpg};
+
+/// Algorithms offered when creating a new key pair
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlgorithm {
+    #[default]
+    Ed25519,
+    Rsa3072,
+    Rsa4096,
+    NistP256,
+    NistP384,
+    NistP521,
+    BrainpoolP256,
+    BrainpoolP384,
+    BrainpoolP512,
+}
+
+impl KeyAlgorithm {
+    pub const ALL: [KeyAlgorithm; 9] = [
+        KeyAlgorithm::Ed25519,
+        KeyAlgorithm::Rsa3072,
+        KeyAlgorithm::Rsa4096,
+        KeyAlgorithm::NistP256,
+        KeyAlgorithm::NistP384,
+        KeyAlgorithm::NistP521,
+        KeyAlgorithm::BrainpoolP256,
+        KeyAlgorithm::BrainpoolP384,
+        KeyAlgorithm::BrainpoolP512,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "ed25519 / cv25519",
+            KeyAlgorithm::Rsa3072 => "RSA 3072",
+            KeyAlgorithm::Rsa4096 => "RSA 4096",
+            KeyAlgorithm::NistP256 => "NIST P-256",
+            KeyAlgorithm::NistP384 => "NIST P-384",
+            KeyAlgorithm::NistP521 => "NIST P-521",
+            KeyAlgorithm::BrainpoolP256 => "Brainpool P-256",
+            KeyAlgorithm::BrainpoolP384 => "Brainpool P-384",
+            KeyAlgorithm::BrainpoolP512 => "Brainpool P-512",
+        }
+    }
+
+    /// Algorithm string of the primary (certify and sign) key
+    pub fn primary_algo(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "ed25519",
+            other => other.subkey_algo(),
+        }
+    }
+
+    /// The choice `subkey` was made with, if it is one of the offered algorithms
+    pub fn of(subkey: &Subkey) -> Option<Self> {
+        let name = subkey.algorithm_name();
+        Self::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.primary_algo() == name || algorithm.subkey_algo() == name)
+    }
+
+    /// Algorithm string of the encryption subkey
+    pub fn subkey_algo(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::Ed25519 => "cv25519",
+            KeyAlgorithm::Rsa3072 => "rsa3072",
+            KeyAlgorithm::Rsa4096 => "rsa4096",
+            KeyAlgorithm::NistP256 => "nistp256",
+            KeyAlgorithm::NistP384 => "nistp384",
+            KeyAlgorithm::NistP521 => "nistp521",
+            KeyAlgorithm::BrainpoolP256 => "brainpoolP256r1",
+            KeyAlgorithm::BrainpoolP384 => "brainpoolP384r1",
+            KeyAlgorithm::BrainpoolP512 => "brainpoolP512r1",
+        }
+    }
+}
+
+/// Everything needed to create a new key pair
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyParams {
+    pub name: String,
+    pub email: String,
+    pub comment: String,
+    pub algorithm: KeyAlgorithm,
+    /// Expiry as understood by gpg, see [`normalize_expiry`]
+    pub expires: String,
+    /// An empty passphrase creates an unprotected key
+    pub passphrase: String,
+}
+
+impl KeyParams {
+    /// The user ID in the `Name (Comment) <email>` form
+    pub fn user_id(&self) -> String {
+        format_user_id(&self.name, &self.email, &self.comment)
+    }
+}
+
+/// Put a user ID together in the `Name (Comment) <email>` form, leaving out empty parts
+pub fn format_user_id(name: &str, email: &str, comment: &str) -> String {
+    let mut uid = name.trim().to_string();
+    if !comment.trim().is_empty() {
+        uid.push_str(&format!(" ({})", comment.trim()));
+    }
+    if !email.trim().is_empty() {
+        if !uid.is_empty() {
+            uid.push(' ');
+        }
+        uid.push_str(&format!("<{}>", email.trim()));
+    }
+    uid
+}
+
+/// Check an expiry input and turn it into the form gpg expects
+///
+/// Accepts `never`/`0`, a relative period like `2y`, `6m`, `3w` or `10d`,
+/// and an ISO date like `2030-12-31`.
+pub fn normalize_expiry(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() || input == "0" || input.eq_ignore_ascii_case("never") {
+        return Some("never".to_string());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+    let (count, unit) = input.split_at(input.len() - input.chars().last()?.len_utf8());
+    match (count.parse::<u32>(), unit) {
+        (Ok(count), "d" | "w" | "m" | "y") if count > 0 => Some(format!("{count}{unit}")),
+        _ => None,
+    }
+}
+
+impl Gpg {
+    /// Create a primary key plus an encryption subkey, returning the fingerprint
+    ///
+    /// `progress` sees the status lines while gpg generates primes.
+    pub fn generate_key(
+        &self,
+        params: &KeyParams,
+        mut progress: impl FnMut(&StatusLine),
+    ) -> Result<String> {
+        let passphrase = format!("{}\n", params.passphrase);
+        let user_id = params.user_id();
+        let output = self
+            .run_with_status(
+                &[
+                    "--pinentry-mode",
+                    "loopback",
+                    "--passphrase-fd",
+                    "0",
+                    "--quick-generate-key",
+                    &user_id,
+                    params.algorithm.primary_algo(),
+                    "cert,sign",
+                    &params.expires,
+                ],
+                passphrase.as_bytes(),
+                &mut progress,
+            )?
+            .check()?;
+        let fingerprint = output
+            .find("KEY_CREATED")
+            .map(|line| line.arg(1).to_string())
+            .ok_or_else(|| Error::other("gpg did not report the created key"))?;
+
+        self.add_subkey(
+            &fingerprint,
+            params.algorithm.subkey_algo(),
+            "encr",
+            &params.expires,
+            Some(&params.passphrase),
+            progress,
+        )?;
+        Ok(fingerprint)
+    }
+
+    /// Add a subkey to the key with `fingerprint`
+    ///
+    /// Without a `passphrase` the agent asks for the one of the primary key.
+    pub fn add_subkey(
+        &self,
+        fingerprint: &str,
+        algo: &str,
+        usage: &str,
+        expires: &str,
+        passphrase: Option<&str>,
+        progress: impl FnMut(&StatusLine),
+    ) -> Result<()> {
+        let mut args = vec![];
+        if passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+        }
+        args.extend(["--quick-add-key", fingerprint, algo, usage, expires]);
+        let stdin = passphrase
+            .map(|passphrase| format!("{passphrase}\n").into_bytes())
+            .unwrap_or_default();
+        self.run_with_status(&args, &stdin, progress)?.check()?;
+        Ok(())
+    }
+}
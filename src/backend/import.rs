@@ -0,0 +1,196 @@
+use std::io::Result;
+
+use super::{status::StatusLine, Gpg};
+
+/// What changed for a single imported certificate
+///
+/// Mirrors the reason flags of the `IMPORT_OK` status line
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportFlags {
+    pub new_key: bool,
+    pub new_user_ids: bool,
+    pub new_signatures: bool,
+    pub new_subkeys: bool,
+    pub secret: bool,
+}
+
+impl ImportFlags {
+    pub fn from_field(field: &str) -> Self {
+        let bits: u32 = field.parse().unwrap_or(0);
+        Self {
+            new_key: bits & 1 != 0,
+            new_user_ids: bits & 2 != 0,
+            new_signatures: bits & 4 != 0,
+            new_subkeys: bits & 8 != 0,
+            secret: bits & 16 != 0,
+        }
+    }
+
+    /// Whether the certificate was already known without any change
+    pub fn is_unchanged(&self) -> bool {
+        !(self.new_key || self.new_user_ids || self.new_signatures || self.new_subkeys)
+    }
+
+    /// Short human readable summary, e.g. `new key` or `new user IDs, new signatures`
+    pub fn describe(&self) -> String {
+        let mut changes = Vec::new();
+        if self.new_key {
+            changes.push("new key");
+        }
+        if self.new_user_ids {
+            changes.push("new user IDs");
+        }
+        if self.new_subkeys {
+            changes.push("new subkeys");
+        }
+        if self.new_signatures {
+            changes.push("new signatures");
+        }
+        if changes.is_empty() {
+            changes.push("unchanged");
+        }
+        if self.secret {
+            changes.push("secret key");
+        }
+        changes.join(", ")
+    }
+}
+
+/// A certificate which could not be imported (`IMPORT_PROBLEM`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProblem {
+    pub reason: String,
+    pub fingerprint: Option<String>,
+}
+
+/// Counters of the `IMPORT_RES` status line
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportCounts {
+    pub considered: u32,
+    pub no_user_id: u32,
+    pub imported: u32,
+    pub unchanged: u32,
+    pub new_user_ids: u32,
+    pub new_subkeys: u32,
+    pub new_signatures: u32,
+    pub new_revocations: u32,
+    pub secret_read: u32,
+    pub secret_imported: u32,
+    pub secret_unchanged: u32,
+    pub not_imported: u32,
+}
+
+/// Outcome of `gpg --import`
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ImportResult {
+    /// Fingerprints with what changed for them, in the order gpg reported them
+    pub keys: Vec<(String, ImportFlags)>,
+    pub problems: Vec<ImportProblem>,
+    pub counts: ImportCounts,
+}
+
+impl ImportResult {
+    /// Add the outcome of another import, e.g. when fetching several keys
+    pub fn merge(&mut self, other: ImportResult) {
+        self.keys.extend(other.keys);
+        self.problems.extend(other.problems);
+        let (counts, other) = (&mut self.counts, other.counts);
+        counts.considered += other.considered;
+        counts.no_user_id += other.no_user_id;
+        counts.imported += other.imported;
+        counts.unchanged += other.unchanged;
+        counts.new_user_ids += other.new_user_ids;
+        counts.new_subkeys += other.new_subkeys;
+        counts.new_signatures += other.new_signatures;
+        counts.new_revocations += other.new_revocations;
+        counts.secret_read += other.secret_read;
+        counts.secret_imported += other.secret_imported;
+        counts.secret_unchanged += other.secret_unchanged;
+        counts.not_imported += other.not_imported;
+    }
+
+    /// Fingerprint of the first certificate which was new or changed
+    pub fn first_changed(&self) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(_, flags)| !flags.is_unchanged())
+            .or(self.keys.first())
+            .map(|(fingerprint, _)| fingerprint.as_str())
+    }
+}
+
+fn problem_reason(code: &str) -> String {
+    match code {
+        "1" => "invalid certificate".to_string(),
+        "2" => "issuer certificate missing".to_string(),
+        "3" => "certificate chain too long".to_string(),
+        "4" => "error storing certificate".to_string(),
+        _ => "no specific reason given".to_string(),
+    }
+}
+
+/// Collect the import related status lines
+pub fn parse_import(status: &[StatusLine]) -> ImportResult {
+    let mut result = ImportResult::default();
+    for line in status {
+        match line.keyword.as_str() {
+            "IMPORT_OK" => {
+                let fingerprint = line.arg(1).to_string();
+                let flags = ImportFlags::from_field(line.arg(0));
+                // Secret and public part of a key are reported separately
+                match result.keys.iter_mut().find(|(fpr, _)| *fpr == fingerprint) {
+                    Some((_, known)) => {
+                        known.new_key |= flags.new_key;
+                        known.new_user_ids |= flags.new_user_ids;
+                        known.new_signatures |= flags.new_signatures;
+                        known.new_subkeys |= flags.new_subkeys;
+                        known.secret |= flags.secret;
+                    }
+                    None => result.keys.push((fingerprint, flags)),
+                }
+            }
+            "IMPORT_PROBLEM" => result.problems.push(ImportProblem {
+                reason: problem_reason(line.arg(0)),
+                fingerprint: Some(line.arg(1).to_string()).filter(|fpr| !fpr.is_empty()),
+            }),
+            "IMPORT_RES" => {
+                let count = |index: usize| line.arg(index).parse().unwrap_or(0);
+                result.counts = ImportCounts {
+                    considered: count(0),
+                    no_user_id: count(1),
+                    imported: count(2),
+                    unchanged: count(4),
+                    new_user_ids: count(5),
+                    new_subkeys: count(6),
+                    new_signatures: count(7),
+                    new_revocations: count(8),
+                    secret_read: count(9),
+                    secret_imported: count(10),
+                    secret_unchanged: count(11),
+                    not_imported: count(13),
+                };
+            }
+            _ => (),
+        }
+    }
+    result
+}
+
+impl Gpg {
+    /// Import certificates (binary or ASCII armored) from `data`
+    pub fn import(&self, data: &[u8]) -> Result<ImportResult> {
+        let output = self.run_with_status(&["--import"], data, |_| ())?;
+        let result = parse_import(&output.status);
+        // gpg fails if only some of the certificates could be imported, the
+        // result still tells what happened to each of them
+        if output
+            .status
+            .iter()
+            .any(|line| line.keyword == "IMPORT_RES")
+        {
+            Ok(result)
+        } else {
+            output.check().map(|_| result)
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    io::{Error, Result},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+enum JobEvent<T> {
+    Progress(String),
+    Done(Result<T>),
+}
+
+/// Reports progress of a running [`Job`] back to the UI
+#[derive(Clone)]
+pub struct Progress<T> {
+    sender: Sender<JobEvent<T>>,
+}
+
+impl<T> Progress<T> {
+    pub fn report(&self, message: impl Into<String>) {
+        let _ = self.sender.send(JobEvent::Progress(message.into()));
+    }
+}
+
+/// A long running gpg invocation on a background thread
+///
+/// The UI polls the job on every tick, so it stays responsive while e.g. a
+/// key is generated or a keyserver is contacted.
+pub struct Job<T> {
+    receiver: Receiver<JobEvent<T>>,
+    /// Progress messages received so far
+    pub progress: Vec<String>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    pub fn spawn<F>(task: F) -> Self
+    where
+        F: FnOnce(&Progress<T>) -> Result<T> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let progress = Progress { sender };
+            let result = task(&progress);
+            let _ = progress.sender.send(JobEvent::Done(result));
+        });
+        Self {
+            receiver,
+            progress: Vec::new(),
+        }
+    }
+
+    /// Collect new progress messages, returning the result once finished
+    pub fn poll(&mut self) -> Option<Result<T>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(JobEvent::Progress(message)) => self.progress.push(message),
+                Ok(JobEvent::Done(result)) => return Some(result),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    return Some(Err(Error::other("background job terminated unexpectedly")))
+                }
+            }
+        }
+    }
+
+    /// The latest progress message, if any
+    pub fn last_progress(&self) -> Option<&str> {
+        self.progress.last().map(String::as_str)
+    }
+}
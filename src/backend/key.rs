@@ -0,0 +1,439 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Validity (or owner trust) of a key or user ID
+///
+/// Mirrors field 2 (and field 9) of the `--with-colons` output
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Validity {
+    #[default]
+    Unknown,
+    Invalid,
+    Disabled,
+    Revoked,
+    Expired,
+    Undefined,
+    Never,
+    Marginal,
+    Full,
+    Ultimate,
+    WellKnown,
+    Special,
+}
+
+impl Validity {
+    pub fn from_field(field: &str) -> Self {
+        match field.chars().next() {
+            Some('i') => Validity::Invalid,
+            Some('d') => Validity::Disabled,
+            Some('r') => Validity::Revoked,
+            Some('e') => Validity::Expired,
+            Some('q') => Validity::Undefined,
+            Some('n') => Validity::Never,
+            Some('m') => Validity::Marginal,
+            Some('f') => Validity::Full,
+            Some('u') => Validity::Ultimate,
+            Some('w') => Validity::WellKnown,
+            Some('s') => Validity::Special,
+            _ => Validity::Unknown,
+        }
+    }
+
+    /// Whether the key or user ID can be used at all
+    pub fn is_usable(&self) -> bool {
+        !matches!(
+            self,
+            Validity::Invalid | Validity::Disabled | Validity::Revoked | Validity::Expired
+        )
+    }
+}
+
+impl fmt::Display for Validity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Validity::Unknown => "unknown",
+            Validity::Invalid => "invalid",
+            Validity::Disabled => "disabled",
+            Validity::Revoked => "revoked",
+            Validity::Expired => "expired",
+            Validity::Undefined => "undefined",
+            Validity::Never => "never",
+            Validity::Marginal => "marginal",
+            Validity::Full => "full",
+            Validity::Ultimate => "ultimate",
+            Validity::WellKnown => "well-known",
+            Validity::Special => "special",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Usage flags of a key
+///
+/// Mirrors field 12 of the `--with-colons` output
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    pub sign: bool,
+    pub encrypt: bool,
+    pub certify: bool,
+    pub authenticate: bool,
+}
+
+impl Capabilities {
+    pub fn from_field(field: &str) -> Self {
+        let mut caps = Capabilities::default();
+        for ch in field.chars() {
+            match ch.to_ascii_lowercase() {
+                's' => caps.sign = true,
+                'e' => caps.encrypt = true,
+                'c' => caps.certify = true,
+                'a' => caps.authenticate = true,
+                _ => (),
+            }
+        }
+        caps
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (flag, ch) in [
+            (self.sign, 'S'),
+            (self.encrypt, 'E'),
+            (self.authenticate, 'A'),
+            (self.certify, 'C'),
+        ] {
+            if flag {
+                write!(f, "{ch}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Public key algorithm
+///
+/// Mirrors field 4 of the `--with-colons` output (RFC 4880 algorithm IDs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Rsa,
+    RsaEncryptOnly,
+    RsaSignOnly,
+    Elgamal,
+    Dsa,
+    Ecdh,
+    Ecdsa,
+    EdDsa,
+    Other(u8),
+}
+
+impl Algorithm {
+    pub fn from_id(id: u8) -> Self {
+        match id {
+            1 => Algorithm::Rsa,
+            2 => Algorithm::RsaEncryptOnly,
+            3 => Algorithm::RsaSignOnly,
+            16 => Algorithm::Elgamal,
+            17 => Algorithm::Dsa,
+            18 => Algorithm::Ecdh,
+            19 => Algorithm::Ecdsa,
+            22 => Algorithm::EdDsa,
+            _ => Algorithm::Other(id),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Algorithm::Rsa | Algorithm::RsaEncryptOnly | Algorithm::RsaSignOnly => {
+                f.write_str("rsa")
+            }
+            Algorithm::Elgamal => f.write_str("elg"),
+            Algorithm::Dsa => f.write_str("dsa"),
+            Algorithm::Ecdh => f.write_str("ecdh"),
+            Algorithm::Ecdsa => f.write_str("ecdsa"),
+            Algorithm::EdDsa => f.write_str("eddsa"),
+            Algorithm::Other(id) => write!(f, "algo{id}"),
+        }
+    }
+}
+
+/// A primary key or a subkey
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subkey {
+    pub fingerprint: String,
+    pub key_id: String,
+    pub validity: Validity,
+    pub algorithm: Algorithm,
+    /// Key length in bits
+    pub length: u32,
+    /// Curve name for ECC keys
+    pub curve: Option<String>,
+    pub capabilities: Capabilities,
+    pub created: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    pub keygrip: Option<String>,
+    /// Whether the secret part of this key is available
+    pub has_secret: bool,
+    /// Whether only a stub of the secret part is there, e.g. for an offline primary key (`#`)
+    pub is_stub: bool,
+    /// Serial number of the smartcard holding the secret part (`>`)
+    pub card_serial: Option<String>,
+    /// Binding and revocation signatures (only with `--list-sigs`)
+    pub signatures: Vec<Signature>,
+}
+
+impl Subkey {
+    /// Algorithm name in the form GnuPG uses, e.g. `rsa3072` or `ed25519`
+    pub fn algorithm_name(&self) -> String {
+        match &self.curve {
+            Some(curve) => curve.clone(),
+            None => format!("{}{}", self.algorithm, self.length),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.validity == Validity::Expired || self.expires.is_some_and(|date| date <= Utc::now())
+    }
+}
+
+/// A user ID (or user attribute) of a certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserId {
+    pub validity: Validity,
+    /// The user ID string, or a short description for user attributes
+    pub uid: String,
+    /// Hash over the user ID, usable to address it with `--quick-*` commands
+    pub hash: String,
+    pub created: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    /// Whether this is a user attribute (e.g. a photo ID)
+    pub is_attribute: bool,
+    /// Self-signatures and certifications (only with `--list-sigs`)
+    pub signatures: Vec<Signature>,
+}
+
+impl UserId {
+    /// The latest self-signature binding the user ID to the key with long key ID `key_id`
+    pub fn self_signature(&self, key_id: &str) -> Option<&Signature> {
+        self.signatures
+            .iter()
+            .filter(|sig| !sig.is_revocation && sig.is_self_signature(key_id))
+            .max_by_key(|sig| sig.created)
+    }
+
+    /// The self-revocation of the user ID, if it was revoked
+    pub fn revocation(&self, key_id: &str) -> Option<&Signature> {
+        self.signatures
+            .iter()
+            .filter(|sig| sig.is_revocation && sig.is_self_signature(key_id))
+            .max_by_key(|sig| sig.created)
+    }
+
+    /// The name part of `Name (Comment) <email>`
+    pub fn name(&self) -> &str {
+        let end = self
+            .uid
+            .find(" (")
+            .or_else(|| self.uid.find('<'))
+            .unwrap_or(self.uid.len());
+        self.uid[..end].trim()
+    }
+
+    /// The comment part of `Name (Comment) <email>`
+    pub fn comment(&self) -> Option<&str> {
+        let start = self.uid.find(" (")? + 2;
+        let end = start + self.uid[start..].find(')')?;
+        Some(&self.uid[start..end])
+    }
+
+    /// The email part of `Name (Comment) <email>`
+    pub fn email(&self) -> Option<&str> {
+        let start = self.uid.rfind('<')? + 1;
+        let end = start + self.uid[start..].find('>')?;
+        Some(&self.uid[start..end])
+    }
+}
+
+/// Result of checking a signature
+///
+/// Mirrors field 2 of `sig`/`rev` records from `--check-sigs`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureCheck {
+    #[default]
+    Unchecked,
+    Good,
+    Bad,
+    MissingKey,
+    Error,
+}
+
+impl SignatureCheck {
+    pub fn from_field(field: &str) -> Self {
+        match field.chars().next() {
+            Some('!') => SignatureCheck::Good,
+            Some('-') => SignatureCheck::Bad,
+            Some('?') => SignatureCheck::MissingKey,
+            Some('%') => SignatureCheck::Error,
+            _ => SignatureCheck::Unchecked,
+        }
+    }
+}
+
+impl fmt::Display for SignatureCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SignatureCheck::Unchecked => " ",
+            SignatureCheck::Good => "!",
+            SignatureCheck::Bad => "-",
+            SignatureCheck::MissingKey => "?",
+            SignatureCheck::Error => "%",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A signature over a user ID or key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub is_revocation: bool,
+    pub check: SignatureCheck,
+    /// Long key ID of the issuer
+    pub key_id: String,
+    pub issuer_fingerprint: Option<String>,
+    /// Primary user ID of the issuer, if its key is known
+    pub signer: String,
+    pub created: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    /// Signature class, e.g. `0x13` for a positive certification
+    pub class: u8,
+    pub exportable: bool,
+    /// Reason code of a revocation signature
+    pub revocation_reason: Option<u8>,
+    /// Description given with the reason of a revocation signature
+    pub revocation_comment: Option<String>,
+}
+
+impl Signature {
+    /// Whether the signature is made by the key with long key ID `key_id`
+    pub fn is_self_signature(&self, key_id: &str) -> bool {
+        self.key_id == key_id
+    }
+
+    /// Reason of a revocation signature
+    pub fn reason(&self) -> Option<RevocationReason> {
+        self.revocation_reason.map(RevocationReason::from_code)
+    }
+}
+
+/// Why a key or user ID was revoked
+///
+/// Mirrors the reason codes of RFC 4880, section 5.2.3.23
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevocationReason {
+    #[default]
+    Unspecified,
+    Superseded,
+    Compromised,
+    Retired,
+    UserIdInvalid,
+}
+
+impl RevocationReason {
+    /// The reasons which apply to user IDs, the usual one first
+    pub const FOR_USER_IDS: [RevocationReason; 2] = [
+        RevocationReason::UserIdInvalid,
+        RevocationReason::Unspecified,
+    ];
+
+    /// The reasons which apply to keys, the one of a routine rotation first
+    pub const FOR_KEYS: [RevocationReason; 4] = [
+        RevocationReason::Superseded,
+        RevocationReason::Compromised,
+        RevocationReason::Retired,
+        RevocationReason::Unspecified,
+    ];
+
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => RevocationReason::Superseded,
+            0x02 => RevocationReason::Compromised,
+            0x03 => RevocationReason::Retired,
+            0x20 => RevocationReason::UserIdInvalid,
+            _ => RevocationReason::Unspecified,
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        match self {
+            RevocationReason::Unspecified => 0x00,
+            RevocationReason::Superseded => 0x01,
+            RevocationReason::Compromised => 0x02,
+            RevocationReason::Retired => 0x03,
+            RevocationReason::UserIdInvalid => 0x20,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RevocationReason::Unspecified => "No reason specified",
+            RevocationReason::Superseded => "Key is superseded",
+            RevocationReason::Compromised => "Key has been compromised",
+            RevocationReason::Retired => "Key is no longer used",
+            RevocationReason::UserIdInvalid => "User ID is no longer valid",
+        }
+    }
+}
+
+/// An OpenPGP certificate together with its subkeys and user IDs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub primary: Subkey,
+    pub owner_trust: Validity,
+    /// Usable capabilities of the whole certificate
+    pub capabilities: Capabilities,
+    pub user_ids: Vec<UserId>,
+    pub subkeys: Vec<Subkey>,
+}
+
+impl Key {
+    pub fn fingerprint(&self) -> &str {
+        &self.primary.fingerprint
+    }
+
+    /// The primary user ID, which GnuPG always lists first
+    pub fn primary_uid(&self) -> Option<&UserId> {
+        self.user_ids
+            .iter()
+            .find(|uid| !uid.is_attribute && uid.validity != Validity::Revoked)
+            .or_else(|| self.user_ids.iter().find(|uid| !uid.is_attribute))
+    }
+
+    /// Whether the secret part of the primary key or any subkey is available
+    pub fn has_secret(&self) -> bool {
+        self.primary.has_secret || self.subkeys.iter().any(|subkey| subkey.has_secret)
+    }
+
+    /// The earliest expiry of the primary key and its usable subkeys
+    ///
+    /// An expiring encryption subkey breaks a key as much as its primary key expiring.
+    pub fn next_expiry(&self) -> Option<DateTime<Utc>> {
+        if !self.primary.validity.is_usable() {
+            return None;
+        }
+        self.subkeys
+            .iter()
+            .filter(|subkey| subkey.validity.is_usable())
+            .chain([&self.primary])
+            .filter_map(|subkey| subkey.expires)
+            .min()
+    }
+
+    /// Whether `id` is the fingerprint or key ID of the primary key, e.g. `0x1234ABCD...`
+    pub fn matches_id(&self, id: &str) -> bool {
+        let id = id.trim().trim_start_matches("0x").to_ascii_uppercase();
+        id.len() >= 8 && self.fingerprint().ends_with(&id)
+    }
+}
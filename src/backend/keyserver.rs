@@ -0,0 +1,400 @@
+use std::{
+    io::{Error, Read, Result},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    colons::parse_date,
+    import::ImportResult,
+    key::{Algorithm, Key, Validity},
+    network::Proxy,
+    Gpg,
+};
+
+pub const DEFAULT_KEYSERVER: &str = "hkps://keys.openpgp.org";
+
+/// Largest response accepted from a keyserver
+const MAX_RESPONSE: u64 = 16 * 1024 * 1024;
+
+/// How to talk to a keyserver
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    #[default]
+    Hkp, // `/pks/lookup`, understood by almost every keyserver
+    Vks, // `/vks/v1`, the API of keys.openpgp.org (Hagrid)
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 2] = [Protocol::Hkp, Protocol::Vks];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Protocol::Hkp => "HKP",
+            Protocol::Vks => "VKS (by e-mail or fingerprint)",
+        }
+    }
+}
+
+/// A certificate found on a keyserver
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResult {
+    /// Fingerprint, or only the long key ID on old servers
+    pub key_id: String,
+    pub algorithm: Option<Algorithm>,
+    pub length: u32,
+    pub created: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    pub is_revoked: bool,
+    pub is_expired: bool,
+    pub user_ids: Vec<String>,
+    /// The certificate itself, if the search already returned it (VKS)
+    pub data: Option<Vec<u8>>,
+}
+
+impl SearchResult {
+    fn from_key(key: &Key, data: &[u8]) -> Self {
+        Self {
+            key_id: key.fingerprint().to_string(),
+            algorithm: Some(key.primary.algorithm),
+            length: key.primary.length,
+            created: key.primary.created,
+            expires: key.primary.expires,
+            is_revoked: key.primary.validity == Validity::Revoked,
+            is_expired: key.primary.is_expired(),
+            user_ids: key.user_ids.iter().map(|uid| uid.uid.clone()).collect(),
+            data: Some(data.to_vec()),
+        }
+    }
+}
+
+/// Percent-encode everything except unreserved characters
+pub fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Undo the percent-encoding of an HKP index
+pub fn url_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parse the machine readable output of `/pks/lookup?op=index&options=mr`
+pub fn parse_index(body: &str) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = Vec::new();
+    for line in body.lines() {
+        let fields: Vec<&str> = line.trim().split(':').collect();
+        let field = |index: usize| fields.get(index).copied().unwrap_or("");
+        match field(0) {
+            "pub" => results.push(SearchResult {
+                key_id: field(1).to_uppercase(),
+                algorithm: field(2).parse().ok().map(Algorithm::from_id),
+                length: field(3).parse().unwrap_or(0),
+                created: parse_date(field(4)),
+                expires: parse_date(field(5)),
+                is_revoked: field(6).contains('r'),
+                is_expired: field(6).contains('e'),
+                ..Default::default()
+            }),
+            "uid" => {
+                if let Some(result) = results.last_mut() {
+                    result.user_ids.push(url_decode(field(1)));
+                }
+            }
+            _ => (),
+        }
+    }
+    results
+}
+
+/// Check that `server` is a keyserver address dirmngr understands
+pub fn check_keyserver(server: &str) -> Result<()> {
+    let Some((scheme, host)) = server.split_once("://") else {
+        return Err(Error::other(format!(
+            "{server} needs a scheme, e.g. hkps://{server}"
+        )));
+    };
+    if !matches!(scheme, "hkp" | "hkps" | "ldap" | "ldaps") {
+        return Err(Error::other(format!(
+            "keyserver scheme {scheme}:// is not supported, use hkp, hkps or ldap"
+        )));
+    }
+    if host.trim_end_matches('/').is_empty() || host.contains(char::is_whitespace) {
+        return Err(Error::other(format!("{server} has no valid host")));
+    }
+    Ok(())
+}
+
+/// Turn a keyserver address like `hkps://keys.openpgp.org` into an HTTP base URL
+pub fn base_url(server: &str) -> Result<String> {
+    let server = server.trim().trim_end_matches('/');
+    let (scheme, rest) = server.split_once("://").unwrap_or(("hkps", server));
+    if rest.is_empty() {
+        return Err(Error::other("no keyserver given"));
+    }
+    match scheme.to_lowercase().as_str() {
+        "hkp" if rest.contains(':') => Ok(format!("http://{rest}")),
+        "hkp" => Ok(format!("http://{rest}:11371")),
+        "hkps" | "https" => Ok(format!("https://{rest}")),
+        "http" => Ok(format!("http://{rest}")),
+        other => Err(Error::other(format!(
+            "keyserver scheme {other}:// is not supported for searching"
+        ))),
+    }
+}
+
+/// An HKP or VKS keyserver
+#[derive(Debug, Clone)]
+pub struct Keyserver {
+    base: String,
+    agent: ureq::Agent,
+}
+
+impl Keyserver {
+    /// Talk to `server` through `proxy`, see [`Gpg::keyserver`]
+    pub fn new(server: &str, proxy: &Proxy) -> Result<Self> {
+        Ok(Self {
+            base: base_url(server)?,
+            agent: proxy.http_agent(Duration::from_secs(30))?,
+        })
+    }
+
+    /// GET `path`, `None` if the server does not know the resource
+    fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let url = format!("{}{path}", self.base);
+        match self.agent.get(&url).call() {
+            Ok(response) => {
+                let mut body = Vec::new();
+                response
+                    .into_reader()
+                    .take(MAX_RESPONSE)
+                    .read_to_end(&mut body)?;
+                Ok(Some(body))
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(err) => Err(Error::other(format!("keyserver request failed: {err}"))),
+        }
+    }
+
+    /// Search with HKP `op=index`
+    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+        let path = format!(
+            "/pks/lookup?op=index&options=mr&search={}",
+            url_encode(query)
+        );
+        let body = self.get(&path)?.unwrap_or_default();
+        Ok(parse_index(&String::from_utf8_lossy(&body)))
+    }
+
+    /// Download a certificate with HKP `op=get`
+    pub fn fetch(&self, key_id: &str) -> Result<Vec<u8>> {
+        let path = format!(
+            "/pks/lookup?op=get&options=mr&search=0x{}",
+            url_encode(key_id)
+        );
+        self.get(&path)?
+            .ok_or_else(|| Error::other(format!("key {key_id} not found on the keyserver")))
+    }
+
+    /// Download a certificate with VKS by e-mail address or fingerprint
+    pub fn vks_lookup(&self, query: &str) -> Result<Option<Vec<u8>>> {
+        let query = query.trim();
+        let path = if query.contains('@') {
+            format!("/vks/v1/by-email/{}", url_encode(query))
+        } else {
+            let fingerprint: String = query
+                .trim_start_matches("0x")
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .collect::<String>()
+                .to_uppercase();
+            let kind = if fingerprint.len() == 16 {
+                "by-keyid"
+            } else {
+                "by-fingerprint"
+            };
+            format!("/vks/v1/{kind}/{}", url_encode(&fingerprint))
+        };
+        self.get(&path)
+    }
+}
+
+impl Gpg {
+    /// Connect to `server` with the proxy settings of dirmngr
+    pub fn keyserver(&self, server: &str) -> Result<Keyserver> {
+        Keyserver::new(server, &self.server_settings()?.proxy)
+    }
+
+    /// Look up `query` on `server`
+    pub fn search_keyserver(
+        &self,
+        server: &Keyserver,
+        protocol: Protocol,
+        query: &str,
+    ) -> Result<Vec<SearchResult>> {
+        match protocol {
+            Protocol::Hkp => server.search(query),
+            Protocol::Vks => match server.vks_lookup(query)? {
+                Some(data) => Ok(self
+                    .show_keys(&data)?
+                    .iter()
+                    .map(|key| SearchResult::from_key(key, &data))
+                    .collect()),
+                None => Ok(vec![]),
+            },
+        }
+    }
+
+    /// Download `result` unless the search already returned it, then import it
+    ///
+    /// Only the certificate of `result` is imported, whatever else the server
+    /// sent along with it, and nothing at all if it sent secret key material.
+    pub fn fetch_from_keyserver(
+        &self,
+        server: &Keyserver,
+        result: &SearchResult,
+    ) -> Result<ImportResult> {
+        let data = match &result.data {
+            Some(data) => data.clone(),
+            None => server.fetch(&result.key_id)?,
+        };
+        let keys = self.show_keys(&data)?;
+        if keys.iter().any(has_secret_packets) {
+            return Err(Error::other(
+                "the keyserver sent secret key material, refusing to import it",
+            ));
+        }
+        let fingerprint = keys
+            .iter()
+            .map(|key| key.fingerprint())
+            .find(|fingerprint| matches_key_id(fingerprint, &result.key_id))
+            .ok_or_else(|| {
+                Error::other(format!("the keyserver did not send key {}", result.key_id))
+            })?
+            .to_string();
+
+        // Let gpg normalize the data to binary packets, to cut out the one certificate
+        let certs = self
+            .run_with_status(
+                &["--import-options", "import-export", "--import"],
+                &data,
+                |_| (),
+            )?
+            .check()?
+            .stdout;
+        let mut cert = None;
+        for data in split_certs(&certs) {
+            let keys = self.show_keys(data)?;
+            if keys.len() == 1 && keys[0].fingerprint() == fingerprint {
+                cert = Some(data);
+                break;
+            }
+        }
+        let cert =
+            cert.ok_or_else(|| Error::other(format!("key {fingerprint} could not be read")))?;
+
+        let imported = self.import(cert)?;
+        if imported.keys.iter().any(|(key, _)| *key != fingerprint) {
+            return Err(Error::other(format!(
+                "importing {fingerprint} changed other keys as well"
+            )));
+        }
+        Ok(imported)
+    }
+}
+
+/// Whether `key` comes with secret key packets, also stubs of them
+fn has_secret_packets(key: &Key) -> bool {
+    std::iter::once(&key.primary)
+        .chain(&key.subkeys)
+        .any(|subkey| subkey.has_secret || subkey.is_stub || subkey.card_serial.is_some())
+}
+
+/// Whether `fingerprint` is the key a search result calls `key_id`, which
+/// old servers only give as long key ID
+fn matches_key_id(fingerprint: &str, key_id: &str) -> bool {
+    let key_id = key_id.trim_start_matches("0x").to_uppercase();
+    key_id.len() >= 16 && fingerprint.ends_with(&key_id)
+}
+
+/// Split binary OpenPGP data into its certificates, each starting with a
+/// public or secret key packet
+fn split_certs(data: &[u8]) -> Vec<&[u8]> {
+    let mut certs = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    while offset < data.len() {
+        let Some((tag, length)) = packet_header(&data[offset..]) else {
+            // Not something to cut, leave the rest in one piece
+            break;
+        };
+        if matches!(tag, 5 | 6) && offset > start {
+            certs.push(&data[start..offset]);
+            start = offset;
+        }
+        offset = offset.saturating_add(length).min(data.len());
+    }
+    certs.push(&data[start..]);
+    certs
+}
+
+/// Tag and total length of the packet at the start of `data`
+fn packet_header(data: &[u8]) -> Option<(u8, usize)> {
+    let header = *data.first()?;
+    if header & 0x80 == 0 {
+        return None;
+    }
+    let byte = |index: usize| data.get(index).map(|byte| *byte as usize);
+    if header & 0x40 != 0 {
+        // New format, partial body lengths do not occur in certificates
+        let tag = header & 0x3f;
+        match byte(1)? {
+            length @ 0..=191 => Some((tag, 2 + length)),
+            first @ 192..=223 => Some((tag, 3 + ((first - 192) << 8) + byte(2)? + 192)),
+            255 => {
+                let length =
+                    (1..5).try_fold(0, |length, index| Some(length << 8 | byte(1 + index)?))?;
+                Some((tag, 6 + length))
+            }
+            _ => None,
+        }
+    } else {
+        let tag = (header >> 2) & 0x0f;
+        match header & 0x03 {
+            0 => Some((tag, 2 + byte(1)?)),
+            1 => Some((tag, 3 + (byte(1)? << 8 | byte(2)?))),
+            2 => {
+                let length =
+                    (1..5).try_fold(0, |length, index| Some(length << 8 | byte(index)?))?;
+                Some((tag, 5 + length))
+            }
+            _ => None,
+        }
+    }
+}
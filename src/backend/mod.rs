@@ -0,0 +1,244 @@
+pub mod colons;
+pub mod component;
+pub mod decrypt;
+pub mod delete;
+pub mod edit;
+pub mod encrypt;
+pub mod expiry;
+pub mod export;
+pub mod generate;
+pub mod import;
+pub mod job;
+pub mod key;
+pub mod keyserver;
+pub mod network;
+pub mod options;
+pub mod refresh;
+pub mod status;
+pub mod subkey;
+pub mod update;
+pub mod user_id;
+pub mod version;
+
+use std::{
+    io::{BufRead, BufReader, Error, Read, Result, Write},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    thread,
+};
+
+use key::Key;
+use status::StatusLine;
+
+/// Handle to the GnuPG command line tool
+///
+/// All interaction with GnuPG goes through here, so the binary and the home
+/// directory only have to be chosen once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gpg {
+    /// Path (or name in `$PATH`) of the `gpg` binary
+    pub program: PathBuf,
+    /// Overrides `$GNUPGHOME` if set
+    pub homedir: Option<PathBuf>,
+}
+
+impl Default for Gpg {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::from("gpg"),
+            homedir: None,
+        }
+    }
+}
+
+impl Gpg {
+    pub fn new(program: impl Into<PathBuf>, homedir: Option<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            homedir,
+        }
+    }
+
+    /// A `gpg` command with the common options already applied
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        if let Some(homedir) = &self.homedir {
+            command.arg("--homedir").arg(homedir);
+        }
+        command.args(["--batch", "--no-tty"]);
+        command
+    }
+
+    /// List all public keys, marking the ones with secret material
+    pub fn list_keys(&self) -> Result<Vec<Key>> {
+        let mut keys = colons::parse_keys(&self.run_listing("--list-keys", &[])?);
+        let secret = colons::parse_keys(&self.run_listing("--list-secret-keys", &[])?);
+        colons::merge_secret(&mut keys, &secret);
+        Ok(keys)
+    }
+
+    /// List only the secret keys
+    pub fn list_secret_keys(&self) -> Result<Vec<Key>> {
+        Ok(colons::parse_keys(
+            &self.run_listing("--list-secret-keys", &[])?,
+        ))
+    }
+
+    /// List a single key including all signatures on it
+    pub fn key_details(&self, fingerprint: &str) -> Result<Key> {
+        let mut keys = colons::parse_keys(&self.run_listing("--check-sigs", &[fingerprint])?);
+        let secret = colons::parse_keys(
+            &self
+                .run_listing("--list-secret-keys", &[fingerprint])
+                .unwrap_or_default(),
+        );
+        colons::merge_secret(&mut keys, &secret);
+        keys.into_iter()
+            .find(|key| key.fingerprint() == fingerprint)
+            .ok_or_else(|| Error::other(format!("key {fingerprint} not found")))
+    }
+
+    /// Parse certificates from `data` without importing them
+    pub fn show_keys(&self, data: &[u8]) -> Result<Vec<Key>> {
+        let output = self
+            .run_with_status(
+                &["--with-colons", "--with-fingerprint", "--show-keys"],
+                data,
+                |_| (),
+            )?
+            .check()?;
+        Ok(colons::parse_keys(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Run gpg with `--status-fd 2`, feeding `input` to its stdin
+    ///
+    /// `on_status` sees every status line as soon as gpg prints it, which
+    /// allows reporting progress of slow operations.
+    pub fn run_with_status(
+        &self,
+        args: &[&str],
+        input: &[u8],
+        mut on_status: impl FnMut(&StatusLine),
+    ) -> Result<StatusOutput> {
+        let mut child = self
+            .command()
+            .args(["--status-fd", "2"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Feed stdin and drain stdout on their own threads, so a large input
+        // or output can not dead lock against the status lines on stderr
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_vec();
+        let writer = thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+        let mut stdout = child.stdout.take().unwrap();
+        let reader = thread::spawn(move || {
+            let mut data = Vec::new();
+            let _ = stdout.read_to_end(&mut data);
+            data
+        });
+
+        // gpg prints messages in the locale's charset, so stderr is not always UTF-8
+        let mut status = Vec::new();
+        let mut log = String::new();
+        let mut read_error = None;
+        for line in BufReader::new(child.stderr.take().unwrap()).split(b'\n') {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    read_error = Some(err);
+                    break;
+                }
+            };
+            let line = String::from_utf8_lossy(&line);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            match StatusLine::parse(line) {
+                Some(status_line) => {
+                    on_status(&status_line);
+                    status.push(status_line);
+                }
+                None => {
+                    log.push_str(line);
+                    log.push('\n');
+                }
+            }
+        }
+
+        // Always reap the child and the threads, also when stderr broke off
+        if read_error.is_some() {
+            let _ = child.kill();
+        }
+        let _ = writer.join();
+        let stdout = reader.join().unwrap_or_default();
+        let exit_status = child.wait()?;
+        if let Some(err) = read_error {
+            return Err(err);
+        }
+        Ok(StatusOutput {
+            success: exit_status.success(),
+            status,
+            log,
+            stdout,
+        })
+    }
+
+    fn run_listing(&self, command: &str, patterns: &[&str]) -> Result<String> {
+        let output = self
+            .command()
+            .args([
+                "--with-colons",
+                "--with-fingerprint",
+                "--with-keygrip",
+                command,
+            ])
+            .args(patterns)
+            .output()?;
+        check_output(&output)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Result of [`Gpg::run_with_status`]
+#[derive(Debug, Clone, Default)]
+pub struct StatusOutput {
+    pub success: bool,
+    pub status: Vec<StatusLine>,
+    /// Human readable messages gpg printed on stderr
+    pub log: String,
+    pub stdout: Vec<u8>,
+}
+
+impl StatusOutput {
+    /// Turn a failed invocation into an error carrying its log
+    pub fn check(self) -> Result<Self> {
+        if self.success {
+            Ok(self)
+        } else {
+            Err(Error::other(format!("gpg failed: {}", self.log.trim())))
+        }
+    }
+
+    /// The first status line with `keyword`
+    pub fn find(&self, keyword: &str) -> Option<&StatusLine> {
+        self.status.iter().find(|line| line.keyword == keyword)
+    }
+}
+
+/// Turn a failed gpg invocation into an error carrying its stderr
+pub fn check_output(output: &Output) -> Result<()> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(Error::other(format!(
+            "gpg failed ({}): {}",
+            output.status,
+            stderr.trim()
+        )))
+    }
+}
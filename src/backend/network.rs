@@ -0,0 +1,201 @@
+use std::{
+    io::{Error, Result},
+    time::Duration,
+};
+
+use super::{
+    keyserver::check_keyserver,
+    options::{ConfOption, OptionValue},
+    Gpg,
+};
+
+/// `auto-key-locate` mechanisms which can be toggled, in their usual order
+pub const LOCATE_MECHANISMS: [&str; 4] = ["local", "wkd", "dane", "keyserver"];
+
+/// What gpg uses when `auto-key-locate` is not set
+const DEFAULT_LOCATE: [&str; 2] = ["local", "wkd"];
+
+/// How dirmngr connects to the network
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Proxy {
+    #[default]
+    Direct,
+    System,       // Honor `http_proxy` from the environment
+    Http(String), // Http(URL)
+    Tor,
+}
+
+impl Proxy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Proxy::Direct => "Direct connection",
+            Proxy::System => "System proxy ($http_proxy)",
+            Proxy::Http(_) => "HTTP proxy",
+            Proxy::Tor => "Tor",
+        }
+    }
+
+    /// An HTTP agent connecting the way dirmngr does with this setting
+    ///
+    /// Only dirmngr can talk to Tor, so with Tor this is an error instead of
+    /// a direct connection.
+    pub fn http_agent(&self, timeout: Duration) -> Result<ureq::Agent> {
+        let builder = ureq::AgentBuilder::new().timeout(timeout);
+        let builder = match self {
+            Proxy::Direct => builder,
+            Proxy::System => builder.try_proxy_from_env(true),
+            Proxy::Http(url) => builder.proxy(
+                ureq::Proxy::new(url)
+                    .map_err(|err| Error::other(format!("invalid proxy {url}: {err}")))?,
+            ),
+            Proxy::Tor => {
+                return Err(Error::other(
+                    "Tor is enabled in Setting > Server, refusing to connect without it",
+                ))
+            }
+        };
+        Ok(builder.build())
+    }
+}
+
+/// Keyserver and key discovery settings of dirmngr and gpg
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerSettings {
+    /// Keyservers in order of preference, the first one is the default
+    pub keyservers: Vec<String>,
+    /// `auto-key-locate` mechanisms in order, e.g. `["local", "wkd"]`
+    pub auto_key_locate: Vec<String>,
+    pub proxy: Proxy,
+}
+
+impl ServerSettings {
+    /// Whether gpg tries `mechanism` when looking up a key by mail address
+    pub fn locates_with(&self, mechanism: &str) -> bool {
+        if mechanism == "local" && !self.auto_key_locate.iter().any(|m| m == "nodefault") {
+            // The local keyring is always tried first unless `nodefault` is given
+            return true;
+        }
+        self.auto_key_locate.iter().any(|m| m == mechanism)
+    }
+
+    /// Turn `mechanism` on or off
+    pub fn set_locate(&mut self, mechanism: &str, enabled: bool) {
+        if mechanism == "local" {
+            self.auto_key_locate.retain(|m| m != "nodefault");
+            if !enabled {
+                self.auto_key_locate.push("nodefault".to_string());
+            }
+        }
+        self.auto_key_locate.retain(|m| m != mechanism);
+        if enabled {
+            // The local keyring goes first, it needs no network access
+            let position = if mechanism == "local" {
+                0
+            } else {
+                self.auto_key_locate.len()
+            };
+            self.auto_key_locate.insert(position, mechanism.to_string());
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for server in self.keyservers.iter() {
+            check_keyserver(server)?;
+        }
+        if let Proxy::Http(url) = &self.proxy {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(Error::other("the proxy needs an http:// or https:// URL"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find<'a>(options: &'a [ConfOption], name: &str) -> Option<&'a ConfOption> {
+    options.iter().find(|option| option.name == name)
+}
+
+fn flag(enabled: bool) -> OptionValue {
+    if enabled {
+        OptionValue::Enabled
+    } else {
+        OptionValue::Default
+    }
+}
+
+impl Gpg {
+    /// Read the keyserver, `auto-key-locate` and proxy settings via gpgconf
+    pub fn server_settings(&self) -> Result<ServerSettings> {
+        let dirmngr = self.list_options("dirmngr")?;
+        let gpg = self.list_options("gpg")?;
+        let is_set = |name: &str| find(&dirmngr, name).is_some_and(ConfOption::is_set);
+
+        let proxy = if is_set("use-tor") {
+            Proxy::Tor
+        } else if let Some(url) = find(&dirmngr, "http-proxy").and_then(|o| o.values.first()) {
+            Proxy::Http(url.clone())
+        } else if is_set("honor-http-proxy") {
+            Proxy::System
+        } else {
+            Proxy::Direct
+        };
+        let auto_key_locate = match find(&gpg, "auto-key-locate").and_then(|o| o.values.first()) {
+            Some(value) => value
+                .split(',')
+                .map(str::trim)
+                .filter(|mechanism| !mechanism.is_empty() && *mechanism != "clear")
+                .map(str::to_string)
+                .collect(),
+            None => DEFAULT_LOCATE.map(str::to_string).to_vec(),
+        };
+        Ok(ServerSettings {
+            keyservers: find(&dirmngr, "keyserver")
+                .map(|option| match option.is_set() {
+                    true => option.values.clone(),
+                    false => option.defaults.clone(),
+                })
+                .unwrap_or_default(),
+            auto_key_locate,
+            proxy,
+        })
+    }
+
+    /// Write `settings` to `dirmngr.conf` and `gpg.conf`
+    ///
+    /// Returns whether all keyservers were stored, older dirmngr versions
+    /// only keep one.
+    pub fn save_server_settings(&self, settings: &ServerSettings) -> Result<bool> {
+        settings.validate()?;
+        let dirmngr = self.list_options("dirmngr")?;
+        let keeps_all = find(&dirmngr, "keyserver").is_some_and(ConfOption::is_list);
+        let keyservers = match settings.keyservers.as_slice() {
+            [] => OptionValue::Default,
+            all if keeps_all => OptionValue::Strings(all.to_vec()),
+            [default, ..] => OptionValue::Strings(vec![default.clone()]),
+        };
+        let http_proxy = match &settings.proxy {
+            Proxy::Http(url) => OptionValue::Strings(vec![url.clone()]),
+            _ => OptionValue::Default,
+        };
+        self.change_options(
+            "dirmngr",
+            &[
+                ("keyserver", keyservers),
+                ("use-tor", flag(settings.proxy == Proxy::Tor)),
+                ("http-proxy", http_proxy),
+                ("honor-http-proxy", flag(settings.proxy == Proxy::System)),
+            ],
+        )?;
+
+        let auto_key_locate = if settings.auto_key_locate == DEFAULT_LOCATE {
+            OptionValue::Default
+        } else if settings.auto_key_locate.is_empty() {
+            // Only the implied local keyring
+            OptionValue::Strings(vec!["local".to_string()])
+        } else {
+            OptionValue::Strings(vec![settings.auto_key_locate.join(",")])
+        };
+        self.change_options("gpg", &[("auto-key-locate", auto_key_locate)])?;
+        Ok(keeps_all || settings.keyservers.len() <= 1)
+    }
+}
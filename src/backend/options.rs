@@ -0,0 +1,126 @@
+use std::{
+    io::{Error, Result, Write},
+    process::Stdio,
+};
+
+use super::{check_output, keyserver::url_decode, Gpg};
+
+/// Flag of `gpgconf --list-options`: the option takes a list of values
+const FLAG_LIST: u32 = 4;
+
+/// Flag of `gpgconf --change-options`: remove the option from the config file
+const FLAG_DEFAULT: u32 = 16;
+
+/// An option of a component as listed by `gpgconf --list-options`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfOption {
+    pub name: String,
+    pub flags: u32,
+    pub description: String,
+    /// Values GnuPG uses if the option is not set, if it tells
+    pub defaults: Vec<String>,
+    /// Current values, empty if the option is not set
+    pub values: Vec<String>,
+}
+
+impl ConfOption {
+    pub fn is_list(&self) -> bool {
+        self.flags & FLAG_LIST != 0
+    }
+
+    pub fn is_set(&self) -> bool {
+        !self.values.is_empty()
+    }
+}
+
+/// New value of an option for `gpgconf --change-options`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Default, // Remove the option, GnuPG uses its default again
+    Enabled, // A flag without argument
+    Strings(Vec<String>),
+}
+
+/// Parse the value field of `--list-options`, e.g. `"hkps%3a//a,"hkp%3a//b`
+pub fn parse_option_values(field: &str) -> Vec<String> {
+    if field.is_empty() {
+        return Vec::new();
+    }
+    field
+        .split(',')
+        .map(|value| url_decode(value.strip_prefix('"').unwrap_or(value)))
+        .collect()
+}
+
+/// Escape a string argument for `--change-options`
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::from('"');
+    for ch in value.chars() {
+        match ch {
+            '%' => escaped.push_str("%25"),
+            ':' => escaped.push_str("%3a"),
+            ',' => escaped.push_str("%2c"),
+            '\n' => escaped.push_str("%0a"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// A line of `--change-options` input
+pub fn change_line(name: &str, value: &OptionValue) -> String {
+    match value {
+        OptionValue::Default => format!("{name}:{FLAG_DEFAULT}:"),
+        OptionValue::Enabled => format!("{name}:0:1"),
+        OptionValue::Strings(values) => {
+            let values: Vec<String> = values.iter().map(|value| escape_value(value)).collect();
+            format!("{name}:0:{}", values.join(","))
+        }
+    }
+}
+
+impl Gpg {
+    /// Options of `component`, e.g. `gpg` or `dirmngr`
+    pub fn list_options(&self, component: &str) -> Result<Vec<ConfOption>> {
+        let output = self
+            .gpgconf()
+            .args(["--list-options", component])
+            .output()?;
+        check_output(&output)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(':').collect();
+                Some(ConfOption {
+                    name: fields.first()?.to_string(),
+                    flags: fields.get(1)?.parse().ok()?,
+                    description: url_decode(fields.get(3)?),
+                    defaults: parse_option_values(fields.get(7)?),
+                    values: parse_option_values(fields.get(9)?),
+                })
+            })
+            .collect())
+    }
+
+    /// Change options of `component` in its config file and reload the daemon
+    pub fn change_options(&self, component: &str, changes: &[(&str, OptionValue)]) -> Result<()> {
+        let mut child = self
+            .gpgconf()
+            .args(["--change-options", component])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut input = String::new();
+        for (name, value) in changes {
+            input.push_str(&change_line(name, value));
+            input.push('\n');
+        }
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::other("gpgconf stdin unavailable"))?
+            .write_all(input.as_bytes())?;
+        check_output(&child.wait_with_output()?)
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{Error, Result};
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    import::parse_import,
+    key::{Key, Subkey, Validity},
+    Gpg,
+};
+
+/// Where updated certificates are looked up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshSource {
+    Keyserver(String), // Keyserver(URL), e.g. `hkps://keys.openpgp.org`
+    Wkd,               // The Web Key Directory of each key's e-mail domain
+}
+
+/// Something which changed on a certificate during a refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertChange {
+    NewSubkey(String), // NewSubkey(Key ID)
+    NewUserId(String),
+    Expiry {
+        key_id: String,
+        old: Option<DateTime<Utc>>,
+        new: Option<DateTime<Utc>>,
+    },
+    KeyRevoked(String), // KeyRevoked(Key ID), the primary key or a subkey
+    UserIdRevoked(String),
+}
+
+impl CertChange {
+    pub fn describe(&self) -> String {
+        let date = |date: &Option<DateTime<Utc>>| match date {
+            Some(date) => date.format("%Y-%m-%d").to_string(),
+            None => "never".to_string(),
+        };
+        match self {
+            CertChange::NewSubkey(key_id) => format!("new subkey {key_id}"),
+            CertChange::NewUserId(uid) => format!("new user ID {uid}"),
+            CertChange::Expiry { key_id, old, new } => {
+                let extended = match (old, new) {
+                    (_, None) => true,
+                    (None, Some(_)) => false,
+                    (Some(old), Some(new)) => new > old,
+                };
+                let verb = if extended { "extended" } else { "shortened" };
+                format!(
+                    "expiry of {key_id} {verb} from {} to {}",
+                    date(old),
+                    date(new)
+                )
+            }
+            CertChange::KeyRevoked(key_id) => format!("key {key_id} was REVOKED"),
+            CertChange::UserIdRevoked(uid) => format!("user ID {uid} was revoked"),
+        }
+    }
+}
+
+/// Compare two listings of the same certificate
+pub fn diff_keys(old: &Key, new: &Key) -> Vec<CertChange> {
+    let mut changes = Vec::new();
+    let old_keys: Vec<&Subkey> = [&old.primary].into_iter().chain(&old.subkeys).collect();
+    for subkey in [&new.primary].into_iter().chain(&new.subkeys) {
+        let Some(previous) = old_keys
+            .iter()
+            .find(|previous| previous.fingerprint == subkey.fingerprint)
+        else {
+            changes.push(CertChange::NewSubkey(subkey.key_id.clone()));
+            continue;
+        };
+        if subkey.validity == Validity::Revoked && previous.validity != Validity::Revoked {
+            changes.push(CertChange::KeyRevoked(subkey.key_id.clone()));
+        }
+        if subkey.expires != previous.expires {
+            changes.push(CertChange::Expiry {
+                key_id: subkey.key_id.clone(),
+                old: previous.expires,
+                new: subkey.expires,
+            });
+        }
+    }
+    for uid in new.user_ids.iter().filter(|uid| !uid.is_attribute) {
+        match old.user_ids.iter().find(|previous| previous.uid == uid.uid) {
+            None => changes.push(CertChange::NewUserId(uid.uid.clone())),
+            Some(previous)
+                if uid.validity == Validity::Revoked && previous.validity != Validity::Revoked =>
+            {
+                changes.push(CertChange::UserIdRevoked(uid.uid.clone()))
+            }
+            _ => (),
+        }
+    }
+    changes
+}
+
+/// Result of refreshing a single certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshOutcome {
+    pub fingerprint: String,
+    pub user_id: String,
+    /// What changed, or why the refresh failed
+    pub result: std::result::Result<Vec<CertChange>, String>,
+}
+
+impl Gpg {
+    /// List a single public key
+    pub fn find_key(&self, fingerprint: &str) -> Result<Key> {
+        super::colons::parse_keys(&self.run_listing("--list-keys", &[fingerprint])?)
+            .into_iter()
+            .find(|key| key.fingerprint() == fingerprint)
+            .ok_or_else(|| Error::other(format!("key {fingerprint} not found")))
+    }
+
+    /// Update `key` from `source` and report what changed
+    pub fn refresh_key(&self, key: &Key, source: &RefreshSource) -> Result<Vec<CertChange>> {
+        let fingerprint = key.fingerprint();
+        let output = match source {
+            RefreshSource::Keyserver(server) => self.run_with_status(
+                &["--keyserver", server, "--refresh-keys", fingerprint],
+                &[],
+                |_| (),
+            )?,
+            RefreshSource::Wkd => {
+                let email = key
+                    .user_ids
+                    .iter()
+                    .filter(|uid| uid.validity != Validity::Revoked)
+                    .find_map(|uid| uid.email())
+                    .ok_or_else(|| Error::other("no e-mail address to look up"))?;
+                self.run_with_status(
+                    &[
+                        "--auto-key-locate",
+                        "clear,wkd,nodefault",
+                        "--locate-external-keys",
+                        email,
+                    ],
+                    &[],
+                    |_| (),
+                )?
+            }
+        };
+        // gpg fails if the server sent unrelated keys as well, so look for
+        // the key itself among the imported ones
+        let imported = parse_import(&output.status);
+        if !imported
+            .keys
+            .iter()
+            .any(|(imported, _)| imported == fingerprint)
+        {
+            let reason = output.log.lines().last().unwrap_or("refresh failed");
+            return Err(Error::other(reason.trim_start_matches("gpg: ").to_string()));
+        }
+        Ok(diff_keys(key, &self.find_key(fingerprint)?))
+    }
+
+    /// Refresh `keys` one after the other, calling `on_progress` before each
+    pub fn refresh_keys(
+        &self,
+        keys: &[Key],
+        source: &RefreshSource,
+        mut on_progress: impl FnMut(usize, &Key),
+    ) -> Vec<RefreshOutcome> {
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| {
+                on_progress(i, key);
+                RefreshOutcome {
+                    fingerprint: key.fingerprint().to_string(),
+                    user_id: key
+                        .primary_uid()
+                        .map(|uid| uid.uid.clone())
+                        .unwrap_or_default(),
+                    result: self.refresh_key(key, source).map_err(|err| err.to_string()),
+                }
+            })
+            .collect()
+    }
+}
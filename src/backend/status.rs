@@ -0,0 +1,37 @@
+//! Parser for the machine readable `--status-fd` output of GnuPG
+//!
+//! See `doc/DETAILS` in the GnuPG source tree for the keywords.
+
+/// A single `[GNUPG:] KEYWORD args...` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusLine {
+    pub keyword: String,
+    pub args: Vec<String>,
+}
+
+impl StatusLine {
+    /// Parse a status line, returning `None` for any other output
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut words = line.strip_prefix("[GNUPG:] ")?.split(' ');
+        let keyword = words.next()?.to_string();
+        Some(Self {
+            keyword,
+            args: words.map(str::to_string).collect(),
+        })
+    }
+
+    /// Argument `index` (0-based), or an empty string
+    pub fn arg(&self, index: usize) -> &str {
+        self.args.get(index).map(String::as_str).unwrap_or("")
+    }
+
+    /// All arguments from `index` on joined back together, e.g. for user IDs
+    pub fn rest(&self, index: usize) -> String {
+        self.args.get(index..).unwrap_or_default().join(" ")
+    }
+}
+
+/// Parse all status lines of an output, skipping everything else
+pub fn parse_status(output: &str) -> Vec<StatusLine> {
+    output.lines().filter_map(StatusLine::parse).collect()
+}
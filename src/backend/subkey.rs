@@ -0,0 +1,86 @@
+use std::io::Result;
+
+use super::{
+    edit::revocation_answers,
+    generate::KeyAlgorithm,
+    key::{Capabilities, RevocationReason},
+    Gpg,
+};
+
+/// What a new subkey is used for
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubkeyUsage {
+    #[default]
+    Encrypt,
+    Sign,
+    Authenticate,
+}
+
+impl SubkeyUsage {
+    pub const ALL: [SubkeyUsage; 3] = [
+        SubkeyUsage::Encrypt,
+        SubkeyUsage::Sign,
+        SubkeyUsage::Authenticate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubkeyUsage::Encrypt => "Encrypt",
+            SubkeyUsage::Sign => "Sign",
+            SubkeyUsage::Authenticate => "Authenticate",
+        }
+    }
+
+    /// The usage of an existing subkey, encryption wins if it has several
+    pub fn of(capabilities: &Capabilities) -> Self {
+        if capabilities.encrypt {
+            SubkeyUsage::Encrypt
+        } else if capabilities.sign {
+            SubkeyUsage::Sign
+        } else if capabilities.authenticate {
+            SubkeyUsage::Authenticate
+        } else {
+            SubkeyUsage::default()
+        }
+    }
+
+    /// Usage string of `--quick-add-key`
+    pub fn usage(&self) -> &'static str {
+        match self {
+            SubkeyUsage::Encrypt => "encr",
+            SubkeyUsage::Sign => "sign",
+            SubkeyUsage::Authenticate => "auth",
+        }
+    }
+
+    /// Algorithm string of `--quick-add-key`, the curves differ for encryption
+    pub fn algo(&self, algorithm: KeyAlgorithm) -> &'static str {
+        match self {
+            SubkeyUsage::Encrypt => algorithm.subkey_algo(),
+            _ => algorithm.primary_algo(),
+        }
+    }
+}
+
+impl Gpg {
+    /// Revoke the subkey `subkey` (a fingerprint) of the key with `fingerprint`
+    ///
+    /// There is no `--quick-*` command for this, so it goes through `--edit-key`.
+    pub fn revoke_subkey(
+        &self,
+        fingerprint: &str,
+        subkey: &str,
+        reason: RevocationReason,
+        description: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        let mut script = vec![
+            ("keyedit.prompt", format!("key {subkey}")),
+            ("keyedit.prompt", "revkey".to_string()),
+            ("keyedit.revoke.subkey.okay", "y".to_string()),
+        ];
+        script.extend(revocation_answers(reason, description));
+        script.push(("keyedit.prompt", "save".to_string()));
+        self.edit_key(fingerprint, &script, passphrase)
+    }
+}
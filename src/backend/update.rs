@@ -0,0 +1,412 @@
+use std::{
+    fs,
+    io::{Error, Read, Result},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde_json::Value;
+
+use super::{
+    decrypt::{parse_decrypt, SignatureStatus, TempFile},
+    keyserver::url_decode,
+    network::Proxy,
+    Gpg,
+};
+
+/// Releases of gpg4terminal as published on GitHub
+pub const DEFAULT_UPDATE_FEED: &str = "https://github.com/Oct-autumn/gpg4terminal/releases.atom";
+
+/// Largest feed or signature accepted
+const MAX_FEED: u64 = 4 * 1024 * 1024;
+
+/// A release listed in the feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: Version,
+    pub title: String,
+    pub published: Option<DateTime<Utc>>,
+    /// The changelog as plain text
+    pub notes: String,
+    /// Where to download the release
+    pub link: Option<String>,
+}
+
+/// Outcome of [`Gpg::check_update`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateCheck {
+    pub current: Version,
+    /// Releases newer than `current`, newest first
+    pub newer: Vec<Release>,
+    /// Primary fingerprint of the release key which signed the feed, if checked
+    pub signed_by: Option<String>,
+}
+
+/// The version of this build
+pub fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("package version is semver")
+}
+
+/// Check that `url` is a feed location [`fetch_feed`] can read
+pub fn check_feed_url(url: &str) -> Result<()> {
+    match url.split_once("://") {
+        Some(("file", path)) if path.starts_with('/') => Ok(()),
+        Some(("http" | "https", host)) if !host.is_empty() => Ok(()),
+        _ => Err(Error::other(format!(
+            "unsupported feed URL {url}, use file://, http:// or https://"
+        ))),
+    }
+}
+
+/// Read `url`, either a local `file://` or an HTTP(S) URL fetched through `proxy`
+pub fn fetch_feed(url: &str, proxy: &Proxy) -> Result<Vec<u8>> {
+    check_feed_url(url)?;
+    if let Some(path) = url.strip_prefix("file://") {
+        let mut data = Vec::new();
+        fs::File::open(url_decode(path))?
+            .take(MAX_FEED)
+            .read_to_end(&mut data)?;
+        return Ok(data);
+    }
+    let agent = proxy.http_agent(Duration::from_secs(30))?;
+    match agent.get(url).call() {
+        Ok(response) => {
+            let mut data = Vec::new();
+            response
+                .into_reader()
+                .take(MAX_FEED)
+                .read_to_end(&mut data)?;
+            Ok(data)
+        }
+        Err(err) => Err(Error::other(format!("fetching {url} failed: {err}"))),
+    }
+}
+
+/// Parse a JSON or Atom release feed, the format is guessed from the content
+///
+/// JSON is either a list of releases as returned by the GitHub API
+/// (`tag_name`, `body`, `html_url`, `published_at`), or an object with a
+/// `releases` list using `version`, `notes`, `url` and `date`. Entries
+/// without a semver version are skipped.
+pub fn parse_feed(text: &str) -> Result<Vec<Release>> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with('[') || text.starts_with('{') {
+        parse_json_feed(text)
+    } else if text.starts_with('<') {
+        Ok(parse_atom_feed(text))
+    } else {
+        Err(Error::other("the feed is neither JSON nor Atom"))
+    }
+}
+
+fn parse_json_feed(text: &str) -> Result<Vec<Release>> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|err| Error::other(format!("invalid feed: {err}")))?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(object) => match object.get("releases") {
+            Some(Value::Array(entries)) => entries,
+            _ => return Err(Error::other("invalid feed: no `releases` list")),
+        },
+        _ => return Err(Error::other("invalid feed: expected a list of releases")),
+    };
+    let field = |entry: &Value, names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| entry.get(name)?.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    Ok(entries
+        .iter()
+        .filter(|entry| !entry.get("draft").and_then(Value::as_bool).unwrap_or(false))
+        .filter_map(|entry| {
+            let tag = field(entry, &["tag_name", "version"]);
+            let title = field(entry, &["name", "title"]);
+            Some(Release {
+                version: parse_version_tag(&tag)?,
+                title: if title.is_empty() { tag } else { title },
+                published: parse_feed_date(&field(entry, &["published_at", "date"])),
+                notes: field(entry, &["body", "notes", "changelog"]),
+                link: Some(field(entry, &["html_url", "url"])).filter(|link| !link.is_empty()),
+            })
+        })
+        .collect())
+}
+
+fn parse_atom_feed(text: &str) -> Vec<Release> {
+    xml_elements(text, "entry")
+        .into_iter()
+        .filter_map(|entry| {
+            let title = xml_text(xml_elements(entry, "title").first().copied().unwrap_or(""));
+            let id = xml_text(xml_elements(entry, "id").first().copied().unwrap_or(""));
+            let link = xml_attribute(entry, "link", "href");
+            // GitHub puts the tag at the end of the id and the link
+            let version = parse_version_tag(&title)
+                .or_else(|| parse_version_tag(id.rsplit('/').next()?))
+                .or_else(|| parse_version_tag(link.as_deref()?.rsplit('/').next()?))?;
+            let notes = xml_elements(entry, "content")
+                .first()
+                .or(xml_elements(entry, "summary").first())
+                .map(|content| html_to_text(&xml_text(content)))
+                .unwrap_or_default();
+            let date = xml_elements(entry, "updated")
+                .first()
+                .or(xml_elements(entry, "published").first())
+                .map(|date| xml_text(date))
+                .unwrap_or_default();
+            Some(Release {
+                version,
+                title,
+                published: parse_feed_date(&date),
+                notes,
+                link,
+            })
+        })
+        .collect()
+}
+
+/// A version in a tag or title like `v1.2.0` or `gpg4terminal 1.2.0`
+pub fn parse_version_tag(tag: &str) -> Option<Version> {
+    tag.split_whitespace().find_map(|word| {
+        let word = word.strip_prefix(['v', 'V']).unwrap_or(word);
+        Version::parse(word).ok()
+    })
+}
+
+fn parse_feed_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date.trim())
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// The inner text of every `<tag>` element, not descending into nested ones
+fn xml_elements<'a>(text: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut elements = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // `<entry>` must not match `<entryfoo>`
+        if !after.starts_with(['>', ' ', '\t', '\r', '\n', '/']) {
+            rest = after;
+            continue;
+        }
+        let Some(end_of_open) = after.find('>') else {
+            break;
+        };
+        if after[..end_of_open].ends_with('/') {
+            elements.push("");
+            rest = &after[end_of_open + 1..];
+            continue;
+        }
+        let body = &after[end_of_open + 1..];
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        elements.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    elements
+}
+
+/// The value of `attribute` of the first `<tag>` element
+fn xml_attribute(text: &str, tag: &str, attribute: &str) -> Option<String> {
+    let start = text.find(&format!("<{tag} "))?;
+    let element = &text[start..start + text[start..].find('>')?];
+    let pattern = format!("{attribute}=\"");
+    let value = &element[element.find(&pattern)? + pattern.len()..];
+    Some(xml_unescape(&value[..value.find('"')?]))
+}
+
+/// Character data of an element, with CDATA sections and entities resolved
+fn xml_text(text: &str) -> String {
+    let text = text.trim();
+    match text
+        .strip_prefix("<![CDATA[")
+        .and_then(|text| text.strip_suffix("]]>"))
+    {
+        Some(data) => data.to_string(),
+        None => xml_unescape(text),
+    }
+}
+
+fn xml_unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';').filter(|end| *end < 10) else {
+            unescaped.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let ch = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#')?.parse().ok())
+                .and_then(char::from_u32),
+        };
+        match ch {
+            Some(ch) => {
+                unescaped.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Reduce the HTML of a release note to readable plain text
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = rest[start + 1..start + end]
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match tag.as_str() {
+            "br" | "br/" | "/p" | "/h1" | "/h2" | "/h3" | "/h4" | "/li" | "/pre" => text.push('\n'),
+            "li" => text.push_str("- "),
+            _ => (),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    let text = xml_unescape(&text);
+    // Drop the blank lines left by the markup
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if !(line.trim().is_empty() && lines.last().is_none_or(|last| last.is_empty())) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Releases newer than `current`, newest first
+pub fn newer_releases(mut releases: Vec<Release>, current: &Version) -> Vec<Release> {
+    releases.retain(|release| release.version > *current);
+    releases.sort_by(|a, b| b.version.cmp(&a.version));
+    releases.dedup_by(|a, b| a.version == b.version);
+    releases
+}
+
+/// A fingerprint as entered, without spaces or `0x`, in upper case
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    let fingerprint: String = fingerprint.split_whitespace().collect();
+    fingerprint.trim_start_matches("0x").to_ascii_uppercase()
+}
+
+/// Whether `fingerprint` looks like a v4 fingerprint
+pub fn is_fingerprint(fingerprint: &str) -> bool {
+    let fingerprint = normalize_fingerprint(fingerprint);
+    fingerprint.len() == 40 && fingerprint.chars().all(|ch| ch.is_ascii_hexdigit())
+}
+
+impl Gpg {
+    /// Verify the detached `signature` over `data`, made by the key `release_key`
+    ///
+    /// Returns the signer's primary fingerprint. Any signature by a different
+    /// key, or a signature which is not good, is an error.
+    pub fn verify_release_signature(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        release_key: &str,
+    ) -> Result<String> {
+        let release_key = normalize_fingerprint(release_key);
+        let signature_file = TempFile::with_data(signature)?;
+        let data_file = TempFile::with_data(data)?;
+        let output = self.run_with_status(
+            &[
+                "--verify",
+                &signature_file.0.to_string_lossy(),
+                &data_file.0.to_string_lossy(),
+            ],
+            &[],
+            |_| (),
+        )?;
+        let result = parse_decrypt(&output.status);
+        // Bad signatures only carry the key ID
+        let by_release_key =
+            result
+                .signatures
+                .iter()
+                .find(|signature| match &signature.primary_fingerprint {
+                    Some(fingerprint) => *fingerprint == release_key,
+                    None => {
+                        !signature.key_id.is_empty() && release_key.ends_with(&signature.key_id)
+                    }
+                });
+        if let Some(signature) = by_release_key {
+            return match signature.status.is_good() {
+                true => Ok(release_key),
+                false => Err(Error::other(format!(
+                    "feed signature: {}",
+                    signature.status.describe()
+                ))),
+            };
+        }
+        Err(Error::other(match result.signatures.first() {
+            Some(signature) if signature.status == SignatureStatus::MissingKey => format!(
+                "the feed is signed by the unknown key {}, import the release key {release_key}",
+                signature.key_id
+            ),
+            Some(signature) => format!(
+                "the feed is signed by {}, not by the release key {release_key}",
+                signature
+                    .primary_fingerprint
+                    .as_deref()
+                    .unwrap_or(&signature.key_id)
+            ),
+            None => format!("no signature on the feed: {}", output.log.trim()),
+        }))
+    }
+
+    /// Fetch the release feed and list the releases newer than this build
+    ///
+    /// With a `release_key` the feed must carry a detached signature at
+    /// `<feed>.sig` made by that key, otherwise the feed is rejected.
+    pub fn check_update(&self, feed: &str, release_key: Option<&str>) -> Result<UpdateCheck> {
+        // Connect the way dirmngr does, see `Proxy::http_agent`
+        let proxy = self.server_settings()?.proxy;
+        let data = fetch_feed(feed, &proxy)?;
+        let signed_by = match release_key {
+            Some(release_key) => {
+                let signature = fetch_feed(&format!("{feed}.sig"), &proxy)
+                    .map_err(|err| Error::other(format!("no feed signature: {err}")))?;
+                Some(self.verify_release_signature(&data, &signature, release_key)?)
+            }
+            None => None,
+        };
+        let releases = parse_feed(&String::from_utf8_lossy(&data))?;
+        let current = current_version();
+        Ok(UpdateCheck {
+            newer: newer_releases(releases, &current),
+            current,
+            signed_by,
+        })
+    }
+}
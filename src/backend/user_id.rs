@@ -0,0 +1,79 @@
+use std::io::Result;
+
+use super::{
+    edit::revocation_answers,
+    key::{RevocationReason, UserId},
+    Gpg,
+};
+
+impl Gpg {
+    /// Add `user_id` to the key with `fingerprint`
+    pub fn add_user_id(
+        &self,
+        fingerprint: &str,
+        user_id: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        self.quick_user_id_command("--quick-add-uid", fingerprint, user_id, passphrase)
+    }
+
+    /// Make `user_id` the primary user ID of the key with `fingerprint`
+    pub fn set_primary_user_id(
+        &self,
+        fingerprint: &str,
+        user_id: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        self.quick_user_id_command("--quick-set-primary-uid", fingerprint, user_id, passphrase)
+    }
+
+    /// Revoke `user_id` of the key with `fingerprint`
+    ///
+    /// `--quick-revoke-uid` always states that the user ID is no longer valid,
+    /// any other reason or a description goes through `--edit-key`.
+    pub fn revoke_user_id(
+        &self,
+        fingerprint: &str,
+        user_id: &UserId,
+        reason: RevocationReason,
+        description: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        if reason == RevocationReason::UserIdInvalid && description.trim().is_empty() {
+            return self.quick_user_id_command(
+                "--quick-revoke-uid",
+                fingerprint,
+                &user_id.uid,
+                passphrase,
+            );
+        }
+        // The hash picks the user ID even if another one has the same text
+        let mut script = vec![
+            ("keyedit.prompt", format!("uid {}", user_id.hash)),
+            ("keyedit.prompt", "revuid".to_string()),
+            ("keyedit.revoke.uid.okay", "y".to_string()),
+        ];
+        script.extend(revocation_answers(reason, description));
+        script.push(("keyedit.prompt", "save".to_string()));
+        self.edit_key(fingerprint, &script, passphrase)
+    }
+
+    fn quick_user_id_command(
+        &self,
+        command: &str,
+        fingerprint: &str,
+        user_id: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        let mut args = Vec::new();
+        if passphrase.is_some() {
+            args.extend(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+        }
+        args.extend([command, fingerprint, user_id]);
+        let stdin = passphrase
+            .map(|passphrase| format!("{passphrase}\n").into_bytes())
+            .unwrap_or_default();
+        self.run_with_status(&args, &stdin, |_| ())?.check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::Result;
+
+use super::{check_output, Gpg};
+
+/// What `gpg --version` reports
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GpgVersion {
+    /// The version number, e.g. `2.2.40`
+    pub version: String,
+    /// Version lines of the libraries, e.g. `libgcrypt 1.10.1`
+    pub libraries: Vec<String>,
+    pub home: String,
+    /// Supported algorithms by kind, e.g. `("Hash", "SHA1, SHA256")`
+    pub algorithms: Vec<(String, String)>,
+    /// The whole output
+    pub text: String,
+}
+
+/// Parse the output of `gpg --version`
+pub fn parse_version(text: &str) -> GpgVersion {
+    let mut info = GpgVersion {
+        text: text.to_string(),
+        ..Default::default()
+    };
+    let mut in_algorithms = false;
+    for (i, line) in text.lines().enumerate() {
+        if i == 0 {
+            info.version = line.rsplit(' ').next().unwrap_or_default().to_string();
+        } else if line.starts_with("lib") {
+            info.libraries.push(line.to_string());
+        } else if let Some(home) = line.strip_prefix("Home:") {
+            info.home = home.trim().to_string();
+        } else if line.starts_with("Supported algorithms") {
+            in_algorithms = true;
+        } else if in_algorithms && line.starts_with(char::is_whitespace) {
+            // Long lists are wrapped onto indented lines
+            if let Some((_, list)) = info.algorithms.last_mut() {
+                list.push(' ');
+                list.push_str(line.trim());
+            }
+        } else if let Some((kind, list)) = line.split_once(':').filter(|_| in_algorithms) {
+            info.algorithms
+                .push((kind.trim().to_string(), list.trim().to_string()));
+        }
+    }
+    info
+}
+
+impl Gpg {
+    /// Version, home directory and supported algorithms of gpg
+    pub fn version(&self) -> Result<GpgVersion> {
+        let output = self.command().arg("--version").output()?;
+        check_output(&output)?;
+        Ok(parse_version(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
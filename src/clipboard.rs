@@ -0,0 +1,98 @@
+use std::{
+    io::{stdout, Error, ErrorKind, Result, Write},
+    process::{Command, Stdio},
+};
+
+/// Programs which print the system clipboard, tried in order
+const PASTE_COMMANDS: [&[&str]; 4] = [
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-out", "-selection", "clipboard"],
+    &["xsel", "--output", "--clipboard"],
+    &["pbpaste"],
+];
+
+/// Read the text of the system clipboard
+///
+/// A terminal can not be asked for the clipboard content, so this relies on
+/// the usual clipboard helpers of Wayland, X11 and macOS.
+pub fn paste() -> Result<String> {
+    for command in PASTE_COMMANDS {
+        let output = match Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) => output,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    Err(Error::other(
+        "could not read the clipboard, install wl-clipboard, xclip or xsel",
+    ))
+}
+
+/// Programs which set the system clipboard from their stdin, tried in order
+const COPY_COMMANDS: [&[&str]; 4] = [
+    &["wl-copy"],
+    &["xclip", "-in", "-selection", "clipboard"],
+    &["xsel", "--input", "--clipboard"],
+    &["pbcopy"],
+];
+
+/// Put `text` into the system clipboard
+///
+/// Without a clipboard helper the text is sent to the terminal as an OSC 52
+/// sequence, which most terminal emulators (also over SSH) turn into a copy.
+pub fn copy(text: &str) -> Result<()> {
+    for command in COPY_COMMANDS {
+        // The helpers may stay in the background to own the selection, so
+        // their output is not captured and only the exit of the parent awaited
+        let mut child = match Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    let mut stdout = stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()
+}
+
+/// Standard base64 with padding, for OSC 52
+pub fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,324 @@
+use std::{
+    env, fs,
+    io::{ErrorKind, Result},
+    path::{Path, PathBuf},
+};
+
+use chrono::format::{Item, StrftimeItems};
+use toml::Table;
+
+use crate::{
+    backend::{
+        keyserver::{check_keyserver, DEFAULT_KEYSERVER},
+        update::{check_feed_url, is_fingerprint, DEFAULT_UPDATE_FEED},
+        Gpg,
+    },
+    event_handler::keymap::KEYMAP_PRESETS,
+    theme::{is_theme_name, THEME_PRESETS},
+};
+
+/// When deleting a key has to be confirmed
+///
+/// Deleting secret key material is always confirmed by typing the fingerprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfirmDelete {
+    #[default]
+    Always,
+    SecretKeys, // Only keys with a secret part
+    Never,
+}
+
+impl ConfirmDelete {
+    pub const ALL: [ConfirmDelete; 3] = [
+        ConfirmDelete::Always,
+        ConfirmDelete::SecretKeys,
+        ConfirmDelete::Never,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfirmDelete::Always => "Always",
+            ConfirmDelete::SecretKeys => "Secret keys only",
+            ConfirmDelete::Never => "Never",
+        }
+    }
+
+    /// Value in the config file
+    fn name(&self) -> &'static str {
+        match self {
+            ConfirmDelete::Always => "always",
+            ConfirmDelete::SecretKeys => "secret",
+            ConfirmDelete::Never => "never",
+        }
+    }
+
+    /// Whether deleting a key with or without a secret part needs confirmation
+    pub fn applies_to(&self, has_secret: bool) -> bool {
+        match self {
+            ConfirmDelete::Always => true,
+            ConfirmDelete::SecretKeys => has_secret,
+            ConfirmDelete::Never => false,
+        }
+    }
+}
+
+/// Settings from `config.toml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Key used for signing unless chosen otherwise, passed to `--local-user`
+    pub default_signing_key: Option<String>,
+    /// Write ASCII armored output by default
+    pub armor: bool,
+    pub confirm_delete: ConfirmDelete,
+    /// Path (or name in `$PATH`) of the `gpg` binary
+    pub gpg_path: PathBuf,
+    /// Overrides `$GNUPGHOME` if set
+    pub gnupghome: Option<PathBuf>,
+    /// `strftime` format of dates in lists and details
+    pub date_format: String,
+    /// Keys expiring within this many days are highlighted in the key list, 0 turns it off
+    pub expiry_warning_days: u32,
+    /// A preset or the name of a file in `themes/`
+    pub theme: String,
+    /// Key bindings preset, changed by `keymap.toml`
+    pub keymap: String,
+    /// Keyservers in order of preference, the first one is dirmngr's default
+    pub keyservers: Vec<String>,
+    /// Allow Help > Check Update, off on air-gapped machines
+    pub update_check: bool,
+    /// URL of the JSON or Atom release feed
+    pub update_feed: String,
+    /// Fingerprint of the release key, requires a valid `<feed>.sig` if set
+    pub update_key: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_signing_key: None,
+            armor: true,
+            confirm_delete: ConfirmDelete::default(),
+            gpg_path: PathBuf::from("gpg"),
+            gnupghome: None,
+            date_format: "%Y-%m-%d".to_string(),
+            expiry_warning_days: 30,
+            theme: THEME_PRESETS[0].to_string(),
+            keymap: KEYMAP_PRESETS[0].to_string(),
+            keyservers: Vec::new(),
+            update_check: true,
+            update_feed: DEFAULT_UPDATE_FEED.to_string(),
+            update_key: None,
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/gpg4terminal/config.toml`, falling back to `~/.config`
+    pub fn path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+        Some(config_home.join("gpg4terminal").join("config.toml"))
+    }
+
+    /// Read the config at `path`, together with warnings about what was ignored
+    ///
+    /// A missing file gives the defaults. Unknown keys and invalid values are
+    /// skipped, so a bad config never keeps the program from starting.
+    pub fn load(path: &Path) -> (Config, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(err) if err.kind() == ErrorKind::NotFound => (Config::default(), Vec::new()),
+            Err(err) => (
+                Config::default(),
+                vec![format!("{}: {err}", path.display())],
+            ),
+        }
+    }
+
+    /// Parse the contents of a config file, see [`Config::load`]
+    pub fn parse(text: &str) -> (Config, Vec<String>) {
+        let mut config = Config::default();
+        let mut warnings = Vec::new();
+        let table: Table = match text.parse() {
+            Ok(table) => table,
+            Err(err) => {
+                let reason = err.message().to_string();
+                return (
+                    config,
+                    vec![format!("invalid config, using defaults: {reason}")],
+                );
+            }
+        };
+
+        for (key, value) in table {
+            let applied = match key.as_str() {
+                "default_signing_key" => value.as_str().map(|fingerprint| {
+                    config.default_signing_key =
+                        Some(fingerprint.to_string()).filter(|key| !key.is_empty())
+                }),
+                "armor" => value.as_bool().map(|armor| config.armor = armor),
+                "confirm_delete" => value
+                    .as_str()
+                    .and_then(|name| ConfirmDelete::ALL.into_iter().find(|c| c.name() == name))
+                    .map(|policy| config.confirm_delete = policy),
+                "gpg_path" => value
+                    .as_str()
+                    .filter(|path| !path.is_empty())
+                    .map(|path| config.gpg_path = PathBuf::from(path)),
+                "gnupghome" => value.as_str().map(|home| {
+                    config.gnupghome = Some(PathBuf::from(home)).filter(|_| !home.is_empty())
+                }),
+                "date_format" => value
+                    .as_str()
+                    .filter(|format| is_valid_date_format(format))
+                    .map(|format| config.date_format = format.to_string()),
+                "expiry_warning_days" => value
+                    .as_integer()
+                    .and_then(|days| u32::try_from(days).ok())
+                    .map(|days| config.expiry_warning_days = days),
+                "theme" => value
+                    .as_str()
+                    .filter(|theme| is_theme_name(theme))
+                    .map(|theme| config.theme = theme.to_string()),
+                "keymap" => value
+                    .as_str()
+                    .filter(|keymap| KEYMAP_PRESETS.contains(keymap))
+                    .map(|keymap| config.keymap = keymap.to_string()),
+                "keyservers" => value
+                    .as_array()
+                    .and_then(|servers| {
+                        servers
+                            .iter()
+                            .map(|server| server.as_str().map(str::to_string))
+                            .collect::<Option<Vec<String>>>()
+                    })
+                    .filter(|servers| servers.iter().all(|s| check_keyserver(s).is_ok()))
+                    .map(|servers| config.keyservers = servers),
+                "update_check" => value.as_bool().map(|check| config.update_check = check),
+                "update_feed" => value
+                    .as_str()
+                    .filter(|url| check_feed_url(url).is_ok())
+                    .map(|url| config.update_feed = url.to_string()),
+                "update_key" => value
+                    .as_str()
+                    .filter(|key| key.is_empty() || is_fingerprint(key))
+                    .map(|key| {
+                        config.update_key = Some(key.to_string()).filter(|_| !key.is_empty())
+                    }),
+                _ => {
+                    warnings.push(format!("unknown setting `{key}` ignored"));
+                    continue;
+                }
+            };
+            if applied.is_none() {
+                warnings.push(format!("invalid value for `{key}`, using the default"));
+            }
+        }
+        (config, warnings)
+    }
+
+    /// The config in the file format
+    pub fn to_toml(&self) -> String {
+        let mut table = Table::new();
+        if let Some(key) = &self.default_signing_key {
+            table.insert("default_signing_key".into(), key.clone().into());
+        }
+        table.insert("armor".into(), self.armor.into());
+        table.insert("confirm_delete".into(), self.confirm_delete.name().into());
+        table.insert(
+            "gpg_path".into(),
+            self.gpg_path.to_string_lossy().into_owned().into(),
+        );
+        if let Some(home) = &self.gnupghome {
+            table.insert(
+                "gnupghome".into(),
+                home.to_string_lossy().into_owned().into(),
+            );
+        }
+        table.insert("date_format".into(), self.date_format.clone().into());
+        table.insert(
+            "expiry_warning_days".into(),
+            i64::from(self.expiry_warning_days).into(),
+        );
+        table.insert("theme".into(), self.theme.clone().into());
+        table.insert("keymap".into(), self.keymap.clone().into());
+        if !self.keyservers.is_empty() {
+            table.insert("keyservers".into(), self.keyservers.clone().into());
+        }
+        table.insert("update_check".into(), self.update_check.into());
+        table.insert("update_feed".into(), self.update_feed.clone().into());
+        if let Some(key) = &self.update_key {
+            table.insert("update_key".into(), key.clone().into());
+        }
+        table.to_string()
+    }
+
+    /// Write the config to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    /// Check the settings before they are saved
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if !program_exists(&self.gpg_path) {
+            return Err(format!("gpg binary {} not found", self.gpg_path.display()));
+        }
+        if let Some(home) = &self.gnupghome {
+            if !home.is_dir() {
+                return Err(format!("GNUPGHOME {} is not a directory", home.display()));
+            }
+        }
+        if !is_valid_date_format(&self.date_format) {
+            return Err(format!("invalid date format {}", self.date_format));
+        }
+        if !is_theme_name(&self.theme) {
+            return Err(format!("invalid theme name {}", self.theme));
+        }
+        if !KEYMAP_PRESETS.contains(&self.keymap.as_str()) {
+            return Err(format!("unknown keymap {}", self.keymap));
+        }
+        check_feed_url(&self.update_feed).map_err(|err| err.to_string())?;
+        if let Some(key) = self
+            .update_key
+            .as_deref()
+            .filter(|key| !is_fingerprint(key))
+        {
+            return Err(format!("release key {key} is not a fingerprint"));
+        }
+        Ok(())
+    }
+
+    /// The keyserver preselected when searching or refreshing
+    pub fn default_keyserver(&self) -> &str {
+        self.keyservers
+            .first()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_KEYSERVER)
+    }
+
+    /// The gpg binary and home directory to use
+    pub fn gpg(&self) -> Gpg {
+        Gpg::new(&self.gpg_path, self.gnupghome.clone())
+    }
+}
+
+/// Whether `format` only contains `strftime` items chrono understands
+pub fn is_valid_date_format(format: &str) -> bool {
+    !format.is_empty() && StrftimeItems::new(format).all(|item| item != Item::Error)
+}
+
+/// Whether `program` is a file, or a name found in `$PATH`
+fn program_exists(program: &Path) -> bool {
+    if program.components().count() > 1 {
+        return program.is_file();
+    }
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths)
+            .any(|dir| !program.as_os_str().is_empty() && dir.join(program).is_file())
+    })
+}
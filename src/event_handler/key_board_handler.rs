@@ -1,13 +1,16 @@
 use std::io::Result;
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
+use ratatui::layout::Position;
 
-use crate::ui::{focus, UiState};
+use crate::ui::{focus, key_detail::DetailAction, menu_bar::Action, UiState};
+
+use super::keymap::{KeyAction, KeyContext, KeyLookup, Keymap};
 
 /*
  枚举 捕获的事件
 */
-#[derive(Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Clone, PartialEq, Eq)]
 pub enum EventResult {
     #[default]
     None,
@@ -16,84 +19,97 @@ pub enum EventResult {
     MenuNextItem,
     MenuPrevItem,
     MenuChoose,
+    MenuAccelerator(char), // Alt+letter
+    MenuHotkey(char),
+    MenuAction(Action), // A menu item bound to a key
+    MouseClick(Position),
+    NextMenuLevel,
     PrevMenuLevel,
+    KeyListPrevItem,
+    KeyListNextItem,
+    KeyListPrevPage,
+    KeyListNextPage,
+    KeyListFirstItem,
+    KeyListLastItem,
+    KeyListFind,
+    KeyListFindKey(KeyEvent), // Typed into the search of the key list
+    OpenKeyDetail,
+    CloseKeyDetail,
+    KeyDetailPrevItem,
+    KeyDetailNextItem,
+    KeyDetailFirstItem,
+    KeyDetailLastItem,
+    KeyDetailPrevPage,
+    KeyDetailNextPage,
+    KeyDetailScrollUp,   // Mouse wheel
+    KeyDetailScrollDown, // Mouse wheel
+    KeyDetailAction(DetailAction),
+    DialogKey(KeyEvent),
+    DialogPaste(String),
     Quit,
 }
 
-pub fn handle_events(ui_state: &UiState) -> Result<(bool, EventResult)> {
+pub fn handle_events(ui_state: &UiState, keymap: &mut Keymap) -> Result<(bool, EventResult)> {
     if event::poll(std::time::Duration::from_millis(50))? {
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        // Pasted text only goes to the inputs of dialogs
+        if let Event::Paste(text) = &event {
+            if ui_state.focus_on == focus::FocusOn::Dialog {
+                return Ok((true, EventResult::DialogPaste(text.clone())));
+            }
+        }
+        // Clicks are resolved by the App, which knows where everything was drawn
+        if let Event::Mouse(mouse) = event {
+            let position = Position::new(mouse.column, mouse.row);
+            let result = match (mouse.kind, ui_state.focus_on) {
+                (MouseEventKind::Down(MouseButton::Left), _) => EventResult::MouseClick(position),
+                // The wheel scrolls the key list like `Up/Down`, the detail view line-wise
+                (MouseEventKind::ScrollUp, focus::FocusOn::MainPanel) => {
+                    EventResult::KeyListPrevItem
+                }
+                (MouseEventKind::ScrollDown, focus::FocusOn::MainPanel) => {
+                    EventResult::KeyListNextItem
+                }
+                (MouseEventKind::ScrollUp, focus::FocusOn::KeyDetail) => {
+                    EventResult::KeyDetailScrollUp
+                }
+                (MouseEventKind::ScrollDown, focus::FocusOn::KeyDetail) => {
+                    EventResult::KeyDetailScrollDown
+                }
+                _ => return Ok((false, EventResult::None)),
+            };
+            return Ok((true, result));
+        }
+        if let Event::Key(key) = event {
             if key.kind == event::KeyEventKind::Press {
-                match ui_state.focus_on {
-                    focus::FocusOn::MainPanel => {
-                        // `Q` can quit program when in MainPanel
-                        if key.code == KeyCode::Char('q') {
-                            return Ok((true, EventResult::Quit));
-                        }
-                        // Tab can enter the menu bar when in MainPanel
-                        if key.code == KeyCode::Tab
-                            && !matches!(ui_state.focus_on, focus::FocusOn::MenuBar(_))
-                        {
-                            return Ok((true, EventResult::EnterMenuBar));
-                        }
+                // Dialogs handle all keys themselves, e.g. for text input
+                let Some(context) = KeyContext::of(ui_state.focus_on) else {
+                    return Ok((true, EventResult::DialogKey(key)));
+                };
+                // So does the search of the key list while it is typed
+                if ui_state.focus_on == focus::FocusOn::MainPanel
+                    && ui_state.key_list_state.borrow().find.is_some()
+                {
+                    return Ok((true, EventResult::KeyListFindKey(key)));
+                }
+                match keymap.lookup(context, key) {
+                    KeyLookup::Action(action) => {
+                        return Ok((true, apply_action(ui_state, action)));
                     }
-                    focus::FocusOn::MenuBar(focus::Menu::Tab) => {
-                        // `L/R` can select the next/previous menu tab when focus is on MenuTab
-                        if key.code == KeyCode::Left {
-                            return Ok((true, EventResult::MenuPrevItem));
-                        }
-                        if key.code == KeyCode::Right {
-                            return Ok((true, EventResult::MenuNextItem));
-                        }
-                        // `Enter` can choose menu item when focus is on MenuTab
-                        // `Down` can choose the menu tab when focus is on MenuTab
-                        if key.code == KeyCode::Enter || key.code == KeyCode::Down {
-                            return Ok((true, EventResult::MenuChoose));
-                        }
-                        // `Tab` can quit menu when focus is on MenuTab
-                        // `Esc` can quit menu when focus is on MenuTab
-                        if key.code == KeyCode::Tab || key.code == KeyCode::Esc {
-                            return Ok((true, EventResult::QuitMenu));
-                        }
+                    KeyLookup::Pending => return Ok((true, EventResult::None)),
+                    KeyLookup::Unbound => (),
+                }
+                // Keys the keymap leaves alone can open the menu by hotkey
+                if let KeyCode::Char(ch) = key.code {
+                    // `Alt+letter` can open a menu tab from anywhere but a dialog
+                    if key.modifiers.contains(KeyModifiers::ALT) {
+                        return Ok((true, EventResult::MenuAccelerator(ch)));
                     }
-                    focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
-                        // `Esc` can go back to previous menu when focus is on MenuTabItem
-                        if key.code == KeyCode::Esc {
-                            return Ok((true, EventResult::PrevMenuLevel));
-                        }
-                        // `Up/Down` can select the previous/next menu item when focus is on MenuTabItem
-                        // `Up` can go back to the menu tab when focus is on MenuTabItem(0) and the first item is focused
-                        if key.code == KeyCode::Up {
-                            if level == 0
-                                && ui_state.menu_bar_state.borrow().tab_state
-                                    [ui_state.menu_bar_state.borrow().focus_on]
-                                    .borrow()
-                                    .sub_item_state
-                                    .is_some()
-                                && ui_state.menu_bar_state.borrow().tab_state
-                                    [ui_state.menu_bar_state.borrow().focus_on]
-                                    .borrow()
-                                    .sub_item_state
-                                    .as_ref()
-                                    .unwrap()[0]
-                                    .borrow()
-                                    .is_focused
-                            {
-                                return Ok((true, EventResult::PrevMenuLevel));
-                            }
-                            return Ok((true, EventResult::MenuPrevItem));
-                        }
-                        if key.code == KeyCode::Down {
-                            return Ok((true, EventResult::MenuNextItem));
-                        }
-                        // `Enter` can choose menu item when focus is on MenuTabItem
-                        if key.code == KeyCode::Enter {
-                            return Ok((true, EventResult::MenuChoose));
-                        }
-                        // `Tab` can quit menu when focus is on MenuTabItem
-                        if key.code == KeyCode::Tab {
-                            return Ok((true, EventResult::QuitMenu));
-                        }
+                    // A letter can choose the tab or item with that hotkey when in the menu
+                    if matches!(ui_state.focus_on, focus::FocusOn::MenuBar(_))
+                        && !key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok((true, EventResult::MenuHotkey(ch)));
                     }
                 }
             }
@@ -101,3 +117,58 @@ pub fn handle_events(ui_state: &UiState) -> Result<(bool, EventResult)> {
     }
     Ok((false, EventResult::None))
 }
+
+/// What a key bound to `action` does where the focus is
+fn apply_action(ui_state: &UiState, action: KeyAction) -> EventResult {
+    match (ui_state.focus_on, action) {
+        (focus::FocusOn::MainPanel, KeyAction::Quit) => EventResult::Quit,
+        (focus::FocusOn::MainPanel, KeyAction::Menu) => EventResult::EnterMenuBar,
+        (focus::FocusOn::MainPanel, KeyAction::Up) => EventResult::KeyListPrevItem,
+        (focus::FocusOn::MainPanel, KeyAction::Down) => EventResult::KeyListNextItem,
+        (focus::FocusOn::MainPanel, KeyAction::PageUp) => EventResult::KeyListPrevPage,
+        (focus::FocusOn::MainPanel, KeyAction::PageDown) => EventResult::KeyListNextPage,
+        (focus::FocusOn::MainPanel, KeyAction::First) => EventResult::KeyListFirstItem,
+        (focus::FocusOn::MainPanel, KeyAction::Last) => EventResult::KeyListLastItem,
+        (focus::FocusOn::MainPanel, KeyAction::Choose) => EventResult::OpenKeyDetail,
+        (focus::FocusOn::MainPanel, KeyAction::Find) => EventResult::KeyListFind,
+        (focus::FocusOn::MainPanel, KeyAction::Run(action)) => EventResult::MenuAction(action),
+        (focus::FocusOn::KeyDetail, KeyAction::Back) => EventResult::CloseKeyDetail,
+        (focus::FocusOn::KeyDetail, KeyAction::Up) => EventResult::KeyDetailPrevItem,
+        (focus::FocusOn::KeyDetail, KeyAction::Down) => EventResult::KeyDetailNextItem,
+        (focus::FocusOn::KeyDetail, KeyAction::First) => EventResult::KeyDetailFirstItem,
+        (focus::FocusOn::KeyDetail, KeyAction::Last) => EventResult::KeyDetailLastItem,
+        (focus::FocusOn::KeyDetail, KeyAction::PageUp) => EventResult::KeyDetailPrevPage,
+        (focus::FocusOn::KeyDetail, KeyAction::PageDown) => EventResult::KeyDetailNextPage,
+        (focus::FocusOn::KeyDetail, KeyAction::Detail(action)) => {
+            EventResult::KeyDetailAction(action)
+        }
+        (focus::FocusOn::MenuBar(focus::Menu::Tab), KeyAction::Left) => EventResult::MenuPrevItem,
+        (focus::FocusOn::MenuBar(focus::Menu::Tab), KeyAction::Right) => EventResult::MenuNextItem,
+        (focus::FocusOn::MenuBar(focus::Menu::Tab), KeyAction::Choose) => EventResult::MenuChoose,
+        (focus::FocusOn::MenuBar(_), KeyAction::QuitMenu) => EventResult::QuitMenu,
+        (focus::FocusOn::MenuBar(focus::Menu::TabItem(level)), action) => match action {
+            // `Up` on the first item of the drop down goes back to the menu tab
+            KeyAction::Up if level == 0 && is_first_item_focused(ui_state) => {
+                EventResult::PrevMenuLevel
+            }
+            KeyAction::Up => EventResult::MenuPrevItem,
+            KeyAction::Down => EventResult::MenuNextItem,
+            // `Right` opens the sub menu of the focused item, `Left` closes a sub menu
+            KeyAction::Right => EventResult::NextMenuLevel,
+            KeyAction::Left if level > 0 => EventResult::PrevMenuLevel,
+            KeyAction::Choose => EventResult::MenuChoose,
+            KeyAction::Back => EventResult::PrevMenuLevel,
+            _ => EventResult::None,
+        },
+        _ => EventResult::None,
+    }
+}
+
+/// Whether the first item of the open tab has the focus
+fn is_first_item_focused(ui_state: &UiState) -> bool {
+    let menu_bar_state = ui_state.menu_bar_state.borrow();
+    let tab = menu_bar_state.tab_state[menu_bar_state.focus_on].borrow();
+    tab.sub_item_state
+        .as_ref()
+        .is_some_and(|items| items[0].borrow().is_focused)
+}
@@ -0,0 +1,593 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use toml::{Table, Value};
+
+use crate::ui::{focus, key_detail::DetailAction, menu_bar::Action};
+
+/// Names accepted by the `keymap` setting
+pub const KEYMAP_PRESETS: [&str; 3] = ["default", "vim", "emacs"];
+
+/// Bindings of the default preset, the other presets and `keymap.toml` change them
+const DEFAULT_KEYMAP: &str = r#"
+[main_panel]
+quit = "q"
+menu = "tab"
+up = "up"
+down = "down"
+page_up = "pageup"
+page_down = "pagedown"
+first = "home"
+last = "end"
+choose = "enter"
+delete_key = "delete"
+
+[key_detail]
+back = ["esc", "q"]
+up = "up"
+down = "down"
+page_up = "pageup"
+page_down = "pagedown"
+first = "home"
+last = "end"
+expire = "e"
+add_subkey = "s"
+add_uid = "u"
+revoke = "r"
+primary_uid = "p"
+
+[menu_tab]
+left = "left"
+right = "right"
+choose = ["enter", "down"]
+quit_menu = ["tab", "esc"]
+
+[menu_item]
+up = "up"
+down = "down"
+left = "left"
+right = "right"
+choose = "enter"
+back = "esc"
+quit_menu = "tab"
+"#;
+
+// Letters in the menus choose items by their hotkey, so the presets leave them alone
+const VIM_KEYMAP: &str = r#"
+[main_panel]
+up = ["k", "up"]
+down = ["j", "down"]
+page_up = ["ctrl+b", "pageup"]
+page_down = ["ctrl+f", "pagedown"]
+first = ["g g", "home"]
+last = ["G", "end"]
+choose = ["l", "enter"]
+find = "/"
+
+[key_detail]
+back = ["h", "esc", "q"]
+up = ["k", "up"]
+down = ["j", "down"]
+page_up = ["ctrl+b", "pageup"]
+page_down = ["ctrl+f", "pagedown"]
+first = ["g g", "home"]
+last = ["G", "end"]
+"#;
+
+const EMACS_KEYMAP: &str = r#"
+[main_panel]
+quit = ["q", "ctrl+x ctrl+c"]
+menu = ["tab", "f10"]
+up = ["ctrl+p", "up"]
+down = ["ctrl+n", "down"]
+page_up = ["alt+v", "pageup"]
+page_down = ["ctrl+v", "pagedown"]
+first = ["alt+<", "home"]
+last = ["alt+>", "end"]
+find = "ctrl+s"
+
+[key_detail]
+back = ["ctrl+g", "esc", "q"]
+up = ["ctrl+p", "up"]
+down = ["ctrl+n", "down"]
+page_up = ["alt+v", "pageup"]
+page_down = ["ctrl+v", "pagedown"]
+first = ["alt+<", "home"]
+last = ["alt+>", "end"]
+
+[menu_tab]
+left = ["ctrl+b", "left"]
+right = ["ctrl+f", "right"]
+choose = ["enter", "down", "ctrl+n"]
+quit_menu = ["tab", "esc", "ctrl+g"]
+
+[menu_item]
+up = ["ctrl+p", "up"]
+down = ["ctrl+n", "down"]
+left = ["ctrl+b", "left"]
+right = ["ctrl+f", "right"]
+quit_menu = ["tab", "ctrl+g"]
+"#;
+
+/// Menu actions which can be bound to a key in the main panel
+const MENU_ACTIONS: [(&str, Action); 16] = [
+    ("new_key_pair", Action::NewKeyPair),
+    ("import", Action::Import),
+    ("export_public", Action::ExportPublic),
+    ("export_secret", Action::ExportSecret),
+    ("delete_key", Action::DeleteKey),
+    ("search_on_server", Action::SearchOnServer),
+    ("decrypt_verify", Action::DecryptVerify),
+    ("encrypt_sign", Action::EncryptSign),
+    ("quit_program", Action::Quit),
+    ("refresh_cert", Action::RefreshCert),
+    ("backend_process", Action::BackendProcess),
+    ("general_settings", Action::GeneralSettings),
+    ("server_settings", Action::ServerSettings),
+    ("theme_settings", Action::ThemeSettings),
+    ("check_update", Action::CheckUpdate),
+    ("about", Action::About),
+];
+
+/// Changes of the shown key which can be bound to a key in the key detail view
+const DETAIL_ACTIONS: [(&str, DetailAction); 5] = [
+    ("expire", DetailAction::Expire),
+    ("add_subkey", DetailAction::AddSubkey),
+    ("add_uid", DetailAction::AddUserId),
+    ("revoke", DetailAction::Revoke),
+    ("primary_uid", DetailAction::PrimaryUserId),
+];
+
+/// Where keys are looked up, follows the focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    MainPanel,
+    KeyDetail,
+    MenuTab,
+    MenuItem,
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 4] = [
+        KeyContext::MainPanel,
+        KeyContext::KeyDetail,
+        KeyContext::MenuTab,
+        KeyContext::MenuItem,
+    ];
+
+    /// Table name in the keymap file
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyContext::MainPanel => "main_panel",
+            KeyContext::KeyDetail => "key_detail",
+            KeyContext::MenuTab => "menu_tab",
+            KeyContext::MenuItem => "menu_item",
+        }
+    }
+
+    /// The context of `focus_on`, `None` for dialogs which handle keys themselves
+    pub fn of(focus_on: focus::FocusOn) -> Option<KeyContext> {
+        match focus_on {
+            focus::FocusOn::MainPanel => Some(KeyContext::MainPanel),
+            focus::FocusOn::KeyDetail => Some(KeyContext::KeyDetail),
+            focus::FocusOn::MenuBar(focus::Menu::Tab) => Some(KeyContext::MenuTab),
+            focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => Some(KeyContext::MenuItem),
+            focus::FocusOn::Dialog => None,
+        }
+    }
+
+    /// Whether `action` means something in this context
+    fn allows(&self, action: KeyAction) -> bool {
+        use KeyAction::*;
+        match self {
+            KeyContext::MainPanel => matches!(
+                action,
+                Quit | Menu | Up | Down | PageUp | PageDown | First | Last | Choose | Find | Run(_)
+            ),
+            KeyContext::KeyDetail => {
+                matches!(
+                    action,
+                    Back | Up | Down | PageUp | PageDown | First | Last | Detail(_)
+                )
+            }
+            KeyContext::MenuTab => matches!(action, Left | Right | Choose | QuitMenu),
+            KeyContext::MenuItem => {
+                matches!(action, Up | Down | Left | Right | Choose | Back | QuitMenu)
+            }
+        }
+    }
+}
+
+/// What a key does, the meaning depends on the context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Choose,
+    Back,
+    Quit,
+    Menu,
+    QuitMenu,
+    /// Search the key list
+    Find,
+    /// Execute a menu item
+    Run(Action),
+    /// Change the key shown in the key detail view
+    Detail(DetailAction),
+}
+
+impl KeyAction {
+    /// Parse the name used in the keymap file
+    pub fn from_name(name: &str) -> Option<KeyAction> {
+        let action = match name {
+            "up" => KeyAction::Up,
+            "down" => KeyAction::Down,
+            "left" => KeyAction::Left,
+            "right" => KeyAction::Right,
+            "page_up" => KeyAction::PageUp,
+            "page_down" => KeyAction::PageDown,
+            "first" => KeyAction::First,
+            "last" => KeyAction::Last,
+            "choose" => KeyAction::Choose,
+            "back" => KeyAction::Back,
+            "quit" => KeyAction::Quit,
+            "menu" => KeyAction::Menu,
+            "quit_menu" => KeyAction::QuitMenu,
+            "find" => KeyAction::Find,
+            _ => {
+                if let Some((_, action)) = DETAIL_ACTIONS.iter().find(|(action, _)| *action == name)
+                {
+                    return Some(KeyAction::Detail(*action));
+                }
+                let (_, action) = MENU_ACTIONS.iter().find(|(action, _)| *action == name)?;
+                KeyAction::Run(*action)
+            }
+        };
+        Some(action)
+    }
+}
+
+/// A key with its modifiers, e.g. `ctrl+n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// Parse a chord like `q`, `G`, `ctrl+n`, `alt+<`, `shift+tab` or `pagedown`
+    pub fn parse(text: &str) -> Option<KeyChord> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // `+` itself, alone or after a modifier
+        if text.ends_with("++") || text == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+        let key = parts.pop()?;
+        for modifier in parts {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => KeyCode::Char(ch),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        Some(KeyChord::new(code, modifiers))
+    }
+
+    /// A chord as reported by the terminal
+    ///
+    /// Terminals report `Shift` differently, so a shifted letter becomes
+    /// the upper case letter and `Shift+Tab` becomes `BackTab`.
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(ch) if shift => KeyCode::Char(ch.to_ascii_uppercase()),
+            KeyCode::Tab if shift => KeyCode::BackTab,
+            code => code,
+        };
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyChord {
+            code,
+            modifiers: modifiers
+                & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT),
+        }
+    }
+
+    /// How the chord is shown in the hint bar
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("Shift+");
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(ch) => label.push(ch),
+            KeyCode::F(n) => label.push_str(&format!("F{n}")),
+            KeyCode::BackTab => label.push_str("Shift+Tab"),
+            KeyCode::PageUp => label.push_str("PgUp"),
+            KeyCode::PageDown => label.push_str("PgDn"),
+            code => label.push_str(&format!("{code:?}")),
+        }
+        label
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        KeyChord::new(key.code, key.modifiers)
+    }
+}
+
+/// A key sequence and what it does
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    keys: Vec<KeyChord>,
+    action: KeyAction,
+}
+
+/// Outcome of looking up a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLookup {
+    Action(KeyAction),
+    /// The key starts a sequence like `g g`, wait for the next one
+    Pending,
+    Unbound,
+}
+
+/// Key bindings per context, from a preset and the keymap file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<KeyContext, Vec<Binding>>,
+    /// Keys typed so far of a sequence, and where
+    pending: Option<(KeyContext, Vec<KeyChord>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::preset(KEYMAP_PRESETS[0])
+    }
+}
+
+impl Keymap {
+    /// `keymap.toml` next to `config.toml`
+    pub fn path(config_path: &Path) -> PathBuf {
+        config_path.with_file_name("keymap.toml")
+    }
+
+    /// The bindings of preset `name`, the default preset for unknown names
+    pub fn preset(name: &str) -> Keymap {
+        let mut keymap = Keymap {
+            bindings: HashMap::new(),
+            pending: None,
+        };
+        let preset = match name {
+            "vim" => Some(VIM_KEYMAP),
+            "emacs" => Some(EMACS_KEYMAP),
+            _ => None,
+        };
+        for text in [Some(DEFAULT_KEYMAP), preset].into_iter().flatten() {
+            let warnings = keymap.apply(text);
+            debug_assert!(warnings.is_empty(), "{warnings:?}");
+        }
+        keymap
+    }
+
+    /// Preset `name` changed by the keymap file at `path`, with warnings about what was ignored
+    ///
+    /// Like the config, a missing file is fine and a bad one never keeps the program from starting.
+    pub fn load(name: &str, path: Option<&Path>) -> (Keymap, Vec<String>) {
+        let mut keymap = Keymap::preset(name);
+        let Some(path) = path else {
+            return (keymap, Vec::new());
+        };
+        let warnings = match fs::read_to_string(path) {
+            Ok(text) => keymap.apply(&text),
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => vec![format!("{}: {err}", path.display())],
+        };
+        (keymap, warnings)
+    }
+
+    /// Apply a keymap file, the keys given for an action replace its bindings in that context
+    pub fn apply(&mut self, text: &str) -> Vec<String> {
+        let table: Table = match text.parse() {
+            Ok(table) => table,
+            Err(err) => return vec![format!("invalid keymap: {}", err.message())],
+        };
+        let mut warnings = Vec::new();
+        for (name, value) in table {
+            let Some(context) = KeyContext::ALL.into_iter().find(|c| c.name() == name) else {
+                warnings.push(format!("unknown keymap context `{name}` ignored"));
+                continue;
+            };
+            let Some(actions) = value.as_table() else {
+                warnings.push(format!("`{name}` is not a table"));
+                continue;
+            };
+            for (action_name, keys) in actions {
+                let Some(action) =
+                    KeyAction::from_name(action_name).filter(|action| context.allows(*action))
+                else {
+                    warnings.push(format!("unknown action `{name}.{action_name}` ignored"));
+                    continue;
+                };
+                let Some(sequences) = parse_sequences(keys) else {
+                    warnings.push(format!("invalid keys for `{name}.{action_name}`"));
+                    continue;
+                };
+                let bindings = self.bindings.entry(context).or_default();
+                bindings.retain(|binding| binding.action != action);
+                for keys in sequences {
+                    // A key can only do one thing
+                    bindings.retain(|binding| binding.keys != keys);
+                    bindings.push(Binding { keys, action });
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Look up a key press in `context`, keeping track of sequences
+    pub fn lookup(&mut self, context: KeyContext, key: KeyEvent) -> KeyLookup {
+        let chord = KeyChord::from(key);
+        let mut keys = match self.pending.take() {
+            Some((pending_context, keys)) if pending_context == context => keys,
+            _ => Vec::new(),
+        };
+        keys.push(chord);
+        let bindings = self
+            .bindings
+            .get(&context)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if let Some(binding) = bindings.iter().find(|binding| binding.keys == keys) {
+            return KeyLookup::Action(binding.action);
+        }
+        if bindings
+            .iter()
+            .any(|binding| binding.keys.starts_with(&keys))
+        {
+            self.pending = Some((context, keys));
+            return KeyLookup::Pending;
+        }
+        // A key which does not continue the sequence counts on its own
+        if keys.len() > 1 {
+            return self.lookup(context, key);
+        }
+        KeyLookup::Unbound
+    }
+
+    /// The first keys bound to `action` in `context`, like `g g`
+    pub fn keys(&self, context: KeyContext, action: KeyAction) -> Option<String> {
+        let binding = self
+            .bindings
+            .get(&context)?
+            .iter()
+            .find(|binding| binding.action == action)?;
+        let labels: Vec<String> = binding.keys.iter().map(KeyChord::label).collect();
+        Some(labels.join(" "))
+    }
+
+    /// Hints for the bottom bar when `focus_on` has the focus
+    pub fn hints(&self, focus_on: focus::FocusOn) -> Vec<(String, &'static str)> {
+        use KeyAction::*;
+        let Some(context) = KeyContext::of(focus_on) else {
+            return Vec::new();
+        };
+        // The actions of a hint, keys which are not in the keymap and what they do
+        let hints: &[(&[KeyAction], Option<&str>, &str)] = match focus_on {
+            focus::FocusOn::MainPanel => &[
+                (&[Quit], None, "Quit"),
+                (&[Menu], Some("Alt+Key"), "Menu"),
+                (&[Up, Down], None, "Select"),
+                (&[Choose], None, "Choose"),
+                (&[Find], None, "Find"),
+            ],
+            focus::FocusOn::KeyDetail => &[
+                (&[Back], None, "Back"),
+                (&[Up, Down], None, "Select"),
+                (&[Detail(DetailAction::Expire)], None, "Expiry"),
+                (&[Detail(DetailAction::AddSubkey)], None, "Add Subkey"),
+                (&[Detail(DetailAction::AddUserId)], None, "Add UID"),
+                (&[Detail(DetailAction::Revoke)], None, "Revoke"),
+                (&[Detail(DetailAction::PrimaryUserId)], None, "Primary"),
+            ],
+            focus::FocusOn::MenuBar(focus::Menu::Tab) => &[
+                (&[Left, Right], None, "Select"),
+                (&[Choose], Some("Key"), "Choose"),
+                (&[QuitMenu], None, "Quit Menu"),
+            ],
+            focus::FocusOn::MenuBar(focus::Menu::TabItem(0)) => &[
+                (&[Up, Down], None, "Select"),
+                (&[Choose, Right], Some("Key"), "Choose"),
+                (&[Back], None, "Back"),
+                (&[QuitMenu], None, "Quit Menu"),
+            ],
+            _ => &[
+                (&[Up, Down], None, "Select"),
+                (&[Choose, Right], Some("Key"), "Choose"),
+                (&[Back, Left], None, "Back"),
+                (&[QuitMenu], None, "Quit Menu"),
+            ],
+        };
+        hints
+            .iter()
+            .filter_map(|(actions, extra, description)| {
+                let mut keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.keys(context, *action))
+                    .collect();
+                if keys.is_empty() {
+                    return None;
+                }
+                keys.extend(extra.map(str::to_string));
+                Some((keys.join("/"), *description))
+            })
+            .collect()
+    }
+}
+
+/// A key sequence like `g g`, or a list of them
+fn parse_sequences(value: &Value) -> Option<Vec<Vec<KeyChord>>> {
+    let sequences = match value {
+        Value::String(keys) => vec![keys.as_str()],
+        Value::Array(keys) => keys.iter().map(Value::as_str).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    sequences
+        .into_iter()
+        .map(|keys| {
+            let keys: Vec<KeyChord> = keys
+                .split_whitespace()
+                .map(KeyChord::parse)
+                .collect::<Option<_>>()?;
+            Some(keys).filter(|keys| !keys.is_empty())
+        })
+        .collect()
+}
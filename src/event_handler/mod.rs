@@ -1 +1,2 @@
 pub mod key_board_handler;
+pub mod keymap;
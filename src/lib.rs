@@ -0,0 +1,7 @@
+pub mod app;
+pub mod backend;
+pub mod clipboard;
+pub mod config;
+pub mod event_handler;
+pub mod theme;
+pub mod ui;
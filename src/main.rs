@@ -1,23 +1,33 @@
-mod app;
-mod event_handler;
-mod theme;
-mod ui;
-
-use std::io::{self, stdout};
+use std::io::{self, stdin, stdout, IsTerminal, Read};
 
 use crossterm::{
-    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
-use crate::app::App;
+use gpg4terminal::{app::App, config::Config};
 
 fn main() -> Result<(), io::Error> {
+    // Certificates can be piped in, e.g. `curl https://.../key.asc | gpg4terminal`.
+    // Keyboard input is then read from the controlling terminal instead.
+    let mut piped = Vec::new();
+    if !stdin().is_terminal() {
+        stdin().read_to_end(&mut piped)?;
+    }
+
     // Enable raw mode and enter alternate screen
     enable_raw_mode()?;
-    execute!(stdout(), EnterAlternateScreen)?;
+    execute!(
+        stdout(),
+        EnterAlternateScreen,
+        EnableBracketedPaste,
+        EnableMouseCapture
+    )?;
     execute!(
         stdout(),
         PushKeyboardEnhancementFlags(
@@ -27,11 +37,26 @@ fn main() -> Result<(), io::Error> {
     )?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
-    App::default().run(&mut terminal)?;
+    let config_path = Config::path();
+    let (config, warnings) = match &config_path {
+        Some(path) => Config::load(path),
+        None => (Config::default(), Vec::new()),
+    };
+    let mut app = App::new(config, config_path);
+    app.show_config_warnings(&warnings);
+    if !piped.is_empty() {
+        app.import_stdin(piped);
+    }
+    app.run(&mut terminal)?;
 
     // Restore terminal state
     disable_raw_mode()?;
-    execute!(stdout(), LeaveAlternateScreen)?;
+    execute!(
+        stdout(),
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     execute!(stdout(), PopKeyboardEnhancementFlags)?;
     terminal.show_cursor()?;
 
@@ -1,12 +1,25 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock, RwLockReadGuard},
+};
+
 use ratatui::style::{Color, Modifier, Style};
+use toml::Table;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Theme {
     pub content: Style,
     pub menu_bar: MenuBar,
     pub hint_bar: HintBar,
+    pub key_list: KeyList,
+    pub key_detail: KeyDetail,
+    pub dialog: Dialog,
     pub borders: Style,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MenuBar {
     pub title_style: Style,
     pub default_style: Style,
@@ -15,46 +28,381 @@ pub struct MenuBar {
     pub selected_style: Style,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct HintBar {
     pub default_style: Style,
     pub key: Style,
     pub description: Style,
 }
 
-pub const THEME: Theme = Theme {
-    content: Style::new().bg(DARK_PURPLE).fg(LIGHT_GRAY),
-    menu_bar: MenuBar {
-        title_style: Style::new()
-            .fg(WHITE)
-            .bg(DARK_PURPLE)
-            .add_modifier(Modifier::BOLD),
-        default_style: Style::new().fg(MID_GRAY).bg(DARK_PURPLE),
-        disabled_style: Style::new().fg(DARK_GRAY).bg(DARK_PURPLE),
-        focused_style: Style::new().fg(LIGHT_GRAY).bg(MID_PURPLE),
-        selected_style: Style::new()
-            .fg(LIGHT_GRAY)
-            .bg(MID_PURPLE)
-            .add_modifier(Modifier::BOLD)
-            .add_modifier(Modifier::REVERSED),
-    },
-    borders: Style::new().fg(LIGHT_GRAY),
-    hint_bar: HintBar {
-        default_style: Style::new().fg(MID_GRAY).bg(DARK_PURPLE),
-        key: Style::new().fg(DARK_PURPLE).bg(MID_GRAY),
-        description: Style::new().fg(MID_GRAY).bg(DARK_PURPLE),
-    },
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyList {
+    pub header_style: Style,
+    pub default_style: Style,
+    pub invalid_style: Style,
+    /// Keys expiring soon, see `expiry_warning_days` in the config
+    pub expiring_style: Style,
+    pub selected_style: Style,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyDetail {
+    pub header_style: Style,
+    pub label_style: Style,
+    pub value_style: Style,
+    pub signature_style: Style,
+    pub invalid_style: Style,
+    pub selected_style: Style,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialog {
+    pub default_style: Style,
+    pub border_style: Style,
+    pub label_style: Style,
+    pub input_style: Style,
+    pub focused_input_style: Style,
+    pub button_style: Style,
+    pub focused_button_style: Style,
+    pub error_style: Style,
+    pub ok_style: Style,
+}
+
+/// Built-in themes, theme files in [`Theme::dir`] add to them
+pub const THEME_PRESETS: [&str; 4] = ["dark", "light", "high-contrast", "16-color"];
+
+/// The few colors a theme is made of, also the keys of a theme file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub background: Color,
+    /// Background of focused and selected things
+    pub highlight: Color,
+    pub text: Color,
+    /// Labels and everything less important than the text
+    pub dim: Color,
+    pub disabled: Color,
+    /// Titles and headers
+    pub bright: Color,
+    /// Borders of dialogs
+    pub accent: Color,
+    pub error: Color,
+    /// Things which need attention soon, e.g. keys about to expire
+    pub warning: Color,
+    pub ok: Color,
+}
+
+pub const DARK: Palette = Palette {
+    background: Color::Rgb(51, 0, 51),
+    highlight: Color::Rgb(102, 80, 102),
+    text: Color::Rgb(188, 188, 188),
+    dim: Color::Rgb(128, 128, 128),
+    disabled: Color::Rgb(68, 68, 68),
+    bright: Color::Rgb(238, 238, 238), // not really white, often #eeeeee
+    accent: Color::Rgb(185, 121, 192),
+    error: Color::Rgb(192, 96, 96),
+    warning: Color::Rgb(216, 176, 72),
+    ok: Color::Rgb(64, 192, 96),
+};
+
+pub const LIGHT: Palette = Palette {
+    background: Color::Rgb(238, 238, 238),
+    highlight: Color::Rgb(200, 196, 216),
+    text: Color::Rgb(48, 48, 48),
+    dim: Color::Rgb(100, 100, 100),
+    disabled: Color::Rgb(168, 168, 168),
+    bright: Color::Rgb(8, 8, 8),
+    accent: Color::Rgb(102, 51, 153),
+    error: Color::Rgb(175, 0, 0),
+    warning: Color::Rgb(150, 90, 0),
+    ok: Color::Rgb(0, 120, 40),
+};
+
+pub const HIGH_CONTRAST: Palette = Palette {
+    background: Color::Rgb(0, 0, 0),
+    highlight: Color::Rgb(0, 64, 160),
+    text: Color::Rgb(255, 255, 255),
+    dim: Color::Rgb(216, 216, 216),
+    disabled: Color::Rgb(144, 144, 144),
+    bright: Color::Rgb(255, 255, 0),
+    accent: Color::Rgb(255, 255, 0),
+    error: Color::Rgb(255, 96, 96),
+    warning: Color::Rgb(255, 176, 0),
+    ok: Color::Rgb(96, 255, 96),
+};
+
+/// Only the 16 colors of the terminal, which picks what they look like
+pub const ANSI_16: Palette = Palette {
+    background: Color::Black,
+    highlight: Color::Blue,
+    text: Color::Gray,
+    dim: Color::Cyan,
+    disabled: Color::DarkGray,
+    bright: Color::White,
+    accent: Color::Magenta,
+    error: Color::LightRed,
+    warning: Color::Yellow,
+    ok: Color::LightGreen,
 };
 
-const DARK_PURPLE: Color = Color::Rgb(51, 0, 51);
-const MID_PURPLE: Color = Color::Rgb(102, 80, 102);
-const LIGHT_PURPLE: Color = Color::Rgb(185, 121, 192);
-const LIGHT_BLUE: Color = Color::Rgb(64, 96, 192);
-const LIGHT_YELLOW: Color = Color::Rgb(192, 192, 96);
-const LIGHT_GREEN: Color = Color::Rgb(64, 192, 96);
-const LIGHT_RED: Color = Color::Rgb(192, 96, 96);
-const RED: Color = Color::Rgb(215, 0, 0);
-const BLACK: Color = Color::Rgb(8, 8, 8); // not really black, often #080808
-const DARK_GRAY: Color = Color::Rgb(68, 68, 68);
-const MID_GRAY: Color = Color::Rgb(128, 128, 128);
-const LIGHT_GRAY: Color = Color::Rgb(188, 188, 188);
-const WHITE: Color = Color::Rgb(238, 238, 238); // not really white, often #eeeeee
+impl Palette {
+    /// The palette of preset `name`
+    pub fn preset(name: &str) -> Option<Palette> {
+        match name {
+            // `default` is what `dark` was called when it was the only theme
+            "dark" | "default" => Some(DARK),
+            "light" => Some(LIGHT),
+            "high-contrast" => Some(HIGH_CONTRAST),
+            "16-color" => Some(ANSI_16),
+            _ => None,
+        }
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        match name {
+            "background" => Some(&mut self.background),
+            "highlight" => Some(&mut self.highlight),
+            "text" => Some(&mut self.text),
+            "dim" => Some(&mut self.dim),
+            "disabled" => Some(&mut self.disabled),
+            "bright" => Some(&mut self.bright),
+            "accent" => Some(&mut self.accent),
+            "error" => Some(&mut self.error),
+            "warning" => Some(&mut self.warning),
+            "ok" => Some(&mut self.ok),
+            _ => None,
+        }
+    }
+}
+
+impl Theme {
+    pub const fn from_palette(palette: &Palette) -> Theme {
+        let bg = palette.background;
+        Theme {
+            content: Style::new().bg(bg).fg(palette.text),
+            menu_bar: MenuBar {
+                title_style: Style::new()
+                    .fg(palette.bright)
+                    .bg(bg)
+                    .add_modifier(Modifier::BOLD),
+                default_style: Style::new().fg(palette.dim).bg(bg),
+                disabled_style: Style::new().fg(palette.disabled).bg(bg),
+                focused_style: Style::new().fg(palette.text).bg(palette.highlight),
+                selected_style: Style::new()
+                    .fg(palette.text)
+                    .bg(palette.highlight)
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::REVERSED),
+            },
+            key_list: KeyList {
+                header_style: Style::new()
+                    .fg(palette.bright)
+                    .bg(bg)
+                    .add_modifier(Modifier::BOLD),
+                default_style: Style::new().fg(palette.text).bg(bg),
+                invalid_style: Style::new().fg(palette.error).bg(bg),
+                expiring_style: Style::new().fg(palette.warning).bg(bg),
+                selected_style: Style::new()
+                    .bg(palette.highlight)
+                    .add_modifier(Modifier::BOLD),
+            },
+            key_detail: KeyDetail {
+                header_style: Style::new()
+                    .fg(palette.bright)
+                    .bg(bg)
+                    .add_modifier(Modifier::BOLD),
+                label_style: Style::new().fg(palette.dim).bg(bg),
+                value_style: Style::new().fg(palette.text).bg(bg),
+                signature_style: Style::new().fg(palette.dim).bg(bg),
+                invalid_style: Style::new().fg(palette.error).bg(bg),
+                selected_style: Style::new()
+                    .fg(palette.text)
+                    .bg(palette.highlight)
+                    .add_modifier(Modifier::BOLD),
+            },
+            dialog: Dialog {
+                default_style: Style::new().fg(palette.text).bg(bg),
+                border_style: Style::new().fg(palette.accent).bg(bg),
+                label_style: Style::new().fg(palette.dim).bg(bg),
+                input_style: Style::new().fg(palette.text).bg(palette.highlight),
+                focused_input_style: Style::new().fg(palette.bright).bg(palette.highlight),
+                button_style: Style::new().fg(palette.text).bg(bg),
+                focused_button_style: Style::new()
+                    .fg(palette.text)
+                    .bg(palette.highlight)
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::REVERSED),
+                error_style: Style::new().fg(palette.error).bg(bg),
+                ok_style: Style::new().fg(palette.ok).bg(bg),
+            },
+            borders: Style::new().fg(palette.text),
+            hint_bar: HintBar {
+                default_style: Style::new().fg(palette.dim).bg(bg),
+                key: Style::new().fg(bg).bg(palette.dim),
+                description: Style::new().fg(palette.dim).bg(bg),
+            },
+        }
+    }
+
+    /// The terminal's own colors, what is focused or selected is shown reversed instead
+    pub const fn no_color() -> Theme {
+        let plain = Style::new();
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        let reversed = Style::new().add_modifier(Modifier::REVERSED);
+        Theme {
+            content: plain,
+            menu_bar: MenuBar {
+                title_style: bold,
+                default_style: plain,
+                disabled_style: Style::new().add_modifier(Modifier::DIM),
+                focused_style: reversed,
+                selected_style: reversed.add_modifier(Modifier::BOLD),
+            },
+            key_list: KeyList {
+                header_style: bold,
+                default_style: plain,
+                invalid_style: Style::new().add_modifier(Modifier::CROSSED_OUT),
+                expiring_style: Style::new().add_modifier(Modifier::UNDERLINED),
+                selected_style: reversed,
+            },
+            key_detail: KeyDetail {
+                header_style: bold,
+                label_style: plain,
+                value_style: plain,
+                signature_style: plain,
+                invalid_style: Style::new().add_modifier(Modifier::CROSSED_OUT),
+                selected_style: reversed,
+            },
+            dialog: Dialog {
+                default_style: plain,
+                border_style: plain,
+                label_style: plain,
+                input_style: Style::new().add_modifier(Modifier::UNDERLINED),
+                focused_input_style: reversed,
+                button_style: plain,
+                focused_button_style: reversed.add_modifier(Modifier::BOLD),
+                error_style: bold,
+                ok_style: plain,
+            },
+            borders: plain,
+            hint_bar: HintBar {
+                default_style: plain,
+                key: reversed,
+                description: plain,
+            },
+        }
+    }
+
+    /// The built-in theme `name`
+    pub fn preset(name: &str) -> Option<Theme> {
+        Palette::preset(name).map(|palette| Theme::from_palette(&palette))
+    }
+
+    /// `themes/` next to `config.toml`, a theme file there is named after the theme
+    pub fn dir(config_path: &Path) -> PathBuf {
+        config_path.with_file_name("themes")
+    }
+
+    /// The presets followed by the themes in `dir`
+    pub fn names(dir: Option<&Path>) -> Vec<String> {
+        let mut files: Vec<String> = dir
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_string();
+                Some(name).filter(|name| is_theme_name(name))
+            })
+            .filter(|name| Palette::preset(name).is_none())
+            .collect();
+        files.sort();
+        let mut names: Vec<String> = THEME_PRESETS.iter().map(|name| name.to_string()).collect();
+        names.extend(files);
+        names
+    }
+
+    /// Theme `name`, a preset or a file in `dir`, with warnings about what was ignored
+    ///
+    /// Like the config, a bad theme never keeps the program from starting, it falls
+    /// back to the first preset.
+    pub fn load(name: &str, dir: Option<&Path>) -> (Theme, Vec<String>) {
+        if let Some(theme) = Theme::preset(name) {
+            return (theme, Vec::new());
+        }
+        let fallback = Theme::from_palette(&DARK);
+        let Some(path) = dir
+            .filter(|_| is_theme_name(name))
+            .map(|dir| dir.join(format!("{name}.toml")))
+        else {
+            return (fallback, vec![format!("unknown theme `{name}`")]);
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Theme::parse(&text),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                (fallback, vec![format!("unknown theme `{name}`")])
+            }
+            Err(err) => (fallback, vec![format!("{}: {err}", path.display())]),
+        }
+    }
+
+    /// Parse a theme file, colors it does not give come from its `base` preset
+    ///
+    /// Colors are names like `"blue"`, `"#rrggbb"` or an index like `"208"`.
+    pub fn parse(text: &str) -> (Theme, Vec<String>) {
+        let table: Table = match text.parse() {
+            Ok(table) => table,
+            Err(err) => {
+                let warning = format!("invalid theme: {}", err.message());
+                return (Theme::from_palette(&DARK), vec![warning]);
+            }
+        };
+        let mut warnings = Vec::new();
+        let mut palette = match table.get("base") {
+            None => DARK,
+            Some(base) => match base.as_str().and_then(Palette::preset) {
+                Some(palette) => palette,
+                None => {
+                    warnings.push(format!("unknown base theme {base}, using dark"));
+                    DARK
+                }
+            },
+        };
+        for (key, value) in table.iter().filter(|(key, _)| *key != "base") {
+            let Some(color) = palette.color_mut(key) else {
+                warnings.push(format!("unknown color `{key}` ignored"));
+                continue;
+            };
+            match value.as_str().and_then(|value| value.parse().ok()) {
+                Some(value) => *color = value,
+                None => warnings.push(format!("invalid color for `{key}`")),
+            }
+        }
+        (Theme::from_palette(&palette), warnings)
+    }
+}
+
+/// Whether `name` can be the name of a theme file
+pub fn is_theme_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+/// Whether `$NO_COLOR` asks to leave out all colors, see <https://no-color.org>
+pub fn no_color() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::from_palette(&DARK));
+
+/// The theme everything is drawn with
+pub fn theme() -> RwLockReadGuard<'static, Theme> {
+    CURRENT.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Draw with `theme` from now on, or without colors if `$NO_COLOR` is set
+pub fn set_theme(theme: Theme) {
+    let theme = if no_color() { Theme::no_color() } else { theme };
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = theme;
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{backend::Gpg, clipboard, theme::theme, ui::widget::popup::Popup};
+
+use super::{render_buttons, DialogResult, DialogState, FormKey};
+
+const BUTTONS: [&str; 2] = ["Copy to Clipboard", "Close"];
+
+/// Help > About, the versions and paths asked for in bug reports
+pub struct AboutDialog {
+    /// Plain text, also what is copied
+    report: String,
+    scroll: usize,
+    /// Focused button
+    focus: usize,
+    message: Option<Result<String, String>>,
+}
+
+impl AboutDialog {
+    pub fn new(gpg: &Gpg, config_path: Option<&Path>) -> Self {
+        Self {
+            report: report(gpg, config_path),
+            scroll: 0,
+            focus: 0,
+            message: None,
+        }
+    }
+}
+
+impl DialogState for AboutDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, _gpg: &Gpg) -> DialogResult {
+        let last_line = self.report.lines().count().saturating_sub(1);
+        match key.code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(last_line),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = (self.scroll + 10).min(last_line),
+            KeyCode::Home => self.scroll = 0,
+            KeyCode::End => self.scroll = last_line,
+            KeyCode::Left | KeyCode::Right => self.focus = 1 - self.focus,
+            _ => match FormKey::from_key(key) {
+                FormKey::Cancel => return DialogResult::Close,
+                FormKey::Next | FormKey::Prev => self.focus = 1 - self.focus,
+                FormKey::Confirm if BUTTONS[self.focus] == "Close" => return DialogResult::Close,
+                FormKey::Confirm => {
+                    self.message = Some(match clipboard::copy(&self.report) {
+                        Ok(()) => Ok("Copied to the clipboard".to_string()),
+                        Err(err) => Err(format!("Failed to copy: {err}")),
+                    })
+                }
+                FormKey::Other => (),
+            },
+        }
+        DialogResult::Continue
+    }
+
+    fn handle_paste(&mut self, _text: &str) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Up/Down", "Scroll"),
+            ("Tab", "Button"),
+            ("Enter", "Choose"),
+            ("Esc", "Close"),
+        ]
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 84, 24);
+        Popup::new(
+            "About gpg4terminal",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+
+        // Section titles are the lines which are not indented
+        let lines: Vec<Line> = self
+            .report
+            .lines()
+            .map(|line| {
+                if line.starts_with(' ') {
+                    Line::styled(line, theme().dialog.default_style)
+                } else {
+                    Line::styled(line, theme().dialog.label_style)
+                }
+            })
+            .collect();
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll as u16, 0))
+            .render(layout[0], buf);
+
+        match &self.message {
+            Some(Ok(message)) => Span::styled(message.as_str(), theme().dialog.ok_style),
+            Some(Err(message)) => Span::styled(message.as_str(), theme().dialog.error_style),
+            None => Span::default(),
+        }
+        .render(layout[1], buf);
+        render_buttons(&BUTTONS, Some(self.focus), layout[2], buf, button_areas);
+    }
+}
+
+/// Collect the versions, algorithms and paths as plain text
+pub fn report(gpg: &Gpg, config_path: Option<&Path>) -> String {
+    let mut report = vec![format!("gpg4terminal {}", env!("CARGO_PKG_VERSION"))];
+    let row = |name: &str, value: &str| format!("  {name:<16}{value}");
+
+    report.push(String::new());
+    report.push("GnuPG".to_string());
+    report.push(row("Program", &gpg.program.to_string_lossy()));
+    let version = gpg.version();
+    match &version {
+        Ok(version) => {
+            report.push(row("Version", &version.version));
+            for library in version.libraries.iter() {
+                report.push(row("Library", library));
+            }
+            report.push(row("Home", &version.home));
+            report.push(String::new());
+            report.push("Supported algorithms".to_string());
+            for (kind, list) in version.algorithms.iter() {
+                report.push(row(kind, list));
+            }
+        }
+        Err(err) => report.push(row("Error", &format!("gpg --version failed: {err}"))),
+    }
+
+    let dirs = gpg.list_dirs().unwrap_or_default();
+    let homedir = dirs
+        .iter()
+        .find(|(name, _)| name == "homedir")
+        .map(|(_, dir)| Path::new(dir));
+    report.push(String::new());
+    report.push("Config files".to_string());
+    let mut files: Vec<(&str, Option<std::path::PathBuf>)> =
+        vec![("gpg4terminal", config_path.map(Path::to_path_buf))];
+    for name in ["gpg.conf", "gpg-agent.conf", "dirmngr.conf"] {
+        files.push((name, homedir.map(|dir| dir.join(name))));
+    }
+    for (name, path) in files {
+        let value = match path {
+            Some(path) if path.exists() => path.display().to_string(),
+            Some(path) => format!("{} (not present)", path.display()),
+            None => "unknown".to_string(),
+        };
+        report.push(row(name, &value));
+    }
+
+    report.push(String::new());
+    report.push("gpgconf --list-dirs".to_string());
+    if dirs.is_empty() {
+        report.push("  (failed)".to_string());
+    }
+    for (name, dir) in dirs.iter() {
+        report.push(row(name, dir));
+    }
+
+    if let Ok(version) = &version {
+        report.push(String::new());
+        report.push("gpg --version".to_string());
+        report.extend(version.text.lines().map(|line| format!("  {line}")));
+    }
+    report.join("\n")
+}
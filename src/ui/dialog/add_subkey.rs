@@ -0,0 +1,245 @@
+use chrono::Utc;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{
+        expiry::expiry_date,
+        generate::{normalize_expiry, KeyAlgorithm},
+        job::Job,
+        key::{Key, RevocationReason, Subkey, Validity},
+        subkey::SubkeyUsage,
+        Gpg,
+    },
+    theme::theme,
+    ui::{key_detail::DetailItem, widget::input::TextInputUiState},
+};
+
+use super::{cycle, render_input, KeyEditForm};
+
+/// Form behind Add Subkey in the key detail view
+///
+/// Started on a subkey it proposes a successor of the same kind and revoking
+/// the old one as superseded, so a routine rotation is just a confirmation.
+pub struct AddSubkeyForm {
+    key: Key,
+    /// Index into `SubkeyUsage::ALL`
+    usage: usize,
+    /// Index into `KeyAlgorithm::ALL`
+    algorithm: usize,
+    expires: TextInputUiState,
+    /// The subkey the new one takes over from, if any
+    replaces: Option<Subkey>,
+    revoke_replaced: bool,
+    passphrase: TextInputUiState,
+}
+
+impl AddSubkeyForm {
+    /// Add a subkey to `key`, modeled on the item selected in the detail view
+    pub fn new(key: Key, selected: Option<DetailItem>) -> Self {
+        let replaces = match selected {
+            Some(DetailItem::Subkey(index)) => Some(key.subkeys[index].clone())
+                .filter(|subkey| subkey.validity != Validity::Revoked),
+            _ => None,
+        };
+        let model = replaces.as_ref().unwrap_or(&key.primary);
+        let usage = match &replaces {
+            Some(subkey) => SubkeyUsage::of(&subkey.capabilities),
+            None => SubkeyUsage::Encrypt,
+        };
+        let algorithm = KeyAlgorithm::of(model).unwrap_or_default();
+        Self {
+            usage: SubkeyUsage::ALL
+                .iter()
+                .position(|u| *u == usage)
+                .unwrap_or(0),
+            algorithm: KeyAlgorithm::ALL
+                .iter()
+                .position(|a| *a == algorithm)
+                .unwrap_or(0),
+            expires: TextInputUiState::new("1y"),
+            revoke_replaced: replaces.is_some(),
+            replaces,
+            key,
+            passphrase: TextInputUiState::masked(),
+        }
+    }
+
+    fn usage(&self) -> SubkeyUsage {
+        SubkeyUsage::ALL[self.usage]
+    }
+
+    fn algo(&self) -> &'static str {
+        self.usage().algo(KeyAlgorithm::ALL[self.algorithm])
+    }
+
+    /// The subkey to revoke once the new one is there
+    fn revoked(&self) -> Option<&Subkey> {
+        self.replaces.as_ref().filter(|_| self.revoke_replaced)
+    }
+
+    /// The expiry in the form gpg expects
+    fn validate(&self) -> Result<String, String> {
+        if !self.key.primary.has_secret {
+            return Err("The secret primary key is not available to sign the subkey".to_string());
+        }
+        let expiry = normalize_expiry(&self.expires.value)
+            .ok_or("Enter an expiry like 1y, 6m, 2w, 30d, 2030-12-31 or never")?;
+        if expiry_date(&expiry, Utc::now()).is_some_and(|date| date <= Utc::now()) {
+            return Err("The expiry date is in the past".to_string());
+        }
+        Ok(expiry)
+    }
+}
+
+impl KeyEditForm for AddSubkeyForm {
+    const TITLE: &'static str = "Add Subkey";
+    const SIZE: (u16, u16) = (76, 14);
+    const ACTION: &'static str = "Add";
+    const RUNNING: &'static str = "Adding the subkey ...";
+    // The new subkey may be there even if revoking the old one failed
+    const RELOAD_ON_FAILURE: bool = true;
+
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn field_count(&self) -> usize {
+        5
+    }
+
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState> {
+        match field {
+            2 => Some(&mut self.expires),
+            4 => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn is_choice(&self, field: usize) -> bool {
+        matches!(field, 0 | 1) || field == 3 && self.replaces.is_some()
+    }
+
+    fn change(&mut self, field: usize, key: KeyEvent) {
+        match field {
+            0 => self.usage = cycle(self.usage, SubkeyUsage::ALL.len(), key),
+            1 => self.algorithm = cycle(self.algorithm, KeyAlgorithm::ALL.len(), key),
+            _ => self.revoke_replaced = !self.revoke_replaced,
+        }
+    }
+
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String> {
+        let expiry = self.validate()?;
+        let fingerprint = self.key.fingerprint().to_string();
+        let algo = self.algo();
+        let usage = self.usage().usage();
+        let revoked = self.revoked().map(|subkey| subkey.fingerprint.clone());
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        Ok(Job::spawn(move |progress| {
+            progress.report("Adding the subkey ...");
+            gpg.add_subkey(
+                &fingerprint,
+                algo,
+                usage,
+                &expiry,
+                passphrase.as_deref(),
+                |_| (),
+            )?;
+            if let Some(revoked) = revoked {
+                progress.report("Revoking the replaced subkey ...");
+                gpg.revoke_subkey(
+                    &fingerprint,
+                    &revoked,
+                    RevocationReason::Superseded,
+                    "",
+                    passphrase.as_deref(),
+                )?;
+            }
+            Ok(())
+        }))
+    }
+
+    fn clear_passphrase(&mut self) {
+        self.passphrase.set_value("");
+    }
+
+    fn done_message(&self) -> String {
+        let mut message = format!("Added a new {} subkey", self.usage().label().to_lowercase());
+        if let Some(revoked) = self.revoked() {
+            message.push_str(&format!(" and revoked {} as superseded", revoked.key_id));
+        }
+        message
+    }
+
+    fn render(&self, focus: usize, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let field_style = |index: usize| {
+            if focus == index {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 9]).split(body);
+        // The algorithm gpg gets, which depends on the usage for elliptic curves
+        let choices = [
+            ("Usage", self.usage().label(), ""),
+            (
+                "Algorithm",
+                KeyAlgorithm::ALL[self.algorithm].label(),
+                self.algo(),
+            ),
+        ];
+        for (i, (name, choice, note)) in choices.into_iter().enumerate() {
+            Line::from(vec![
+                Span::styled(format!("{name:<16}"), label),
+                Span::styled(format!("< {choice:<20} >"), field_style(i)),
+                Span::styled(format!("  {note}"), label),
+            ])
+            .render(rows[i], buf);
+        }
+
+        render_input("Expiry", 16, &self.expires, focus == 2, rows[2], buf);
+        let preview = match normalize_expiry(&self.expires.value) {
+            Some(expiry) => match expiry_date(&expiry, Utc::now()) {
+                Some(date) => format!("Expires on {}", date.format("%Y-%m-%d")),
+                None => "Never expires".to_string(),
+            },
+            None => "e.g. 1y, 6m, 2w, 30d, 2030-12-31 or never".to_string(),
+        };
+        Span::styled(format!("{:16}{preview}", ""), label).render(rows[3], buf);
+
+        let replace = match &self.replaces {
+            Some(subkey) => {
+                let check = if self.revoke_replaced { "[x]" } else { "[ ]" };
+                format!(
+                    "{check} Revoke {} {} as superseded",
+                    subkey.capabilities, subkey.key_id
+                )
+            }
+            None => "Select a subkey first to replace it".to_string(),
+        };
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Replace"), label),
+            Span::styled(replace, field_style(3)),
+        ])
+        .render(rows[5], buf);
+
+        render_input("Passphrase", 16, &self.passphrase, focus == 4, rows[7], buf);
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[8], buf);
+    }
+}
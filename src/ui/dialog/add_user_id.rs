@@ -0,0 +1,122 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{generate::format_user_id, job::Job, key::Key, Gpg},
+    theme::theme,
+    ui::widget::input::TextInputUiState,
+};
+
+use super::{new_key_pair::check_identity, render_input, KeyEditForm};
+
+/// Form behind Add UID in the key detail view
+pub struct AddUserIdForm {
+    key: Key,
+    name: TextInputUiState,
+    email: TextInputUiState,
+    comment: TextInputUiState,
+    passphrase: TextInputUiState,
+}
+
+impl AddUserIdForm {
+    /// Add a user ID to `key`, starting with the name of its primary user ID
+    pub fn new(key: Key) -> Self {
+        let name = key
+            .primary_uid()
+            .map(|uid| uid.name().to_string())
+            .unwrap_or_default();
+        Self {
+            key,
+            name: TextInputUiState::new(&name),
+            email: TextInputUiState::default(),
+            comment: TextInputUiState::default(),
+            passphrase: TextInputUiState::masked(),
+        }
+    }
+
+    fn user_id(&self) -> String {
+        format_user_id(&self.name.value, &self.email.value, &self.comment.value)
+    }
+
+    fn validate(&self) -> Result<String, String> {
+        check_identity(&self.name.value, &self.email.value, &self.comment.value)?;
+        if !self.key.primary.has_secret {
+            return Err("The secret primary key is not available to sign the user ID".to_string());
+        }
+        let user_id = self.user_id();
+        if self.key.user_ids.iter().any(|uid| uid.uid == user_id) {
+            return Err("The key already has this user ID".to_string());
+        }
+        Ok(user_id)
+    }
+}
+
+impl KeyEditForm for AddUserIdForm {
+    const TITLE: &'static str = "Add User ID";
+    const SIZE: (u16, u16) = (76, 14);
+    const ACTION: &'static str = "Add";
+    const RUNNING: &'static str = "Adding the user ID ...";
+
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn field_count(&self) -> usize {
+        4
+    }
+
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState> {
+        match field {
+            0 => Some(&mut self.name),
+            1 => Some(&mut self.email),
+            2 => Some(&mut self.comment),
+            3 => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String> {
+        let user_id = self.validate()?;
+        let fingerprint = self.key.fingerprint().to_string();
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        Ok(Job::spawn(move |_| {
+            gpg.add_user_id(&fingerprint, &user_id, passphrase.as_deref())
+        }))
+    }
+
+    fn clear_passphrase(&mut self) {
+        self.passphrase.set_value("");
+    }
+
+    fn done_message(&self) -> String {
+        format!("Added {}", self.user_id())
+    }
+
+    fn render(&self, focus: usize, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 9]).split(body);
+        render_input("Name", 16, &self.name, focus == 0, rows[0], buf);
+        render_input("E-Mail", 16, &self.email, focus == 1, rows[2], buf);
+        render_input("Comment", 16, &self.comment, focus == 2, rows[4], buf);
+        render_input("Passphrase", 16, &self.passphrase, focus == 3, rows[6], buf);
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[7], buf);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "New user ID"), label),
+            Span::styled(self.user_id(), theme().dialog.default_style),
+        ])
+        .render(rows[8], buf);
+    }
+}
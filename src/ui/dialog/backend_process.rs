@@ -0,0 +1,297 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        component::{Component, ComponentState},
+        job::Job,
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::popup::Popup,
+};
+
+use super::{render_buttons, spinner, DialogResult, DialogState, FormKey};
+
+const BUTTONS: [&str; 5] = ["Restart", "Kill", "Launch", "Restart All", "Close"];
+
+/// Components and the message of the action before listing them
+type Listing = (Vec<Component>, Result<String, String>);
+
+/// Panel behind Tool > Restart Backend Process
+pub struct BackendProcessDialog {
+    /// 0 is the component list, followed by the buttons
+    focus: usize,
+    components: Vec<Component>,
+    /// Highlighted component
+    selected: usize,
+    /// Outcome of the last action
+    message: Option<Result<String, String>>,
+    job: Option<Job<Listing>>,
+    /// What the running job does, e.g. `Restarting gpg-agent`
+    running: String,
+    ticks: usize,
+}
+
+impl BackendProcessDialog {
+    pub fn new(gpg: &Gpg) -> Self {
+        let mut dialog = Self {
+            focus: 0,
+            components: Vec::new(),
+            selected: 0,
+            message: None,
+            job: None,
+            running: String::new(),
+            ticks: 0,
+        };
+        dialog.run(gpg, "Listing components", |_| Ok(String::new()));
+        dialog
+    }
+
+    fn selected_component(&self) -> Option<&Component> {
+        self.components.get(self.selected)
+    }
+
+    /// Run `action` in the background and list the components afterwards
+    fn run<F>(&mut self, gpg: &Gpg, running: &str, action: F)
+    where
+        F: FnOnce(&Gpg) -> std::io::Result<String> + Send + 'static,
+    {
+        let gpg = gpg.clone();
+        self.running = running.to_string();
+        self.job = Some(Job::spawn(move |_| {
+            let outcome = action(&gpg).map_err(|err| err.to_string());
+            Ok((gpg.list_components()?, outcome))
+        }));
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        if button == "Close" {
+            return DialogResult::Close;
+        }
+        if button == "Restart All" {
+            self.run(gpg, "Restarting all daemons", |gpg| {
+                gpg.restart_all()?;
+                Ok("All daemons were restarted".to_string())
+            });
+            return DialogResult::Continue;
+        }
+        let Some(component) = self.selected_component() else {
+            return DialogResult::Continue;
+        };
+        if !component.is_daemon() {
+            self.message = Some(Err(format!(
+                "{} is not a daemon, it is started for every use",
+                component.name
+            )));
+            return DialogResult::Continue;
+        }
+        let name = component.name.clone();
+        match button {
+            "Restart" => self.run(gpg, &format!("Restarting {name}"), move |gpg| {
+                gpg.kill_component(&name)?;
+                gpg.launch_component(&name)?;
+                Ok(format!("{name} was restarted"))
+            }),
+            "Kill" => self.run(gpg, &format!("Stopping {name}"), move |gpg| {
+                gpg.kill_component(&name)?;
+                Ok(format!("{name} was stopped"))
+            }),
+            _ => self.run(gpg, &format!("Launching {name}"), move |gpg| {
+                gpg.launch_component(&name)?;
+                Ok(format!("{name} was launched"))
+            }),
+        }
+        DialogResult::Continue
+    }
+
+    fn component_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::styled(
+            format!(
+                "{:<12} {:<20} {:<8} Program",
+                "Component", "Description", "State"
+            ),
+            theme().dialog.label_style,
+        )];
+        for (i, component) in self.components.iter().enumerate() {
+            let style = if i == self.selected && self.focus == 0 {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.default_style
+            };
+            let state_style = match component.state {
+                ComponentState::Running if style == theme().dialog.default_style => {
+                    theme().dialog.ok_style
+                }
+                ComponentState::Stopped if style == theme().dialog.default_style => {
+                    theme().dialog.error_style
+                }
+                _ => style,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<12} {:<20.20} ", component.name, component.description),
+                    style,
+                ),
+                Span::styled(format!("{:<8}", component.state.label()), state_style),
+                Span::styled(format!(" {}", component.program.to_string_lossy()), style),
+            ]));
+        }
+        lines
+    }
+}
+
+impl DialogState for BackendProcessDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = index + 1;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.job.is_some() {
+            return DialogResult::Continue;
+        }
+        let focus_count = 1 + BUTTONS.len();
+
+        // The component list uses Up/Down itself, only Tab leaves it
+        if self.focus == 0 {
+            let last = self.components.len().saturating_sub(1);
+            match key.code {
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down => self.selected = (self.selected + 1).min(last),
+                KeyCode::Home => self.selected = 0,
+                KeyCode::End => self.selected = last,
+                // Restart the highlighted component
+                KeyCode::Enter => return self.press(BUTTONS[0], gpg),
+                _ => (),
+            }
+            if matches!(key.code, KeyCode::Up | KeyCode::Down) {
+                return DialogResult::Continue;
+            }
+        }
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if self.focus > 0 => {
+                return self.press(BUTTONS[self.focus - 1], gpg);
+            }
+            FormKey::Confirm => (),
+            FormKey::Other => match key.code {
+                KeyCode::Left if self.focus > 1 => self.focus -= 1,
+                KeyCode::Right if self.focus > 0 && self.focus + 1 < focus_count => self.focus += 1,
+                _ => (),
+            },
+        }
+        DialogResult::Continue
+    }
+
+    fn handle_paste(&mut self, _text: &str) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        match result {
+            Ok((components, outcome)) => {
+                self.components = components;
+                self.selected = self.selected.min(self.components.len().saturating_sub(1));
+                self.message = Some(outcome).filter(|outcome| outcome != &Ok(String::new()));
+            }
+            Err(err) => self.message = Some(Err(format!("Failed to list components: {err}"))),
+        }
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.focus {
+            _ if self.job.is_some() => vec![],
+            0 => vec![
+                ("Up/Down", "Select"),
+                ("Enter", "Restart"),
+                ("Tab", "Buttons"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Close")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 90, 22);
+        Popup::new(
+            "Backend Processes",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(6),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let label = theme().dialog.label_style;
+
+        let list = self.component_lines();
+        let scroll = (self.selected + 2).saturating_sub(layout[0].height as usize);
+        Paragraph::new(list)
+            .scroll((scroll as u16, 0))
+            .render(layout[0], buf);
+
+        // Sockets of the highlighted component
+        let mut details = Vec::new();
+        if let Some(component) = self.selected_component() {
+            if component.sockets.is_empty() {
+                let note = match component.state {
+                    ComponentState::NotDaemon => "Started by gpg for every use, no socket",
+                    _ => "Started by gpg-agent on demand, no socket of its own",
+                };
+                details.push(Line::styled(note, label));
+            }
+            for (name, path) in component.sockets.iter() {
+                details.push(Line::from(vec![
+                    Span::styled(format!("{name:<22}"), label),
+                    Span::styled(
+                        path.to_string_lossy().into_owned(),
+                        theme().dialog.default_style,
+                    ),
+                ]));
+            }
+        }
+        Paragraph::new(details)
+            .wrap(Wrap { trim: false })
+            .render(layout[1], buf);
+
+        let status = match (&self.job, &self.message) {
+            (Some(_), _) => Span::styled(
+                format!("{} {} ...", spinner(self.ticks), self.running),
+                theme().dialog.default_style,
+            ),
+            (None, Some(Ok(message))) => Span::styled(message.clone(), theme().dialog.ok_style),
+            (None, Some(Err(message))) => Span::styled(message.clone(), theme().dialog.error_style),
+            (None, None) => Span::default(),
+        };
+        status.render(layout[2], buf);
+        render_buttons(
+            &BUTTONS,
+            self.focus.checked_sub(1),
+            layout[3],
+            buf,
+            button_areas,
+        );
+    }
+}
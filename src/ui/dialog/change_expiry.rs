@@ -0,0 +1,425 @@
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        expiry::expiry_date,
+        generate::normalize_expiry,
+        job::Job,
+        key::{Key, Subkey, Validity},
+        Gpg,
+    },
+    theme::theme,
+    ui::{
+        key_detail::DetailItem,
+        widget::{input::TextInputUiState, popup::Popup},
+    },
+};
+
+use super::{render_buttons, render_input, spinner, DialogResult, DialogState, FormKey};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Options,
+    Running,
+    Done(String),   // Done(Message)
+    Failed(String), // Failed(Error)
+}
+
+/// Form behind Expiry in the key detail view, for the primary key and any of the subkeys
+pub struct ChangeExpiryDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    key: Key,
+    /// The keys listed, `None` for the primary key and the index of a subkey otherwise
+    rows: Vec<Option<usize>>,
+    /// Which of the `rows` get the new expiry
+    chosen: Vec<bool>,
+    /// Row under the cursor
+    cursor: usize,
+    expires: TextInputUiState,
+    passphrase: TextInputUiState,
+    error: Option<String>,
+    job: Option<Job<()>>,
+    ticks: usize,
+}
+
+impl ChangeExpiryDialog {
+    /// Change the expiry of `key`, starting with the item selected in the detail view
+    pub fn new(key: Key, selected: Option<DetailItem>) -> Self {
+        // Revoked subkeys stay revoked whatever their expiry is
+        let mut rows = vec![None];
+        rows.extend(
+            (0..key.subkeys.len())
+                .filter(|i| key.subkeys[*i].validity != Validity::Revoked)
+                .map(Some),
+        );
+        let cursor = match selected {
+            Some(DetailItem::Subkey(index)) => {
+                rows.iter().position(|row| *row == Some(index)).unwrap_or(0)
+            }
+            _ => 0,
+        };
+        let mut chosen = vec![false; rows.len()];
+        chosen[cursor] = true;
+        Self {
+            step: Step::Options,
+            focus: 0,
+            key,
+            rows,
+            chosen,
+            cursor,
+            expires: TextInputUiState::new("1y"),
+            passphrase: TextInputUiState::masked(),
+            error: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Options => 3,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Options => &["Change", "Cancel"],
+            Step::Running => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.focus = 0;
+        self.step = step;
+        self.error = None;
+    }
+
+    fn subkey(&self, row: Option<usize>) -> &Subkey {
+        match row {
+            Some(index) => &self.key.subkeys[index],
+            None => &self.key.primary,
+        }
+    }
+
+    /// The fingerprints of the chosen subkeys, and whether the primary key is chosen
+    fn chosen_keys(&self) -> (bool, Vec<String>) {
+        let mut primary = false;
+        let mut subkeys = Vec::new();
+        for (row, _) in self.rows.iter().zip(&self.chosen).filter(|(_, c)| **c) {
+            match row {
+                Some(index) => subkeys.push(self.key.subkeys[*index].fingerprint.clone()),
+                None => primary = true,
+            }
+        }
+        (primary, subkeys)
+    }
+
+    /// The expiry in the form gpg expects
+    fn validate(&self) -> Result<String, String> {
+        if !self.chosen.contains(&true) {
+            return Err("Choose the keys to change with Space".to_string());
+        }
+        if !self.key.primary.has_secret {
+            return Err("The secret primary key is not available to sign the change".to_string());
+        }
+        let expiry = normalize_expiry(&self.expires.value)
+            .ok_or("Enter an expiry like 1y, 6m, 2w, 30d, 2030-12-31 or never")?;
+        if expiry_date(&expiry, Utc::now()).is_some_and(|date| date <= Utc::now()) {
+            return Err("The expiry date is in the past".to_string());
+        }
+        Ok(expiry)
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        let expiry = match self.validate() {
+            Ok(expiry) => expiry,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        let fingerprint = self.key.fingerprint().to_string();
+        let (primary, subkeys) = self.chosen_keys();
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |_| {
+            if primary {
+                gpg.set_expiry(&fingerprint, &[], &expiry, passphrase.as_deref())?;
+            }
+            if !subkeys.is_empty() {
+                gpg.set_expiry(&fingerprint, &subkeys, &expiry, passphrase.as_deref())?;
+            }
+            Ok(())
+        }));
+        self.go_to(Step::Running);
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Options, 1) => Some(&mut self.expires),
+            (Step::Options, 2) => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.start(gpg),
+            FormKey::Other => {
+                if let Some(input) = self.focused_input() {
+                    input.handle_key(key);
+                } else if on_button {
+                    match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Change" => self.start(gpg),
+            "Back" => self.go_to(Step::Options),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match self.step {
+            Step::Done(_) => DialogResult::CloseAndReload(Some(self.key.fingerprint().to_string())),
+            _ => DialogResult::Close,
+        }
+    }
+
+    fn render_options(&self, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(body);
+
+        let lines: Vec<Line> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let subkey = self.subkey(*row);
+                let style = match (i == self.cursor, self.focus == 0) {
+                    (true, true) => theme().dialog.focused_input_style,
+                    (true, false) => theme().dialog.input_style,
+                    _ => theme().dialog.default_style,
+                };
+                let check = if self.chosen[i] { "[x]" } else { "[ ]" };
+                let kind = if row.is_none() { "Primary" } else { "Subkey" };
+                let expires = match subkey.expires {
+                    Some(date) => date.format("%Y-%m-%d").to_string(),
+                    None => "never".to_string(),
+                };
+                Line::styled(
+                    format!(
+                        "{check} {kind:<8}{:<5}{:<16}{:<18}expires {expires:<12}",
+                        subkey.capabilities.to_string(),
+                        subkey.algorithm_name(),
+                        subkey.key_id,
+                    ),
+                    style,
+                )
+            })
+            .collect();
+        let scroll = (self.cursor + 1).saturating_sub(layout[0].height as usize);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .render(layout[0], buf);
+
+        render_input(
+            "New expiry",
+            16,
+            &self.expires,
+            self.focus == 1,
+            layout[2],
+            buf,
+        );
+        let preview = match normalize_expiry(&self.expires.value) {
+            Some(expiry) => match expiry_date(&expiry, Utc::now()) {
+                Some(date) => format!("Expires on {}", date.format("%Y-%m-%d")),
+                None => "Never expires".to_string(),
+            },
+            None => "e.g. 1y, 6m, 2w, 30d, 2030-12-31 or never".to_string(),
+        };
+        Span::styled(format!("{:16}{preview}", ""), label).render(layout[3], buf);
+        render_input(
+            "Passphrase",
+            16,
+            &self.passphrase,
+            self.focus == 2,
+            layout[4],
+            buf,
+        );
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(layout[5], buf);
+    }
+}
+
+impl DialogState for ChangeExpiryDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Running {
+            return DialogResult::Continue;
+        }
+
+        // The key list uses Up/Down and Space itself
+        if self.step == Step::Options && self.focus == 0 {
+            let last = self.rows.len() - 1;
+            match key.code {
+                KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+                KeyCode::Down => self.cursor = (self.cursor + 1).min(last),
+                KeyCode::Char(' ') => self.chosen[self.cursor] = !self.chosen[self.cursor],
+                KeyCode::Char('a') => {
+                    let all = !self.chosen.iter().all(|chosen| *chosen);
+                    self.chosen.fill(all);
+                }
+                _ => return self.handle_form_key(key, gpg),
+            }
+            return DialogResult::Continue;
+        }
+        self.handle_form_key(key, gpg)
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        self.passphrase.set_value("");
+        match result {
+            Ok(()) => {
+                let count = self.chosen.iter().filter(|chosen| **chosen).count();
+                let expiry = normalize_expiry(&self.expires.value).unwrap_or_default();
+                let until = match expiry_date(&expiry, Utc::now()) {
+                    Some(date) => format!("until {}", date.format("%Y-%m-%d")),
+                    None => "to never expire".to_string(),
+                };
+                self.go_to(Step::Done(format!("Changed {count} key(s) {until}")));
+                DialogResult::Reload(Some(self.key.fingerprint().to_string()))
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match (&self.step, self.focus) {
+            (Step::Running, _) => vec![],
+            (Step::Options, 0) => vec![
+                ("Up/Down", "Select"),
+                ("Space", "Choose"),
+                ("a", "All"),
+                ("Tab", "Next Field"),
+                ("Esc", "Cancel"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 80, 16);
+        Popup::new(
+            "Change Expiry",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Options => self.render_options(body, buf),
+            Step::Running => Line::styled(
+                format!("{} Changing the expiry ...", spinner(self.ticks)),
+                value,
+            )
+            .render(body, buf),
+            Step::Done(message) => {
+                Line::styled(message.clone(), theme().dialog.ok_style).render(body, buf)
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
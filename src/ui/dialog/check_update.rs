@@ -0,0 +1,225 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{job::Job, update::UpdateCheck, Gpg},
+    config::Config,
+    theme::theme,
+    ui::widget::popup::Popup,
+};
+
+use super::{render_buttons, spinner, DialogResult, DialogState, FormKey};
+
+const BUTTONS: [&str; 2] = ["Check Again", "Close"];
+
+/// Help > Check Update, lists the releases newer than this build
+pub struct CheckUpdateDialog {
+    feed: String,
+    /// Fingerprint the feed signature has to be made by
+    release_key: Option<String>,
+    date_format: String,
+    job: Option<Job<UpdateCheck>>,
+    result: Option<Result<UpdateCheck, String>>,
+    /// First shown line of the changelog
+    scroll: usize,
+    /// Focused button
+    focus: usize,
+    ticks: usize,
+}
+
+impl CheckUpdateDialog {
+    pub fn new(gpg: &Gpg, config: &Config) -> Self {
+        let mut dialog = Self {
+            feed: config.update_feed.clone(),
+            release_key: config.update_key.clone(),
+            date_format: config.date_format.clone(),
+            job: None,
+            result: None,
+            scroll: 0,
+            focus: 0,
+            ticks: 0,
+        };
+        dialog.check(gpg);
+        dialog
+    }
+
+    fn check(&mut self, gpg: &Gpg) {
+        let gpg = gpg.clone();
+        let feed = self.feed.clone();
+        let release_key = self.release_key.clone();
+        self.result = None;
+        self.scroll = 0;
+        self.job = Some(Job::spawn(move |_| {
+            gpg.check_update(&feed, release_key.as_deref())
+        }));
+    }
+
+    /// The changelog of the newer releases
+    fn changelog(&self) -> Vec<Line<'static>> {
+        let Some(Ok(check)) = &self.result else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        for release in check.newer.iter() {
+            let date = release
+                .published
+                .map(|date| format!("  ({})", date.format(&self.date_format)))
+                .unwrap_or_default();
+            let version = release.version.to_string();
+            // Titles like `v1.2.0` already name the version
+            let heading = if release.title.contains(&version) {
+                release.title.clone()
+            } else {
+                format!("{version}  {}", release.title)
+            };
+            lines.push(Line::styled(
+                format!("{heading}{date}"),
+                theme().dialog.label_style,
+            ));
+            for line in release.notes.lines() {
+                lines.push(Line::styled(
+                    format!("  {line}"),
+                    theme().dialog.default_style,
+                ));
+            }
+            if let Some(link) = &release.link {
+                lines.push(Line::styled(
+                    format!("  {link}"),
+                    theme().dialog.label_style,
+                ));
+            }
+            lines.push(Line::default());
+        }
+        lines
+    }
+}
+
+impl DialogState for CheckUpdateDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let last_line = self.changelog().len().saturating_sub(1);
+        match key.code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = (self.scroll + 1).min(last_line),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = (self.scroll + 10).min(last_line),
+            KeyCode::Left | KeyCode::Right => self.focus = 1 - self.focus,
+            _ => match FormKey::from_key(key) {
+                FormKey::Cancel => return DialogResult::Close,
+                FormKey::Next | FormKey::Prev => self.focus = 1 - self.focus,
+                FormKey::Confirm if BUTTONS[self.focus] == "Close" => return DialogResult::Close,
+                FormKey::Confirm if self.job.is_none() => self.check(gpg),
+                _ => (),
+            },
+        }
+        DialogResult::Continue
+    }
+
+    fn handle_paste(&mut self, _text: &str) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks += 1;
+        if let Some(result) = self.job.as_mut().and_then(Job::poll) {
+            self.job = None;
+            self.result = Some(result.map_err(|err| err.to_string()));
+        }
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Up/Down", "Scroll"),
+            ("Tab", "Button"),
+            ("Enter", "Choose"),
+            ("Esc", "Close"),
+        ]
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 80, 22);
+        Popup::new(
+            "Check Update",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let label = theme().dialog.label_style;
+
+        Span::styled(format!("Feed: {}", self.feed), label).render(layout[0], buf);
+        let (status, signature) = match &self.result {
+            None => (
+                Span::styled(
+                    format!("{} Checking for updates...", spinner(self.ticks)),
+                    label,
+                ),
+                Span::default(),
+            ),
+            Some(Err(err)) => (
+                Span::styled(
+                    format!("Update check failed: {err}"),
+                    theme().dialog.error_style,
+                ),
+                Span::default(),
+            ),
+            Some(Ok(check)) => (
+                match check.newer.len() {
+                    0 => Span::styled(
+                        format!("gpg4terminal {} is up to date", check.current),
+                        theme().dialog.ok_style,
+                    ),
+                    count => Span::styled(
+                        format!(
+                            "{count} newer release(s), this is gpg4terminal {}",
+                            check.current
+                        ),
+                        theme().dialog.ok_style,
+                    ),
+                },
+                match &check.signed_by {
+                    Some(fingerprint) => Span::styled(
+                        format!("Feed signed by release key {fingerprint}"),
+                        theme().dialog.ok_style,
+                    ),
+                    None => Span::styled(
+                        "Feed signature not checked, no update_key in config.toml",
+                        label,
+                    ),
+                },
+            ),
+        };
+        Paragraph::new(status)
+            .wrap(Wrap { trim: true })
+            .render(layout[1], buf);
+        signature.render(layout[2], buf);
+
+        Paragraph::new(self.changelog())
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll as u16, 0))
+            .render(layout[4], buf);
+        let focused = self.job.is_none().then_some(self.focus);
+        render_buttons(&BUTTONS, focused, layout[5], buf, button_areas);
+    }
+}
@@ -0,0 +1,580 @@
+use std::{fs, path::PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        decrypt::{detached_pair, DecryptResult, Input, Integrity},
+        job::Job,
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::{
+        input::TextInputUiState,
+        popup::Popup,
+        text_area::{TextArea, TextAreaUiState},
+    },
+};
+
+use super::{render_buttons, render_input, spinner, DialogResult, DialogState, FormKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    File,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Input,
+    Running,
+    Result,
+    View,
+    Save,
+    Failed(String), // Failed(Error)
+}
+
+/// Dialog behind File > Decrypt/Verify
+pub struct DecryptVerifyDialog {
+    step: Step,
+    /// 0 is the source, 1 the file or text, 2 the passphrase, then the buttons
+    focus: usize,
+    source: Source,
+    path: TextInputUiState,
+    text: TextAreaUiState,
+    passphrase: TextInputUiState,
+    output: TextInputUiState,
+    result: Option<DecryptResult>,
+    /// First line of the plaintext view
+    scroll: u16,
+    /// The output file exists, saving again overwrites it
+    confirm_overwrite: bool,
+    error: Option<String>,
+    /// Message after saving the plaintext
+    saved: Option<String>,
+    job: Option<Job<DecryptResult>>,
+    ticks: usize,
+}
+
+impl Default for DecryptVerifyDialog {
+    fn default() -> Self {
+        Self {
+            step: Step::Input,
+            focus: 1,
+            source: Source::File,
+            path: TextInputUiState::default(),
+            text: TextAreaUiState::default(),
+            passphrase: TextInputUiState::masked(),
+            output: TextInputUiState::default(),
+            result: None,
+            scroll: 0,
+            confirm_overwrite: false,
+            error: None,
+            saved: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+}
+
+impl DecryptVerifyDialog {
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Input => 3,
+            Step::Save => 1,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        let has_plaintext = self
+            .result
+            .as_ref()
+            .is_some_and(|result| !result.plaintext.is_empty());
+        let is_text = self.result.as_ref().is_some_and(DecryptResult::is_text);
+        match self.step {
+            Step::Input => &["Run", "Cancel"],
+            Step::Running => &[],
+            Step::Result if has_plaintext && is_text => &["View", "Save", "Back", "Close"],
+            Step::Result if has_plaintext => &["Save", "Back", "Close"],
+            Step::Result => &["Back", "Close"],
+            Step::View => &["Save", "Back", "Close"],
+            Step::Save => &["Save", "Back"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.focus = match step {
+            Step::Input => 1,
+            _ => 0,
+        };
+        self.step = step;
+        self.scroll = 0;
+        self.confirm_overwrite = false;
+        self.error = None;
+    }
+
+    /// The detached signature and data file, if the picked file is one of them
+    fn detached(&self) -> Option<(PathBuf, PathBuf)> {
+        let path = self.path.value.trim();
+        match self.source {
+            Source::File if !path.is_empty() => detached_pair(&PathBuf::from(path)),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        let path = PathBuf::from(self.path.value.trim());
+        match (self.source, self.detached()) {
+            (Source::File, _) if !path.is_file() => {
+                self.error = Some("Enter the path of an existing file".to_string());
+                return;
+            }
+            (Source::File, Some((signature, data))) => {
+                self.job = Some(Job::spawn(move |_| gpg.verify_detached(&signature, &data)));
+            }
+            (Source::File, None) => {
+                let input = Input::File(path);
+                self.job = Some(Job::spawn(move |_| {
+                    gpg.decrypt(&input, passphrase.as_deref())
+                }));
+            }
+            (Source::Text, _) if self.text.is_empty() => {
+                self.error = Some("Paste an encrypted or signed message".to_string());
+                return;
+            }
+            (Source::Text, _) => {
+                let input = Input::Data(self.text.value().into_bytes());
+                self.job = Some(Job::spawn(move |_| {
+                    gpg.decrypt(&input, passphrase.as_deref())
+                }));
+            }
+        }
+        self.saved = None;
+        self.go_to(Step::Running);
+    }
+
+    /// Where to save the plaintext unless the user picks another path
+    fn default_output(&self) -> String {
+        let path = PathBuf::from(self.path.value.trim());
+        let stored_name = self
+            .result
+            .as_ref()
+            .and_then(|result| result.filename.clone())
+            .filter(|name| !name.contains('/') && name != "_CONSOLE");
+        match (self.source, path.extension().and_then(|ext| ext.to_str())) {
+            (Source::File, Some("gpg" | "pgp" | "asc")) => {
+                path.with_extension("").to_string_lossy().into_owned()
+            }
+            (Source::File, _) => format!("{}.out", path.to_string_lossy()),
+            (Source::Text, _) => stored_name.unwrap_or_else(|| "plaintext.txt".to_string()),
+        }
+    }
+
+    fn save(&mut self) {
+        let path = PathBuf::from(self.output.value.trim());
+        if self.output.value.trim().is_empty() {
+            self.error = Some("Enter the path to save the plaintext to".to_string());
+            return;
+        }
+        if path.exists() && !self.confirm_overwrite {
+            self.confirm_overwrite = true;
+            self.error = Some("The file exists, press Save again to overwrite it".to_string());
+            return;
+        }
+        let plaintext = self
+            .result
+            .as_ref()
+            .map(|result| result.plaintext.as_slice())
+            .unwrap_or_default();
+        match fs::write(&path, plaintext) {
+            Ok(()) => {
+                self.saved = Some(format!("Saved to {}", path.to_string_lossy()));
+                self.go_to(Step::Result);
+            }
+            Err(err) => self.error = Some(format!("Failed to save: {err}")),
+        }
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.source, self.focus) {
+            (Step::Input, Source::File, 1) => Some(&mut self.path),
+            (Step::Input, _, 2) => Some(&mut self.passphrase),
+            (Step::Save, _, 0) => Some(&mut self.output),
+            _ => None,
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm if self.step == Step::Save => self.save(),
+            FormKey::Confirm => self.start(gpg),
+            FormKey::Other => {
+                if self.step == Step::Input && self.focus == 0 {
+                    if matches!(
+                        key.code,
+                        KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                    ) {
+                        self.source = match self.source {
+                            Source::File => Source::Text,
+                            Source::Text => Source::File,
+                        };
+                        self.error = None;
+                    }
+                } else if self.step == Step::Input && self.source == Source::Text && self.focus == 1
+                {
+                    self.text.handle_key(key);
+                } else if let Some(input) = self.focused_input() {
+                    input.handle_key(key);
+                    self.confirm_overwrite = false;
+                } else if on_button {
+                    match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match (button, &self.step) {
+            ("Run", _) => self.start(gpg),
+            ("View", _) => self.go_to(Step::View),
+            ("Save", Step::Save) => self.save(),
+            ("Save", _) => {
+                let output = self.default_output();
+                self.output.set_value(&output);
+                self.go_to(Step::Save);
+            }
+            ("Back", Step::Save | Step::View) => self.go_to(Step::Result),
+            ("Back", _) => self.go_to(Step::Input),
+            _ => return DialogResult::Close,
+        }
+        DialogResult::Continue
+    }
+
+    fn result_lines(result: &DecryptResult) -> Vec<Line<'static>> {
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let ok = theme().dialog.ok_style;
+        let bad = theme().dialog.error_style;
+        let row = |name: &str, text: String, style| {
+            Line::from(vec![
+                Span::styled(format!("{name:<16}"), label),
+                Span::styled(text, style),
+            ])
+        };
+        let mut lines = Vec::new();
+
+        if result.is_encrypted() {
+            let recipients = if result.symmetric && result.recipients.is_empty() {
+                "passphrase only".to_string()
+            } else {
+                result.recipients.join(", ")
+            };
+            lines.push(row("Encrypted to", recipients, value));
+            if let Some(key) = &result.decryption_key {
+                lines.push(row("Decrypted with", key.clone(), value));
+            }
+            let style = match result.integrity {
+                Integrity::Mdc | Integrity::Aead => ok,
+                _ => bad,
+            };
+            lines.push(row(
+                "Integrity",
+                result.integrity.describe().to_string(),
+                style,
+            ));
+        }
+
+        if result.signatures.is_empty() {
+            lines.push(row("Signature", "not signed".to_string(), value));
+        }
+        for signature in result.signatures.iter() {
+            let style = if signature.status.is_good() { ok } else { bad };
+            let signer = if signature.user_id.is_empty() {
+                String::new()
+            } else {
+                format!(" from \"{}\"", signature.user_id)
+            };
+            lines.push(row(
+                "Signature",
+                format!("{}{signer}", signature.status.describe()),
+                style,
+            ));
+            let key = signature
+                .primary_fingerprint
+                .clone()
+                .or(signature.fingerprint.clone())
+                .unwrap_or(signature.key_id.clone());
+            lines.push(row("", format!("key {key}"), label));
+            let mut details = Vec::new();
+            if let Some(created) = signature.created {
+                details.push(format!("made {}", created.format("%Y-%m-%d %H:%M")));
+            }
+            if let Some(trust) = &signature.trust {
+                details.push(format!("trust {trust}"));
+            }
+            if !details.is_empty() {
+                lines.push(row("", details.join(", "), label));
+            }
+        }
+
+        if !result.plaintext.is_empty() {
+            let kind = if result.is_text() { "text" } else { "binary" };
+            let mut text = format!("{} bytes, {kind}", result.plaintext.len());
+            if let Some(name) = &result.filename {
+                text.push_str(&format!(", file name \"{name}\""));
+            }
+            lines.push(row("Plaintext", text, value));
+        }
+
+        if !result.success {
+            lines.push(Line::default());
+            lines.push(Line::styled("gpg reported an error:", bad));
+            lines.extend(
+                result
+                    .log
+                    .lines()
+                    .map(|line| Line::styled(line.to_string(), label)),
+            );
+        }
+        lines
+    }
+}
+
+impl DialogState for DecryptVerifyDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Running {
+            return DialogResult::Continue;
+        }
+        let on_text = self.step == Step::Input && self.source == Source::Text && self.focus == 1;
+
+        // The text area and the plaintext view use Up/Down themselves
+        if on_text && matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::Enter) {
+            self.text.handle_key(key);
+            return DialogResult::Continue;
+        }
+        if self.step == Step::View {
+            match key.code {
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::Home => self.scroll = 0,
+                _ => return self.handle_form_key(key, gpg),
+            }
+            return DialogResult::Continue;
+        }
+        self.handle_form_key(key, gpg)
+    }
+
+    /// Insert pasted text, switching to the text source for multi-line pastes
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        let is_multi_line = text.trim().contains(['\n', '\r']);
+        if self.step == Step::Input && (is_multi_line || self.source == Source::Text) {
+            self.source = Source::Text;
+            self.focus = 1;
+            self.text.insert_str(text);
+        } else if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        // Do not keep the passphrase around longer than needed
+        self.passphrase.set_value("");
+        match result {
+            Ok(result) => {
+                self.result = Some(result);
+                self.go_to(Step::Result);
+            }
+            Err(err) => self.go_to(Step::Failed(err.to_string())),
+        }
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.step {
+            Step::Running => vec![],
+            Step::Input if self.focus == 0 => vec![
+                ("L/R", "Source"),
+                ("Tab", "Next Field"),
+                ("Enter", "Run"),
+                ("Esc", "Cancel"),
+            ],
+            Step::Input if self.source == Source::Text && self.focus == 1 => vec![
+                ("Paste", "Insert Message"),
+                ("Ctrl+U", "Clear"),
+                ("Tab", "Next Field"),
+                ("Esc", "Cancel"),
+            ],
+            Step::View => vec![
+                ("Up/Down/PgUp/PgDn", "Scroll"),
+                ("Tab", "Buttons"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Close")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 84, 22);
+        Popup::new(
+            "Decrypt/Verify",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Input => {
+                let rows = Layout::new(
+                    Direction::Vertical,
+                    [
+                        Constraint::Length(2),
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                    ],
+                )
+                .split(body);
+                let style = if self.focus == 0 {
+                    theme().dialog.focused_input_style
+                } else {
+                    theme().dialog.input_style
+                };
+                let source = match self.source {
+                    Source::File => "File",
+                    Source::Text => "Pasted text",
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Source"), label),
+                    Span::styled(format!("< {source:<16} >"), style),
+                ])
+                .render(rows[0], buf);
+                match self.source {
+                    Source::File => {
+                        let field_rows =
+                            Layout::new(Direction::Vertical, [Constraint::Length(1); 2])
+                                .split(rows[1]);
+                        render_input("File", 12, &self.path, self.focus == 1, field_rows[0], buf);
+                        let note = match self.detached() {
+                            Some((signature, data)) => format!(
+                                "Detached signature {} over {}",
+                                signature.to_string_lossy(),
+                                data.to_string_lossy()
+                            ),
+                            None => "Encrypted or signed file, .sig/.asc next to it is detected"
+                                .to_string(),
+                        };
+                        Span::styled(note, label).render(field_rows[1], buf);
+                    }
+                    Source::Text => TextArea::new(
+                        &self.text,
+                        self.focus == 1,
+                        theme().dialog.input_style,
+                        theme().dialog.focused_input_style,
+                    )
+                    .render(rows[1], buf),
+                }
+                render_input(
+                    "Passphrase",
+                    12,
+                    &self.passphrase,
+                    self.focus == 2,
+                    rows[3],
+                    buf,
+                );
+            }
+            Step::Running => {
+                Line::styled(format!("{} Running gpg ...", spinner(self.ticks)), value)
+                    .render(body, buf);
+            }
+            Step::Result => {
+                if let Some(result) = &self.result {
+                    let mut lines = Self::result_lines(result);
+                    if let Some(saved) = &self.saved {
+                        lines.push(Line::default());
+                        lines.push(Line::styled(saved.clone(), theme().dialog.ok_style));
+                    }
+                    Paragraph::new(lines)
+                        .wrap(Wrap { trim: false })
+                        .render(body, buf);
+                }
+            }
+            Step::View => {
+                let text = self
+                    .result
+                    .as_ref()
+                    .map(|result| String::from_utf8_lossy(&result.plaintext).into_owned())
+                    .unwrap_or_default();
+                Paragraph::new(text)
+                    .style(theme().dialog.input_style)
+                    .scroll((self.scroll, 0))
+                    .render(body, buf);
+            }
+            Step::Save => {
+                render_input("Save to", 12, &self.output, self.focus == 0, body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
@@ -0,0 +1,507 @@
+use std::{path::PathBuf, slice};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        delete::DeleteKind,
+        export::{ExportKind, ExportOptions},
+        job::Job,
+        key::Key,
+        Gpg,
+    },
+    config::ConfirmDelete,
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{
+    cycle, render_buttons, render_input, spinner, user_id, DialogResult, DialogState, FormKey,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Options,
+    /// Secret keys are confirmed by typing their fingerprint
+    Confirm,
+    Running,
+    Done(String),   // Done(Message)
+    Failed(String), // Failed(Error)
+}
+
+/// Form behind File > Delete Key
+pub struct DeleteKeyDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    key: Key,
+    confirm_delete: ConfirmDelete,
+    /// Index into `DeleteKind::choices`
+    kind: usize,
+    /// Export the key to `backup` before deleting it
+    make_backup: bool,
+    backup: TextInputUiState,
+    passphrase: TextInputUiState,
+    /// The fingerprint typed to confirm
+    typed: TextInputUiState,
+    error: Option<String>,
+    job: Option<Job<()>>,
+    ticks: usize,
+}
+
+impl DeleteKeyDialog {
+    pub fn new(key: Key, confirm_delete: ConfirmDelete) -> Self {
+        let make_backup = key.has_secret();
+        let mut dialog = Self {
+            step: Step::Options,
+            focus: 0,
+            key,
+            confirm_delete,
+            kind: 0,
+            make_backup,
+            backup: TextInputUiState::default(),
+            passphrase: TextInputUiState::masked(),
+            typed: TextInputUiState::default(),
+            error: None,
+            job: None,
+            ticks: 0,
+        };
+        dialog.backup.set_value(&dialog.default_backup());
+        dialog
+    }
+
+    fn kind(&self) -> DeleteKind {
+        DeleteKind::choices(self.key.has_secret())[self.kind]
+    }
+
+    /// Whether the deletion has to be confirmed, secret keys always by typing their fingerprint
+    ///
+    /// The `confirm_delete` policy only decides for public keys.
+    fn needs_confirm(&self) -> bool {
+        self.kind().is_secret() || self.confirm_delete.applies_to(false)
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Options => 4,
+            Step::Confirm if self.kind().is_secret() => 1,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Options => &["Delete", "Cancel"],
+            Step::Confirm => &["Back", "Delete", "Cancel"],
+            Step::Running => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        // Confirming starts on the fingerprint or on Back, so a hasty Enter deletes nothing
+        self.focus = 0;
+        self.step = step;
+        self.error = None;
+        self.typed.set_value("");
+    }
+
+    /// What the backup contains, the secret key if it is deleted
+    fn backup_options(&self) -> ExportOptions {
+        let kind = if self.kind().is_secret() {
+            ExportKind::Secret
+        } else {
+            ExportKind::Public
+        };
+        ExportOptions {
+            kind,
+            armor: true,
+            passphrase: Some(self.passphrase.value.clone())
+                .filter(|passphrase| !passphrase.is_empty()),
+            ..Default::default()
+        }
+    }
+
+    fn default_backup(&self) -> String {
+        self.backup_options()
+            .file_name(&format!("0x{}", self.key.primary.key_id))
+    }
+
+    /// Apply a change of the kind, keeping a backup file typed by hand
+    fn change(&mut self, change: impl FnOnce(&mut Self)) {
+        let default = self.default_backup();
+        change(self);
+        if self.backup.value == default {
+            self.backup.set_value(&self.default_backup());
+        }
+    }
+
+    fn backup_path(&self) -> Option<PathBuf> {
+        self.make_backup
+            .then(|| PathBuf::from(self.backup.value.trim()))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.make_backup && self.backup.value.trim().is_empty() {
+            return Err("Enter the backup file".to_string());
+        }
+        if self.step == Step::Confirm && self.kind().is_secret() {
+            let typed: String = self
+                .typed
+                .value
+                .chars()
+                .filter(|ch| !ch.is_whitespace())
+                .collect();
+            if !typed.eq_ignore_ascii_case(self.key.fingerprint()) {
+                return Err("The fingerprint does not match".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// What is said before deleting
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let name = user_id(&self.key);
+        match self.kind() {
+            DeleteKind::Public => {
+                warnings.push(format!("This deletes the public key of {name}."));
+            }
+            DeleteKind::Secret => warnings.push(format!(
+                "This deletes the SECRET key of {name}, the public key stays. Without a backup \
+                 nothing encrypted to this key can be decrypted again and it can not sign anymore."
+            )),
+            DeleteKind::SecretAndPublic => warnings.push(format!(
+                "This deletes the SECRET and the public key of {name}. Without a backup nothing \
+                 encrypted to this key can be decrypted again and it can not be revoked anymore."
+            )),
+        }
+        match self.backup_path() {
+            Some(path) if path.exists() => warnings.push(format!(
+                "A backup is exported to {} first, replacing it.",
+                path.to_string_lossy()
+            )),
+            Some(path) => warnings.push(format!(
+                "A backup is exported to {} first.",
+                path.to_string_lossy()
+            )),
+            None => warnings.push("No backup is made.".to_string()),
+        }
+        warnings
+    }
+
+    fn next_step(&mut self, gpg: &Gpg) {
+        if let Err(err) = self.validate() {
+            self.error = Some(err);
+            return;
+        }
+        match self.step {
+            Step::Options if self.needs_confirm() => self.go_to(Step::Confirm),
+            Step::Options | Step::Confirm => self.start(gpg),
+            _ => (),
+        }
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        let fingerprint = self.key.fingerprint().to_string();
+        let kind = self.kind();
+        let backup = self.backup_path().map(|path| (path, self.backup_options()));
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |progress| {
+            // Nothing is deleted if the backup fails
+            if let Some((path, options)) = backup {
+                progress.report(format!("Exporting a backup to {} ...", path.display()));
+                gpg.export_to_file(slice::from_ref(&fingerprint), &options, &path)?;
+            }
+            progress.report("Deleting ...");
+            gpg.delete_key(&fingerprint, kind)
+        }));
+        self.go_to(Step::Running);
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Options, 2) => Some(&mut self.backup),
+            (Step::Options, 3) => Some(&mut self.passphrase),
+            (Step::Confirm, 0) if self.kind().is_secret() => Some(&mut self.typed),
+            _ => None,
+        }
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Delete" => self.next_step(gpg),
+            "Back" => self.go_to(Step::Options),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match self.step {
+            Step::Done(_) => DialogResult::CloseAndReload(None),
+            _ => DialogResult::Close,
+        }
+    }
+
+    fn render_options(&self, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let field_style = |index: usize| {
+            if self.focus == index {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 10]).split(body);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Key"), label),
+            Span::styled(
+                format!("{} {}", user_id(&self.key), self.key.primary.key_id),
+                theme().dialog.default_style,
+            ),
+        ])
+        .render(rows[0], buf);
+        let check = if self.make_backup { "[x]" } else { "[ ]" };
+        let choices = [
+            ("Delete", format!("< {:<24} >", self.kind().label())),
+            (
+                "Backup",
+                format!("{check} Export the key before deleting it"),
+            ),
+        ];
+        for (i, (name, text)) in choices.into_iter().enumerate() {
+            Line::from(vec![
+                Span::styled(format!("{name:<16}"), label),
+                Span::styled(text, field_style(i)),
+            ])
+            .render(rows[2 + i * 2], buf);
+        }
+        render_input(
+            "Backup file",
+            16,
+            &self.backup,
+            self.focus == 2,
+            rows[6],
+            buf,
+        );
+        render_input(
+            "Passphrase",
+            16,
+            &self.passphrase,
+            self.focus == 3,
+            rows[7],
+            buf,
+        );
+        Span::styled(
+            format!(
+                "{:16}For a secret backup, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[8], buf);
+    }
+
+    fn render_confirm(&self, body: Rect, buf: &mut Buffer) {
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(body);
+        let mut lines = Vec::new();
+        for warning in self.warnings() {
+            lines.push(Line::styled(warning, theme().dialog.error_style));
+            lines.push(Line::default());
+        }
+        if !self.kind().is_secret() {
+            lines.push(Line::styled("Delete anyway?", theme().dialog.default_style));
+        }
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .render(layout[0], buf);
+        if self.kind().is_secret() {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<16}", "Type to confirm"),
+                    theme().dialog.label_style,
+                ),
+                Span::styled(self.key.fingerprint(), theme().dialog.default_style),
+            ])
+            .render(layout[1], buf);
+            render_input(
+                "Fingerprint",
+                16,
+                &self.typed,
+                self.focus == 0,
+                layout[2],
+                buf,
+            );
+        }
+    }
+}
+
+impl DialogState for DeleteKeyDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Running {
+            return DialogResult::Continue;
+        }
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.next_step(gpg),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match (&self.step, self.focus) {
+                    (Step::Options, 0) if is_toggle => {
+                        let count = DeleteKind::choices(self.key.has_secret()).len();
+                        self.change(|dialog| dialog.kind = cycle(dialog.kind, count, key))
+                    }
+                    (Step::Options, 1) if is_toggle => self.make_backup = !self.make_backup,
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
+                        } else if on_button {
+                            match key.code {
+                                KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                                KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        self.passphrase.set_value("");
+        match result {
+            Ok(()) => {
+                let what = match self.kind() {
+                    DeleteKind::Public => "the key",
+                    DeleteKind::Secret => "the secret key",
+                    DeleteKind::SecretAndPublic => "the secret and public key",
+                };
+                let mut message = format!("Deleted {what} of {}", user_id(&self.key));
+                if let Some(path) = self.backup_path() {
+                    message.push_str(&format!(", the backup is in {}", path.display()));
+                }
+                self.go_to(Step::Done(message));
+                DialogResult::Reload(None)
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match (&self.step, self.focus) {
+            (Step::Running, _) => vec![],
+            (Step::Options, 0..=1) => vec![
+                ("L/R/Space", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Delete"),
+                ("Esc", "Cancel"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let title = match self.step {
+            Step::Confirm => "Delete Key - Confirm",
+            _ => "Delete Key",
+        };
+        let area = Popup::centered(area, 88, 16);
+        Popup::new(
+            title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Options => self.render_options(body, buf),
+            Step::Confirm => self.render_confirm(body, buf),
+            Step::Running => {
+                let progress = self
+                    .job
+                    .as_ref()
+                    .and_then(|job| job.progress.last())
+                    .map_or("Deleting ...", String::as_str);
+                Line::styled(format!("{} {progress}", spinner(self.ticks)), value).render(body, buf)
+            }
+            Step::Done(message) => Paragraph::new(message.clone())
+                .style(theme().dialog.ok_style)
+                .wrap(Wrap { trim: true })
+                .render(body, buf),
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
@@ -0,0 +1,813 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        encrypt::{recipient_issue, EncryptOptions, Encryption, Signing},
+        job::Job,
+        key::Key,
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{
+    cycle, render_buttons, render_input, spinner, user_id, DialogResult, DialogState, FormKey,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Files,
+    Options,
+    Recipients,
+    Review,
+    Running,
+    Done(Vec<PathBuf>), // Done(Outputs)
+    Failed(String),     // Failed(Error)
+}
+
+/// Wizard behind File > Encrypt/Sign
+pub struct EncryptSignDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    /// Path typed on the files step, added with Enter
+    path: TextInputUiState,
+    files: Vec<PathBuf>,
+    /// Highlighted file
+    selected_file: usize,
+    encryption: usize,
+    signing: usize,
+    /// Index into `signers`
+    signer: usize,
+    armor: bool,
+    encrypt_to_self: bool,
+    passphrase: TextInputUiState,
+    keys: Vec<Key>,
+    /// Indices of the keys which can sign
+    signers: Vec<usize>,
+    /// Recipients marked with `Space`, per key
+    marked: Vec<bool>,
+    filter: TextInputUiState,
+    /// Highlighted row of the filtered recipient list
+    selected_key: usize,
+    /// Output file, or directory if there are several input files
+    output: TextInputUiState,
+    /// The warnings of the review step were shown, starting again proceeds
+    confirmed: bool,
+    error: Option<String>,
+    job: Option<Job<Vec<PathBuf>>>,
+    ticks: usize,
+}
+
+impl EncryptSignDialog {
+    /// `default_signer` is preselected if it is one of the signing keys
+    pub fn new(keys: Vec<Key>, armor: bool, default_signer: Option<&str>) -> Self {
+        let signers: Vec<usize> = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| {
+                key.has_secret() && key.capabilities.sign && key.primary.validity.is_usable()
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let signer = default_signer
+            .and_then(|id| signers.iter().position(|&i| keys[i].matches_id(id)))
+            .unwrap_or(0);
+        Self {
+            step: Step::Files,
+            focus: 0,
+            path: TextInputUiState::default(),
+            files: Vec::new(),
+            selected_file: 0,
+            encryption: 0,
+            signing: 0,
+            signer,
+            armor,
+            encrypt_to_self: !signers.is_empty(),
+            passphrase: TextInputUiState::masked(),
+            marked: vec![false; keys.len()],
+            keys,
+            signers,
+            filter: TextInputUiState::default(),
+            selected_key: 0,
+            output: TextInputUiState::default(),
+            confirmed: false,
+            error: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Files | Step::Recipients => 2,
+            Step::Options => 6,
+            Step::Review => 1,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Files => &["Next", "Cancel"],
+            Step::Options | Step::Recipients => &["Back", "Next", "Cancel"],
+            Step::Review => &["Back", "Start", "Cancel"],
+            Step::Running => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.focus = match step {
+            Step::Review => 2,
+            Step::Recipients => 0,
+            Step::Files if !self.files.is_empty() => 2,
+            _ => 0,
+        };
+        self.step = step;
+        self.confirmed = false;
+        self.error = None;
+    }
+
+    fn signer_key(&self) -> Option<&Key> {
+        self.signers.get(self.signer).map(|&i| &self.keys[i])
+    }
+
+    fn options(&self) -> EncryptOptions {
+        let encryption = Encryption::ALL[self.encryption];
+        let recipients = match encryption {
+            Encryption::PublicKey => self
+                .recipient_keys()
+                .map(|key| key.fingerprint().to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+        EncryptOptions {
+            encryption,
+            recipients,
+            encrypt_to_self: self.encrypt_to_self,
+            signing: Signing::ALL[self.signing],
+            signer: self.signer_key().map(|key| key.fingerprint().to_string()),
+            armor: self.armor,
+            passphrase: Some(self.passphrase.value.clone())
+                .filter(|passphrase| !passphrase.is_empty()),
+            always_trust: false,
+        }
+    }
+
+    fn recipient_keys(&self) -> impl Iterator<Item = &Key> {
+        self.keys
+            .iter()
+            .zip(self.marked.iter())
+            .filter(|(_, &marked)| marked)
+            .map(|(key, _)| key)
+    }
+
+    /// Indices of the keys matching the recipient filter
+    fn filtered(&self) -> Vec<usize> {
+        let filter = self.filter.value.trim().to_lowercase();
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| {
+                filter.is_empty()
+                    || key.fingerprint().to_lowercase().contains(&filter)
+                    || key
+                        .user_ids
+                        .iter()
+                        .any(|uid| uid.uid.to_lowercase().contains(&filter))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Output path of each input file
+    fn outputs(&self) -> Vec<PathBuf> {
+        let options = self.options();
+        let output = self.output.value.trim();
+        match self.files.as_slice() {
+            [_] if !output.is_empty() => vec![PathBuf::from(output)],
+            files => files
+                .iter()
+                .map(|file| {
+                    let default = options.output_path(file);
+                    match (output.is_empty(), default.file_name()) {
+                        (false, Some(name)) => Path::new(output).join(name),
+                        _ => default,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn add_file(&mut self, path: &str) -> bool {
+        let path = PathBuf::from(path.trim());
+        if !path.is_file() {
+            self.error = Some(format!("{} is not a file", path.to_string_lossy()));
+            return false;
+        }
+        if !self.files.contains(&path) {
+            self.files.push(path);
+        }
+        self.selected_file = self.files.len() - 1;
+        self.error = None;
+        true
+    }
+
+    /// Check the fields of the current step
+    fn validate(&self) -> Result<(), String> {
+        match self.step {
+            Step::Files if self.files.is_empty() => {
+                Err("Add at least one file to encrypt or sign".to_string())
+            }
+            Step::Options => {
+                let mut options = self.options();
+                // The recipients are chosen on the next step
+                options.recipients = vec![String::new()];
+                if options.signing != Signing::None && options.signer.is_none() {
+                    return Err("There is no secret key to sign with".to_string());
+                }
+                options.validate().map_err(|err| err.to_string())
+            }
+            Step::Recipients => {
+                if let Some(key) = self
+                    .recipient_keys()
+                    .find(|key| recipient_issue(key).is_some_and(|issue| issue.is_fatal()))
+                {
+                    let issue = recipient_issue(key).unwrap();
+                    return Err(format!(
+                        "{} can not be used: {}",
+                        user_id(key),
+                        issue.describe()
+                    ));
+                }
+                self.options().validate().map_err(|err| err.to_string())
+            }
+            Step::Review if self.output.value.trim().is_empty() && self.files.len() == 1 => {
+                Err("Enter the output file".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn next_step(&mut self, gpg: &Gpg) {
+        if let Err(err) = self.validate() {
+            self.error = Some(err);
+            return;
+        }
+        match self.step {
+            Step::Files => self.go_to(Step::Options),
+            Step::Options if Encryption::ALL[self.encryption] == Encryption::PublicKey => {
+                self.go_to(Step::Recipients)
+            }
+            Step::Options | Step::Recipients => {
+                let output = match self.files.as_slice() {
+                    [file] => self
+                        .options()
+                        .output_path(file)
+                        .to_string_lossy()
+                        .into_owned(),
+                    _ => String::new(),
+                };
+                self.output.set_value(&output);
+                self.go_to(Step::Review);
+            }
+            Step::Review => self.start(gpg),
+            _ => (),
+        }
+    }
+
+    fn prev_step(&mut self) {
+        match self.step {
+            Step::Options => self.go_to(Step::Files),
+            Step::Recipients => self.go_to(Step::Options),
+            Step::Review | Step::Failed(_)
+                if Encryption::ALL[self.encryption] == Encryption::PublicKey =>
+            {
+                self.go_to(Step::Recipients)
+            }
+            Step::Review | Step::Failed(_) => self.go_to(Step::Options),
+            _ => (),
+        }
+    }
+
+    /// Warnings shown on the review step, which have to be confirmed
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if Encryption::ALL[self.encryption] == Encryption::PublicKey {
+            for key in self.recipient_keys() {
+                if let Some(issue) = recipient_issue(key) {
+                    warnings.push(format!("{} is {}", user_id(key), issue.describe()));
+                }
+            }
+        }
+        for output in self.outputs() {
+            if output.exists() {
+                warnings.push(format!("{} will be replaced", output.to_string_lossy()));
+            }
+        }
+        warnings
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        if !self.warnings().is_empty() && !self.confirmed {
+            self.confirmed = true;
+            self.error = Some("Press Start again to proceed anyway".to_string());
+            return;
+        }
+        let mut options = self.options();
+        // Untrusted recipients were flagged and confirmed
+        options.always_trust = self.confirmed;
+        let jobs: Vec<(PathBuf, PathBuf)> =
+            self.files.iter().cloned().zip(self.outputs()).collect();
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |progress| {
+            let count = jobs.len();
+            for (i, (input, output)) in jobs.iter().enumerate() {
+                progress.report(format!("({}/{count}) {}", i + 1, input.to_string_lossy()));
+                gpg.encrypt_file(input, output, &options)?;
+            }
+            Ok(jobs.into_iter().map(|(_, output)| output).collect())
+        }));
+        self.go_to(Step::Running);
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Files, 0) => Some(&mut self.path),
+            (Step::Options, 5) => Some(&mut self.passphrase),
+            (Step::Recipients, 0) => Some(&mut self.filter),
+            (Step::Review, 0) => Some(&mut self.output),
+            _ => None,
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.next_step(gpg),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match (&self.step, self.focus) {
+                    (Step::Options, 0) if is_toggle => {
+                        self.encryption = cycle(self.encryption, Encryption::ALL.len(), key)
+                    }
+                    (Step::Options, 1) if is_toggle => {
+                        self.signing = cycle(self.signing, Signing::ALL.len(), key)
+                    }
+                    (Step::Options, 2) if is_toggle && !self.signers.is_empty() => {
+                        self.signer = cycle(self.signer, self.signers.len(), key)
+                    }
+                    (Step::Options, 3) if is_toggle => self.armor = !self.armor,
+                    (Step::Options, 4) if is_toggle => self.encrypt_to_self = !self.encrypt_to_self,
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
+                            self.selected_key = 0;
+                            self.confirmed = false;
+                        } else if on_button {
+                            match key.code {
+                                KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                                KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Next" | "Start" => self.next_step(gpg),
+            "Back" => self.prev_step(),
+            _ => return DialogResult::Close,
+        }
+        DialogResult::Continue
+    }
+
+    fn recipient_lines(&self, filtered: &[usize]) -> Vec<Line<'static>> {
+        filtered
+            .iter()
+            .enumerate()
+            .map(|(row, &i)| {
+                let key = &self.keys[i];
+                let issue = recipient_issue(key);
+                let style = if row == self.selected_key && self.focus == 1 {
+                    theme().dialog.focused_input_style
+                } else {
+                    match issue {
+                        Some(issue) if issue.is_fatal() => theme().key_detail.invalid_style,
+                        _ => theme().dialog.default_style,
+                    }
+                };
+                let mark = if self.marked[i] { "[x]" } else { "[ ]" };
+                let key_id = &key.primary.key_id;
+                let mut spans = vec![Span::styled(
+                    format!("{mark} {:<44.44} {key_id} ", user_id(key)),
+                    style,
+                )];
+                if let Some(issue) = issue {
+                    spans.push(Span::styled(issue.describe(), theme().dialog.error_style));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    fn review_lines(&self) -> Vec<Line<'static>> {
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let options = self.options();
+        let row = |name: &str, text: String| {
+            Line::from(vec![
+                Span::styled(format!("{name:<12}"), label),
+                Span::styled(text, value),
+            ])
+        };
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        let mut lines = vec![row("Files", files.join(", "))];
+        let encryption = match options.encryption {
+            Encryption::PublicKey => {
+                let mut recipients: Vec<String> = self.recipient_keys().map(user_id).collect();
+                let own_key = self.signer_key().filter(|key| {
+                    !options
+                        .recipients
+                        .iter()
+                        .any(|fpr| fpr == key.fingerprint())
+                });
+                if let (true, Some(key)) = (options.encrypt_to_self, own_key) {
+                    recipients.push(format!("{} (self)", user_id(key)));
+                }
+                format!("to {}", recipients.join(", "))
+            }
+            encryption => encryption.label().to_string(),
+        };
+        lines.push(row("Encrypt", encryption));
+        let signing = match (options.signing, self.signer_key()) {
+            (Signing::None, _) | (_, None) => options.signing.label().to_string(),
+            (signing, Some(key)) => format!("{} by {}", signing.label(), user_id(key)),
+        };
+        lines.push(row("Sign", signing));
+        let format = if options.armor {
+            "ASCII armor"
+        } else {
+            "binary"
+        };
+        lines.push(row("Format", format.to_string()));
+        for warning in self.warnings() {
+            lines.push(Line::styled(
+                format!("Warning: {warning}"),
+                theme().dialog.error_style,
+            ));
+        }
+        lines
+    }
+}
+
+impl DialogState for EncryptSignDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Running {
+            return DialogResult::Continue;
+        }
+
+        // The lists use Up/Down themselves, only Tab leaves them
+        match (&self.step, self.focus) {
+            (Step::Files, 0) if key.code == KeyCode::Enter && !self.path.value.is_empty() => {
+                let path = self.path.value.clone();
+                if self.add_file(&path) {
+                    self.path.set_value("");
+                }
+                return DialogResult::Continue;
+            }
+            (Step::Files, 1) => {
+                let last = self.files.len().saturating_sub(1);
+                self.selected_file = match key.code {
+                    KeyCode::Up => self.selected_file.saturating_sub(1),
+                    KeyCode::Down => (self.selected_file + 1).min(last),
+                    KeyCode::Delete | KeyCode::Backspace if !self.files.is_empty() => {
+                        self.files.remove(self.selected_file);
+                        self.selected_file.min(self.files.len().saturating_sub(1))
+                    }
+                    _ => return self.handle_form_key(key, gpg),
+                };
+                return DialogResult::Continue;
+            }
+            (Step::Recipients, 1) => {
+                let filtered = self.filtered();
+                let last = filtered.len().saturating_sub(1);
+                self.selected_key = match key.code {
+                    KeyCode::Up => self.selected_key.saturating_sub(1),
+                    KeyCode::Down => (self.selected_key + 1).min(last),
+                    KeyCode::Home => 0,
+                    KeyCode::End => last,
+                    KeyCode::Char(' ') => {
+                        if let Some(&i) = filtered.get(self.selected_key) {
+                            self.marked[i] = !self.marked[i];
+                        }
+                        self.selected_key
+                    }
+                    _ => return self.handle_form_key(key, gpg),
+                };
+                return DialogResult::Continue;
+            }
+            _ => (),
+        }
+        self.handle_form_key(key, gpg)
+    }
+
+    /// Add pasted paths as files, or insert the text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if self.step == Step::Files {
+            let paths: Vec<&str> = text
+                .split(['\n', '\r'])
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect();
+            if paths.iter().all(|path| Path::new(path).is_file()) {
+                for path in paths {
+                    self.add_file(path);
+                }
+                return DialogResult::Continue;
+            }
+        }
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        self.passphrase.set_value("");
+        match result {
+            Ok(outputs) => self.go_to(Step::Done(outputs)),
+            Err(err) => self.go_to(Step::Failed(err.to_string())),
+        }
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match (&self.step, self.focus) {
+            (Step::Running, _) => vec![],
+            (Step::Files, 0) => vec![
+                ("Enter", "Add File"),
+                ("Tab", "Next Field"),
+                ("Esc", "Cancel"),
+            ],
+            (Step::Files, 1) => vec![
+                ("Up/Down", "Select"),
+                ("Del", "Remove"),
+                ("Tab", "Next Field"),
+                ("Esc", "Cancel"),
+            ],
+            (Step::Options, 0..=4) => vec![
+                ("L/R/Space", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Next"),
+                ("Esc", "Cancel"),
+            ],
+            (Step::Recipients, 1) => vec![
+                ("Up/Down", "Select"),
+                ("Space", "Mark"),
+                ("Tab", "Next Field"),
+                ("Enter", "Next"),
+                ("Esc", "Cancel"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Next"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let (number, title) = match self.step {
+            Step::Files => (1, "Files"),
+            Step::Options => (2, "Options"),
+            Step::Recipients => (3, "Recipients"),
+            Step::Review => (4, "Review"),
+            _ => (4, "Run"),
+        };
+        let title = format!("Encrypt/Sign - {title} ({number}/4)");
+        let area = Popup::centered(area, 88, 22);
+        Popup::new(
+            &title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let field_style = |index: usize| {
+            if self.focus == index {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+
+        match &self.step {
+            Step::Files => {
+                let rows = Layout::new(
+                    Direction::Vertical,
+                    [
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Min(0),
+                    ],
+                )
+                .split(body);
+                render_input("Add file", 12, &self.path, self.focus == 0, rows[0], buf);
+                let lines: Vec<Line> = if self.files.is_empty() {
+                    vec![Line::styled(
+                        "Type or paste a path and press Enter to add it",
+                        label,
+                    )]
+                } else {
+                    self.files
+                        .iter()
+                        .enumerate()
+                        .map(|(i, file)| {
+                            let style = if i == self.selected_file && self.focus == 1 {
+                                theme().dialog.focused_input_style
+                            } else {
+                                value
+                            };
+                            Line::styled(format!("  {}", file.to_string_lossy()), style)
+                        })
+                        .collect()
+                };
+                let scroll = (self.selected_file + 1).saturating_sub(rows[2].height as usize);
+                Paragraph::new(lines)
+                    .scroll((scroll as u16, 0))
+                    .render(rows[2], buf);
+            }
+            Step::Options => {
+                let rows =
+                    Layout::new(Direction::Vertical, [Constraint::Length(1); 12]).split(body);
+                let signer = self
+                    .signer_key()
+                    .map(user_id)
+                    .unwrap_or_else(|| "no secret key".to_string());
+                let check = |flag: bool| if flag { "[x]" } else { "[ ]" };
+                let choices = [
+                    (
+                        "Encrypt",
+                        format!("< {:<20} >", Encryption::ALL[self.encryption].label()),
+                    ),
+                    (
+                        "Sign",
+                        format!("< {:<20} >", Signing::ALL[self.signing].label()),
+                    ),
+                    ("Signing key", format!("< {signer:<50.50} >")),
+                    ("Armor", check(self.armor).to_string()),
+                    ("Encrypt to self", check(self.encrypt_to_self).to_string()),
+                ];
+                for (i, (name, text)) in choices.into_iter().enumerate() {
+                    Line::from(vec![
+                        Span::styled(format!("{name:<16}"), label),
+                        Span::styled(text, field_style(i)),
+                    ])
+                    .render(rows[i * 2], buf);
+                }
+                render_input(
+                    "Passphrase",
+                    16,
+                    &self.passphrase,
+                    self.focus == 5,
+                    rows[10],
+                    buf,
+                );
+                Span::styled(
+                    "Needed for passphrase-only encryption, optional for the signing key",
+                    label,
+                )
+                .render(rows[11], buf);
+            }
+            Step::Recipients => {
+                let rows = Layout::new(
+                    Direction::Vertical,
+                    [
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Min(0),
+                    ],
+                )
+                .split(body);
+                render_input("Filter", 12, &self.filter, self.focus == 0, rows[0], buf);
+                let count = self.marked.iter().filter(|&&marked| marked).count();
+                Span::styled(format!("{count} recipient(s) marked"), label).render(rows[1], buf);
+                let filtered = self.filtered();
+                let scroll = (self.selected_key + 1).saturating_sub(rows[2].height as usize);
+                Paragraph::new(self.recipient_lines(&filtered))
+                    .scroll((scroll as u16, 0))
+                    .render(rows[2], buf);
+            }
+            Step::Review => {
+                let rows = Layout::new(
+                    Direction::Vertical,
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                    ],
+                )
+                .split(body);
+                Paragraph::new(self.review_lines())
+                    .wrap(Wrap { trim: false })
+                    .render(rows[0], buf);
+                let output_label = if self.files.len() == 1 {
+                    "Output"
+                } else {
+                    "Output dir"
+                };
+                render_input(
+                    output_label,
+                    12,
+                    &self.output,
+                    self.focus == 0,
+                    rows[2],
+                    buf,
+                );
+                if self.files.len() > 1 && self.output.value.is_empty() {
+                    Span::styled("Empty writes next to each file", label).render(rows[1], buf);
+                }
+            }
+            Step::Running => {
+                let message = self
+                    .job
+                    .as_ref()
+                    .and_then(|job| job.last_progress())
+                    .unwrap_or("Starting gpg");
+                Line::styled(format!("{} {message} ...", spinner(self.ticks)), value)
+                    .render(body, buf);
+            }
+            Step::Done(outputs) => {
+                let mut lines = vec![Line::styled("Written:", value)];
+                lines.extend(
+                    outputs.iter().map(|output| {
+                        Line::styled(format!("  {}", output.to_string_lossy()), label)
+                    }),
+                );
+                Paragraph::new(lines).render(body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
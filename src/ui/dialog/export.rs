@@ -0,0 +1,509 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        export::{ExportKind, ExportOptions},
+        job::Job,
+        key::Key,
+        Gpg,
+    },
+    clipboard,
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{
+    cycle, render_buttons, render_input, spinner, user_id, DialogResult, DialogState, FormKey,
+};
+
+/// Where the export goes
+const DESTINATIONS: [&str; 2] = ["File", "Clipboard"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Options,
+    /// Warnings which have to be confirmed, e.g. for secret keys
+    Confirm,
+    Running,
+    Done(String),   // Done(Message)
+    Failed(String), // Failed(Error)
+}
+
+/// Form behind File > Export
+pub struct ExportDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    keys: Vec<Key>,
+    /// The key selected in the key list
+    selected: Option<Key>,
+    /// Export all keys instead of the selected one
+    all_keys: bool,
+    kind: usize,
+    armor: bool,
+    minimal: bool,
+    clean: bool,
+    destination: usize,
+    output: TextInputUiState,
+    passphrase: TextInputUiState,
+    error: Option<String>,
+    /// The exported text, if it goes to the clipboard
+    job: Option<Job<Option<Vec<u8>>>>,
+    ticks: usize,
+}
+
+impl ExportDialog {
+    /// Export `selected` as `kind`, or all `keys` if nothing is selected
+    pub fn new(keys: Vec<Key>, selected: Option<Key>, kind: ExportKind, armor: bool) -> Self {
+        let mut dialog = Self {
+            step: Step::Options,
+            focus: 0,
+            keys,
+            all_keys: selected.is_none(),
+            selected,
+            kind: ExportKind::ALL.iter().position(|k| *k == kind).unwrap_or(0),
+            armor,
+            minimal: false,
+            clean: false,
+            destination: 0,
+            output: TextInputUiState::default(),
+            passphrase: TextInputUiState::masked(),
+            error: None,
+            job: None,
+            ticks: 0,
+        };
+        dialog.output.set_value(&dialog.default_output());
+        dialog
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Options => 8,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Options => &["Export", "Cancel"],
+            Step::Confirm => &["Back", "Export", "Cancel"],
+            Step::Running => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        // Confirming starts on Back, so a hasty Enter does not export a secret key
+        self.focus = 0;
+        self.step = step;
+        self.error = None;
+    }
+
+    fn options(&self) -> ExportOptions {
+        ExportOptions {
+            kind: ExportKind::ALL[self.kind],
+            armor: self.armor,
+            minimal: self.minimal,
+            clean: self.clean,
+            passphrase: Some(self.passphrase.value.clone())
+                .filter(|passphrase| !passphrase.is_empty()),
+        }
+    }
+
+    /// The keys which are exported
+    fn export_keys(&self) -> Vec<&Key> {
+        let secret = ExportKind::ALL[self.kind].is_secret();
+        match &self.selected {
+            Some(key) if !self.all_keys => vec![key],
+            _ => self
+                .keys
+                .iter()
+                .filter(|key| !secret || key.has_secret())
+                .collect(),
+        }
+    }
+
+    fn default_output(&self) -> String {
+        let name = match &self.selected {
+            Some(key) if !self.all_keys => format!("0x{}", key.primary.key_id),
+            _ => "keys".to_string(),
+        };
+        self.options().file_name(&name)
+    }
+
+    /// Apply a change of the options, keeping an output file typed by hand
+    fn change(&mut self, change: impl FnOnce(&mut Self)) {
+        let default = self.default_output();
+        change(self);
+        if self.output.value == default {
+            self.output.set_value(&self.default_output());
+        }
+    }
+
+    fn to_clipboard(&self) -> bool {
+        DESTINATIONS[self.destination] == "Clipboard"
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let options = self.options();
+        let keys = self.export_keys();
+        options
+            .validate(keys.len())
+            .map_err(|err| err.to_string())?;
+        if options.kind.is_secret() {
+            if let Some(key) = keys.iter().find(|key| !key.has_secret()) {
+                return Err(format!("{} has no secret key", user_id(key)));
+            }
+        }
+        if options.kind == ExportKind::Ssh && !keys[0].capabilities.authenticate {
+            return Err(format!(
+                "{} has no authentication key for SSH",
+                user_id(keys[0])
+            ));
+        }
+        if self.to_clipboard() && !options.is_text() {
+            return Err("Only ASCII armored keys can be copied, turn on Armor".to_string());
+        }
+        if !self.to_clipboard() && self.output.value.trim().is_empty() {
+            return Err("Enter the output file".to_string());
+        }
+        Ok(())
+    }
+
+    /// What has to be confirmed before exporting
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let kind = ExportKind::ALL[self.kind];
+        if kind.is_secret() {
+            let names: Vec<String> = self.export_keys().into_iter().map(user_id).collect();
+            warnings.push(format!(
+                "This exports the SECRET key of {}.",
+                names.join(", ")
+            ));
+            warnings.push(
+                "The export is only protected by the key's passphrase. Anyone who gets a copy \
+                 can use the key once they guess it, so make sure the passphrase is strong and \
+                 keep the export offline and safe."
+                    .to_string(),
+            );
+            if kind == ExportKind::SecretSubkeys {
+                warnings.push(
+                    "The primary key is left out, the export can sign and decrypt but not \
+                     certify keys or change this key."
+                        .to_string(),
+                );
+            }
+            if self.to_clipboard() {
+                warnings.push(
+                    "Every program of your session can read the clipboard, clear it afterwards."
+                        .to_string(),
+                );
+            }
+        }
+        let output = PathBuf::from(self.output.value.trim());
+        if !self.to_clipboard() && output.exists() {
+            warnings.push(format!("{} will be replaced.", output.to_string_lossy()));
+        }
+        warnings
+    }
+
+    fn next_step(&mut self, gpg: &Gpg) {
+        if let Err(err) = self.validate() {
+            self.error = Some(err);
+            return;
+        }
+        match self.step {
+            Step::Options if !self.warnings().is_empty() => {
+                self.go_to(Step::Confirm);
+            }
+            Step::Options | Step::Confirm => self.start(gpg),
+            _ => (),
+        }
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        let options = self.options();
+        let fingerprints: Vec<String> = self
+            .export_keys()
+            .into_iter()
+            .map(|key| key.fingerprint().to_string())
+            .collect();
+        let output = (!self.to_clipboard()).then(|| PathBuf::from(self.output.value.trim()));
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |_| match output {
+            Some(path) => gpg
+                .export_to_file(&fingerprints, &options, &path)
+                .map(|_| None),
+            None => gpg.export_keys(&fingerprints, &options).map(Some),
+        }));
+        self.go_to(Step::Running);
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Options, 6) => Some(&mut self.output),
+            (Step::Options, 7) => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Export" => self.next_step(gpg),
+            "Back" => self.go_to(Step::Options),
+            _ => return DialogResult::Close,
+        }
+        DialogResult::Continue
+    }
+
+    fn render_options(&self, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let field_style = |index: usize| {
+            if self.focus == index {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 15]).split(body);
+        let keys = match &self.selected {
+            Some(key) if !self.all_keys => format!("{} {}", user_id(key), key.primary.key_id),
+            _ => format!("All {} keys", self.export_keys().len()),
+        };
+        let check = |flag: bool| if flag { "[x]" } else { "[ ]" };
+        let choices = [
+            ("Keys", format!("< {keys:<50.50} >")),
+            (
+                "Export",
+                format!("< {:<20} >", ExportKind::ALL[self.kind].label()),
+            ),
+            (
+                "Armor",
+                format!("{} ASCII armored output", check(self.armor)),
+            ),
+            (
+                "Minimal",
+                format!(
+                    "{} Only the latest self-signatures (export-minimal)",
+                    check(self.minimal)
+                ),
+            ),
+            (
+                "Clean",
+                format!(
+                    "{} No signatures by unknown keys (export-clean)",
+                    check(self.clean)
+                ),
+            ),
+            (
+                "Destination",
+                format!("< {:<20} >", DESTINATIONS[self.destination]),
+            ),
+        ];
+        for (i, (name, text)) in choices.into_iter().enumerate() {
+            Line::from(vec![
+                Span::styled(format!("{name:<16}"), label),
+                Span::styled(text, field_style(i)),
+            ])
+            .render(rows[i * 2], buf);
+        }
+        render_input(
+            "Output file",
+            16,
+            &self.output,
+            self.focus == 6,
+            rows[12],
+            buf,
+        );
+        render_input(
+            "Passphrase",
+            16,
+            &self.passphrase,
+            self.focus == 7,
+            rows[13],
+            buf,
+        );
+        Span::styled(
+            format!("{:16}For secret keys, empty lets the agent ask for it", ""),
+            label,
+        )
+        .render(rows[14], buf);
+    }
+}
+
+impl DialogState for ExportDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Running {
+            return DialogResult::Continue;
+        }
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.next_step(gpg),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match (&self.step, self.focus) {
+                    (Step::Options, 0) if is_toggle && self.selected.is_some() => {
+                        self.change(|dialog| dialog.all_keys = !dialog.all_keys)
+                    }
+                    (Step::Options, 1) if is_toggle => self.change(|dialog| {
+                        dialog.kind = cycle(dialog.kind, ExportKind::ALL.len(), key)
+                    }),
+                    (Step::Options, 2) if is_toggle => {
+                        self.change(|dialog| dialog.armor = !dialog.armor)
+                    }
+                    (Step::Options, 3) if is_toggle => self.minimal = !self.minimal,
+                    (Step::Options, 4) if is_toggle => self.clean = !self.clean,
+                    (Step::Options, 5) if is_toggle => {
+                        self.destination = cycle(self.destination, DESTINATIONS.len(), key)
+                    }
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
+                        } else if on_button {
+                            match key.code {
+                                KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                                KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        self.passphrase.set_value("");
+        let count = self.export_keys().len();
+        let step = match result {
+            Ok(Some(data)) => match clipboard::copy(&String::from_utf8_lossy(&data)) {
+                Ok(()) => Step::Done(format!("Copied {count} key(s) to the clipboard")),
+                Err(err) => Step::Failed(format!("Failed to copy to the clipboard: {err}")),
+            },
+            Ok(None) => Step::Done(format!(
+                "Exported {count} key(s) to {}",
+                self.output.value.trim()
+            )),
+            Err(err) => Step::Failed(err.to_string()),
+        };
+        self.go_to(step);
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match (&self.step, self.focus) {
+            (Step::Running, _) => vec![],
+            (Step::Options, 0..=5) => vec![
+                ("L/R/Space", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Export"),
+                ("Esc", "Cancel"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let title = match self.step {
+            Step::Confirm => "Export - Confirm",
+            _ => "Export",
+        };
+        let area = Popup::centered(area, 88, 22);
+        Popup::new(
+            title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Options => self.render_options(body, buf),
+            Step::Confirm => {
+                let mut lines = Vec::new();
+                for warning in self.warnings() {
+                    lines.push(Line::styled(warning, theme().dialog.error_style));
+                    lines.push(Line::default());
+                }
+                lines.push(Line::styled("Export anyway?", value));
+                Paragraph::new(lines)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+            Step::Running => Line::styled(
+                format!(
+                    "{} Exporting {} key(s) ...",
+                    spinner(self.ticks),
+                    self.export_keys().len()
+                ),
+                value,
+            )
+            .render(body, buf),
+            Step::Done(message) => {
+                Line::styled(message.clone(), theme().dialog.ok_style).render(body, buf)
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
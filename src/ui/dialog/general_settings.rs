@@ -0,0 +1,447 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{key::Key, Gpg},
+    config::{is_valid_date_format, Config, ConfirmDelete},
+    event_handler::keymap::KEYMAP_PRESETS,
+    theme::{theme, Theme},
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{cycle, render_buttons, render_input, DialogResult, DialogState, FormKey};
+
+const FIELD_COUNT: usize = 10;
+
+const BUTTONS: [&str; 3] = ["Save", "Revert", "Close"];
+
+/// Form behind Setting > General
+pub struct GeneralSettingsDialog {
+    /// Focused field, followed by the buttons
+    focus: usize,
+    /// The config as last saved, restored by Revert
+    saved: Config,
+    path: Option<PathBuf>,
+    /// Choices for the signing key as `(fingerprint, label)`, `None` lets gpg decide
+    signers: Vec<(Option<String>, String)>,
+    signer: usize,
+    armor: bool,
+    confirm_delete: usize,
+    gpg_path: TextInputUiState,
+    gnupghome: TextInputUiState,
+    date_format: TextInputUiState,
+    /// Days, checked when saving
+    expiry_warning: TextInputUiState,
+    /// The presets and the theme files
+    themes: Vec<String>,
+    theme: usize,
+    keymap: usize,
+    update_check: bool,
+    /// Outcome of the last save, or a validation error
+    message: Option<Result<String, String>>,
+    /// Close was pressed once with unsaved changes
+    confirm_discard: bool,
+}
+
+impl GeneralSettingsDialog {
+    /// `keys` offers the secret keys as default signing key
+    pub fn new(config: Config, path: Option<PathBuf>, keys: Vec<Key>) -> Self {
+        let mut signers = vec![(None, "(gpg default)".to_string())];
+        signers.extend(
+            keys.iter()
+                .filter(|key| key.has_secret() && key.capabilities.sign)
+                .map(|key| {
+                    let uid = key.primary_uid().map(|uid| uid.uid.as_str()).unwrap_or("");
+                    let key_id = &key.primary.key_id;
+                    (
+                        Some(key.fingerprint().to_string()),
+                        format!("{uid} {key_id}"),
+                    )
+                }),
+        );
+        // Keep a configured key which is not in the keyring, so it is not lost
+        if let Some(id) = &config.default_signing_key {
+            if !keys
+                .iter()
+                .any(|key| key.has_secret() && key.matches_id(id))
+            {
+                signers.push((Some(id.clone()), format!("{id} (not in keyring)")));
+            }
+        }
+        let themes = Theme::names(path.as_deref().map(Theme::dir).as_deref());
+        let mut dialog = Self {
+            focus: 0,
+            saved: config.clone(),
+            path,
+            signers,
+            signer: 0,
+            armor: false,
+            confirm_delete: 0,
+            gpg_path: TextInputUiState::default(),
+            gnupghome: TextInputUiState::default(),
+            date_format: TextInputUiState::default(),
+            expiry_warning: TextInputUiState::default(),
+            themes,
+            theme: 0,
+            keymap: 0,
+            update_check: false,
+            message: None,
+            confirm_discard: false,
+        };
+        dialog.load(&config);
+        dialog
+    }
+
+    /// Fill the form from `config`
+    fn load(&mut self, config: &Config) {
+        self.signer = match &config.default_signing_key {
+            Some(id) => self
+                .signers
+                .iter()
+                .position(|(fingerprint, _)| {
+                    fingerprint.as_deref().is_some_and(|fingerprint| {
+                        fingerprint == id
+                            || fingerprint
+                                .ends_with(&id.trim_start_matches("0x").to_ascii_uppercase())
+                    })
+                })
+                .unwrap_or(0),
+            None => 0,
+        };
+        self.armor = config.armor;
+        self.confirm_delete = ConfirmDelete::ALL
+            .iter()
+            .position(|policy| *policy == config.confirm_delete)
+            .unwrap_or(0);
+        self.gpg_path = TextInputUiState::new(&config.gpg_path.to_string_lossy());
+        self.gnupghome = TextInputUiState::new(
+            &config
+                .gnupghome
+                .as_ref()
+                .map(|home| home.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        );
+        self.date_format = TextInputUiState::new(&config.date_format);
+        self.expiry_warning = TextInputUiState::new(&config.expiry_warning_days.to_string());
+        self.theme = self
+            .themes
+            .iter()
+            .position(|name| *name == config.theme)
+            .unwrap_or(0);
+        self.keymap = KEYMAP_PRESETS
+            .iter()
+            .position(|name| *name == config.keymap)
+            .unwrap_or(0);
+        self.update_check = config.update_check;
+    }
+
+    /// The config as currently entered
+    fn config(&self) -> Config {
+        let gnupghome = self.gnupghome.value.trim();
+        Config {
+            default_signing_key: self.signers[self.signer].0.clone(),
+            armor: self.armor,
+            confirm_delete: ConfirmDelete::ALL[self.confirm_delete],
+            gpg_path: PathBuf::from(self.gpg_path.value.trim()),
+            gnupghome: Some(PathBuf::from(gnupghome)).filter(|_| !gnupghome.is_empty()),
+            date_format: self.date_format.value.clone(),
+            expiry_warning_days: self
+                .expiry_warning
+                .value
+                .trim()
+                .parse()
+                .unwrap_or(self.saved.expiry_warning_days),
+            theme: self.themes[self.theme].clone(),
+            keymap: KEYMAP_PRESETS[self.keymap].to_string(),
+            // Edited in the Server settings
+            keyservers: self.saved.keyservers.clone(),
+            update_check: self.update_check,
+            // Only set in config.toml
+            update_feed: self.saved.update_feed.clone(),
+            update_key: self.saved.update_key.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<Config, String> {
+        let config = self.config();
+        if self.signers[self.signer].1.ends_with("(not in keyring)") {
+            return Err("The default signing key is not in the keyring".to_string());
+        }
+        if self.expiry_warning.value.trim().parse::<u32>().is_err() {
+            return Err("The expiry warning is a number of days".to_string());
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match self.focus {
+            3 => Some(&mut self.gpg_path),
+            4 => Some(&mut self.gnupghome),
+            5 => Some(&mut self.date_format),
+            6 => Some(&mut self.expiry_warning),
+            _ => None,
+        }
+    }
+
+    fn save(&mut self) -> DialogResult {
+        let config = match self.validate() {
+            Ok(config) => config,
+            Err(err) => {
+                self.message = Some(Err(err));
+                return DialogResult::Continue;
+            }
+        };
+        let Some(path) = &self.path else {
+            self.message = Some(Err(
+                "No config directory, set $XDG_CONFIG_HOME or $HOME".to_string()
+            ));
+            return DialogResult::Continue;
+        };
+        if let Err(err) = config.save(path) {
+            self.message = Some(Err(format!("Failed to save {}: {err}", path.display())));
+            return DialogResult::Continue;
+        }
+        self.message = Some(Ok(format!("Saved to {}", path.display())));
+        self.saved = config.clone();
+        DialogResult::ConfigChanged(config)
+    }
+
+    fn press(&mut self, button: &str) -> DialogResult {
+        match button {
+            "Save" => return self.save(),
+            "Revert" => {
+                let saved = self.saved.clone();
+                self.load(&saved);
+                self.message = Some(Ok("Reverted to the saved settings".to_string()));
+            }
+            _ if self.config() == self.saved || self.confirm_discard => return DialogResult::Close,
+            _ => {
+                self.confirm_discard = true;
+                self.message = Some(Err(
+                    "Unsaved changes, press Close again to discard them".to_string()
+                ));
+            }
+        }
+        DialogResult::Continue
+    }
+}
+
+impl DialogState for GeneralSettingsDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = FIELD_COUNT + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, _gpg: &Gpg) -> DialogResult {
+        let focus_count = FIELD_COUNT + BUTTONS.len();
+        let on_button = self.focus >= FIELD_COUNT;
+        if !(on_button && key.code == KeyCode::Enter) {
+            self.confirm_discard = false;
+        }
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => return self.press(BUTTONS[self.focus - FIELD_COUNT]),
+            FormKey::Confirm => return self.save(),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match self.focus {
+                    0 if is_toggle => self.signer = cycle(self.signer, self.signers.len(), key),
+                    1 if is_toggle => self.armor = !self.armor,
+                    2 if is_toggle => {
+                        self.confirm_delete =
+                            cycle(self.confirm_delete, ConfirmDelete::ALL.len(), key)
+                    }
+                    7 if is_toggle => self.theme = cycle(self.theme, self.themes.len(), key),
+                    8 if is_toggle => self.keymap = cycle(self.keymap, KEYMAP_PRESETS.len(), key),
+                    9 if is_toggle => self.update_check = !self.update_check,
+                    _ if on_button => match key.code {
+                        KeyCode::Left if self.focus > FIELD_COUNT => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    },
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
+                        }
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text);
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.focus {
+            0 | 2 | 7 | 8 => vec![
+                ("L/R", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Save"),
+                ("Esc", "Close"),
+            ],
+            1 | 9 => vec![
+                ("Space", "Toggle"),
+                ("Tab", "Next Field"),
+                ("Enter", "Save"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![
+                ("Tab/Up/Down", "Field"),
+                ("Enter", "Save"),
+                ("Esc", "Close"),
+            ],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 76, 20);
+        Popup::new(
+            "Settings - General",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 14]).split(layout[0]);
+        let label = theme().dialog.label_style;
+        let selector = |row: usize, name: &str, text: String, focus: usize, buf: &mut Buffer| {
+            let style = if self.focus == focus {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            };
+            Line::from(vec![
+                Span::styled(format!("{name:<16}"), label),
+                Span::styled(text, style),
+            ])
+            .render(rows[row], buf);
+        };
+
+        let signer = &self.signers[self.signer].1;
+        selector(0, "Signing key", format!("< {signer:<52.52} >"), 0, buf);
+        let armor = if self.armor { "x" } else { " " };
+        selector(
+            1,
+            "Armor",
+            format!("[{armor}] ASCII armored output"),
+            1,
+            buf,
+        );
+        let policy = ConfirmDelete::ALL[self.confirm_delete].label();
+        selector(2, "Confirm delete", format!("< {policy:<20} >"), 2, buf);
+        render_input(
+            "gpg binary",
+            16,
+            &self.gpg_path,
+            self.focus == 3,
+            rows[4],
+            buf,
+        );
+        render_input(
+            "GNUPGHOME",
+            16,
+            &self.gnupghome,
+            self.focus == 4,
+            rows[5],
+            buf,
+        );
+        Span::styled(format!("{:16}Empty uses $GNUPGHOME or ~/.gnupg", ""), label)
+            .render(rows[6], buf);
+        render_input(
+            "Date format",
+            16,
+            &self.date_format,
+            self.focus == 5,
+            rows[7],
+            buf,
+        );
+        let preview = if is_valid_date_format(&self.date_format.value) {
+            let now = chrono::Local::now();
+            Span::styled(
+                format!("{:16}e.g. {}", "", now.format(&self.date_format.value)),
+                label,
+            )
+        } else {
+            Span::styled(
+                format!("{:16}Use strftime items like %Y-%m-%d", ""),
+                theme().dialog.error_style,
+            )
+        };
+        preview.render(rows[8], buf);
+        render_input(
+            "Expiry warning",
+            16,
+            &self.expiry_warning,
+            self.focus == 6,
+            rows[9],
+            buf,
+        );
+        selector(
+            10,
+            "Theme",
+            format!("< {:<20} >", self.themes[self.theme]),
+            7,
+            buf,
+        );
+        selector(
+            11,
+            "Keymap",
+            format!("< {:<20} >", KEYMAP_PRESETS[self.keymap]),
+            8,
+            buf,
+        );
+        let update_check = if self.update_check { "x" } else { " " };
+        selector(
+            12,
+            "Updates",
+            format!("[{update_check}] Allow Help > Check Update"),
+            9,
+            buf,
+        );
+        if let Some(path) = &self.path {
+            Span::styled(format!("Config file: {}", path.display()), label).render(rows[13], buf);
+        }
+
+        match &self.message {
+            Some(Ok(message)) => Span::styled(message.as_str(), theme().dialog.ok_style),
+            Some(Err(message)) => Span::styled(message.as_str(), theme().dialog.error_style),
+            None => Span::default(),
+        }
+        .render(layout[1], buf);
+        let focused = self.focus.checked_sub(FIELD_COUNT);
+        render_buttons(&BUTTONS, focused, layout[2], buf, button_areas);
+    }
+}
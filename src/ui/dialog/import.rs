@@ -0,0 +1,500 @@
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{import::ImportResult, job::Job, Gpg},
+    clipboard,
+    theme::theme,
+    ui::widget::{
+        input::TextInputUiState,
+        popup::Popup,
+        text_area::{TextArea, TextAreaUiState},
+    },
+};
+
+use super::{render_buttons, render_input, spinner, DialogResult, DialogState, FormKey};
+
+/// Where the certificates come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    File,
+    Clipboard,
+    Text,
+    Stdin, // Binary data piped in at startup
+}
+
+impl Source {
+    fn label(&self) -> &'static str {
+        match self {
+            Source::File => "File",
+            Source::Clipboard => "Clipboard",
+            Source::Text => "Pasted text",
+            Source::Stdin => "Standard input",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Input,
+    Importing,
+    Done(ImportResult),
+    Failed(String), // Failed(Error)
+}
+
+/// Dialog behind File > Import
+pub struct ImportDialog {
+    step: Step,
+    /// 0 is the source, 1 the file or text, followed by the buttons
+    focus: usize,
+    sources: Vec<Source>,
+    source: usize,
+    path: TextInputUiState,
+    text: TextAreaUiState,
+    stdin: Vec<u8>,
+    error: Option<String>,
+    /// First line of the result shown
+    scroll: u16,
+    job: Option<Job<ImportResult>>,
+    ticks: usize,
+}
+
+impl Default for ImportDialog {
+    fn default() -> Self {
+        Self {
+            step: Step::Input,
+            focus: 1,
+            sources: vec![Source::File, Source::Clipboard, Source::Text],
+            source: 0,
+            path: TextInputUiState::default(),
+            text: TextAreaUiState::default(),
+            stdin: Vec::new(),
+            error: None,
+            scroll: 0,
+            job: None,
+            ticks: 0,
+        }
+    }
+}
+
+impl ImportDialog {
+    /// Import what was piped to the program, e.g. `curl ... | gpg4terminal`
+    pub fn from_stdin(data: Vec<u8>) -> Self {
+        let mut dialog = Self::default();
+        match String::from_utf8(data) {
+            Ok(text) => {
+                dialog.text.set_value(&text);
+                dialog.select_source(Source::Text);
+            }
+            Err(err) => {
+                dialog.stdin = err.into_bytes();
+                dialog.sources.push(Source::Stdin);
+                dialog.select_source(Source::Stdin);
+            }
+        }
+        // Piped data is ready to go, start on the Import button
+        dialog.focus = dialog.field_count();
+        dialog
+    }
+
+    fn current_source(&self) -> Source {
+        self.sources[self.source]
+    }
+
+    fn select_source(&mut self, source: Source) {
+        if let Some(index) = self.sources.iter().position(|&s| s == source) {
+            self.source = index;
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match (&self.step, self.current_source()) {
+            (Step::Input, Source::File | Source::Text) => 2,
+            (Step::Input, _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Input => &["Import", "Cancel"],
+            Step::Importing => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.step = step;
+        self.focus = 0;
+        self.scroll = 0;
+        self.error = None;
+    }
+
+    /// Read the data of the selected source
+    fn data(&self) -> Result<Vec<u8>, String> {
+        match self.current_source() {
+            Source::File => {
+                let path = self.path.value.trim();
+                if path.is_empty() {
+                    return Err("Enter the path of the file to import".to_string());
+                }
+                fs::read(path).map_err(|err| format!("Failed to read {path}: {err}"))
+            }
+            Source::Clipboard => match clipboard::paste() {
+                Ok(text) if text.trim().is_empty() => Err("The clipboard is empty".to_string()),
+                Ok(text) => Ok(text.into_bytes()),
+                Err(err) => Err(format!("Failed to read the clipboard: {err}")),
+            },
+            Source::Text if self.text.is_empty() => {
+                Err("Paste an ASCII armored certificate".to_string())
+            }
+            Source::Text => Ok(self.text.value().into_bytes()),
+            Source::Stdin => Ok(self.stdin.clone()),
+        }
+    }
+
+    fn start_import(&mut self, gpg: &Gpg) {
+        let data = match self.data() {
+            Ok(data) => data,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |_| gpg.import(&data)));
+        self.go_to(Step::Importing);
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Import" => self.start_import(gpg),
+            "Back" => self.go_to(Step::Input),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match &self.step {
+            Step::Done(result) => {
+                DialogResult::CloseAndReload(result.first_changed().map(str::to_string))
+            }
+            _ => DialogResult::Close,
+        }
+    }
+}
+
+impl DialogState for ImportDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Importing {
+            return DialogResult::Continue;
+        }
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+        let on_text =
+            self.step == Step::Input && self.current_source() == Source::Text && self.focus == 1;
+
+        // The text area uses Up/Down/Enter itself, only Tab leaves it
+        if on_text && matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::Enter) {
+            self.text.handle_key(key);
+            return DialogResult::Continue;
+        }
+        if matches!(self.step, Step::Done(_)) {
+            match key.code {
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                _ => (),
+            }
+        }
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.start_import(gpg),
+            FormKey::Other => {
+                if self.step == Step::Input && self.focus == 0 {
+                    let count = self.sources.len();
+                    match key.code {
+                        KeyCode::Left => self.source = (self.source + count - 1) % count,
+                        KeyCode::Right | KeyCode::Char(' ') => {
+                            self.source = (self.source + 1) % count
+                        }
+                        _ => (),
+                    }
+                    self.error = None;
+                } else if on_text {
+                    self.text.handle_key(key);
+                } else if self.step == Step::Input
+                    && self.current_source() == Source::File
+                    && self.focus == 1
+                {
+                    self.path.handle_key(key);
+                } else if on_button {
+                    match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text, switching to the text source unless a path is edited
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if self.step != Step::Input {
+            return DialogResult::Continue;
+        }
+        if self.current_source() == Source::File && self.focus == 1 && !text.contains(['\n', '\r'])
+        {
+            self.path.insert_str(text);
+        } else {
+            self.select_source(Source::Text);
+            self.focus = 1;
+            self.text.insert_str(text);
+        }
+        self.error = None;
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        match result {
+            Ok(result) => {
+                let fingerprint = result.first_changed().map(str::to_string);
+                self.go_to(Step::Done(result));
+                DialogResult::Reload(fingerprint)
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.step {
+            Step::Importing => vec![],
+            Step::Input if self.focus == 0 => vec![
+                ("L/R", "Source"),
+                ("Tab", "Next Field"),
+                ("Enter", "Import"),
+                ("Esc", "Cancel"),
+            ],
+            Step::Input if self.current_source() == Source::Text && self.focus == 1 => vec![
+                ("Paste", "Insert Armor"),
+                ("Ctrl+U", "Clear"),
+                ("Tab", "Next Field"),
+                ("Esc", "Cancel"),
+            ],
+            Step::Done(_) => vec![("Up/Down", "Scroll"), ("Enter/Esc", "Close")],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let title = match self.step {
+            Step::Done(_) => "Import Certificates - Result",
+            _ => "Import Certificates",
+        };
+        let area = Popup::centered(area, 76, 20);
+        Popup::new(
+            title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Input => {
+                let rows = Layout::new(
+                    Direction::Vertical,
+                    [
+                        Constraint::Length(1),
+                        Constraint::Length(1),
+                        Constraint::Min(0),
+                    ],
+                )
+                .split(body);
+                let style = if self.focus == 0 {
+                    theme().dialog.focused_input_style
+                } else {
+                    theme().dialog.input_style
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Source"), label),
+                    Span::styled(format!("< {:<16} >", self.current_source().label()), style),
+                ])
+                .render(rows[0], buf);
+                let field = rows[2];
+                match self.current_source() {
+                    Source::File => {
+                        let field_rows =
+                            Layout::new(Direction::Vertical, [Constraint::Length(1); 2])
+                                .split(field);
+                        render_input("File", 12, &self.path, self.focus == 1, field_rows[0], buf);
+                        Span::styled("Binary and ASCII armored certificates are supported", label)
+                            .render(field_rows[1], buf);
+                    }
+                    Source::Text => TextArea::new(
+                        &self.text,
+                        self.focus == 1,
+                        theme().dialog.input_style,
+                        theme().dialog.focused_input_style,
+                    )
+                    .render(field, buf),
+                    Source::Clipboard => {
+                        Paragraph::new("The clipboard content is read when pressing Import.")
+                            .style(label)
+                            .wrap(Wrap { trim: true })
+                            .render(field, buf)
+                    }
+                    Source::Stdin => Paragraph::new(format!(
+                        "{} bytes of binary data were read from standard input.",
+                        self.stdin.len()
+                    ))
+                    .style(label)
+                    .wrap(Wrap { trim: true })
+                    .render(field, buf),
+                }
+            }
+            Step::Importing => {
+                Line::styled(
+                    format!("{} Importing certificates ...", spinner(self.ticks)),
+                    value,
+                )
+                .render(body, buf);
+            }
+            Step::Done(result) => {
+                Paragraph::new(import_summary(result))
+                    .scroll((self.scroll, 0))
+                    .render(body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
+
+/// Counters and per certificate outcome of an import, also used after fetching keys
+pub fn import_summary(result: &ImportResult) -> Vec<Line<'static>> {
+    let label = theme().dialog.label_style;
+    let value = theme().dialog.default_style;
+    let counts = &result.counts;
+    let pair = |left: &str, left_count: String, right: &str, right_count: u32| {
+        Line::from(vec![
+            Span::styled(format!("{left:<16}"), label),
+            Span::styled(format!("{left_count:<10}"), value),
+            Span::styled(format!("{right:<18}"), label),
+            Span::styled(right_count.to_string(), value),
+        ])
+    };
+    let mut lines = vec![
+        pair(
+            "Processed",
+            counts.considered.to_string(),
+            "New user IDs",
+            counts.new_user_ids,
+        ),
+        pair(
+            "Imported",
+            counts.imported.to_string(),
+            "New subkeys",
+            counts.new_subkeys,
+        ),
+        pair(
+            "Unchanged",
+            counts.unchanged.to_string(),
+            "New signatures",
+            counts.new_signatures,
+        ),
+        pair(
+            "Not imported",
+            (counts.not_imported + counts.no_user_id).to_string(),
+            "New revocations",
+            counts.new_revocations,
+        ),
+    ];
+    if counts.secret_read > 0 {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<16}", "Secret keys"), label),
+            Span::styled(
+                format!(
+                    "{} read, {} imported, {} unchanged",
+                    counts.secret_read, counts.secret_imported, counts.secret_unchanged
+                ),
+                value,
+            ),
+        ]));
+    }
+    lines.push(Line::default());
+    for (fingerprint, flags) in result.keys.iter() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{fingerprint}  "), value),
+            Span::styled(flags.describe(), label),
+        ]));
+    }
+    for problem in result.problems.iter() {
+        lines.push(Line::styled(
+            format!(
+                "{}  {}",
+                problem.fingerprint.as_deref().unwrap_or("(unknown)"),
+                problem.reason
+            ),
+            theme().dialog.error_style,
+        ));
+    }
+    if result.keys.is_empty() && result.problems.is_empty() {
+        lines.push(Line::styled(
+            "No certificates were found in the data.",
+            theme().dialog.error_style,
+        ));
+    }
+    lines
+}
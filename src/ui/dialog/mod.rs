@@ -0,0 +1,493 @@
+pub mod about;
+pub mod add_subkey;
+pub mod add_user_id;
+pub mod backend_process;
+pub mod change_expiry;
+pub mod check_update;
+pub mod decrypt_verify;
+pub mod delete_key;
+pub mod encrypt_sign;
+pub mod export;
+pub mod general_settings;
+pub mod import;
+pub mod new_key_pair;
+pub mod primary_user_id;
+pub mod refresh_cert;
+pub mod revoke_subkey;
+pub mod revoke_user_id;
+pub mod search_on_server;
+pub mod server_settings;
+pub mod theme_settings;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    backend::{job::Job, key::Key, Gpg},
+    config::Config,
+    theme::theme,
+};
+
+use super::widget::{
+    input::{TextInput, TextInputUiState},
+    popup::{ButtonBar, Popup},
+};
+
+/// A modal dialog, driven by the App through [`Dialog`]
+pub trait DialogState {
+    /// Focus button `index`, to press it like `Enter` when it is clicked
+    fn focus_button(&mut self, index: usize);
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult;
+
+    /// Text pasted into the terminal (bracketed paste)
+    fn handle_paste(&mut self, text: &str) -> DialogResult;
+
+    /// Called on every tick of the main loop, to poll background jobs
+    fn tick(&mut self) -> DialogResult;
+
+    /// Key hints for the bottom bar
+    fn hints(&self) -> Vec<(&'static str, &'static str)>;
+
+    /// Draw the dialog, pushing the area of each button to `button_areas`
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>);
+}
+
+/// The modal dialog currently shown on top of the main panel
+pub struct Dialog(Box<dyn DialogState>);
+
+/// What the App has to do after a dialog handled an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogResult {
+    /// Nothing, the dialog stays open
+    Continue,
+    /// Close the dialog
+    Close,
+    /// Re-read the keyring and select the key with the fingerprint, if any
+    Reload(Option<String>),
+    /// Like `Reload`, but also close the dialog
+    CloseAndReload(Option<String>),
+    /// The settings were saved, apply them
+    ConfigChanged(Config),
+}
+
+impl Dialog {
+    pub fn new(dialog: impl DialogState + 'static) -> Self {
+        Self(Box::new(dialog))
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        self.0.handle_key(key, gpg)
+    }
+
+    /// A click at `position`, which presses the button drawn there
+    pub fn handle_click(
+        &mut self,
+        position: Position,
+        button_areas: &[Rect],
+        gpg: &Gpg,
+    ) -> DialogResult {
+        let Some(button) = button_areas.iter().position(|area| area.contains(position)) else {
+            return DialogResult::Continue;
+        };
+        self.0.focus_button(button);
+        self.handle_key(KeyEvent::from(KeyCode::Enter), gpg)
+    }
+
+    /// Text pasted into the terminal (bracketed paste)
+    pub fn handle_paste(&mut self, text: &str) -> DialogResult {
+        self.0.handle_paste(text)
+    }
+
+    /// Called on every tick of the main loop, to poll background jobs
+    pub fn tick(&mut self) -> DialogResult {
+        self.0.tick()
+    }
+
+    /// Key hints for the bottom bar
+    pub fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        self.0.hints()
+    }
+}
+
+/// The state is where the buttons of the dialog were drawn
+impl StatefulWidget for &Dialog {
+    type State = Vec<Rect>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        button_areas.clear();
+        self.0.render(area, buf, button_areas);
+    }
+}
+
+/// Focus movement shared by all dialog forms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormKey {
+    Next,
+    Prev,
+    Confirm,
+    Cancel,
+    Other,
+}
+
+impl FormKey {
+    pub fn from_key(key: KeyEvent) -> Self {
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => FormKey::Next,
+            KeyCode::BackTab | KeyCode::Up => FormKey::Prev,
+            KeyCode::Enter => FormKey::Confirm,
+            KeyCode::Esc => FormKey::Cancel,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => FormKey::Cancel,
+            _ => FormKey::Other,
+        }
+    }
+}
+
+const SPINNER: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// The spinner frame to show for a running job, `ticks` counting its ticks
+pub(super) fn spinner(ticks: usize) -> &'static str {
+    SPINNER[ticks / 2 % SPINNER.len()]
+}
+
+/// Move a selector of `count` choices back with Left, forward with anything else
+pub(super) fn cycle(index: usize, count: usize, key: KeyEvent) -> usize {
+    match key.code {
+        KeyCode::Left => (index + count - 1) % count,
+        _ => (index + 1) % count,
+    }
+}
+
+/// How to name `key` in a dialog, by its primary user ID or else its fingerprint
+pub(super) fn user_id(key: &Key) -> String {
+    key.primary_uid()
+        .map(|uid| uid.uid.clone())
+        .unwrap_or_else(|| key.fingerprint().to_string())
+}
+
+/// Render a labeled text input with the dialog styles
+pub fn render_input(
+    label: &str,
+    label_width: u16,
+    state: &TextInputUiState,
+    is_focused: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    TextInput::new(
+        label,
+        label_width,
+        state,
+        is_focused,
+        theme().dialog.label_style,
+        theme().dialog.input_style,
+        theme().dialog.focused_input_style,
+    )
+    .render(area, buf);
+}
+
+/// Render a row of buttons with the dialog styles, keeping where they are for clicks
+pub fn render_buttons(
+    labels: &[&str],
+    focused: Option<usize>,
+    area: Rect,
+    buf: &mut Buffer,
+    button_areas: &mut Vec<Rect>,
+) {
+    let button_bar = ButtonBar::new(
+        labels,
+        focused,
+        theme().dialog.button_style,
+        theme().dialog.focused_button_style,
+    );
+    *button_areas = button_bar.button_areas(area);
+    button_bar.render(area, buf);
+}
+
+/// The form of a dialog which changes a key with one background job, see [`KeyEditDialog`]
+pub trait KeyEditForm {
+    const TITLE: &'static str;
+    /// Width and height of the popup
+    const SIZE: (u16, u16);
+    /// The button which starts the job
+    const ACTION: &'static str;
+    /// Shown while the job runs, unless it reports its progress
+    const RUNNING: &'static str;
+    /// Whether Enter in the form starts the job, or only moves on for changes
+    /// which can not be undone
+    const ENTER_STARTS: bool = true;
+    /// Whether the key may have changed although the job failed
+    const RELOAD_ON_FAILURE: bool = false;
+
+    /// The key being changed, reloaded and selected afterwards
+    fn key(&self) -> &Key;
+
+    fn field_count(&self) -> usize;
+
+    /// The text input of `field`, if it is one
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState>;
+
+    /// Whether `field` is a selector or checkbox, changed with Left/Right/Space
+    fn is_choice(&self, _field: usize) -> bool {
+        false
+    }
+
+    /// Change the choice of `field` for `key`
+    fn change(&mut self, _field: usize, _key: KeyEvent) {}
+
+    /// Check the form and start the job, or say what is wrong with it
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String>;
+
+    /// Forget the passphrase once the job no longer needs it
+    fn clear_passphrase(&mut self);
+
+    /// What the job did, once it succeeded
+    fn done_message(&self) -> String;
+
+    /// Draw the fields, `focus` being the focused one
+    fn render(&self, focus: usize, area: Rect, buf: &mut Buffer);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeyEditStep {
+    Options,
+    Running,
+    Done(String),   // Done(Message)
+    Failed(String), // Failed(Error)
+}
+
+/// A dialog which fills in a [`KeyEditForm`], runs its job and shows the outcome
+pub struct KeyEditDialog<F> {
+    step: KeyEditStep,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    form: F,
+    error: Option<String>,
+    job: Option<Job<()>>,
+    ticks: usize,
+}
+
+impl<F: KeyEditForm> KeyEditDialog<F> {
+    pub fn new(form: F) -> Self {
+        Self {
+            step: KeyEditStep::Options,
+            focus: 0,
+            form,
+            error: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            KeyEditStep::Options => self.form.field_count(),
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            KeyEditStep::Options => &[F::ACTION, "Cancel"],
+            KeyEditStep::Running => &[],
+            KeyEditStep::Done(_) => &["Close"],
+            KeyEditStep::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: KeyEditStep) {
+        self.focus = 0;
+        self.step = step;
+        self.error = None;
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        match self.form.start(gpg) {
+            Ok(job) => {
+                self.job = Some(job);
+                self.go_to(KeyEditStep::Running);
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match self.step {
+            KeyEditStep::Options => self.form.input(self.focus),
+            _ => None,
+        }
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Back" => self.go_to(KeyEditStep::Options),
+            "Cancel" | "Close" => return self.close(),
+            _ => self.start(gpg),
+        }
+        DialogResult::Continue
+    }
+
+    fn reload(&self) -> Option<String> {
+        Some(self.form.key().fingerprint().to_string())
+    }
+
+    fn close(&self) -> DialogResult {
+        match self.step {
+            KeyEditStep::Done(_) => DialogResult::CloseAndReload(self.reload()),
+            _ => DialogResult::Close,
+        }
+    }
+}
+
+impl<F: KeyEditForm> DialogState for KeyEditDialog<F> {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == KeyEditStep::Running {
+            return DialogResult::Continue;
+        }
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm if F::ENTER_STARTS => self.start(gpg),
+            FormKey::Confirm => self.focus += 1,
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                if !on_button && is_toggle && self.form.is_choice(self.focus) {
+                    self.form.change(self.focus, key);
+                } else if let Some(input) = self.focused_input() {
+                    input.handle_key(key);
+                } else if on_button {
+                    match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        self.form.clear_passphrase();
+        match result {
+            Ok(()) => {
+                self.go_to(KeyEditStep::Done(self.form.done_message()));
+                DialogResult::Reload(self.reload())
+            }
+            Err(err) => {
+                self.go_to(KeyEditStep::Failed(err.to_string()));
+                if F::RELOAD_ON_FAILURE {
+                    DialogResult::Reload(self.reload())
+                } else {
+                    DialogResult::Continue
+                }
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match &self.step {
+            KeyEditStep::Running => vec![],
+            KeyEditStep::Options if self.focus < self.field_count() => {
+                let mut hints = vec![];
+                if self.form.is_choice(self.focus) {
+                    hints.push(("L/R/Space", "Change"));
+                }
+                hints.push(("Tab", "Next Field"));
+                if F::ENTER_STARTS {
+                    hints.push(("Enter", F::ACTION));
+                }
+                hints.push(("Esc", "Cancel"));
+                hints
+            }
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Cancel")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let (width, height) = F::SIZE;
+        let area = Popup::centered(area, width, height);
+        Popup::new(
+            F::TITLE,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+
+        match &self.step {
+            KeyEditStep::Options => self.form.render(self.focus, body, buf),
+            KeyEditStep::Running => {
+                let progress = self
+                    .job
+                    .as_ref()
+                    .and_then(Job::last_progress)
+                    .unwrap_or(F::RUNNING);
+                Line::styled(
+                    format!("{} {progress}", spinner(self.ticks)),
+                    theme().dialog.default_style,
+                )
+                .render(body, buf)
+            }
+            KeyEditStep::Done(message) => Paragraph::new(message.clone())
+                .style(theme().dialog.ok_style)
+                .wrap(Wrap { trim: true })
+                .render(body, buf),
+            KeyEditStep::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
@@ -0,0 +1,448 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        generate::{normalize_expiry, KeyAlgorithm, KeyParams},
+        job::Job,
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{render_buttons, render_input, spinner, DialogResult, DialogState, FormKey};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Identity,
+    Algorithm,
+    Passphrase,
+    Confirm,
+    Generating,
+    Done(String),   // Done(Fingerprint)
+    Failed(String), // Failed(Error)
+}
+
+/// Wizard behind File > New KeyPair
+pub struct NewKeyPairDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    name: TextInputUiState,
+    email: TextInputUiState,
+    comment: TextInputUiState,
+    algorithm: usize,
+    expires: TextInputUiState,
+    passphrase: TextInputUiState,
+    passphrase_repeat: TextInputUiState,
+    /// Validation error of the current step
+    error: Option<String>,
+    job: Option<Job<String>>,
+    ticks: usize,
+}
+
+impl Default for NewKeyPairDialog {
+    fn default() -> Self {
+        Self {
+            step: Step::Identity,
+            focus: 0,
+            name: TextInputUiState::default(),
+            email: TextInputUiState::default(),
+            comment: TextInputUiState::default(),
+            algorithm: 0,
+            expires: TextInputUiState::new("2y"),
+            passphrase: TextInputUiState::masked(),
+            passphrase_repeat: TextInputUiState::masked(),
+            error: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+}
+
+impl NewKeyPairDialog {
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Identity => 3,
+            Step::Algorithm | Step::Passphrase => 2,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Identity => &["Next", "Cancel"],
+            Step::Algorithm | Step::Passphrase => &["Back", "Next", "Cancel"],
+            Step::Confirm => &["Back", "Generate", "Cancel"],
+            Step::Generating => &[],
+            Step::Done(_) => &["Close"],
+            Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Identity, 0) => Some(&mut self.name),
+            (Step::Identity, 1) => Some(&mut self.email),
+            (Step::Identity, 2) => Some(&mut self.comment),
+            (Step::Algorithm, 1) => Some(&mut self.expires),
+            (Step::Passphrase, 0) => Some(&mut self.passphrase),
+            (Step::Passphrase, 1) => Some(&mut self.passphrase_repeat),
+            _ => None,
+        }
+    }
+
+    fn params(&self) -> KeyParams {
+        KeyParams {
+            name: self.name.value.clone(),
+            email: self.email.value.clone(),
+            comment: self.comment.value.clone(),
+            algorithm: KeyAlgorithm::ALL[self.algorithm],
+            expires: normalize_expiry(&self.expires.value).unwrap_or_default(),
+            passphrase: self.passphrase.value.clone(),
+        }
+    }
+
+    /// Check the fields of the current step
+    fn validate(&self) -> Result<(), String> {
+        match self.step {
+            Step::Identity => {
+                check_identity(&self.name.value, &self.email.value, &self.comment.value)?
+            }
+            Step::Algorithm if normalize_expiry(&self.expires.value).is_none() => {
+                return Err("Use e.g. 2y, 6m, 30d, 2030-12-31 or never".to_string());
+            }
+            Step::Passphrase if self.passphrase.value != self.passphrase_repeat.value => {
+                return Err("The passphrases do not match".to_string());
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn go_to(&mut self, step: Step) {
+        // Steps without fields start on their main button
+        self.focus = match step {
+            Step::Confirm => 1,
+            _ => 0,
+        };
+        self.step = step;
+        self.error = None;
+    }
+
+    fn next_step(&mut self, gpg: &Gpg) {
+        if let Err(err) = self.validate() {
+            self.error = Some(err);
+            return;
+        }
+        match self.step {
+            Step::Identity => self.go_to(Step::Algorithm),
+            Step::Algorithm => self.go_to(Step::Passphrase),
+            Step::Passphrase => self.go_to(Step::Confirm),
+            Step::Confirm => {
+                let params = self.params();
+                let gpg = gpg.clone();
+                self.job = Some(Job::spawn(move |progress| {
+                    progress.report("Creating primary key");
+                    gpg.generate_key(&params, |status| {
+                        if status.keyword == "KEY_CREATED" {
+                            progress.report("Adding encryption subkey");
+                        }
+                    })
+                }));
+                self.go_to(Step::Generating);
+            }
+            _ => (),
+        }
+    }
+
+    fn prev_step(&mut self) {
+        match self.step {
+            Step::Algorithm => self.go_to(Step::Identity),
+            Step::Passphrase => self.go_to(Step::Algorithm),
+            Step::Confirm | Step::Failed(_) => self.go_to(Step::Passphrase),
+            _ => (),
+        }
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Next" | "Generate" => self.next_step(gpg),
+            "Back" => self.prev_step(),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match &self.step {
+            Step::Done(fingerprint) => DialogResult::CloseAndReload(Some(fingerprint.clone())),
+            _ => DialogResult::Close,
+        }
+    }
+}
+
+impl DialogState for NewKeyPairDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if self.step == Step::Generating {
+            // gpg can not be interrupted safely while it writes the keyring
+            return DialogResult::Continue;
+        }
+        let focus_count = self.field_count() + self.buttons().len();
+        let on_button = self.focus >= self.field_count();
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - self.field_count()], gpg);
+            }
+            FormKey::Confirm => self.next_step(gpg),
+            FormKey::Other => {
+                if self.step == Step::Algorithm && self.focus == 0 {
+                    let count = KeyAlgorithm::ALL.len();
+                    match key.code {
+                        KeyCode::Left => self.algorithm = (self.algorithm + count - 1) % count,
+                        KeyCode::Right | KeyCode::Char(' ') => {
+                            self.algorithm = (self.algorithm + 1) % count
+                        }
+                        _ => (),
+                    }
+                } else if let Some(input) = self.focused_input() {
+                    input.handle_key(key);
+                } else if on_button {
+                    let first = self.field_count();
+                    match key.code {
+                        KeyCode::Left if self.focus > first => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text);
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        match result {
+            Ok(fingerprint) => {
+                self.go_to(Step::Done(fingerprint.clone()));
+                DialogResult::Reload(Some(fingerprint))
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.step {
+            Step::Generating => vec![],
+            Step::Algorithm if self.focus == 0 => vec![
+                ("L/R", "Algorithm"),
+                ("Tab", "Next Field"),
+                ("Enter", "Next"),
+                ("Esc", "Cancel"),
+            ],
+            _ => vec![
+                ("Tab/Up/Down", "Field"),
+                ("Enter", "Next"),
+                ("Esc", "Cancel"),
+            ],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let (number, title) = match self.step {
+            Step::Identity => (1, "Identity"),
+            Step::Algorithm => (2, "Algorithm & Expiry"),
+            Step::Passphrase => (3, "Passphrase"),
+            Step::Confirm => (4, "Review"),
+            _ => (4, "Generate"),
+        };
+        let title = format!("New KeyPair - {title} ({number}/4)");
+        let area = Popup::centered(area, 64, 14);
+        Popup::new(
+            &title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 8]).split(body);
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+
+        match &self.step {
+            Step::Identity => {
+                render_input("Name", 12, &self.name, self.focus == 0, rows[0], buf);
+                render_input("E-Mail", 12, &self.email, self.focus == 1, rows[2], buf);
+                render_input("Comment", 12, &self.comment, self.focus == 2, rows[4], buf);
+            }
+            Step::Algorithm => {
+                let style = if self.focus == 0 {
+                    theme().dialog.focused_input_style
+                } else {
+                    theme().dialog.input_style
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Algorithm"), label),
+                    Span::styled(
+                        format!("< {:<20} >", KeyAlgorithm::ALL[self.algorithm].label()),
+                        style,
+                    ),
+                ])
+                .render(rows[0], buf);
+                render_input("Expires", 12, &self.expires, self.focus == 1, rows[2], buf);
+                Span::styled(
+                    "e.g. 2y, 6m, 30d, 2030-12-31 or never",
+                    theme().dialog.label_style,
+                )
+                .render(rows[3], buf);
+            }
+            Step::Passphrase => {
+                render_input(
+                    "Passphrase",
+                    12,
+                    &self.passphrase,
+                    self.focus == 0,
+                    rows[0],
+                    buf,
+                );
+                render_input(
+                    "Repeat",
+                    12,
+                    &self.passphrase_repeat,
+                    self.focus == 1,
+                    rows[2],
+                    buf,
+                );
+                Span::styled(
+                    "Leave empty to create a key without passphrase",
+                    theme().dialog.label_style,
+                )
+                .render(rows[4], buf);
+            }
+            Step::Confirm => {
+                let params = self.params();
+                let summary = [
+                    ("User ID", params.user_id()),
+                    ("Algorithm", params.algorithm.label().to_string()),
+                    ("Expires", params.expires.clone()),
+                    (
+                        "Protection",
+                        if params.passphrase.is_empty() {
+                            "none".to_string()
+                        } else {
+                            "passphrase".to_string()
+                        },
+                    ),
+                ];
+                for (i, (name, text)) in summary.into_iter().enumerate() {
+                    Line::from(vec![
+                        Span::styled(format!("{name:<12}"), label),
+                        Span::styled(text, value),
+                    ])
+                    .render(rows[i], buf);
+                }
+                if params.passphrase.is_empty() {
+                    Span::styled(
+                        "Warning: the secret key will not be protected!",
+                        theme().dialog.error_style,
+                    )
+                    .render(rows[5], buf);
+                }
+            }
+            Step::Generating => {
+                let message = self
+                    .job
+                    .as_ref()
+                    .and_then(|job| job.last_progress())
+                    .unwrap_or("Starting gpg");
+                Paragraph::new(vec![
+                    Line::styled(format!("{} {message} ...", spinner(self.ticks)), value),
+                    Line::default(),
+                    Line::styled(
+                        "Generating keys can take a while. Moving the mouse or typing",
+                        label,
+                    ),
+                    Line::styled("in another window helps to gather entropy.", label),
+                ])
+                .render(body, buf);
+            }
+            Step::Done(fingerprint) => {
+                Paragraph::new(vec![
+                    Line::styled("The key pair was created successfully.", value),
+                    Line::default(),
+                    Line::from(vec![
+                        Span::styled("Fingerprint ", label),
+                        Span::styled(fingerprint.clone(), value),
+                    ]),
+                ])
+                .render(body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
+
+/// Check the parts of a user ID before they are put together
+pub fn check_identity(name: &str, email: &str, comment: &str) -> Result<(), String> {
+    let email = email.trim();
+    if name.trim().is_empty() && email.is_empty() {
+        return Err("Enter a name or an e-mail address".to_string());
+    }
+    if !email.is_empty() && !email.contains('@') {
+        return Err("The e-mail address is not valid".to_string());
+    }
+    if comment.contains(['(', ')']) {
+        return Err("The comment must not contain parentheses".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{
+        job::Job,
+        key::{Key, UserId, Validity},
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::input::TextInputUiState,
+};
+
+use super::{render_input, KeyEditForm};
+
+/// Form behind Primary on a user ID in the key detail view
+pub struct PrimaryUserIdForm {
+    key: Key,
+    user_id: UserId,
+    passphrase: TextInputUiState,
+}
+
+impl PrimaryUserIdForm {
+    /// Make user ID `index` the primary user ID of `key`
+    pub fn new(key: Key, index: usize) -> Self {
+        let user_id = key.user_ids[index].clone();
+        Self {
+            key,
+            user_id,
+            passphrase: TextInputUiState::masked(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.user_id.is_attribute {
+            return Err("Only a user ID can be the primary one".to_string());
+        }
+        if self.user_id.validity == Validity::Revoked {
+            return Err("A revoked user ID can not be the primary one".to_string());
+        }
+        if !self.key.primary.has_secret {
+            return Err("The secret primary key is not available to sign the change".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl KeyEditForm for PrimaryUserIdForm {
+    const TITLE: &'static str = "Primary User ID";
+    const SIZE: (u16, u16) = (76, 10);
+    const ACTION: &'static str = "Make Primary";
+    const RUNNING: &'static str = "Changing the primary user ID ...";
+
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn field_count(&self) -> usize {
+        1
+    }
+
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState> {
+        match field {
+            0 => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String> {
+        self.validate()?;
+        let fingerprint = self.key.fingerprint().to_string();
+        let user_id = self.user_id.uid.clone();
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        Ok(Job::spawn(move |_| {
+            gpg.set_primary_user_id(&fingerprint, &user_id, passphrase.as_deref())
+        }))
+    }
+
+    fn clear_passphrase(&mut self) {
+        self.passphrase.set_value("");
+    }
+
+    fn done_message(&self) -> String {
+        format!("{} is now the primary user ID", self.user_id.uid)
+    }
+
+    fn render(&self, focus: usize, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 5]).split(body);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "User ID"), label),
+            Span::styled(self.user_id.uid.clone(), theme().dialog.default_style),
+        ])
+        .render(rows[0], buf);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Currently"), label),
+            Span::styled(
+                self.key
+                    .primary_uid()
+                    .map(|uid| uid.uid.clone())
+                    .unwrap_or_default(),
+                theme().dialog.default_style,
+            ),
+        ])
+        .render(rows[1], buf);
+        render_input("Passphrase", 16, &self.passphrase, focus == 0, rows[3], buf);
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[4], buf);
+    }
+}
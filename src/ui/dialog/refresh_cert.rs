@@ -0,0 +1,398 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        job::Job,
+        key::Key,
+        refresh::{RefreshOutcome, RefreshSource},
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{render_buttons, render_input, spinner, user_id, DialogResult, DialogState, FormKey};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Options,
+    Refreshing,
+    Done(Vec<RefreshOutcome>),
+    Failed(String), // Failed(Error)
+}
+
+/// Dialog behind Tool > Refresh OpenPGP Cert
+pub struct RefreshCertDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    keys: Vec<Key>,
+    /// The key selected in the key list, if any
+    selected: Option<Key>,
+    /// Refresh only `selected` instead of all keys
+    only_selected: bool,
+    use_wkd: bool,
+    server: TextInputUiState,
+    /// First line of the result list
+    scroll: u16,
+    error: Option<String>,
+    job: Option<Job<Vec<RefreshOutcome>>>,
+    ticks: usize,
+}
+
+impl RefreshCertDialog {
+    /// `server` is preselected as the keyserver to refresh from
+    pub fn new(keys: Vec<Key>, selected: Option<Key>, server: &str) -> Self {
+        Self {
+            step: Step::Options,
+            focus: 3,
+            keys,
+            only_selected: selected.is_some(),
+            selected,
+            use_wkd: false,
+            server: TextInputUiState::new(server),
+            scroll: 0,
+            error: None,
+            job: None,
+            ticks: 0,
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Options => 3,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Options => &["Refresh", "Cancel"],
+            Step::Refreshing => &[],
+            Step::Done(_) | Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.focus = match step {
+            Step::Options => 3,
+            _ => 1,
+        };
+        self.step = step;
+        self.scroll = 0;
+        self.error = None;
+    }
+
+    fn targets(&self) -> Vec<Key> {
+        match (&self.selected, self.only_selected) {
+            (Some(key), true) => vec![key.clone()],
+            _ => self.keys.clone(),
+        }
+    }
+
+    fn start(&mut self, gpg: &Gpg) {
+        let keys = self.targets();
+        if keys.is_empty() {
+            self.error = Some("There are no certificates to refresh".to_string());
+            return;
+        }
+        let source = if self.use_wkd {
+            RefreshSource::Wkd
+        } else if self.server.value.trim().is_empty() {
+            self.error = Some("Enter a keyserver".to_string());
+            return;
+        } else {
+            RefreshSource::Keyserver(self.server.value.trim().to_string())
+        };
+        let gpg = gpg.clone();
+        self.job = Some(Job::spawn(move |progress| {
+            let count = keys.len();
+            Ok(gpg.refresh_keys(&keys, &source, |i, key| {
+                progress.report(format!("({}/{count}) {}", i + 1, user_id(key)));
+            }))
+        }));
+        self.go_to(Step::Refreshing);
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm => self.start(gpg),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match (&self.step, self.focus) {
+                    (Step::Options, 0) if is_toggle && self.selected.is_some() => {
+                        self.only_selected = !self.only_selected
+                    }
+                    (Step::Options, 1) if is_toggle => self.use_wkd = !self.use_wkd,
+                    (Step::Options, 2) => {
+                        self.server.handle_key(key);
+                    }
+                    _ if on_button => match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    },
+                    _ => (),
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Refresh" => self.start(gpg),
+            "Back" => self.go_to(Step::Options),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match self.step {
+            Step::Done(_) => DialogResult::CloseAndReload(None),
+            _ => DialogResult::Close,
+        }
+    }
+
+    fn outcome_lines(outcomes: &[RefreshOutcome]) -> Vec<Line<'static>> {
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let changed = outcomes
+            .iter()
+            .filter(|outcome| {
+                outcome
+                    .result
+                    .as_ref()
+                    .is_ok_and(|changes| !changes.is_empty())
+            })
+            .count();
+        let failed = outcomes
+            .iter()
+            .filter(|outcome| outcome.result.is_err())
+            .count();
+        let mut lines = vec![
+            Line::styled(
+                format!(
+                    "{} certificate(s) refreshed, {changed} changed, {failed} failed",
+                    outcomes.len()
+                ),
+                value,
+            ),
+            Line::default(),
+        ];
+        for outcome in outcomes {
+            let name = if outcome.user_id.is_empty() {
+                outcome.fingerprint.clone()
+            } else {
+                outcome.user_id.clone()
+            };
+            match &outcome.result {
+                Ok(changes) if changes.is_empty() => lines.push(Line::from(vec![
+                    Span::styled(name, value),
+                    Span::styled("  unchanged", label),
+                ])),
+                Ok(changes) => {
+                    lines.push(Line::styled(name, value));
+                    for change in changes {
+                        lines.push(Line::styled(
+                            format!("  + {}", change.describe()),
+                            theme().dialog.ok_style,
+                        ));
+                    }
+                }
+                Err(err) => {
+                    lines.push(Line::styled(name, value));
+                    lines.push(Line::styled(format!("  {err}"), theme().dialog.error_style));
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl DialogState for RefreshCertDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        match (&self.step, key.code) {
+            (Step::Refreshing, _) => return DialogResult::Continue,
+            (Step::Done(_), KeyCode::Up) => self.scroll = self.scroll.saturating_sub(1),
+            (Step::Done(_), KeyCode::Down) => self.scroll = self.scroll.saturating_add(1),
+            (Step::Done(_), KeyCode::PageUp) => self.scroll = self.scroll.saturating_sub(10),
+            (Step::Done(_), KeyCode::PageDown) => self.scroll = self.scroll.saturating_add(10),
+            _ => return self.handle_form_key(key, gpg),
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the keyserver input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if self.step == Step::Options && self.focus == 2 {
+            self.server.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        let Some(result) = self.job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.job = None;
+        match result {
+            Ok(outcomes) => {
+                self.go_to(Step::Done(outcomes));
+                DialogResult::Reload(None)
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match (&self.step, self.focus) {
+            (Step::Refreshing, _) => vec![],
+            (Step::Options, 0 | 1) => vec![
+                ("L/R", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Refresh"),
+                ("Esc", "Cancel"),
+            ],
+            (Step::Done(_), _) => vec![
+                ("Up/Down/PgUp/PgDn", "Scroll"),
+                ("Enter", "Choose"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Close")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 84, 20);
+        Popup::new(
+            "Refresh OpenPGP Certificates",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let field_style = |index: usize| {
+            if self.focus == index {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+
+        match &self.step {
+            Step::Options => {
+                let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 8]).split(body);
+                let scope = match (&self.selected, self.only_selected) {
+                    (Some(key), true) => user_id(key),
+                    _ => format!("All certificates ({})", self.keys.len()),
+                };
+                let source = if self.use_wkd {
+                    "Web Key Directory (WKD)"
+                } else {
+                    "Keyserver"
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Refresh"), label),
+                    Span::styled(format!("< {scope:<50.50} >"), field_style(0)),
+                ])
+                .render(rows[0], buf);
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Source"), label),
+                    Span::styled(format!("< {source:<30} >"), field_style(1)),
+                ])
+                .render(rows[2], buf);
+                if !self.use_wkd {
+                    render_input("Server", 12, &self.server, self.focus == 2, rows[4], buf);
+                }
+                let note = if self.use_wkd {
+                    "Looks up each certificate by its e-mail address"
+                } else {
+                    "Certificates are fetched by fingerprint and merged"
+                };
+                Span::styled(note, label).render(rows[6], buf);
+            }
+            Step::Refreshing => {
+                let progress = self
+                    .job
+                    .as_ref()
+                    .map(|job| job.progress.as_slice())
+                    .unwrap_or_default();
+                let mut lines = vec![Line::styled(
+                    format!(
+                        "{} Refreshing {} certificate(s) ...",
+                        spinner(self.ticks),
+                        self.targets().len()
+                    ),
+                    value,
+                )];
+                // Keep the latest keys in view
+                let visible = (body.height as usize).saturating_sub(1);
+                let skip = progress.len().saturating_sub(visible);
+                lines.extend(
+                    progress[skip..]
+                        .iter()
+                        .map(|message| Line::styled(message.clone(), label)),
+                );
+                Paragraph::new(lines).render(body, buf);
+            }
+            Step::Done(outcomes) => {
+                Paragraph::new(Self::outcome_lines(outcomes))
+                    .scroll((self.scroll, 0))
+                    .render(body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
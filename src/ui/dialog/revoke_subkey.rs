@@ -0,0 +1,187 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{
+        job::Job,
+        key::{Key, RevocationReason, Subkey, Validity},
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::input::TextInputUiState,
+};
+
+use super::{cycle, render_input, KeyEditForm};
+
+/// Form behind Revoke on a subkey in the key detail view
+pub struct RevokeSubkeyForm {
+    key: Key,
+    subkey: Subkey,
+    /// Index into `RevocationReason::FOR_KEYS`
+    reason: usize,
+    description: TextInputUiState,
+    passphrase: TextInputUiState,
+}
+
+impl RevokeSubkeyForm {
+    /// Revoke subkey `index` of `key`
+    pub fn new(key: Key, index: usize) -> Self {
+        let subkey = key.subkeys[index].clone();
+        Self {
+            key,
+            subkey,
+            reason: 0,
+            description: TextInputUiState::default(),
+            passphrase: TextInputUiState::masked(),
+        }
+    }
+
+    fn reason(&self) -> RevocationReason {
+        RevocationReason::FOR_KEYS[self.reason]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.subkey.validity == Validity::Revoked {
+            return Err("The subkey is already revoked".to_string());
+        }
+        if !self.key.primary.has_secret {
+            return Err(
+                "The secret primary key is not available to sign the revocation".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// What revoking the subkey means for the key
+    fn warning(&self) -> &'static str {
+        let encrypts = |subkey: &Subkey| {
+            subkey.capabilities.encrypt
+                && subkey.validity.is_usable()
+                && subkey.fingerprint != self.subkey.fingerprint
+        };
+        let last_encryption = self.subkey.capabilities.encrypt
+            && !encrypts(&self.key.primary)
+            && !self.key.subkeys.iter().any(encrypts);
+        if last_encryption {
+            "Nobody can encrypt to the key without another encryption subkey"
+        } else {
+            "A revoked subkey stays on the key and can not be restored"
+        }
+    }
+}
+
+impl KeyEditForm for RevokeSubkeyForm {
+    const TITLE: &'static str = "Revoke Subkey";
+    const SIZE: (u16, u16) = (80, 14);
+    const ACTION: &'static str = "Revoke";
+    const RUNNING: &'static str = "Revoking the subkey ...";
+    // Revoking can not be undone, so Enter in the form only moves on
+    const ENTER_STARTS: bool = false;
+
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn field_count(&self) -> usize {
+        3
+    }
+
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState> {
+        match field {
+            1 => Some(&mut self.description),
+            2 => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn is_choice(&self, field: usize) -> bool {
+        field == 0
+    }
+
+    fn change(&mut self, _field: usize, key: KeyEvent) {
+        self.reason = cycle(self.reason, RevocationReason::FOR_KEYS.len(), key);
+    }
+
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String> {
+        self.validate()?;
+        let fingerprint = self.key.fingerprint().to_string();
+        let subkey = self.subkey.fingerprint.clone();
+        let reason = self.reason();
+        let description = self.description.value.clone();
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        Ok(Job::spawn(move |_| {
+            gpg.revoke_subkey(
+                &fingerprint,
+                &subkey,
+                reason,
+                &description,
+                passphrase.as_deref(),
+            )
+        }))
+    }
+
+    fn clear_passphrase(&mut self) {
+        self.passphrase.set_value("");
+    }
+
+    fn done_message(&self) -> String {
+        format!(
+            "Revoked subkey {}, publish the key to let others know",
+            self.subkey.key_id
+        )
+    }
+
+    fn render(&self, focus: usize, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let reason_style = if focus == 0 {
+            theme().dialog.focused_input_style
+        } else {
+            theme().dialog.input_style
+        };
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 9]).split(body);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Subkey"), label),
+            Span::styled(
+                format!(
+                    "{} {} {}",
+                    self.subkey.capabilities,
+                    self.subkey.algorithm_name(),
+                    self.subkey.key_id
+                ),
+                value,
+            ),
+        ])
+        .render(rows[0], buf);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Reason"), label),
+            Span::styled(format!("< {:<26} >", self.reason().label()), reason_style),
+        ])
+        .render(rows[2], buf);
+        render_input(
+            "Description",
+            16,
+            &self.description,
+            focus == 1,
+            rows[3],
+            buf,
+        );
+        render_input("Passphrase", 16, &self.passphrase, focus == 2, rows[5], buf);
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[6], buf);
+        Span::styled(self.warning(), theme().dialog.error_style).render(rows[8], buf);
+    }
+}
@@ -0,0 +1,173 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{
+        job::Job,
+        key::{Key, RevocationReason, UserId, Validity},
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::input::TextInputUiState,
+};
+
+use super::{cycle, render_input, KeyEditForm};
+
+/// Form behind Revoke on a user ID in the key detail view
+pub struct RevokeUserIdForm {
+    key: Key,
+    user_id: UserId,
+    /// Index into `RevocationReason::FOR_USER_IDS`
+    reason: usize,
+    description: TextInputUiState,
+    passphrase: TextInputUiState,
+}
+
+impl RevokeUserIdForm {
+    /// Revoke user ID `index` of `key`
+    pub fn new(key: Key, index: usize) -> Self {
+        let user_id = key.user_ids[index].clone();
+        Self {
+            key,
+            user_id,
+            reason: 0,
+            description: TextInputUiState::default(),
+            passphrase: TextInputUiState::masked(),
+        }
+    }
+
+    fn reason(&self) -> RevocationReason {
+        RevocationReason::FOR_USER_IDS[self.reason]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.user_id.validity == Validity::Revoked {
+            return Err("The user ID is already revoked".to_string());
+        }
+        if !self.key.primary.has_secret {
+            return Err(
+                "The secret primary key is not available to sign the revocation".to_string(),
+            );
+        }
+        // A key without a valid user ID can not be used, gpg refuses it too
+        let others = self.key.user_ids.iter().any(|uid| {
+            uid.hash != self.user_id.hash && !uid.is_attribute && uid.validity != Validity::Revoked
+        });
+        if !others {
+            return Err("The last valid user ID can not be revoked".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl KeyEditForm for RevokeUserIdForm {
+    const TITLE: &'static str = "Revoke User ID";
+    const SIZE: (u16, u16) = (80, 14);
+    const ACTION: &'static str = "Revoke";
+    const RUNNING: &'static str = "Revoking the user ID ...";
+    // Revoking can not be undone, so Enter in the form only moves on
+    const ENTER_STARTS: bool = false;
+
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    fn field_count(&self) -> usize {
+        3
+    }
+
+    fn input(&mut self, field: usize) -> Option<&mut TextInputUiState> {
+        match field {
+            1 => Some(&mut self.description),
+            2 => Some(&mut self.passphrase),
+            _ => None,
+        }
+    }
+
+    fn is_choice(&self, field: usize) -> bool {
+        field == 0
+    }
+
+    fn change(&mut self, _field: usize, key: KeyEvent) {
+        self.reason = cycle(self.reason, RevocationReason::FOR_USER_IDS.len(), key);
+    }
+
+    fn start(&self, gpg: &Gpg) -> Result<Job<()>, String> {
+        self.validate()?;
+        let fingerprint = self.key.fingerprint().to_string();
+        let user_id = self.user_id.clone();
+        let reason = self.reason();
+        let description = self.description.value.clone();
+        let passphrase =
+            Some(self.passphrase.value.clone()).filter(|passphrase| !passphrase.is_empty());
+        let gpg = gpg.clone();
+        Ok(Job::spawn(move |_| {
+            gpg.revoke_user_id(
+                &fingerprint,
+                &user_id,
+                reason,
+                &description,
+                passphrase.as_deref(),
+            )
+        }))
+    }
+
+    fn clear_passphrase(&mut self) {
+        self.passphrase.set_value("");
+    }
+
+    fn done_message(&self) -> String {
+        format!(
+            "Revoked {}, publish the key to let others know",
+            self.user_id.uid
+        )
+    }
+
+    fn render(&self, focus: usize, body: Rect, buf: &mut Buffer) {
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let reason_style = if focus == 0 {
+            theme().dialog.focused_input_style
+        } else {
+            theme().dialog.input_style
+        };
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 9]).split(body);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "User ID"), label),
+            Span::styled(self.user_id.uid.clone(), value),
+        ])
+        .render(rows[0], buf);
+        Line::from(vec![
+            Span::styled(format!("{:<16}", "Reason"), label),
+            Span::styled(format!("< {:<26} >", self.reason().label()), reason_style),
+        ])
+        .render(rows[2], buf);
+        render_input(
+            "Description",
+            16,
+            &self.description,
+            focus == 1,
+            rows[3],
+            buf,
+        );
+        render_input("Passphrase", 16, &self.passphrase, focus == 2, rows[5], buf);
+        Span::styled(
+            format!(
+                "{:16}Of the primary key, empty lets the agent ask for it",
+                ""
+            ),
+            label,
+        )
+        .render(rows[6], buf);
+        Span::styled(
+            "A revoked user ID stays on the key and can not be restored",
+            theme().dialog.error_style,
+        )
+        .render(rows[8], buf);
+    }
+}
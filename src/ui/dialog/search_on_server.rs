@@ -0,0 +1,484 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget, Wrap},
+};
+
+use crate::{
+    backend::{
+        import::ImportResult,
+        job::Job,
+        keyserver::{base_url, Protocol, SearchResult, DEFAULT_KEYSERVER},
+        Gpg,
+    },
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{
+    import::import_summary, render_buttons, render_input, spinner, DialogResult, DialogState,
+    FormKey,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Query,
+    Searching,
+    Results,
+    Fetching,
+    Done(ImportResult),
+    Failed(String), // Failed(Error)
+}
+
+/// Dialog behind File > Search On Server
+pub struct SearchOnServerDialog {
+    step: Step,
+    /// Focused field, followed by the buttons of the step
+    focus: usize,
+    server: TextInputUiState,
+    protocol: usize,
+    query: TextInputUiState,
+    results: Vec<SearchResult>,
+    /// Highlighted result
+    selected: usize,
+    /// Results marked with `Space` for fetching
+    marked: Vec<bool>,
+    error: Option<String>,
+    search_job: Option<Job<Vec<SearchResult>>>,
+    fetch_job: Option<Job<ImportResult>>,
+    ticks: usize,
+}
+
+impl Default for SearchOnServerDialog {
+    fn default() -> Self {
+        Self {
+            step: Step::Query,
+            focus: 2,
+            server: TextInputUiState::new(DEFAULT_KEYSERVER),
+            protocol: 0,
+            query: TextInputUiState::default(),
+            results: Vec::new(),
+            selected: 0,
+            marked: Vec::new(),
+            error: None,
+            search_job: None,
+            fetch_job: None,
+            ticks: 0,
+        }
+    }
+}
+
+impl SearchOnServerDialog {
+    /// Search on `server` unless the user enters another one
+    pub fn new(server: &str) -> Self {
+        Self {
+            server: TextInputUiState::new(server),
+            ..Default::default()
+        }
+    }
+
+    fn field_count(&self) -> usize {
+        match self.step {
+            Step::Query => 3,
+            Step::Results => 1,
+            _ => 0,
+        }
+    }
+
+    fn buttons(&self) -> &'static [&'static str] {
+        match self.step {
+            Step::Query => &["Search", "Cancel"],
+            Step::Searching | Step::Fetching => &[],
+            Step::Results => &["Fetch", "Back", "Close"],
+            Step::Done(_) | Step::Failed(_) => &["Back", "Close"],
+        }
+    }
+
+    fn go_to(&mut self, step: Step) {
+        self.focus = match step {
+            Step::Query => 2,
+            _ => 0,
+        };
+        self.step = step;
+        self.error = None;
+    }
+
+    /// The entered keyserver, the connection is made in the job
+    fn keyserver(&self) -> Result<String, String> {
+        let server = self.server.value.trim().to_string();
+        base_url(&server).map_err(|err| err.to_string())?;
+        Ok(server)
+    }
+
+    fn search(&mut self, gpg: &Gpg) {
+        let query = self.query.value.trim().to_string();
+        if query.is_empty() {
+            self.error = Some("Enter a name, e-mail address or fingerprint".to_string());
+            return;
+        }
+        let protocol = Protocol::ALL[self.protocol];
+        if protocol == Protocol::Vks && !query.contains('@') && query.len() < 16 {
+            self.error = Some("VKS only finds e-mail addresses and fingerprints".to_string());
+            return;
+        }
+        let server = match self.keyserver() {
+            Ok(server) => server,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        let gpg = gpg.clone();
+        self.search_job = Some(Job::spawn(move |_| {
+            gpg.search_keyserver(&gpg.keyserver(&server)?, protocol, &query)
+        }));
+        self.go_to(Step::Searching);
+    }
+
+    /// Fetch the marked results, or the highlighted one if none is marked
+    fn fetch(&mut self, gpg: &Gpg) {
+        let mut wanted: Vec<SearchResult> = self
+            .results
+            .iter()
+            .zip(self.marked.iter())
+            .filter(|(_, &marked)| marked)
+            .map(|(result, _)| result.clone())
+            .collect();
+        if wanted.is_empty() {
+            wanted.extend(self.results.get(self.selected).cloned());
+        }
+        if wanted.is_empty() {
+            return;
+        }
+        let server = match self.keyserver() {
+            Ok(server) => server,
+            Err(err) => {
+                self.error = Some(err);
+                return;
+            }
+        };
+        let gpg = gpg.clone();
+        self.fetch_job = Some(Job::spawn(move |progress| {
+            let server = gpg.keyserver(&server)?;
+            let mut total = ImportResult::default();
+            for result in wanted.iter() {
+                progress.report(format!("Fetching {}", result.key_id));
+                total.merge(gpg.fetch_from_keyserver(&server, result)?);
+            }
+            Ok(total)
+        }));
+        self.go_to(Step::Fetching);
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match (&self.step, self.focus) {
+            (Step::Query, 0) => Some(&mut self.server),
+            (Step::Query, 2) => Some(&mut self.query),
+            _ => None,
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let field_count = self.field_count();
+        let focus_count = field_count + self.buttons().len();
+        let on_button = self.focus >= field_count;
+        let on_list = self.step == Step::Results && self.focus == 0;
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(self.buttons()[self.focus - field_count], gpg);
+            }
+            FormKey::Confirm if on_list => self.fetch(gpg),
+            FormKey::Confirm => self.search(gpg),
+            FormKey::Other => {
+                if self.step == Step::Query && self.focus == 1 {
+                    let count = Protocol::ALL.len();
+                    match key.code {
+                        KeyCode::Left => self.protocol = (self.protocol + count - 1) % count,
+                        KeyCode::Right | KeyCode::Char(' ') => {
+                            self.protocol = (self.protocol + 1) % count
+                        }
+                        _ => (),
+                    }
+                } else if let Some(input) = self.focused_input() {
+                    input.handle_key(key);
+                } else if on_button {
+                    match key.code {
+                        KeyCode::Left if self.focus > field_count => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match (button, &self.step) {
+            ("Search", _) => self.search(gpg),
+            ("Fetch", _) => self.fetch(gpg),
+            ("Back", Step::Results) => self.go_to(Step::Query),
+            ("Back", Step::Done(_)) if !self.results.is_empty() => self.go_to(Step::Results),
+            ("Back", _) => self.go_to(Step::Query),
+            _ => return self.close(),
+        }
+        DialogResult::Continue
+    }
+
+    fn close(&self) -> DialogResult {
+        match &self.step {
+            Step::Done(result) => {
+                DialogResult::CloseAndReload(result.first_changed().map(str::to_string))
+            }
+            _ => DialogResult::Close,
+        }
+    }
+
+    fn result_lines(&self) -> (Vec<Line<'static>>, usize) {
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        for (i, result) in self.results.iter().enumerate() {
+            let is_selected = i == self.selected && self.focus == 0;
+            if i == self.selected {
+                selected_line = lines.len();
+            }
+            let style = if is_selected {
+                theme().dialog.focused_input_style
+            } else if result.is_revoked || result.is_expired {
+                theme().key_detail.invalid_style
+            } else {
+                theme().dialog.default_style
+            };
+            let mark = if self.marked.get(i) == Some(&true) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            let algorithm = match result.algorithm {
+                Some(algorithm) if result.length > 0 => format!("{algorithm}{}", result.length),
+                Some(algorithm) => algorithm.to_string(),
+                None => String::new(),
+            };
+            let created = result
+                .created
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let flags = if result.is_revoked {
+                "revoked"
+            } else if result.is_expired {
+                "expired"
+            } else {
+                ""
+            };
+            lines.push(Line::styled(
+                format!(
+                    "{mark} {:<40} {algorithm:<9} {created:<10} {flags}",
+                    result.key_id
+                ),
+                style,
+            ));
+            for uid in result.user_ids.iter() {
+                lines.push(Line::styled(format!("    {uid}"), style));
+            }
+        }
+        (lines, selected_line)
+    }
+}
+
+impl DialogState for SearchOnServerDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = self.field_count() + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        if matches!(self.step, Step::Searching | Step::Fetching) {
+            return DialogResult::Continue;
+        }
+        let on_list = self.step == Step::Results && self.focus == 0;
+
+        // The result list uses Up/Down itself, only Tab leaves it
+        if on_list {
+            let last = self.results.len().saturating_sub(1);
+            self.selected = match key.code {
+                KeyCode::Up => self.selected.saturating_sub(1),
+                KeyCode::Down => (self.selected + 1).min(last),
+                KeyCode::Home => 0,
+                KeyCode::End => last,
+                KeyCode::Char(' ') => {
+                    if let Some(marked) = self.marked.get_mut(self.selected) {
+                        *marked = !*marked;
+                    }
+                    self.selected
+                }
+                _ => return self.handle_form_key(key, gpg),
+            };
+            return DialogResult::Continue;
+        }
+        self.handle_form_key(key, gpg)
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text.trim());
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        self.ticks = self.ticks.wrapping_add(1);
+        if let Some(result) = self.search_job.as_mut().and_then(Job::poll) {
+            self.search_job = None;
+            match result {
+                Ok(results) => {
+                    self.selected = 0;
+                    self.marked = vec![false; results.len()];
+                    self.results = results;
+                    self.go_to(Step::Results);
+                }
+                Err(err) => self.go_to(Step::Failed(err.to_string())),
+            }
+        }
+        let Some(result) = self.fetch_job.as_mut().and_then(Job::poll) else {
+            return DialogResult::Continue;
+        };
+        self.fetch_job = None;
+        match result {
+            Ok(result) => {
+                let fingerprint = result.first_changed().map(str::to_string);
+                self.go_to(Step::Done(result));
+                DialogResult::Reload(fingerprint)
+            }
+            Err(err) => {
+                self.go_to(Step::Failed(err.to_string()));
+                DialogResult::Continue
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.step {
+            Step::Searching | Step::Fetching => vec![],
+            Step::Query if self.focus == 1 => vec![
+                ("L/R", "Protocol"),
+                ("Tab", "Next Field"),
+                ("Enter", "Search"),
+                ("Esc", "Cancel"),
+            ],
+            Step::Results if self.focus == 0 => vec![
+                ("Up/Down", "Select"),
+                ("Space", "Mark"),
+                ("Enter", "Fetch"),
+                ("Tab", "Buttons"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![
+                ("Tab/Up/Down", "Field"),
+                ("Enter", "Choose"),
+                ("Esc", "Cancel"),
+            ],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let title = match self.step {
+            Step::Results => format!("Search On Server - {} found", self.results.len()),
+            Step::Done(_) => "Search On Server - Fetched".to_string(),
+            _ => "Search On Server".to_string(),
+        };
+        let area = Popup::centered(area, 84, 20);
+        Popup::new(
+            &title,
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let body = layout[0];
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 8]).split(body);
+        let label = theme().dialog.label_style;
+        let value = theme().dialog.default_style;
+        let spinner = spinner(self.ticks);
+
+        match &self.step {
+            Step::Query => {
+                render_input("Server", 12, &self.server, self.focus == 0, rows[0], buf);
+                let style = if self.focus == 1 {
+                    theme().dialog.focused_input_style
+                } else {
+                    theme().dialog.input_style
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<12}", "Protocol"), label),
+                    Span::styled(
+                        format!("< {:<30} >", Protocol::ALL[self.protocol].label()),
+                        style,
+                    ),
+                ])
+                .render(rows[2], buf);
+                render_input("Search", 12, &self.query, self.focus == 2, rows[4], buf);
+                Span::styled(
+                    "Name, e-mail address, key ID or fingerprint",
+                    theme().dialog.label_style,
+                )
+                .render(rows[5], buf);
+            }
+            Step::Searching => {
+                Line::styled(
+                    format!("{spinner} Searching {} ...", self.server.value),
+                    value,
+                )
+                .render(body, buf);
+            }
+            Step::Results if self.results.is_empty() => {
+                Line::styled("No certificates found.", value).render(body, buf);
+            }
+            Step::Results => {
+                let (lines, selected_line) = self.result_lines();
+                // Keep the highlighted result in view
+                let scroll = (selected_line + 1).saturating_sub(body.height as usize);
+                Paragraph::new(lines)
+                    .scroll((scroll as u16, 0))
+                    .render(body, buf);
+            }
+            Step::Fetching => {
+                let message = self
+                    .fetch_job
+                    .as_ref()
+                    .and_then(|job| job.last_progress())
+                    .unwrap_or("Fetching");
+                Line::styled(format!("{spinner} {message} ..."), value).render(body, buf);
+            }
+            Step::Done(result) => {
+                Paragraph::new(import_summary(result)).render(body, buf);
+            }
+            Step::Failed(err) => {
+                Paragraph::new(err.clone())
+                    .style(theme().dialog.error_style)
+                    .wrap(Wrap { trim: true })
+                    .render(body, buf);
+            }
+        }
+
+        if let Some(error) = &self.error {
+            Span::styled(error.clone(), theme().dialog.error_style).render(layout[1], buf);
+        }
+        let focused = self.focus.checked_sub(self.field_count());
+        render_buttons(self.buttons(), focused, layout[2], buf, button_areas);
+    }
+}
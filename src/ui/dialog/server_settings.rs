@@ -0,0 +1,439 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    backend::{
+        keyserver::check_keyserver,
+        network::{Proxy, ServerSettings, LOCATE_MECHANISMS},
+        Gpg,
+    },
+    config::Config,
+    theme::theme,
+    ui::widget::{input::TextInputUiState, popup::Popup},
+};
+
+use super::{render_buttons, render_input, DialogResult, DialogState, FormKey};
+
+/// List, new server input, the `auto-key-locate` toggles, proxy and proxy URL
+const FIELD_COUNT: usize = 4 + LOCATE_MECHANISMS.len();
+
+/// Focus of the first `auto-key-locate` toggle
+const FIRST_TOGGLE: usize = 2;
+
+const PROXY_FIELD: usize = FIRST_TOGGLE + LOCATE_MECHANISMS.len();
+
+const PROXY_URL_FIELD: usize = PROXY_FIELD + 1;
+
+const BUTTONS: [&str; 3] = ["Save", "Revert", "Close"];
+
+/// Rows of the keyserver list
+const LIST_HEIGHT: usize = 5;
+
+const PROXIES: [Proxy; 4] = [
+    Proxy::Direct,
+    Proxy::System,
+    Proxy::Http(String::new()),
+    Proxy::Tor,
+];
+
+/// Form behind Setting > Server
+pub struct ServerSettingsDialog {
+    /// Focused field, followed by the buttons
+    focus: usize,
+    /// The settings as last saved, restored by Revert
+    saved: ServerSettings,
+    /// Keyservers and `auto-key-locate` as edited, the proxy is taken from the fields
+    settings: ServerSettings,
+    /// Highlighted keyserver
+    selected: usize,
+    new_server: TextInputUiState,
+    /// Index into `PROXIES`
+    proxy: usize,
+    proxy_url: TextInputUiState,
+    config: Config,
+    config_path: Option<PathBuf>,
+    /// Whether dirmngr only stores the default keyserver
+    keeps_default_only: bool,
+    /// The settings could not be read, so they must not be overwritten
+    load_failed: bool,
+    message: Option<Result<String, String>>,
+    /// Close was pressed once with unsaved changes
+    confirm_discard: bool,
+}
+
+impl ServerSettingsDialog {
+    pub fn new(gpg: &Gpg, config: Config, config_path: Option<PathBuf>) -> Self {
+        let (mut saved, message) = match gpg.server_settings() {
+            Ok(settings) => (settings, None),
+            Err(err) => (
+                ServerSettings::default(),
+                Some(Err(format!("Failed to read the settings: {err}"))),
+            ),
+        };
+        // Keyservers besides dirmngr's default are only kept in our config
+        for server in config.keyservers.iter() {
+            if !saved.keyservers.contains(server) {
+                saved.keyservers.push(server.clone());
+            }
+        }
+        let keeps_default_only = gpg
+            .list_options("dirmngr")
+            .ok()
+            .and_then(|options| options.into_iter().find(|o| o.name == "keyserver"))
+            .is_some_and(|option| !option.is_list());
+        let mut dialog = Self {
+            focus: 0,
+            settings: saved.clone(),
+            saved,
+            selected: 0,
+            new_server: TextInputUiState::default(),
+            proxy: 0,
+            proxy_url: TextInputUiState::default(),
+            config,
+            config_path,
+            keeps_default_only,
+            load_failed: message.is_some(),
+            message,
+            confirm_discard: false,
+        };
+        dialog.load();
+        dialog
+    }
+
+    /// Fill the form from the saved settings
+    fn load(&mut self) {
+        self.settings = self.saved.clone();
+        self.selected = 0;
+        self.proxy = PROXIES
+            .iter()
+            .position(|proxy| proxy.label() == self.saved.proxy.label())
+            .unwrap_or(0);
+        self.proxy_url = match &self.saved.proxy {
+            Proxy::Http(url) => TextInputUiState::new(url),
+            _ => TextInputUiState::default(),
+        };
+    }
+
+    /// The settings as currently entered
+    fn entered(&self) -> ServerSettings {
+        let proxy = match &PROXIES[self.proxy] {
+            Proxy::Http(_) => Proxy::Http(self.proxy_url.value.trim().to_string()),
+            proxy => proxy.clone(),
+        };
+        ServerSettings {
+            proxy,
+            ..self.settings.clone()
+        }
+    }
+
+    fn focused_input(&mut self) -> Option<&mut TextInputUiState> {
+        match self.focus {
+            1 => Some(&mut self.new_server),
+            PROXY_URL_FIELD => Some(&mut self.proxy_url),
+            _ => None,
+        }
+    }
+
+    fn add_server(&mut self) {
+        let server = self.new_server.value.trim().to_string();
+        if let Err(err) = check_keyserver(&server) {
+            self.message = Some(Err(err.to_string()));
+            return;
+        }
+        if self.settings.keyservers.contains(&server) {
+            self.message = Some(Err(format!("{server} is already in the list")));
+            return;
+        }
+        self.settings.keyservers.push(server);
+        self.selected = self.settings.keyservers.len() - 1;
+        self.new_server = TextInputUiState::default();
+        self.message = None;
+    }
+
+    /// Move the highlighted keyserver to `to`
+    fn move_server(&mut self, to: usize) {
+        if to < self.settings.keyservers.len() && self.selected < self.settings.keyservers.len() {
+            let server = self.settings.keyservers.remove(self.selected);
+            self.settings.keyservers.insert(to, server);
+            self.selected = to;
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) -> bool {
+        let count = self.settings.keyservers.len();
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            KeyCode::Char('-') if self.selected > 0 => self.move_server(self.selected - 1),
+            KeyCode::Char('+') => self.move_server(self.selected + 1),
+            KeyCode::Char(' ') => self.move_server(0),
+            KeyCode::Delete | KeyCode::Backspace if self.selected < count => {
+                self.settings.keyservers.remove(self.selected);
+                self.selected = self.selected.min(count.saturating_sub(2));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn save(&mut self, gpg: &Gpg) -> DialogResult {
+        if self.load_failed {
+            return DialogResult::Continue;
+        }
+        let settings = self.entered();
+        let kept_all = match gpg.save_server_settings(&settings) {
+            Ok(kept_all) => kept_all,
+            Err(err) => {
+                self.message = Some(Err(err.to_string()));
+                return DialogResult::Continue;
+            }
+        };
+        self.saved = settings.clone();
+        self.config.keyservers = settings.keyservers;
+        let mut message = "Saved to dirmngr.conf and gpg.conf".to_string();
+        if let Some(path) = &self.config_path {
+            if let Err(err) = self.config.save(path) {
+                self.message = Some(Err(format!("Failed to save {}: {err}", path.display())));
+                return DialogResult::Continue;
+            }
+            if !kept_all {
+                message.push_str(", the other keyservers to config.toml");
+            }
+        }
+        self.message = Some(Ok(message));
+        DialogResult::ConfigChanged(self.config.clone())
+    }
+
+    fn press(&mut self, button: &str, gpg: &Gpg) -> DialogResult {
+        match button {
+            "Save" => return self.save(gpg),
+            "Revert" => {
+                self.load();
+                self.message = Some(Ok("Reverted to the saved settings".to_string()));
+            }
+            _ if self.entered() == self.saved || self.confirm_discard => {
+                return DialogResult::Close
+            }
+            _ => {
+                self.confirm_discard = true;
+                self.message = Some(Err(
+                    "Unsaved changes, press Close again to discard them".to_string()
+                ));
+            }
+        }
+        DialogResult::Continue
+    }
+}
+
+impl DialogState for ServerSettingsDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = FIELD_COUNT + index;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, gpg: &Gpg) -> DialogResult {
+        let focus_count = FIELD_COUNT + BUTTONS.len();
+        let on_button = self.focus >= FIELD_COUNT;
+        if !(on_button && key.code == KeyCode::Enter) {
+            self.confirm_discard = false;
+        }
+        // The list uses Up/Down itself, only Tab leaves it
+        if self.focus == 0 && self.handle_list_key(key) {
+            return DialogResult::Continue;
+        }
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return DialogResult::Close,
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if on_button => {
+                return self.press(BUTTONS[self.focus - FIELD_COUNT], gpg)
+            }
+            FormKey::Confirm if self.focus == 1 => self.add_server(),
+            FormKey::Confirm => return self.save(gpg),
+            FormKey::Other => {
+                let is_toggle = matches!(
+                    key.code,
+                    KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
+                );
+                match self.focus {
+                    focus if (FIRST_TOGGLE..PROXY_FIELD).contains(&focus) => match key.code {
+                        KeyCode::Char(' ') => {
+                            let mechanism = LOCATE_MECHANISMS[focus - FIRST_TOGGLE];
+                            let enabled = self.settings.locates_with(mechanism);
+                            self.settings.set_locate(mechanism, !enabled);
+                        }
+                        KeyCode::Left if focus > FIRST_TOGGLE => self.focus -= 1,
+                        KeyCode::Right if focus + 1 < PROXY_FIELD => self.focus += 1,
+                        _ => (),
+                    },
+                    PROXY_FIELD if is_toggle => {
+                        self.proxy = match key.code {
+                            KeyCode::Left => (self.proxy + PROXIES.len() - 1) % PROXIES.len(),
+                            _ => (self.proxy + 1) % PROXIES.len(),
+                        }
+                    }
+                    _ if on_button => match key.code {
+                        KeyCode::Left if self.focus > FIELD_COUNT => self.focus -= 1,
+                        KeyCode::Right if self.focus + 1 < focus_count => self.focus += 1,
+                        _ => (),
+                    },
+                    _ => {
+                        if let Some(input) = self.focused_input() {
+                            input.handle_key(key);
+                        }
+                    }
+                }
+            }
+        }
+        DialogResult::Continue
+    }
+
+    /// Insert pasted text into the focused input
+    fn handle_paste(&mut self, text: &str) -> DialogResult {
+        if let Some(input) = self.focused_input() {
+            input.insert_str(text);
+        }
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.focus {
+            0 => vec![
+                ("Up/Down", "Select"),
+                ("-/+", "Move"),
+                ("Space", "Make Default"),
+                ("Del", "Remove"),
+                ("Tab", "Next Field"),
+            ],
+            1 => vec![("Enter", "Add"), ("Tab", "Next Field"), ("Esc", "Close")],
+            focus if (FIRST_TOGGLE..PROXY_FIELD).contains(&focus) => vec![
+                ("Space", "Toggle"),
+                ("L/R", "Select"),
+                ("Tab", "Next Field"),
+                ("Enter", "Save"),
+            ],
+            PROXY_FIELD => vec![
+                ("L/R", "Change"),
+                ("Tab", "Next Field"),
+                ("Enter", "Save"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![
+                ("Tab/Up/Down", "Field"),
+                ("Enter", "Save"),
+                ("Esc", "Close"),
+            ],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 76, 21);
+        Popup::new(
+            "Settings - Server",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let rows = Layout::new(Direction::Vertical, [Constraint::Length(1); 16]).split(layout[0]);
+        let label = theme().dialog.label_style;
+        let style = |focused: bool| {
+            if focused {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.input_style
+            }
+        };
+
+        Span::styled("Keyservers, the first one is the default", label).render(rows[0], buf);
+        let keyservers = &self.settings.keyservers;
+        if keyservers.is_empty() {
+            Span::styled("  (none, dirmngr uses its built-in default)", label).render(rows[1], buf);
+        }
+        let scroll = (self.selected + 1).saturating_sub(LIST_HEIGHT);
+        for (i, server) in keyservers.iter().enumerate().skip(scroll).take(LIST_HEIGHT) {
+            let marker = if i == 0 { " (default)" } else { "" };
+            let line_style = if i == self.selected && self.focus == 0 {
+                theme().dialog.focused_input_style
+            } else {
+                theme().dialog.default_style
+            };
+            Span::styled(format!("  {server}{marker}"), line_style)
+                .render(rows[1 + i - scroll], buf);
+        }
+        render_input("Add", 12, &self.new_server, self.focus == 1, rows[6], buf);
+
+        Span::styled("Key discovery by mail address (auto-key-locate)", label).render(rows[8], buf);
+        let mut toggles = vec![Span::styled(format!("{:<12}", ""), label)];
+        for (i, mechanism) in LOCATE_MECHANISMS.iter().enumerate() {
+            let mark = if self.settings.locates_with(mechanism) {
+                "x"
+            } else {
+                " "
+            };
+            toggles.push(Span::styled(
+                format!("[{mark}] {mechanism}"),
+                style(self.focus == FIRST_TOGGLE + i),
+            ));
+            toggles.push(Span::raw("  "));
+        }
+        Line::from(toggles).render(rows[9], buf);
+
+        Line::from(vec![
+            Span::styled(format!("{:<12}", "Network"), label),
+            Span::styled(
+                format!("< {:<28} >", PROXIES[self.proxy].label()),
+                style(self.focus == PROXY_FIELD),
+            ),
+        ])
+        .render(rows[11], buf);
+        render_input(
+            "Proxy URL",
+            12,
+            &self.proxy_url,
+            self.focus == PROXY_URL_FIELD,
+            rows[12],
+            buf,
+        );
+        if !matches!(PROXIES[self.proxy], Proxy::Http(_)) {
+            Span::styled(format!("{:<12}only used with an HTTP proxy", ""), label)
+                .render(rows[13], buf);
+        }
+        if self.keeps_default_only && keyservers.len() > 1 {
+            Span::styled(
+                "dirmngr only keeps the default, the others are kept in config.toml",
+                label,
+            )
+            .render(rows[14], buf);
+        }
+
+        match &self.message {
+            Some(Ok(message)) => Span::styled(message.as_str(), theme().dialog.ok_style),
+            Some(Err(message)) => Span::styled(message.as_str(), theme().dialog.error_style),
+            None => Span::default(),
+        }
+        .render(layout[1], buf);
+        let focused = self.focus.checked_sub(FIELD_COUNT);
+        render_buttons(&BUTTONS, focused, layout[2], buf, button_areas);
+    }
+}
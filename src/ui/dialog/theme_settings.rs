@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+use crate::{
+    backend::Gpg,
+    config::Config,
+    theme::{no_color, set_theme, theme, Theme},
+    ui::widget::popup::Popup,
+};
+
+use super::{render_buttons, DialogResult, DialogState, FormKey};
+
+const BUTTONS: [&str; 2] = ["Save", "Close"];
+
+/// Picker behind Setting > Theme, the highlighted theme is shown right away
+pub struct ThemeSettingsDialog {
+    /// 0 is the theme list, followed by the buttons
+    focus: usize,
+    /// The config as last saved, its theme is restored on Close
+    saved: Config,
+    path: Option<PathBuf>,
+    dir: Option<PathBuf>,
+    /// The presets and the theme files
+    themes: Vec<String>,
+    /// Highlighted theme
+    selected: usize,
+    /// Outcome of the last save, or what was wrong with a theme file
+    message: Option<Result<String, String>>,
+}
+
+impl ThemeSettingsDialog {
+    pub fn new(config: Config, path: Option<PathBuf>) -> Self {
+        let dir = path.as_deref().map(Theme::dir);
+        let themes = Theme::names(dir.as_deref());
+        let selected = themes
+            .iter()
+            .position(|name| *name == config.theme)
+            .unwrap_or(0);
+        Self {
+            focus: 0,
+            saved: config,
+            path,
+            dir,
+            themes,
+            selected,
+            message: None,
+        }
+    }
+
+    /// Show theme `index`
+    fn preview(&mut self, index: usize) {
+        self.selected = index;
+        let name = &self.themes[index];
+        let (theme, warnings) = Theme::load(name, self.dir.as_deref());
+        set_theme(theme);
+        self.message = if warnings.is_empty() {
+            None
+        } else {
+            Some(Err(format!("{name}: {}", warnings.join("; "))))
+        };
+    }
+
+    /// Show the saved theme again
+    fn revert(&self) {
+        set_theme(Theme::load(&self.saved.theme, self.dir.as_deref()).0);
+    }
+
+    fn save(&mut self) -> DialogResult {
+        let config = Config {
+            theme: self.themes[self.selected].clone(),
+            ..self.saved.clone()
+        };
+        let Some(path) = &self.path else {
+            self.message = Some(Ok(
+                "No config directory, the theme is only used until you quit".to_string(),
+            ));
+            self.saved = config.clone();
+            return DialogResult::ConfigChanged(config);
+        };
+        if let Err(err) = config.save(path) {
+            self.message = Some(Err(format!("Failed to save {}: {err}", path.display())));
+            return DialogResult::Continue;
+        }
+        self.message = Some(Ok(format!("Saved to {}", path.display())));
+        self.saved = config.clone();
+        DialogResult::ConfigChanged(config)
+    }
+
+    fn close(&self) -> DialogResult {
+        if self.themes[self.selected] != self.saved.theme {
+            self.revert();
+        }
+        DialogResult::Close
+    }
+}
+
+impl DialogState for ThemeSettingsDialog {
+    fn focus_button(&mut self, index: usize) {
+        self.focus = index + 1;
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, _gpg: &Gpg) -> DialogResult {
+        let focus_count = 1 + BUTTONS.len();
+
+        // The theme list uses Up/Down itself, only Tab leaves it
+        if self.focus == 0 {
+            let last = self.themes.len() - 1;
+            let selected = match key.code {
+                KeyCode::Up => Some(self.selected.saturating_sub(1)),
+                KeyCode::Down => Some((self.selected + 1).min(last)),
+                KeyCode::Home => Some(0),
+                KeyCode::End => Some(last),
+                _ => None,
+            };
+            if let Some(selected) = selected {
+                if selected != self.selected {
+                    self.preview(selected);
+                }
+                return DialogResult::Continue;
+            }
+        }
+
+        match FormKey::from_key(key) {
+            FormKey::Cancel => return self.close(),
+            FormKey::Next => self.focus = (self.focus + 1) % focus_count,
+            FormKey::Prev => self.focus = (self.focus + focus_count - 1) % focus_count,
+            FormKey::Confirm if self.focus > 0 && BUTTONS[self.focus - 1] == "Close" => {
+                return self.close();
+            }
+            FormKey::Confirm => return self.save(),
+            FormKey::Other => match key.code {
+                KeyCode::Left if self.focus > 1 => self.focus -= 1,
+                KeyCode::Right if self.focus > 0 && self.focus + 1 < focus_count => self.focus += 1,
+                _ => (),
+            },
+        }
+        DialogResult::Continue
+    }
+
+    fn handle_paste(&mut self, _text: &str) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn tick(&mut self) -> DialogResult {
+        DialogResult::Continue
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.focus {
+            0 => vec![
+                ("Up/Down", "Preview"),
+                ("Enter", "Save"),
+                ("Tab", "Buttons"),
+                ("Esc", "Close"),
+            ],
+            _ => vec![("Tab", "Field"), ("Enter", "Choose"), ("Esc", "Close")],
+        }
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, button_areas: &mut Vec<Rect>) {
+        let area = Popup::centered(area, 60, 16);
+        Popup::new(
+            "Settings - Theme",
+            theme().dialog.default_style,
+            theme().dialog.border_style,
+        )
+        .render(area, buf);
+
+        let layout = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Min(0),
+                Constraint::Length(2),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ],
+        )
+        .split(Popup::inner(area));
+        let label = theme().dialog.label_style;
+
+        let lines: Vec<Line> = self
+            .themes
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let style = match (i == self.selected, self.focus == 0) {
+                    (true, true) => theme().dialog.focused_input_style,
+                    (true, false) => theme().dialog.input_style,
+                    _ => theme().dialog.default_style,
+                };
+                let saved = if *name == self.saved.theme { "*" } else { " " };
+                Line::styled(format!("{saved} {name:<40}"), style)
+            })
+            .collect();
+        let scroll = (self.selected + 1).saturating_sub(layout[0].height as usize);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .render(layout[0], buf);
+
+        let mut notes = Vec::new();
+        if no_color() {
+            notes.push(Line::styled("NO_COLOR is set, all colors are off", label));
+        }
+        if let Some(dir) = &self.dir {
+            notes.push(Line::styled(
+                format!("More themes: {}/<name>.toml", dir.display()),
+                label,
+            ));
+        }
+        Paragraph::new(notes).render(layout[1], buf);
+
+        match &self.message {
+            Some(Ok(message)) => Span::styled(message.as_str(), theme().dialog.ok_style),
+            Some(Err(message)) => Span::styled(message.as_str(), theme().dialog.error_style),
+            None => Span::default(),
+        }
+        .render(layout[2], buf);
+        let focused = self.focus.checked_sub(1);
+        render_buttons(&BUTTONS, focused, layout[3], buf, button_areas);
+    }
+}
@@ -2,6 +2,8 @@
 pub enum FocusOn {
     MenuBar(Menu), // MenuBar
     MainPanel,     // MainPanel
+    KeyDetail,     // KeyDetail
+    Dialog,        // Modal Dialog
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,411 @@
+use std::{cell::RefCell, rc::Rc};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+use crate::{
+    backend::key::{Key, Signature, Subkey},
+    event_handler::key_board_handler,
+    theme::theme,
+};
+
+use super::{date_width, UiState};
+
+/// An entry of the detail view which can be selected
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DetailItem {
+    Primary,       // Primary key
+    Subkey(usize), // Subkey(Index)
+    UserId(usize), // UserId(Index)
+}
+
+/// What a key bound in the detail view does to the shown key, executed by the App
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DetailAction {
+    Expire,
+    AddSubkey,
+    AddUserId,
+    /// Revoke the selected user ID or subkey
+    Revoke,
+    PrimaryUserId,
+}
+
+/// KeyDetail UI State
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct KeyDetailUiState {
+    /// The key shown, including signatures
+    pub key: Option<Key>,
+    /// Index into the selectable items
+    pub selected: usize,
+    /// First line shown
+    pub scroll: usize,
+    /// Scroll the selected item into view at the next render, unset by scrolling line-wise
+    pub follow_selected: bool,
+    /// Number of lines at the last render
+    pub line_count: usize,
+    /// Number of lines which fit into the view at the last render
+    pub page_size: usize,
+    /// Shown instead of the key if loading it failed
+    pub message: Option<String>,
+}
+
+impl KeyDetailUiState {
+    /// All selectable items, in display order
+    pub fn items(&self) -> Vec<DetailItem> {
+        let Some(key) = &self.key else {
+            return vec![];
+        };
+        let mut items = vec![DetailItem::Primary];
+        items.extend((0..key.subkeys.len()).map(DetailItem::Subkey));
+        items.extend((0..key.user_ids.len()).map(DetailItem::UserId));
+        items
+    }
+
+    pub fn selected_item(&self) -> Option<DetailItem> {
+        self.items().get(self.selected).copied()
+    }
+
+    /// Last valid `scroll`, which still fills the view
+    fn max_scroll(&self) -> usize {
+        self.line_count.saturating_sub(self.page_size)
+    }
+}
+
+/// Lines scrolled by one step of the mouse wheel
+const WHEEL_LINES: usize = 3;
+
+pub fn init_key_detail_state() -> Rc<RefCell<KeyDetailUiState>> {
+    Rc::new(RefCell::new(KeyDetailUiState::default()))
+}
+
+/// Full fingerprint, grouped by 4 with a wider gap in the middle like GnuPG
+pub fn format_fingerprint(fingerprint: &str) -> String {
+    let groups: Vec<String> = fingerprint
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect();
+    let middle = groups.len() / 2;
+    format!(
+        "{}  {}",
+        groups[..middle].join(" "),
+        groups[middle..].join(" ")
+    )
+}
+
+/// Dates in the configured format, padded to a common column width
+struct DateColumn<'a> {
+    format: &'a str,
+    width: usize,
+}
+
+impl<'a> DateColumn<'a> {
+    fn new(format: &'a str) -> Self {
+        Self {
+            format,
+            width: date_width(format).max(10) + 2,
+        }
+    }
+
+    fn format(&self, date: Option<chrono::DateTime<chrono::Utc>>, none: &str) -> String {
+        match date {
+            Some(date) => date.format(self.format).to_string(),
+            None => none.to_string(),
+        }
+    }
+
+    fn cell(&self, date: Option<chrono::DateTime<chrono::Utc>>, none: &str) -> String {
+        format!("{:<width$}", self.format(date, none), width = self.width)
+    }
+}
+
+/// Where the secret part of a key is, with the markers of `gpg -K`
+fn secret_cell(subkey: &Subkey) -> &'static str {
+    if subkey.card_serial.is_some() {
+        "> card"
+    } else if subkey.is_stub {
+        "# stub"
+    } else if subkey.has_secret {
+        "yes"
+    } else {
+        ""
+    }
+}
+
+fn subkey_line(subkey: &Subkey, dates: &DateColumn, style: Style) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{:<5}", subkey.capabilities.to_string()), style),
+        Span::styled(format!("{:<16}", subkey.algorithm_name()), style),
+        Span::styled(format!("{:<18}", subkey.key_id), style),
+        Span::styled(dates.cell(subkey.created, ""), style),
+        Span::styled(dates.cell(subkey.expires, "never"), style),
+        Span::styled(format!("{:<10}", subkey.validity.to_string()), style),
+        Span::styled(format!("{:<8}", secret_cell(subkey)), style),
+        Span::styled(subkey.keygrip.clone().unwrap_or_default(), style),
+    ])
+}
+
+fn signature_line(signature: &Signature, key: &Key, dates: &DateColumn) -> Line<'static> {
+    let kind = if signature.is_revocation {
+        "rev"
+    } else {
+        "sig"
+    };
+    let signer = if signature.is_self_signature(&key.primary.key_id) {
+        "[self-signature]".to_string()
+    } else if signature.signer.is_empty() {
+        "[User ID not found]".to_string()
+    } else {
+        signature.signer.clone()
+    };
+    let style = theme().key_detail.signature_style;
+    Line::from(vec![
+        Span::styled(
+            format!("      {kind} {} {:02x} ", signature.check, signature.class),
+            style,
+        ),
+        Span::styled(format!("{:<18}", signature.key_id), style),
+        Span::styled(dates.cell(signature.created, ""), style),
+        Span::styled(signer, style),
+    ])
+}
+
+/// Where the secret keys are, e.g. for a key whose primary key is kept offline
+fn secret_summary(key: &Key) -> String {
+    if let Some(serial) = &key.primary.card_serial {
+        format!("on card {serial}")
+    } else if key.primary.has_secret {
+        "available".to_string()
+    } else if key.has_secret() {
+        "subkeys only".to_string()
+    } else {
+        "not available".to_string()
+    }
+}
+
+/// When and why a user ID was revoked, e.g. `[revoked 2026-10-17: User ID is no longer valid]`
+fn revocation_note(revocation: &Signature, dates: &DateColumn) -> String {
+    let mut note = format!("[revoked {}", dates.format(revocation.created, ""));
+    if let Some(reason) = revocation.reason() {
+        note.push_str(&format!(": {}", reason.label()));
+    }
+    if let Some(comment) = &revocation.revocation_comment {
+        note.push_str(&format!(" ({comment})"));
+    }
+    note.push(']');
+    note
+}
+
+pub fn render_key_detail(ui_state: &UiState, area: Rect, buf: &mut Buffer) {
+    let mut key_detail_state = ui_state.key_detail_state.borrow_mut();
+
+    let Some(key) = key_detail_state.key.clone() else {
+        let message = key_detail_state.message.clone().unwrap_or_default();
+        Paragraph::new(message)
+            .style(theme().content)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(theme().borders)
+                    .title(" Certificate "),
+            )
+            .render(area, buf);
+        return;
+    };
+
+    let selected = key_detail_state.selected_item();
+    let item_style = |item: DetailItem, style: Style| {
+        if Some(item) == selected {
+            theme().key_detail.selected_style
+        } else {
+            style
+        }
+    };
+    let label = theme().key_detail.label_style;
+    let value = theme().key_detail.value_style;
+    let dates = DateColumn::new(&ui_state.date_format);
+
+    let mut lines: Vec<Line> = Vec::new();
+    // Line index of the selected item, to scroll it into view
+    let mut selected_line = 0;
+
+    lines.push(Line::from(vec![
+        Span::styled("Fingerprint:  ", label),
+        Span::styled(format_fingerprint(key.fingerprint()), value),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled("Validity:     ", label),
+        Span::styled(key.primary.validity.to_string(), value),
+        Span::styled("    Owner trust: ", label),
+        Span::styled(key.owner_trust.to_string(), value),
+        Span::styled("    Usage: ", label),
+        Span::styled(key.capabilities.to_string(), value),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled("Created:      ", label),
+        Span::styled(dates.format(key.primary.created, "unknown"), value),
+        Span::styled("    Expires: ", label),
+        Span::styled(dates.format(key.primary.expires, "never"), value),
+        Span::styled("    Secret key: ", label),
+        Span::styled(secret_summary(&key), value),
+    ]));
+    lines.push(Line::default());
+
+    lines.push(Line::styled(
+        format!(
+            "{:<5}{:<16}{:<18}{:<w$}{:<w$}{:<10}{:<8}{}",
+            "Use",
+            "Algorithm",
+            "Key ID",
+            "Created",
+            "Expires",
+            "Validity",
+            "Secret",
+            "Keygrip",
+            w = dates.width
+        ),
+        theme().key_detail.header_style,
+    ));
+    if selected == Some(DetailItem::Primary) {
+        selected_line = lines.len();
+    }
+    lines.push(subkey_line(
+        &key.primary,
+        &dates,
+        item_style(DetailItem::Primary, value),
+    ));
+    for (i, subkey) in key.subkeys.iter().enumerate() {
+        if selected == Some(DetailItem::Subkey(i)) {
+            selected_line = lines.len();
+        }
+        let style = if subkey.validity.is_usable() {
+            value
+        } else {
+            theme().key_detail.invalid_style
+        };
+        lines.push(subkey_line(
+            subkey,
+            &dates,
+            item_style(DetailItem::Subkey(i), style),
+        ));
+    }
+    lines.push(Line::default());
+
+    lines.push(Line::styled(
+        format!(
+            "{:<10}{:<w$}{}",
+            "Validity",
+            "Self-signed",
+            "User ID",
+            w = dates.width
+        ),
+        theme().key_detail.header_style,
+    ));
+    let primary_uid = key.primary_uid().map(|uid| uid.hash.clone());
+    for (i, uid) in key.user_ids.iter().enumerate() {
+        if selected == Some(DetailItem::UserId(i)) {
+            selected_line = lines.len();
+        }
+        let style = if uid.validity.is_usable() {
+            value
+        } else {
+            theme().key_detail.invalid_style
+        };
+        let style = item_style(DetailItem::UserId(i), style);
+        let self_signed = uid
+            .self_signature(&key.primary.key_id)
+            .and_then(|sig| sig.created)
+            .or(uid.created);
+        let mut spans = vec![
+            Span::styled(format!("{:<10}", uid.validity.to_string()), style),
+            Span::styled(dates.cell(self_signed, ""), style),
+            Span::styled(uid.uid.clone(), style),
+        ];
+        if let Some(revocation) = uid.revocation(&key.primary.key_id) {
+            spans.push(Span::styled(
+                format!("  {}", revocation_note(revocation, &dates)),
+                theme().key_detail.invalid_style,
+            ));
+        } else if primary_uid.as_ref() == Some(&uid.hash) {
+            spans.push(Span::styled("  [primary]", label));
+        }
+        lines.push(Line::from(spans));
+        for signature in uid.signatures.iter() {
+            lines.push(signature_line(signature, &key, &dates));
+        }
+    }
+
+    // Keep the selected line in view after selecting, and the view filled
+    let height = area.height.saturating_sub(2) as usize;
+    key_detail_state.line_count = lines.len();
+    key_detail_state.page_size = height;
+    if key_detail_state.follow_selected {
+        key_detail_state.follow_selected = false;
+        if selected_line < key_detail_state.scroll {
+            key_detail_state.scroll = selected_line;
+        } else if height > 0 && selected_line >= key_detail_state.scroll + height {
+            key_detail_state.scroll = selected_line + 1 - height;
+        }
+    }
+    key_detail_state.scroll = key_detail_state.scroll.min(key_detail_state.max_scroll());
+
+    let title = match key.primary_uid() {
+        Some(uid) => format!(" Certificate: {} ", uid.uid),
+        None => " Certificate ".to_string(),
+    };
+    Paragraph::new(lines)
+        .style(theme().content)
+        .scroll((key_detail_state.scroll as u16, 0))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(theme().borders)
+                .title(title),
+        )
+        .render(area, buf);
+}
+
+pub fn handle_event(
+    key_detail_state: Rc<RefCell<KeyDetailUiState>>,
+    event: key_board_handler::EventResult,
+) {
+    let mut key_detail_state = key_detail_state.borrow_mut();
+    // Signatures can not be selected, so they are reached by scrolling line-wise
+    let scroll = key_detail_state.scroll;
+    let max_scroll = key_detail_state.max_scroll();
+    let page_size = key_detail_state.page_size.max(1);
+    let scrolled = match event {
+        key_board_handler::EventResult::KeyDetailScrollUp => {
+            Some(scroll.saturating_sub(WHEEL_LINES))
+        }
+        key_board_handler::EventResult::KeyDetailScrollDown => {
+            Some((scroll + WHEEL_LINES).min(max_scroll))
+        }
+        key_board_handler::EventResult::KeyDetailPrevPage => Some(scroll.saturating_sub(page_size)),
+        key_board_handler::EventResult::KeyDetailNextPage => {
+            Some((scroll + page_size).min(max_scroll))
+        }
+        _ => None,
+    };
+    if let Some(scroll) = scrolled {
+        key_detail_state.scroll = scroll;
+        key_detail_state.follow_selected = false;
+        return;
+    }
+
+    let last = key_detail_state.items().len().saturating_sub(1);
+    let current = key_detail_state.selected;
+    key_detail_state.selected = match event {
+        key_board_handler::EventResult::KeyDetailPrevItem => current.saturating_sub(1),
+        key_board_handler::EventResult::KeyDetailNextItem => (current + 1).min(last),
+        key_board_handler::EventResult::KeyDetailFirstItem => 0,
+        key_board_handler::EventResult::KeyDetailLastItem => last,
+        _ => unreachable!(),
+    };
+    key_detail_state.follow_selected = true;
+}
@@ -0,0 +1,461 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use chrono::{TimeDelta, Utc};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Position, Rect},
+    text::Text,
+    widgets::{Block, Borders, Cell, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{backend::key::Key, event_handler::key_board_handler, theme::theme};
+
+use super::{date_width, UiState};
+
+/// KeyList UI State
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct KeyListUiState {
+    pub table_state: TableState,
+    /// Number of rows which fit into the table at the last render
+    pub page_size: usize,
+    /// Shown instead of the table when there are no keys to list
+    pub message: Option<String>,
+    /// Where the table was drawn at the last render, to find the clicked row
+    pub area: Rect,
+    /// Time and row of the last click, to detect a double click
+    pub last_click: Option<(Instant, usize)>,
+    /// The search typed after the `find` key, until it is kept or cancelled
+    pub find: Option<KeyListFind>,
+}
+
+/// A search in the key list, which selects the keys as they are typed
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct KeyListFind {
+    pub query: String,
+    /// The selection before the search, where it starts and goes back to on Esc
+    pub origin: Option<usize>,
+    /// Whether a key matches the query
+    pub is_found: bool,
+}
+
+/// The keys while searching, they are not in the keymap
+pub const FIND_HINTS: [(&str, &str); 3] = [
+    ("Enter", "Keep"),
+    ("Esc", "Cancel"),
+    ("Up/Down", "Other Matches"),
+];
+
+/// Longest time between the clicks of a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+pub fn init_key_list_state() -> Rc<RefCell<KeyListUiState>> {
+    Rc::new(RefCell::new(KeyListUiState::default()))
+}
+
+/// Select the key with `fingerprint`, or keep the selection in range
+pub fn select_key(
+    key_list_state: &Rc<RefCell<KeyListUiState>>,
+    keys: &[Key],
+    fingerprint: Option<&str>,
+) {
+    let mut key_list_state = key_list_state.borrow_mut();
+    let selected = fingerprint
+        .and_then(|fingerprint| keys.iter().position(|key| key.fingerprint() == fingerprint))
+        .or(key_list_state.table_state.selected());
+    let selected = match selected {
+        _ if keys.is_empty() => None,
+        Some(index) => Some(index.min(keys.len() - 1)),
+        None => Some(0),
+    };
+    key_list_state.table_state.select(selected);
+}
+
+/// Last 16 hex digits of the fingerprint, grouped by 4
+pub fn short_fingerprint(fingerprint: &str) -> String {
+    let start = fingerprint.len().saturating_sub(16);
+    fingerprint.as_bytes()[start..]
+        .chunks(4)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The date in the Expires column, the one the "expiring soon" highlight is based on
+///
+/// If a subkey expires before the primary key it is marked with `sub`.
+fn expires_cell(key: &Key, date_format: &str) -> String {
+    match (key.next_expiry(), key.primary.expires) {
+        (Some(next), primary) if Some(next) != primary => {
+            format!("{} sub", next.format(date_format))
+        }
+        (_, Some(date)) => date.format(date_format).to_string(),
+        (_, None) => "never".to_string(),
+    }
+}
+
+/// Whether `key` matches the search `query` by a user ID or its fingerprint
+///
+/// Only a group of hex digits or more is looked for in the fingerprint,
+/// a letter or two would match nearly every key.
+fn matches_query(key: &Key, query: &str) -> bool {
+    let query = query.to_lowercase();
+    let hex: String = query.split_whitespace().collect();
+    let hex = hex.trim_start_matches("0x");
+    key.user_ids
+        .iter()
+        .any(|uid| uid.uid.to_lowercase().contains(&query))
+        || (hex.len() >= 4
+            && hex.chars().all(|ch| ch.is_ascii_hexdigit())
+            && key.fingerprint().to_lowercase().contains(hex))
+}
+
+/// Start a search from the selected key
+pub fn start_find(key_list_state: &Rc<RefCell<KeyListUiState>>) {
+    let mut key_list_state = key_list_state.borrow_mut();
+    key_list_state.find = Some(KeyListFind {
+        origin: key_list_state.table_state.selected(),
+        is_found: true,
+        ..Default::default()
+    });
+}
+
+/// Edit the search with `key` and select the first matching key from where it started
+///
+/// Up/Down move to the previous or next match, Enter keeps the selection
+/// and Esc goes back to where the search started.
+pub fn handle_find_key(key_list_state: &Rc<RefCell<KeyListUiState>>, keys: &[Key], key: KeyEvent) {
+    let mut key_list_state = key_list_state.borrow_mut();
+    let Some(mut find) = key_list_state.find.take() else {
+        return;
+    };
+    let selected = key_list_state.table_state.selected();
+    // Searched in this order, wrapping around at the end of the list
+    let (start, forward) = match key.code {
+        KeyCode::Esc => {
+            let origin = find.origin.filter(|index| *index < keys.len());
+            key_list_state.table_state.select(origin);
+            return;
+        }
+        KeyCode::Enter => return,
+        KeyCode::Backspace => {
+            find.query.pop();
+            (find.origin, true)
+        }
+        KeyCode::Char(ch)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            find.query.push(ch);
+            (find.origin, true)
+        }
+        KeyCode::Down => (selected.map(|index| index + 1), true),
+        KeyCode::Up => (
+            selected.map(|index| index + keys.len().saturating_sub(1)),
+            false,
+        ),
+        _ => (selected, true),
+    };
+    if find.query.is_empty() {
+        find.is_found = true;
+        key_list_state
+            .table_state
+            .select(find.origin.filter(|index| *index < keys.len()));
+    } else if !keys.is_empty() {
+        let start = start.unwrap_or(0);
+        let count = keys.len();
+        let found = (0..count)
+            .map(|step| {
+                if forward {
+                    (start + step) % count
+                } else {
+                    (start + count - step) % count
+                }
+            })
+            .find(|index| matches_query(&keys[*index], &find.query));
+        find.is_found = found.is_some();
+        if found.is_some() {
+            key_list_state.table_state.select(found);
+        }
+    } else {
+        find.is_found = false;
+    }
+    key_list_state.find = Some(find);
+}
+
+/// Select the key in the row at `position`, returning whether it was double clicked
+pub fn handle_click(
+    key_list_state: &Rc<RefCell<KeyListUiState>>,
+    key_count: usize,
+    position: Position,
+) -> bool {
+    let mut key_list_state = key_list_state.borrow_mut();
+    // The rows start below the border and the header
+    let area = key_list_state.area;
+    let rows = Rect {
+        x: area.x + 1,
+        y: area.y + 2,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(3),
+    };
+    if key_count == 0 || !rows.contains(position) {
+        return false;
+    }
+    let index = key_list_state.table_state.offset() + (position.y - rows.y) as usize;
+    if index >= key_count {
+        return false;
+    }
+    let now = Instant::now();
+    let is_double_click = key_list_state
+        .last_click
+        .is_some_and(|(time, row)| row == index && now - time <= DOUBLE_CLICK);
+    // A third click starts over
+    key_list_state.last_click = (!is_double_click).then_some((now, index));
+    key_list_state.table_state.select(Some(index));
+    is_double_click
+}
+
+pub fn render_key_list(ui_state: &UiState, keys: &[Key], area: Rect, buf: &mut Buffer) {
+    let mut key_list_state = ui_state.key_list_state.borrow_mut();
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme().borders)
+        .title(format!(" Certificates ({}) ", keys.len()))
+        .style(theme().content);
+    if let Some(find) = &key_list_state.find {
+        let not_found = if find.is_found { "" } else { " (not found)" };
+        block = block.title(format!(" Find: {}_{not_found} ", find.query));
+    }
+
+    if keys.is_empty() {
+        let message = key_list_state
+            .message
+            .clone()
+            .unwrap_or("No certificates found".to_string());
+        Paragraph::new(Text::styled(message, theme().key_list.default_style))
+            .block(block)
+            .render(area, buf);
+        return;
+    }
+
+    key_list_state.area = area;
+    // Borders and header take 3 rows
+    key_list_state.page_size = area.height.saturating_sub(3).max(1) as usize;
+
+    let warn_until = Utc::now() + TimeDelta::days(ui_state.expiry_warning_days.into());
+    let rows: Vec<Row> = keys
+        .iter()
+        .map(|key| {
+            let uid = key.primary_uid();
+            let expiring = ui_state.expiry_warning_days > 0
+                && key.next_expiry().is_some_and(|date| date <= warn_until);
+            let style = if !key.primary.validity.is_usable() {
+                theme().key_list.invalid_style
+            } else if expiring {
+                theme().key_list.expiring_style
+            } else {
+                theme().key_list.default_style
+            };
+            Row::new(vec![
+                Cell::from(if key.has_secret() { "sec" } else { "pub" }),
+                Cell::from(uid.map(|uid| uid.name().to_string()).unwrap_or_default()),
+                Cell::from(
+                    uid.and_then(|uid| uid.email())
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+                Cell::from(short_fingerprint(key.fingerprint())),
+                Cell::from(key.primary.validity.to_string()),
+                Cell::from(expires_cell(key, &ui_state.date_format)),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        "Type",
+        "Name",
+        "E-Mail",
+        "Fingerprint",
+        "Validity",
+        "Expires",
+    ])
+    .style(theme().key_list.header_style);
+
+    let widths = [
+        Constraint::Length(4),
+        Constraint::Fill(2),
+        Constraint::Fill(3),
+        Constraint::Length(19),
+        Constraint::Length(10),
+        // Room for the ` sub` mark
+        Constraint::Length(date_width(&ui_state.date_format).max(10) as u16 + 4),
+    ];
+
+    StatefulWidget::render(
+        Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(theme().key_list.selected_style),
+        area,
+        buf,
+        &mut key_list_state.table_state,
+    );
+}
+
+pub fn handle_event(
+    key_list_state: Rc<RefCell<KeyListUiState>>,
+    key_count: usize,
+    event: key_board_handler::EventResult,
+) {
+    if key_count == 0 {
+        return;
+    }
+    let mut key_list_state = key_list_state.borrow_mut();
+    let last = key_count - 1;
+    let current = key_list_state.table_state.selected().unwrap_or(0);
+    let page_size = key_list_state.page_size.max(1);
+    let next = match event {
+        key_board_handler::EventResult::KeyListPrevItem => current.saturating_sub(1),
+        key_board_handler::EventResult::KeyListNextItem => (current + 1).min(last),
+        key_board_handler::EventResult::KeyListPrevPage => current.saturating_sub(page_size),
+        key_board_handler::EventResult::KeyListNextPage => (current + page_size).min(last),
+        key_board_handler::EventResult::KeyListFirstItem => 0,
+        key_board_handler::EventResult::KeyListLastItem => last,
+        _ => unreachable!(),
+    };
+    key_list_state.table_state.select(Some(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::colons;
+
+    fn keys() -> Vec<Key> {
+        colons::parse_keys(include_str!("../../tests/fixtures/list-keys.colons"))
+    }
+
+    fn selected(state: &Rc<RefCell<KeyListUiState>>) -> Option<usize> {
+        state.borrow().table_state.selected()
+    }
+
+    #[test]
+    fn short_fingerprints() {
+        assert_eq!(
+            short_fingerprint("4C2A739A8CA6AAF49B42D07943079B3772E1A1AB"),
+            "4307 9B37 72E1 A1AB"
+        );
+        // Shorter IDs are grouped as they are
+        assert_eq!(short_fingerprint("72E1A1AB"), "72E1 A1AB");
+        assert_eq!(short_fingerprint(""), "");
+    }
+
+    #[test]
+    fn select_key_by_fingerprint() {
+        let keys = keys();
+        let state = init_key_list_state();
+        select_key(&state, &keys, None);
+        assert_eq!(selected(&state), Some(0));
+
+        select_key(&state, &keys, Some(keys[2].fingerprint()));
+        assert_eq!(selected(&state), Some(2));
+        // An unknown fingerprint keeps the selection
+        select_key(&state, &keys, Some("0000000000000000"));
+        assert_eq!(selected(&state), Some(2));
+        // A shorter list moves it to the last key
+        select_key(&state, &keys[..1], None);
+        assert_eq!(selected(&state), Some(0));
+        select_key(&state, &[], None);
+        assert_eq!(selected(&state), None);
+    }
+
+    #[test]
+    fn page_through_the_list() {
+        use key_board_handler::EventResult;
+
+        let state = init_key_list_state();
+        state.borrow_mut().page_size = 4;
+        let press = |event: EventResult| {
+            handle_event(state.clone(), 10, event);
+            selected(&state)
+        };
+        assert_eq!(press(EventResult::KeyListNextPage), Some(4));
+        assert_eq!(press(EventResult::KeyListNextPage), Some(8));
+        // Pages stop at the ends
+        assert_eq!(press(EventResult::KeyListNextPage), Some(9));
+        assert_eq!(press(EventResult::KeyListPrevPage), Some(5));
+        assert_eq!(press(EventResult::KeyListPrevItem), Some(4));
+        assert_eq!(press(EventResult::KeyListPrevPage), Some(0));
+        assert_eq!(press(EventResult::KeyListPrevPage), Some(0));
+        assert_eq!(press(EventResult::KeyListLastItem), Some(9));
+        assert_eq!(press(EventResult::KeyListNextItem), Some(9));
+
+        // Nothing to select in an empty list
+        let empty = init_key_list_state();
+        handle_event(empty.clone(), 0, EventResult::KeyListNextPage);
+        assert_eq!(selected(&empty), None);
+    }
+
+    #[test]
+    fn expires_shows_the_first_expiry() {
+        let keys = keys();
+        // The encryption subkey of Alice expires a year before her primary key
+        assert_eq!(expires_cell(&keys[0], "%Y-%m-%d"), "2027-10-17 sub");
+        assert_eq!(expires_cell(&keys[1], "%Y-%m-%d"), "never");
+        // A revoked key has no next expiry
+        assert_eq!(expires_cell(&keys[2], "%Y-%m-%d"), "2020-01-01");
+    }
+    #[test]
+    fn find_keys_as_they_are_typed() {
+        let keys = keys();
+        let state = init_key_list_state();
+        select_key(&state, &keys, None);
+        let press = |code: KeyCode| {
+            handle_find_key(&state, &keys, KeyEvent::new(code, KeyModifiers::NONE));
+            selected(&state)
+        };
+        let type_text = |text: &str| {
+            text.chars().for_each(|ch| {
+                press(KeyCode::Char(ch));
+            });
+            selected(&state)
+        };
+
+        start_find(&state);
+        assert_eq!(type_text("ca"), Some(2));
+        // Esc goes back to where the search started
+        assert_eq!(press(KeyCode::Esc), Some(0));
+        assert!(state.borrow().find.is_none());
+
+        start_find(&state);
+        assert_eq!(type_text("example"), Some(0));
+        assert_eq!(press(KeyCode::Down), Some(1));
+        assert_eq!(press(KeyCode::Down), Some(2));
+        // Around the end of the list
+        assert_eq!(press(KeyCode::Down), Some(0));
+        assert_eq!(press(KeyCode::Up), Some(2));
+        assert_eq!(press(KeyCode::Enter), Some(2));
+        assert!(state.borrow().find.is_none());
+
+        // By fingerprint, spaces as shown in the list or not
+        start_find(&state);
+        assert_eq!(type_text("7220 3a43"), Some(1));
+        // Nothing found keeps the selection
+        assert_eq!(type_text("xx"), Some(1));
+        assert!(!state.borrow().find.as_ref().unwrap().is_found);
+        for _ in 0..11 {
+            press(KeyCode::Backspace);
+        }
+        assert_eq!(selected(&state), Some(2));
+        assert!(state.borrow().find.as_ref().unwrap().is_found);
+    }
+}
@@ -1,8 +1,12 @@
 use std::{cell::RefCell, rc::Rc};
 
-use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    widgets::Widget,
+};
 
-use crate::{event_handler::key_board_handler, theme::THEME};
+use crate::{backend::key::Key, config::Config, event_handler::key_board_handler};
 
 use super::{
     focus,
@@ -13,337 +17,310 @@ use super::{
     UiState,
 };
 
-pub const MENU_TABS: [(&str, Option<&str>); 4] = [
-    ("File", Some("F")),
-    ("Tool", Some("T")),
-    ("Setting", Some("S")),
-    ("Help", Some("H")),
-];
-
-pub const FILE_TAB_ITEMS: [(&str, Option<&str>); 6] = [
-    ("New KeyPair", Some("N")),
-    ("Import", Some("I")),
-    ("Search On Server", Some("S")),
-    ("Decrypt/Verify", None),
-    ("Encrypt/Sign", None),
-    ("Quit", Some("Q")),
-];
-
-pub const TOOL_TAB_ITEMS: [(&str, Option<&str>); 2] = [
-    ("Refresh OpenPGP Cert", Some("R")),
-    ("Restart Backend Process", None),
-];
-
-pub const SETTING_TAB_ITEMS: [(&str, Option<&str>); 2] =
-    [("General", Some("G")), ("Server", Some("S"))];
-
-pub const HELP_TAB_ITEMS: [(&str, Option<&str>); 2] =
-    [("Check Update", Some("C")), ("About", Some("A"))];
-
-pub fn init_menu_bar_state() -> Rc<RefCell<MenuBarUiState>> {
-    let mut menu_tab_states = vec![];
-
-    // File Tab
-    {
-        let mut sub_item_states = vec![];
-        // New KeyPair
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Import
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Search On Server
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Decrypt/Verify
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Encrypt/Sign
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Quit
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        menu_tab_states.push(Rc::new(RefCell::new(MenuTabUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: Some(sub_item_states),
-        })));
+/// What choosing a menu item does, executed by the App
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    NewKeyPair,
+    Import,
+    ExportPublic,
+    ExportSecret,
+    DeleteKey,
+    SearchOnServer,
+    DecryptVerify,
+    EncryptSign,
+    Quit,
+    RefreshCert,
+    BackendProcess,
+    GeneralSettings,
+    ServerSettings,
+    ThemeSettings,
+    CheckUpdate,
+    About,
+}
+
+/// What the enabled predicates of the menu items can look at
+pub struct MenuContext<'a> {
+    pub config: &'a Config,
+    /// The key selected in the key list
+    pub selected_key: Option<&'a Key>,
+}
+
+/// A tab, sub menu or item of the menu tree
+pub struct MenuNode {
+    pub label: &'static str,
+    pub hotkey: Option<&'static str>,
+    /// What choosing a leaf does, `None` for tabs and sub menus
+    pub action: Option<Action>,
+    pub children: Vec<MenuNode>,
+    pub enabled: fn(&MenuContext) -> bool,
+}
+
+impl MenuNode {
+    /// A leaf executing `action`
+    pub fn item(label: &'static str, hotkey: Option<&'static str>, action: Action) -> Self {
+        Self {
+            label,
+            hotkey,
+            action: Some(action),
+            children: Vec::new(),
+            enabled: |_| true,
+        }
     }
 
-    // Tool Tab
-    {
-        let mut sub_item_states = Vec::new();
-
-        // Refresh OpenPGP Cert
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Restart Backend Process
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        menu_tab_states.push(Rc::new(RefCell::new(MenuTabUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: Some(sub_item_states),
-        })));
+    /// A tab or sub menu listing `children`
+    pub fn menu(
+        label: &'static str,
+        hotkey: Option<&'static str>,
+        children: Vec<MenuNode>,
+    ) -> Self {
+        Self {
+            label,
+            hotkey,
+            action: None,
+            children,
+            enabled: |_| true,
+        }
     }
 
-    // Setting Tab
-    {
-        let mut sub_item_states = Vec::new();
-        // General
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // Server
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        menu_tab_states.push(Rc::new(RefCell::new(MenuTabUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: Some(sub_item_states),
-        })));
+    /// Only enable the node while `enabled` holds
+    pub fn enabled_if(mut self, enabled: fn(&MenuContext) -> bool) -> Self {
+        self.enabled = enabled;
+        self
     }
+}
 
-    // Help Tab
-    {
-        let mut sub_item_states = Vec::new();
-        // Check Update
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: true,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        // About
-        sub_item_states.push(Rc::new(RefCell::new(MenuTabItemUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: None,
-        })));
-
-        menu_tab_states.push(Rc::new(RefCell::new(MenuTabUiState {
-            is_disabled: false,
-            is_selected: false,
-            is_focused: false,
-            focus_on: 0,
-            sub_item_state: Some(sub_item_states),
-        })));
+/// The tabs of the menu bar with all their items
+pub fn menu_tree() -> Vec<MenuNode> {
+    vec![
+        MenuNode::menu(
+            "File",
+            Some("F"),
+            vec![
+                MenuNode::item("New KeyPair", Some("N"), Action::NewKeyPair),
+                MenuNode::item("Import", Some("I"), Action::Import),
+                MenuNode::menu(
+                    "Export",
+                    Some("E"),
+                    vec![
+                        MenuNode::item("Public Key", Some("P"), Action::ExportPublic)
+                            .enabled_if(|context| context.selected_key.is_some()),
+                        MenuNode::item("Secret Key", Some("S"), Action::ExportSecret).enabled_if(
+                            |context| context.selected_key.is_some_and(|key| key.has_secret()),
+                        ),
+                    ],
+                ),
+                MenuNode::item("Delete Key", Some("D"), Action::DeleteKey)
+                    .enabled_if(|context| context.selected_key.is_some()),
+                MenuNode::item("Search On Server", Some("S"), Action::SearchOnServer),
+                MenuNode::item("Decrypt/Verify", None, Action::DecryptVerify),
+                MenuNode::item("Encrypt/Sign", None, Action::EncryptSign),
+                MenuNode::item("Quit", Some("Q"), Action::Quit),
+            ],
+        ),
+        MenuNode::menu(
+            "Tool",
+            Some("T"),
+            vec![
+                MenuNode::item("Refresh OpenPGP Cert", Some("R"), Action::RefreshCert),
+                MenuNode::item("Restart Backend Process", None, Action::BackendProcess),
+            ],
+        ),
+        MenuNode::menu(
+            "Setting",
+            Some("S"),
+            vec![
+                MenuNode::item("General", Some("G"), Action::GeneralSettings),
+                MenuNode::item("Server", Some("S"), Action::ServerSettings),
+                MenuNode::item("Theme", Some("T"), Action::ThemeSettings),
+            ],
+        ),
+        MenuNode::menu(
+            "Help",
+            Some("H"),
+            vec![
+                MenuNode::item("Check Update", Some("C"), Action::CheckUpdate)
+                    .enabled_if(|context| context.config.update_check),
+                MenuNode::item("About", Some("A"), Action::About),
+            ],
+        ),
+    ]
+}
+
+/// States of `nodes` and, recursively, of their children
+fn item_states(nodes: &[MenuNode]) -> Option<Vec<Rc<RefCell<MenuTabItemUiState>>>> {
+    if nodes.is_empty() {
+        return None;
     }
+    Some(
+        nodes
+            .iter()
+            .map(|node| {
+                Rc::new(RefCell::new(MenuTabItemUiState {
+                    is_disabled: false,
+                    is_selected: false,
+                    is_focused: false,
+                    focus_on: 0,
+                    sub_item_state: item_states(&node.children),
+                    area: Rect::default(),
+                }))
+            })
+            .collect(),
+    )
+}
 
+/// The state tree matching the tabs and items of `menu`
+pub fn init_menu_bar_state(menu: &[MenuNode]) -> Rc<RefCell<MenuBarUiState>> {
+    let tab_state = menu
+        .iter()
+        .map(|tab| {
+            Rc::new(RefCell::new(MenuTabUiState {
+                is_disabled: false,
+                is_selected: false,
+                is_focused: false,
+                focus_on: 0,
+                sub_item_state: item_states(&tab.children),
+                area: Rect::default(),
+            }))
+        })
+        .collect();
     Rc::new(RefCell::new(MenuBarUiState {
         focus_on: 0,
-        tab_state: menu_tab_states,
+        tab_state,
+        return_focus: focus::FocusOn::MainPanel,
     }))
 }
 
-pub fn render_menu_bar(ui_state: &UiState, area: Rect, buf: &mut Buffer) {
-    let menu_bar_state = ui_state.menu_bar_state.borrow();
-    let menu_bar_style = &MenuBarStyle::new(
-        THEME.menu_bar.title_style,
-        THEME.menu_bar.default_style,
-        THEME.menu_bar.disabled_style,
-        THEME.menu_bar.focused_style,
-        THEME.menu_bar.selected_style,
-    );
-
-    let mut menu_items: Vec<MenuTab> = Vec::new();
-
-    // File Tab
-    {
-        let mut tab_items: Vec<MenuTabItem> = Vec::new();
-
-        for (label, hotkey) in FILE_TAB_ITEMS.iter() {
-            tab_items.push(MenuTabItem::new(
-                label.to_string(),
-                *hotkey,
-                None,
-                menu_bar_state.tab_state[0]
-                    .borrow()
-                    .sub_item_state
-                    .as_ref()
-                    .unwrap()[tab_items.len()]
-                .clone(),
-                menu_bar_style,
-            ));
+/// Re-evaluate the enabled predicates of `menu` in `context`
+pub fn update_enabled(
+    menu_bar_state: &Rc<RefCell<MenuBarUiState>>,
+    menu: &[MenuNode],
+    context: &MenuContext,
+) {
+    fn update_items(
+        nodes: &[MenuNode],
+        states: &[Rc<RefCell<MenuTabItemUiState>>],
+        context: &MenuContext,
+    ) {
+        for (node, state) in nodes.iter().zip(states) {
+            let mut state = state.borrow_mut();
+            state.is_disabled = !(node.enabled)(context);
+            if let Some(sub_states) = &state.sub_item_state {
+                update_items(&node.children, sub_states, context);
+            }
         }
-
-        menu_items.push(MenuTab::new(
-            MENU_TABS[0].0.to_string(),
-            MENU_TABS[0].1,
-            Some(tab_items),
-            menu_bar_state.tab_state[0].clone(),
-            menu_bar_style,
-        ));
     }
 
-    // Tool Tab
-    {
-        let mut tab_items: Vec<MenuTabItem> = Vec::new();
-
-        for (label, hotkey) in TOOL_TAB_ITEMS.iter() {
-            tab_items.push(MenuTabItem::new(
-                label.to_string(),
-                *hotkey,
-                None,
-                menu_bar_state.tab_state[1]
-                    .borrow()
-                    .sub_item_state
-                    .as_ref()
-                    .unwrap()[tab_items.len()]
-                .clone(),
-                menu_bar_style,
-            ));
+    let menu_bar_state = menu_bar_state.borrow();
+    for (tab, state) in menu.iter().zip(menu_bar_state.tab_state.iter()) {
+        let mut state = state.borrow_mut();
+        state.is_disabled = !(tab.enabled)(context);
+        if let Some(sub_states) = &state.sub_item_state {
+            update_items(&tab.children, sub_states, context);
         }
-
-        menu_items.push(MenuTab::new(
-            MENU_TABS[1].0.to_string(),
-            MENU_TABS[1].1,
-            Some(tab_items),
-            menu_bar_state.tab_state[1].clone(),
-            menu_bar_style,
-        ));
     }
+}
 
-    // Setting Tab
-    {
-        let mut tab_items: Vec<MenuTabItem> = Vec::new();
-
-        for (label, hotkey) in SETTING_TAB_ITEMS.iter() {
-            tab_items.push(MenuTabItem::new(
-                label.to_string(),
-                *hotkey,
-                None,
-                menu_bar_state.tab_state[2]
-                    .borrow()
-                    .sub_item_state
-                    .as_ref()
-                    .unwrap()[tab_items.len()]
-                .clone(),
-                menu_bar_style,
-            ));
-        }
-
-        menu_items.push(MenuTab::new(
-            MENU_TABS[2].0.to_string(),
-            MENU_TABS[2].1,
-            Some(tab_items),
-            menu_bar_state.tab_state[2].clone(),
-            menu_bar_style,
-        ));
+/// The action of the item at `path`, `[tab, item, sub item, ...]`
+pub fn action_at(menu: &[MenuNode], path: &[usize]) -> Option<Action> {
+    let (first, rest) = path.split_first()?;
+    let mut node = menu.get(*first)?;
+    for index in rest {
+        node = node.children.get(*index)?;
     }
+    node.action
+}
 
-    // Help Tab
-    {
-        let mut tab_items: Vec<MenuTabItem> = Vec::new();
-
-        for (label, hotkey) in HELP_TAB_ITEMS.iter() {
-            tab_items.push(MenuTabItem::new(
-                label.to_string(),
-                *hotkey,
-                None,
-                menu_bar_state.tab_state[3]
-                    .borrow()
-                    .sub_item_state
-                    .as_ref()
-                    .unwrap()[tab_items.len()]
-                .clone(),
+/// Whether the item with `action` and the menus it is in are enabled in `context`
+pub fn is_enabled(menu: &[MenuNode], action: Action, context: &MenuContext) -> bool {
+    menu.iter().any(|node| {
+        (node.enabled)(context)
+            && (node.action == Some(action) || is_enabled(&node.children, action, context))
+    })
+}
+
+/// Widgets for `nodes` and their sub menus
+fn menu_items<'a>(
+    nodes: &'a [MenuNode],
+    states: &[Rc<RefCell<MenuTabItemUiState>>],
+    menu_bar_style: &'a MenuBarStyle,
+) -> Vec<MenuTabItem<'a>> {
+    nodes
+        .iter()
+        .zip(states)
+        .map(|(node, state)| {
+            let sub_items = state
+                .borrow()
+                .sub_item_state
+                .clone()
+                .map(|sub_states| menu_items(&node.children, &sub_states, menu_bar_style));
+            MenuTabItem::new(
+                node.label.to_string(),
+                node.hotkey,
+                sub_items,
+                state.clone(),
                 menu_bar_style,
-            ));
-        }
+            )
+        })
+        .collect()
+}
 
-        menu_items.push(MenuTab::new(
-            MENU_TABS[3].0.to_string(),
-            MENU_TABS[3].1,
-            Some(tab_items),
-            menu_bar_state.tab_state[3].clone(),
-            menu_bar_style,
-        ));
-    }
+pub fn render_menu_bar(ui_state: &UiState, menu: &[MenuNode], area: Rect, buf: &mut Buffer) {
+    let menu_bar_state = ui_state.menu_bar_state.borrow();
+    let menu_bar_style = &MenuBarStyle::default();
+
+    let menu_tabs = menu
+        .iter()
+        .zip(menu_bar_state.tab_state.iter())
+        .map(|(tab, state)| {
+            let tab_items = state
+                .borrow()
+                .sub_item_state
+                .clone()
+                .map(|sub_states| menu_items(&tab.children, &sub_states, menu_bar_style));
+            MenuTab::new(
+                tab.label.to_string(),
+                tab.hotkey,
+                tab_items,
+                state.clone(),
+                menu_bar_style,
+            )
+        })
+        .collect();
 
-    MenuBar::new(
-        Some("GPG4Terminal".to_string()),
-        menu_items,
-        ui_state.menu_bar_state.clone(),
-        menu_bar_style,
-    )
-    .render(area, buf);
+    MenuBar::new(Some("GPG4Terminal".to_string()), menu_tabs, menu_bar_style).render(area, buf);
 }
 
+/// Apply a menu event, returning the action of a chosen item
 pub fn handle_event(
     focus_on: &mut focus::FocusOn,
     menu_bar_state: Rc<RefCell<MenuBarUiState>>,
+    menu: &[MenuNode],
     event: key_board_handler::EventResult,
-) {
+) -> Option<Action> {
+    if !matches!(focus_on, focus::FocusOn::MenuBar(_)) {
+        menu_bar_state.borrow_mut().return_focus = *focus_on;
+    }
+    // Hotkeys move the focus, then choose like `Enter` would
+    let hotkey = match event {
+        key_board_handler::EventResult::MenuAccelerator(hotkey) => Some((hotkey, true)),
+        key_board_handler::EventResult::MenuHotkey(hotkey) => Some((hotkey, false)),
+        _ => None,
+    };
+    if let Some((hotkey, is_accelerator)) = hotkey {
+        if focus_hotkey(focus_on, &menu_bar_state, menu, hotkey, is_accelerator) {
+            return handle_event(
+                focus_on,
+                menu_bar_state,
+                menu,
+                key_board_handler::EventResult::MenuChoose,
+            );
+        }
+        return None;
+    }
+    if let key_board_handler::EventResult::MouseClick(position) = event {
+        return click(focus_on, menu_bar_state, menu, position);
+    }
+
     let mut menu_bar_state = menu_bar_state.borrow_mut();
     match event {
         key_board_handler::EventResult::EnterMenuBar => {
@@ -356,70 +333,7 @@ pub fn handle_event(
                 .is_focused = true;
         }
         key_board_handler::EventResult::QuitMenu => {
-            match focus_on {
-                focus::FocusOn::MenuBar(focus::Menu::Tab) => {
-                    let current_tab = menu_bar_state.focus_on;
-                    menu_bar_state.tab_state[current_tab]
-                        .try_borrow_mut()
-                        .unwrap()
-                        .is_focused = false;
-                }
-                focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => {
-                    let mut stack = Vec::new();
-
-                    // push in the first focused tab item
-                    {
-                        let current_tab_id = menu_bar_state.focus_on;
-                        let current_tab = menu_bar_state.tab_state[current_tab_id].clone();
-
-                        let current_item_id = current_tab.borrow().focus_on;
-                        let current_item = current_tab
-                            .try_borrow()
-                            .unwrap()
-                            .sub_item_state
-                            .as_ref()
-                            .unwrap()[current_item_id]
-                            .clone();
-
-                        stack.push(current_item);
-                    }
-
-                    // push in the leftover focused tab items
-                    let mut item_point = stack.last().unwrap().clone();
-                    while item_point.borrow().is_focused
-                        && item_point.borrow().sub_item_state.is_some()
-                    {
-                        let current_item_id = item_point.borrow().focus_on;
-                        let current_item = item_point
-                            .try_borrow()
-                            .unwrap()
-                            .sub_item_state
-                            .as_ref()
-                            .unwrap()[current_item_id]
-                            .clone();
-                        item_point = current_item.clone();
-                        stack.push(current_item);
-                    }
-
-                    // De-Select the focused tab items
-                    while !stack.is_empty() {
-                        let current_item_ptr = stack.pop().unwrap();
-                        let mut current_item = current_item_ptr.borrow_mut();
-                        current_item.is_selected = false;
-                        current_item.is_focused = false;
-                    }
-
-                    // De-Select the focused tab
-                    let current_tab_id = menu_bar_state.focus_on;
-                    let mut current_tab = menu_bar_state.tab_state[current_tab_id]
-                        .try_borrow_mut()
-                        .unwrap();
-                    current_tab.is_selected = false;
-                    current_tab.is_focused = false;
-                }
-                _ => unreachable!(),
-            }
-            *focus_on = focus::FocusOn::MainPanel;
+            quit_menu(focus_on, &mut menu_bar_state);
         }
         key_board_handler::EventResult::MenuPrevItem => {
             match focus_on {
@@ -438,24 +352,12 @@ pub fn handle_event(
                         .is_focused = false;
                     menu_bar_state.tab_state[prev_tab].borrow_mut().is_focused = true;
                 }
-                focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => {
-                    let current_tab = menu_bar_state.focus_on;
-                    // Move focus to prev tab item
-                    let current_item = menu_bar_state.tab_state[current_tab].borrow().focus_on;
-                    let prev_item = if current_item != 0 {
-                        current_item - 1
-                    } else {
-                        current_item
-                    };
-                    menu_bar_state.tab_state[current_tab].borrow_mut().focus_on = prev_item;
-
-                    let current_tab = menu_bar_state.tab_state[current_tab].borrow();
-                    current_tab.sub_item_state.as_ref().unwrap()[current_item]
-                        .borrow_mut()
-                        .is_focused = false;
-                    current_tab.sub_item_state.as_ref().unwrap()[prev_item]
-                        .borrow_mut()
-                        .is_focused = true;
+                focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
+                    // Move focus to the prev enabled tab item
+                    let (items, current_item) = level_items(&menu_bar_state, *level);
+                    if let Some(prev_item) = find_enabled(&items, (0..current_item).rev()) {
+                        focus_item(&menu_bar_state, *level, &items, prev_item);
+                    }
                 }
                 _ => unreachable!(),
             }
@@ -477,32 +379,12 @@ pub fn handle_event(
                         .is_focused = false;
                     menu_bar_state.tab_state[next_tab].borrow_mut().is_focused = true;
                 }
-                focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => {
-                    // Move focus to next tab item
-                    let current_tab = menu_bar_state.focus_on;
-                    let current_item = menu_bar_state.tab_state[current_tab].borrow().focus_on;
-                    let next_item = if current_item
-                        != menu_bar_state.tab_state[current_tab]
-                            .borrow()
-                            .sub_item_state
-                            .as_ref()
-                            .unwrap()
-                            .len()
-                            - 1
-                    {
-                        current_item + 1
-                    } else {
-                        current_item
-                    };
-                    menu_bar_state.tab_state[current_tab].borrow_mut().focus_on = next_item;
-
-                    let current_tab = menu_bar_state.tab_state[current_tab].borrow();
-                    current_tab.sub_item_state.as_ref().unwrap()[current_item]
-                        .borrow_mut()
-                        .is_focused = false;
-                    current_tab.sub_item_state.as_ref().unwrap()[next_item]
-                        .borrow_mut()
-                        .is_focused = true;
+                focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
+                    // Move focus to the next enabled tab item
+                    let (items, current_item) = level_items(&menu_bar_state, *level);
+                    if let Some(next_item) = find_enabled(&items, current_item + 1..items.len()) {
+                        focus_item(&menu_bar_state, *level, &items, next_item);
+                    }
                 }
                 _ => unreachable!(),
             }
@@ -522,16 +404,11 @@ pub fn handle_event(
                         .is_some()
                     {
                         // if the tab has sub-items
-                        // move focus to the first sub tab item
+                        // move focus to the first enabled sub tab item
                         *focus_on = focus::FocusOn::MenuBar(focus::Menu::TabItem(0));
-                        menu_bar_state.tab_state[current_tab].borrow_mut().focus_on = 0;
-                        menu_bar_state.tab_state[current_tab]
-                            .borrow()
-                            .sub_item_state
-                            .as_ref()
-                            .unwrap()[0]
-                            .borrow_mut()
-                            .is_focused = true;
+                        let (items, _) = level_items(&menu_bar_state, 0);
+                        let first_item = find_enabled(&items, 0..items.len()).unwrap_or(0);
+                        focus_item(&menu_bar_state, 0, &items, first_item);
                     } else {
                         // [ ] If the tab has no sub-items
                         // choose the tab
@@ -539,18 +416,41 @@ pub fn handle_event(
                         // and quit the menu
                     }
                 }
-                focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => {
-                    // [ ] If the focus is on the tab item
-                    // if the tab item has sub-items ...
-                    //  select the focused tab item and move focus to the first sub tab item
-                    // if the tab item has no sub-items ...
-                    //  choose the tab item
-                    //  and execute the method
-                    //  and quit the menu
+                focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
+                    let level = *level;
+                    let (items, current_item) = level_items(&menu_bar_state, level);
+                    let item = items[current_item].clone();
+                    if item.borrow().is_disabled {
+                        return None;
+                    }
+                    if item.borrow().sub_item_state.is_none() {
+                        // If the tab item has no sub-items
+                        // choose the tab item and quit the menu,
+                        // the caller executes the method
+                        let path = focus_path(&menu_bar_state, level);
+                        quit_menu(focus_on, &mut menu_bar_state);
+                        return action_at(menu, &path);
+                    }
+                    // If the tab item has sub-items
+                    // select the focused tab item and move focus to the first sub tab item
+                    open_sub_menu(&item);
+                    *focus_on = focus::FocusOn::MenuBar(focus::Menu::TabItem(level + 1));
                 }
                 _ => unreachable!(),
             }
         }
+        key_board_handler::EventResult::NextMenuLevel => {
+            if let focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) = *focus_on {
+                // Open the sub menu of the focused tab item, if it has one
+                let (items, current_item) = level_items(&menu_bar_state, level);
+                let item = items[current_item].clone();
+                let can_open = !item.borrow().is_disabled && item.borrow().sub_item_state.is_some();
+                if can_open {
+                    open_sub_menu(&item);
+                    *focus_on = focus::FocusOn::MenuBar(focus::Menu::TabItem(level + 1));
+                }
+            }
+        }
         key_board_handler::EventResult::PrevMenuLevel => {
             match *focus_on {
                 focus::FocusOn::MenuBar(focus::Menu::TabItem(0)) => {
@@ -561,13 +461,508 @@ pub fn handle_event(
                         .is_selected = false;
                     *focus_on = focus::FocusOn::MenuBar(focus::Menu::Tab);
                 }
-                focus::FocusOn::MenuBar(focus::Menu::TabItem(_level)) => {
-                    // [ ] If the focus is on the other tab_item_level
-                    // quit to the previous tab_item_level
+                focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
+                    // Close the sub menu and move focus back to the tab item it belongs to
+                    let (items, current_item) = level_items(&menu_bar_state, level);
+                    items[current_item].borrow_mut().is_focused = false;
+                    if let Some(parent) = parent_item(&menu_bar_state, level) {
+                        parent.borrow_mut().is_selected = false;
+                    }
+                    *focus_on = focus::FocusOn::MenuBar(focus::Menu::TabItem(level - 1));
                 }
                 _ => unreachable!(),
             }
         }
         _ => unreachable!(),
     }
+    None
+}
+
+/// Whether `hotkey` is the key `ch`, ignoring case
+fn matches_hotkey(hotkey: Option<&str>, ch: char) -> bool {
+    let mut chars = hotkey.unwrap_or_default().chars();
+    chars
+        .next()
+        .is_some_and(|key| key.eq_ignore_ascii_case(&ch) && chars.next().is_none())
+}
+
+/// The nodes shown in menu `level` of the focused tab
+fn level_nodes<'a>(
+    menu: &'a [MenuNode],
+    menu_bar_state: &MenuBarUiState,
+    level: u16,
+) -> &'a [MenuNode] {
+    let path = focus_path(menu_bar_state, level);
+    let mut nodes = &menu[path[0]].children;
+    // The last index is the focused node of `level` itself
+    for index in &path[1..path.len() - 1] {
+        nodes = &nodes[*index].children;
+    }
+    nodes
+}
+
+/// Focus the enabled tab or item with `hotkey`, returning whether there is one
+///
+/// An accelerator (Alt+letter) opens a tab from anywhere, a plain hotkey
+/// picks from the tabs or the items of the menu which has the focus.
+fn focus_hotkey(
+    focus_on: &mut focus::FocusOn,
+    menu_bar_state: &Rc<RefCell<MenuBarUiState>>,
+    menu: &[MenuNode],
+    hotkey: char,
+    is_accelerator: bool,
+) -> bool {
+    let mut menu_bar_state = menu_bar_state.borrow_mut();
+    if let focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) = *focus_on {
+        if !is_accelerator {
+            let (items, _) = level_items(&menu_bar_state, level);
+            let nodes = level_nodes(menu, &menu_bar_state, level);
+            let Some(index) = nodes.iter().zip(items.iter()).position(|(node, item)| {
+                matches_hotkey(node.hotkey, hotkey) && !item.borrow().is_disabled
+            }) else {
+                return false;
+            };
+            focus_item(&menu_bar_state, level, &items, index);
+            return true;
+        }
+    }
+
+    let Some(tab) = menu
+        .iter()
+        .zip(menu_bar_state.tab_state.iter())
+        .position(|(node, tab)| matches_hotkey(node.hotkey, hotkey) && !tab.borrow().is_disabled)
+    else {
+        return false;
+    };
+    // Start over from the tab, whatever menu is open
+    if let focus::FocusOn::MenuBar(_) = focus_on {
+        quit_menu(focus_on, &mut menu_bar_state);
+    }
+    *focus_on = focus::FocusOn::MenuBar(focus::Menu::Tab);
+    menu_bar_state.focus_on = tab;
+    menu_bar_state.tab_state[tab].borrow_mut().is_focused = true;
+    true
+}
+
+/// What of the menu bar is under the mouse
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuHit {
+    Tab(usize),
+    /// Item `index` of the open menu `level`
+    Item(u16, usize),
+}
+
+/// Whether `position` is on the menu bar or one of its open drop downs
+pub fn is_hit(
+    focus_on: focus::FocusOn,
+    menu_bar_state: &Rc<RefCell<MenuBarUiState>>,
+    position: Position,
+) -> bool {
+    hit_test(focus_on, &menu_bar_state.borrow(), position).is_some()
+}
+
+/// Find the tab or visible item drawn at `position`
+fn hit_test(
+    focus_on: focus::FocusOn,
+    menu_bar_state: &MenuBarUiState,
+    position: Position,
+) -> Option<MenuHit> {
+    // Deeper drop downs are drawn on top
+    if let focus::FocusOn::MenuBar(focus::Menu::TabItem(open_level)) = focus_on {
+        for level in (0..=open_level).rev() {
+            let (items, _) = level_items(menu_bar_state, level);
+            if let Some(index) = items
+                .iter()
+                .position(|item| item.borrow().area.contains(position))
+            {
+                return Some(MenuHit::Item(level, index));
+            }
+        }
+    }
+    menu_bar_state
+        .tab_state
+        .iter()
+        .position(|tab| tab.borrow().area.contains(position))
+        .map(MenuHit::Tab)
+}
+
+/// Open the tab or choose the item which was clicked, anywhere else closes the menu
+fn click(
+    focus_on: &mut focus::FocusOn,
+    menu_bar_state: Rc<RefCell<MenuBarUiState>>,
+    menu: &[MenuNode],
+    position: Position,
+) -> Option<Action> {
+    let hit = hit_test(*focus_on, &menu_bar_state.borrow(), position);
+    match hit {
+        Some(MenuHit::Tab(tab)) => {
+            let mut state = menu_bar_state.borrow_mut();
+            let is_open = matches!(focus_on, focus::FocusOn::MenuBar(focus::Menu::TabItem(_)))
+                && state.focus_on == tab;
+            if let focus::FocusOn::MenuBar(_) = focus_on {
+                quit_menu(focus_on, &mut state);
+            }
+            // A second click on the open tab closes it
+            if is_open || state.tab_state[tab].borrow().is_disabled {
+                return None;
+            }
+            *focus_on = focus::FocusOn::MenuBar(focus::Menu::Tab);
+            state.focus_on = tab;
+            state.tab_state[tab].borrow_mut().is_focused = true;
+        }
+        Some(MenuHit::Item(level, index)) => {
+            // Close the sub menus below the clicked one
+            while let focus::FocusOn::MenuBar(focus::Menu::TabItem(open_level)) = *focus_on {
+                if open_level == level {
+                    break;
+                }
+                handle_event(
+                    focus_on,
+                    menu_bar_state.clone(),
+                    menu,
+                    key_board_handler::EventResult::PrevMenuLevel,
+                );
+            }
+            let state = menu_bar_state.borrow();
+            let (items, _) = level_items(&state, level);
+            if items[index].borrow().is_disabled {
+                return None;
+            }
+            focus_item(&state, level, &items, index);
+        }
+        None => {
+            if let focus::FocusOn::MenuBar(_) = focus_on {
+                quit_menu(focus_on, &mut menu_bar_state.borrow_mut());
+            }
+            return None;
+        }
+    }
+    handle_event(
+        focus_on,
+        menu_bar_state,
+        menu,
+        key_board_handler::EventResult::MenuChoose,
+    )
+}
+
+/// The tab item whose sub menu is menu `level`, `None` for level 0 which belongs to the tab
+fn parent_item(
+    menu_bar_state: &MenuBarUiState,
+    level: u16,
+) -> Option<Rc<RefCell<MenuTabItemUiState>>> {
+    let tab = menu_bar_state.tab_state[menu_bar_state.focus_on].borrow();
+    let mut items = tab.sub_item_state.clone()?;
+    let mut focus = tab.focus_on;
+    let mut parent = None;
+    for _ in 0..level {
+        let item = items.get(focus)?.clone();
+        items = item.borrow().sub_item_state.clone()?;
+        focus = item.borrow().focus_on;
+        parent = Some(item);
+    }
+    parent
+}
+
+/// The tab items of menu `level` and the index of the focused one
+fn level_items(
+    menu_bar_state: &MenuBarUiState,
+    level: u16,
+) -> (Vec<Rc<RefCell<MenuTabItemUiState>>>, usize) {
+    match parent_item(menu_bar_state, level) {
+        Some(parent) => {
+            let parent = parent.borrow();
+            (
+                parent.sub_item_state.clone().unwrap_or_default(),
+                parent.focus_on,
+            )
+        }
+        None => {
+            let tab = menu_bar_state.tab_state[menu_bar_state.focus_on].borrow();
+            (tab.sub_item_state.clone().unwrap_or_default(), tab.focus_on)
+        }
+    }
+}
+
+/// Move the focus within menu `level` to `items[index]`
+fn focus_item(
+    menu_bar_state: &MenuBarUiState,
+    level: u16,
+    items: &[Rc<RefCell<MenuTabItemUiState>>],
+    index: usize,
+) {
+    for (i, item) in items.iter().enumerate() {
+        item.borrow_mut().is_focused = i == index;
+    }
+    match parent_item(menu_bar_state, level) {
+        Some(parent) => parent.borrow_mut().focus_on = index,
+        None => {
+            menu_bar_state.tab_state[menu_bar_state.focus_on]
+                .borrow_mut()
+                .focus_on = index
+        }
+    }
+}
+
+/// The first of `indices` whose item in `items` is enabled
+fn find_enabled(
+    items: &[Rc<RefCell<MenuTabItemUiState>>],
+    mut indices: impl Iterator<Item = usize>,
+) -> Option<usize> {
+    indices.find(|index| !items[*index].borrow().is_disabled)
+}
+
+/// Select `item` and focus the first enabled entry of its sub menu
+fn open_sub_menu(item: &Rc<RefCell<MenuTabItemUiState>>) {
+    let mut item = item.borrow_mut();
+    item.is_selected = true;
+    let sub_items = item.sub_item_state.clone().unwrap_or_default();
+    let first = find_enabled(&sub_items, 0..sub_items.len()).unwrap_or(0);
+    item.focus_on = first;
+    for (i, sub_item) in sub_items.iter().enumerate() {
+        sub_item.borrow_mut().is_focused = i == first;
+    }
+}
+
+/// Indices of the focused tab and tab items down to menu `level`
+fn focus_path(menu_bar_state: &MenuBarUiState, level: u16) -> Vec<usize> {
+    let mut path = vec![menu_bar_state.focus_on];
+    for level in 0..=level {
+        path.push(level_items(menu_bar_state, level).1);
+    }
+    path
+}
+
+/// Close all open drop downs and move the focus back to where it was before the menu
+fn quit_menu(focus_on: &mut focus::FocusOn, menu_bar_state: &mut MenuBarUiState) {
+    match focus_on {
+        focus::FocusOn::MenuBar(focus::Menu::Tab) => {
+            let current_tab = menu_bar_state.focus_on;
+            menu_bar_state.tab_state[current_tab]
+                .try_borrow_mut()
+                .unwrap()
+                .is_focused = false;
+        }
+        focus::FocusOn::MenuBar(focus::Menu::TabItem(_)) => {
+            let mut stack = Vec::new();
+
+            // push in the first focused tab item
+            {
+                let current_tab_id = menu_bar_state.focus_on;
+                let current_tab = menu_bar_state.tab_state[current_tab_id].clone();
+
+                let current_item_id = current_tab.borrow().focus_on;
+                let current_item = current_tab
+                    .try_borrow()
+                    .unwrap()
+                    .sub_item_state
+                    .as_ref()
+                    .unwrap()[current_item_id]
+                    .clone();
+
+                stack.push(current_item);
+            }
+
+            // push in the leftover focused tab items
+            let mut item_point = stack.last().unwrap().clone();
+            while item_point.borrow().is_focused && item_point.borrow().sub_item_state.is_some() {
+                let current_item_id = item_point.borrow().focus_on;
+                let current_item = item_point
+                    .try_borrow()
+                    .unwrap()
+                    .sub_item_state
+                    .as_ref()
+                    .unwrap()[current_item_id]
+                    .clone();
+                item_point = current_item.clone();
+                stack.push(current_item);
+            }
+
+            // De-Select the focused tab items
+            while let Some(current_item_ptr) = stack.pop() {
+                let mut current_item = current_item_ptr.borrow_mut();
+                current_item.is_selected = false;
+                current_item.is_focused = false;
+            }
+
+            // De-Select the focused tab
+            let current_tab_id = menu_bar_state.focus_on;
+            let mut current_tab = menu_bar_state.tab_state[current_tab_id]
+                .try_borrow_mut()
+                .unwrap();
+            current_tab.is_selected = false;
+            current_tab.is_focused = false;
+        }
+        _ => unreachable!(),
+    }
+    *focus_on = menu_bar_state.return_focus;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use key_board_handler::EventResult;
+
+    fn menu() -> Vec<MenuNode> {
+        vec![MenuNode::menu(
+            "File",
+            Some("F"),
+            vec![
+                MenuNode::item("New KeyPair", Some("N"), Action::NewKeyPair),
+                MenuNode::menu(
+                    "Export",
+                    Some("E"),
+                    vec![
+                        MenuNode::item("Public Key", Some("P"), Action::ExportPublic),
+                        MenuNode::item("Secret Key", Some("S"), Action::ExportSecret),
+                        MenuNode::item("Quit", Some("Q"), Action::Quit),
+                    ],
+                ),
+                MenuNode::item("Delete Key", Some("D"), Action::DeleteKey),
+                MenuNode::item("Import", Some("I"), Action::Import),
+            ],
+        )]
+    }
+
+    /// Item `path` of the first tab
+    fn item(
+        state: &Rc<RefCell<MenuBarUiState>>,
+        path: &[usize],
+    ) -> Rc<RefCell<MenuTabItemUiState>> {
+        let state = state.borrow();
+        let mut items = state.tab_state[0].borrow().sub_item_state.clone().unwrap();
+        let mut item = items[path[0]].clone();
+        for index in &path[1..] {
+            items = item.borrow().sub_item_state.clone().unwrap();
+            item = items[*index].clone();
+        }
+        item
+    }
+
+    struct Menu {
+        nodes: Vec<MenuNode>,
+        state: Rc<RefCell<MenuBarUiState>>,
+        focus_on: focus::FocusOn,
+    }
+
+    impl Menu {
+        fn open() -> Self {
+            let nodes = menu();
+            let state = init_menu_bar_state(&nodes);
+            let mut menu = Self {
+                nodes,
+                state,
+                focus_on: focus::FocusOn::MainPanel,
+            };
+            menu.send(EventResult::EnterMenuBar);
+            menu
+        }
+
+        fn send(&mut self, event: EventResult) -> Option<Action> {
+            handle_event(&mut self.focus_on, self.state.clone(), &self.nodes, event)
+        }
+
+        fn path(&self) -> Vec<usize> {
+            match self.focus_on {
+                focus::FocusOn::MenuBar(focus::Menu::TabItem(level)) => {
+                    focus_path(&self.state.borrow(), level)
+                }
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn descend_into_a_sub_menu_and_back() {
+        let mut menu = Menu::open();
+        menu.send(EventResult::MenuChoose);
+        assert_eq!(menu.path(), [0, 0]);
+        menu.send(EventResult::MenuNextItem);
+        // Right opens the sub menu on its first item
+        menu.send(EventResult::NextMenuLevel);
+        assert!(menu.focus_on == focus::FocusOn::MenuBar(focus::Menu::TabItem(1)));
+        assert_eq!(menu.path(), [0, 1, 0]);
+        assert!(item(&menu.state, &[1]).borrow().is_selected);
+        menu.send(EventResult::MenuNextItem);
+        assert_eq!(menu.path(), [0, 1, 1]);
+
+        // Left closes it and keeps the focus on its parent
+        menu.send(EventResult::PrevMenuLevel);
+        assert!(menu.focus_on == focus::FocusOn::MenuBar(focus::Menu::TabItem(0)));
+        assert_eq!(menu.path(), [0, 1]);
+        assert!(!item(&menu.state, &[1]).borrow().is_selected);
+        assert!(!item(&menu.state, &[1, 1]).borrow().is_focused);
+        menu.send(EventResult::PrevMenuLevel);
+        assert!(menu.focus_on == focus::FocusOn::MenuBar(focus::Menu::Tab));
+
+        // Enter on the sub menu opens it too, Enter on an item chooses it
+        menu.send(EventResult::MenuChoose);
+        menu.send(EventResult::MenuNextItem);
+        menu.send(EventResult::MenuChoose);
+        menu.send(EventResult::MenuNextItem);
+        assert_eq!(
+            menu.send(EventResult::MenuChoose),
+            Some(Action::ExportSecret)
+        );
+        assert!(menu.focus_on == focus::FocusOn::MainPanel);
+    }
+
+    #[test]
+    fn skip_disabled_items() {
+        let mut menu = Menu::open();
+        for path in [&[0][..], &[2], &[1, 0]] {
+            item(&menu.state, path).borrow_mut().is_disabled = true;
+        }
+        // The menu opens on the first enabled item
+        menu.send(EventResult::MenuChoose);
+        assert_eq!(menu.path(), [0, 1]);
+        menu.send(EventResult::MenuNextItem);
+        assert_eq!(menu.path(), [0, 3]);
+        menu.send(EventResult::MenuNextItem);
+        assert_eq!(menu.path(), [0, 3]);
+        menu.send(EventResult::MenuPrevItem);
+        assert_eq!(menu.path(), [0, 1]);
+        // There is nothing enabled above
+        menu.send(EventResult::MenuPrevItem);
+        assert_eq!(menu.path(), [0, 1]);
+
+        menu.send(EventResult::NextMenuLevel);
+        assert_eq!(menu.path(), [0, 1, 1]);
+        menu.send(EventResult::MenuPrevItem);
+        assert_eq!(menu.path(), [0, 1, 1]);
+
+        // A disabled sub menu does not open, a disabled item is not chosen
+        menu.send(EventResult::PrevMenuLevel);
+        item(&menu.state, &[1]).borrow_mut().is_disabled = true;
+        menu.send(EventResult::NextMenuLevel);
+        assert!(menu.focus_on == focus::FocusOn::MenuBar(focus::Menu::TabItem(0)));
+        assert_eq!(menu.send(EventResult::MenuChoose), None);
+        assert!(menu.focus_on == focus::FocusOn::MenuBar(focus::Menu::TabItem(0)));
+    }
+
+    #[test]
+    fn leave_to_the_focus_before_the_menu() {
+        let nodes = menu();
+        let mut menu = Menu {
+            state: init_menu_bar_state(&nodes),
+            nodes,
+            focus_on: focus::FocusOn::KeyDetail,
+        };
+        menu.send(EventResult::MenuAccelerator('f'));
+        assert_eq!(menu.path(), [0, 0]);
+        menu.send(EventResult::QuitMenu);
+        assert!(menu.focus_on == focus::FocusOn::KeyDetail);
+
+        // Also after choosing an item, which then acts on the detailed key
+        menu.send(EventResult::EnterMenuBar);
+        menu.send(EventResult::MenuChoose);
+        menu.send(EventResult::MenuNextItem);
+        menu.send(EventResult::MenuNextItem);
+        assert_eq!(menu.send(EventResult::MenuChoose), Some(Action::DeleteKey));
+        assert!(menu.focus_on == focus::FocusOn::KeyDetail);
+
+        menu.focus_on = focus::FocusOn::MainPanel;
+        menu.send(EventResult::EnterMenuBar);
+        menu.send(EventResult::QuitMenu);
+        assert!(menu.focus_on == focus::FocusOn::MainPanel);
+    }
 }
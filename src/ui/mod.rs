@@ -1,13 +1,34 @@
+pub mod dialog;
 pub mod focus;
+pub mod key_detail;
+pub mod key_list;
 pub mod menu_bar;
 pub mod widget;
 
 use std::{cell::RefCell, rc::Rc};
 
+use ratatui::layout::Rect;
+
+use key_detail::KeyDetailUiState;
+use key_list::KeyListUiState;
 use widget::menu_bar::MenuBarUiState;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct UiState {
     pub focus_on: focus::FocusOn,
     pub menu_bar_state: Rc<RefCell<MenuBarUiState>>,
+    pub key_list_state: Rc<RefCell<KeyListUiState>>,
+    pub key_detail_state: Rc<RefCell<KeyDetailUiState>>,
+    /// Where the buttons of the open dialog were drawn, to click them
+    pub dialog_buttons: Rc<RefCell<Vec<Rect>>>,
+    /// `strftime` format of dates in lists and details
+    pub date_format: String,
+    /// Keys expiring within this many days are highlighted, 0 turns it off
+    pub expiry_warning_days: u32,
+}
+
+/// Width of a date in `format`, to size the date columns
+pub fn date_width(format: &str) -> usize {
+    let sample = chrono::DateTime::from_timestamp(978_307_199, 0).unwrap_or_default();
+    sample.format(format).to_string().chars().count()
 }
@@ -0,0 +1,167 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// TextInput UI State
+///
+/// A single line of editable text with a cursor
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct TextInputUiState {
+    pub value: String,
+    /// Cursor position in characters
+    pub cursor: usize,
+    /// Render every character as `*`, for passphrases
+    pub is_masked: bool,
+}
+
+impl TextInputUiState {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            cursor: value.chars().count(),
+            is_masked: false,
+        }
+    }
+
+    pub fn masked() -> Self {
+        Self {
+            is_masked: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = value.chars().count();
+    }
+
+    /// Insert text at the cursor, e.g. from a bracketed paste
+    pub fn insert_str(&mut self, text: &str) {
+        for ch in text.chars().filter(|ch| !ch.is_control()) {
+            self.insert(ch);
+        }
+    }
+
+    fn insert(&mut self, ch: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, ch);
+        self.cursor += 1;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(index, _)| index)
+            .unwrap_or(self.value.len())
+    }
+
+    /// Apply an editing key, returning whether it was consumed
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let len = self.value.chars().count();
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.insert(ch),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let index = self.byte_index(self.cursor);
+                self.value.remove(index);
+            }
+            KeyCode::Delete if self.cursor < len => {
+                let index = self.byte_index(self.cursor);
+                self.value.remove(index);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            KeyCode::Backspace | KeyCode::Delete => (),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// TextInput widget
+pub struct TextInput<'a> {
+    label: &'a str,
+    /// Width reserved for the label, so the inputs of a form line up
+    label_width: u16,
+    state: &'a TextInputUiState,
+    is_focused: bool,
+    label_style: Style,
+    style: Style,
+    focused_style: Style,
+}
+
+impl<'a> TextInput<'a> {
+    pub fn new(
+        label: &'a str,
+        label_width: u16,
+        state: &'a TextInputUiState,
+        is_focused: bool,
+        label_style: Style,
+        style: Style,
+        focused_style: Style,
+    ) -> Self {
+        Self {
+            label,
+            label_width,
+            state,
+            is_focused,
+            label_style,
+            style,
+            focused_style,
+        }
+    }
+}
+
+impl Widget for &TextInput<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let style = if self.is_focused {
+            self.focused_style
+        } else {
+            self.style
+        };
+        let width = area.width.saturating_sub(self.label_width) as usize;
+        let text: Vec<char> = if self.state.is_masked {
+            vec!['*'; self.state.value.chars().count()]
+        } else {
+            self.state.value.chars().collect()
+        };
+        // Scroll horizontally so the cursor stays visible
+        let start = (self.state.cursor + 1).saturating_sub(width);
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", self.label, width = self.label_width as usize),
+            self.label_style,
+        )];
+        let mut value: String = text.iter().skip(start).take(width).collect();
+        if self.is_focused {
+            let cursor = self.state.cursor - start;
+            let before: String = value.chars().take(cursor).collect();
+            let at: String = value.chars().nth(cursor).unwrap_or(' ').to_string();
+            let after: String = value.chars().skip(cursor + 1).collect();
+            spans.push(Span::styled(before, style));
+            spans.push(Span::styled(at, style.add_modifier(Modifier::REVERSED)));
+            value = after;
+        }
+        let used: usize = spans
+            .iter()
+            .skip(1)
+            .map(|span| span.content.chars().count())
+            .sum();
+        spans.push(Span::styled(
+            format!("{value:<width$}", width = width.saturating_sub(used)),
+            style,
+        ));
+        Line::from(spans).render(area, buf);
+    }
+}
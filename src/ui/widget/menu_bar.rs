@@ -3,11 +3,13 @@ use std::{cell::RefCell, rc::Rc};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::Style,
     text::Span,
-    widgets::{Block, Borders, Widget},
+    widgets::{Block, Borders, Clear, Widget},
 };
 
+use crate::{theme::theme, ui::focus};
+
 /// Style for the MenuBar
 pub struct MenuBarStyle {
     title_style: Style,
@@ -17,19 +19,21 @@ pub struct MenuBarStyle {
     selected_style: Style,
 }
 
-impl MenuBarStyle {
-    pub fn default() -> Self {
-        Self {
-            title_style: Style::default()
-                .bg(Color::Black)
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-            default_style: Style::default().bg(Color::Black).fg(Color::Gray),
-            disabled_style: Style::default().bg(Color::Black).fg(Color::DarkGray),
-            focused_style: Style::default().bg(Color::Gray).fg(Color::DarkGray),
-            selected_style: Style::default().bg(Color::DarkGray).fg(Color::Gray),
-        }
+/// The menu bar styles of the current theme
+impl Default for MenuBarStyle {
+    fn default() -> Self {
+        let menu_bar = theme().menu_bar;
+        Self::new(
+            menu_bar.title_style,
+            menu_bar.default_style,
+            menu_bar.disabled_style,
+            menu_bar.focused_style,
+            menu_bar.selected_style,
+        )
     }
+}
+
+impl MenuBarStyle {
     pub fn new(
         title_style: Style,
         default_style: Style,
@@ -53,7 +57,6 @@ pub struct MenuBar<'a> {
     title: Option<String>,
     /// List of MenuTabs
     menu_tabs: Vec<MenuTab<'a>>,
-    state: Rc<RefCell<MenuBarUiState>>,
     menu_bar_style: &'a MenuBarStyle,
 }
 
@@ -82,6 +85,8 @@ pub struct MenuTabItem<'a> {
 pub struct MenuBarUiState {
     pub focus_on: usize,
     pub tab_state: Vec<Rc<RefCell<MenuTabUiState>>>,
+    /// Where the focus was before the menu was entered, and goes back to on leaving it
+    pub return_focus: focus::FocusOn,
 }
 
 /// MenuTab UI State
@@ -94,6 +99,8 @@ pub struct MenuTabUiState {
     pub is_disabled: bool,
     pub is_selected: bool,
     pub sub_item_state: Option<Vec<Rc<RefCell<MenuTabItemUiState>>>>,
+    /// Where it was drawn last, to find it under the mouse
+    pub area: Rect,
 }
 
 /// MenuTabItem UI State
@@ -106,23 +113,23 @@ pub struct MenuTabItemUiState {
     pub is_disabled: bool,
     pub is_selected: bool,
     pub sub_item_state: Option<Vec<Rc<RefCell<MenuTabItemUiState>>>>,
+    /// Where it was drawn last, to find it under the mouse
+    pub area: Rect,
 }
 
 impl<'a> MenuBar<'a> {
     pub fn new(
         title: Option<String>,
         menu_tabs: Vec<MenuTab<'a>>,
-        state: Rc<RefCell<MenuBarUiState>>,
         menu_bar_style: &'a MenuBarStyle,
     ) -> Self {
         assert!(
-            menu_tabs.len() > 0,
+            !menu_tabs.is_empty(),
             "Menu bar must have at least one menu tab"
         );
         Self {
             title,
             menu_tabs,
-            state,
             menu_bar_style,
         }
     }
@@ -140,9 +147,8 @@ impl Widget for &MenuBar<'_> {
             ));
         }
 
-        for (_i, menu_tab) in self.menu_tabs.iter().enumerate() {
-            let len: u16 = if menu_tab.hotkey.is_some() {
-                let hotkey = menu_tab.hotkey.unwrap();
+        for menu_tab in self.menu_tabs.iter() {
+            let len: u16 = if let Some(hotkey) = menu_tab.hotkey {
                 (menu_tab.label.len() + hotkey.len()) as u16 + 5
             } else {
                 menu_tab.label.len() as u16 + 2
@@ -203,68 +209,86 @@ impl Widget for &MenuTab<'_> {
         };
         let label = format!(" {label}{hotkey} ", label = self.label, hotkey = hotkey);
         //  Define the style
-        let style: Style;
-        if self.state.borrow().is_disabled {
-            style = self.menu_bar_style.disabled_style;
+        let style = if self.state.borrow().is_disabled {
+            self.menu_bar_style.disabled_style
         } else if self.state.borrow().is_selected {
-            style = self.menu_bar_style.selected_style;
+            self.menu_bar_style.selected_style
         } else if self.state.borrow().is_focused {
-            style = self.menu_bar_style.focused_style;
+            self.menu_bar_style.focused_style
         } else {
-            style = self.menu_bar_style.default_style;
-        }
+            self.menu_bar_style.default_style
+        };
         Span::styled(label, style).render(area, buf);
+        self.state.borrow_mut().area = area;
 
         // If there are menu tab items and the menu tab is selected
         // then render the menu tab items
-        if self.menu_tab_items.is_some() && self.state.borrow().is_selected {
-            // Calculate the render area
-            let mut width = 0u16;
-            self.menu_tab_items
-                .as_ref()
-                .unwrap()
-                .iter()
-                .for_each(|menu_tab_item| {
-                    let len = if menu_tab_item.hotkey.is_some() {
-                        let hotkey = menu_tab_item.hotkey.unwrap();
-                        (menu_tab_item.label.len() + hotkey.len()) as u16 + 5
-                    } else {
-                        menu_tab_item.label.len() as u16 + 2
-                    };
-                    if len > width {
-                        width = len;
-                    }
-                });
-            width += 2;
-            let area = Rect {
-                x: area.x,
-                y: area.y + 1,
-                width,
-                height: self.menu_tab_items.as_ref().unwrap().len() as u16,
-            };
-
-            // Calculate the layout for the menu tab items
-            let mut constraints = vec![];
+        if let Some(menu_tab_items) = self
+            .menu_tab_items
+            .as_ref()
+            .filter(|_| self.state.borrow().is_selected)
+        {
+            // Drop down below the tab, aligned to its right end if there is no room
+            render_drop_down(menu_tab_items, area.x, area.y + 1, area.right(), buf);
+        }
+    }
+}
 
-            for (_i, menu_tab_item) in self.menu_tab_items.as_ref().unwrap().iter().enumerate() {
-                let len: u16;
-                if menu_tab_item.hotkey.is_some() {
-                    let hotkey = menu_tab_item.hotkey.unwrap();
-                    len = (menu_tab_item.label.len() + hotkey.len()) as u16 + 4;
-                } else {
-                    len = menu_tab_item.label.len() as u16 + 2;
-                }
-                constraints.push(Constraint::Length(len));
+/// Width of a drop down listing `items`
+fn drop_down_width(items: &[MenuTabItem]) -> u16 {
+    let width = items
+        .iter()
+        .map(|item| {
+            let len = if let Some(hotkey) = item.hotkey {
+                (item.label.len() + hotkey.len()) as u16 + 5
+            } else {
+                item.label.len() as u16 + 2
+            };
+            // Room for the arrow of a sub menu
+            if item.sub_menu_tab_items.is_some() {
+                len + 2
+            } else {
+                len
             }
-            constraints.push(Constraint::Min(0));
+        })
+        .max()
+        .unwrap_or(0);
+    width + 2
+}
 
-            let layout = Layout::new(Direction::Vertical, constraints).split(area);
+/// Render `items` as a drop down with its top left corner at `(x, y)`
+///
+/// A drop down which does not fit right of `x` ends at `flip_x` instead, and
+/// one which does not fit below `y` is moved up, so it always stays on screen.
+fn render_drop_down(items: &[MenuTabItem], x: u16, y: u16, flip_x: u16, buf: &mut Buffer) {
+    let bounds = buf.area;
+    let width = drop_down_width(items).min(bounds.width);
+    let height = (items.len() as u16).min(bounds.height);
+    let x = if x.saturating_add(width) <= bounds.right() {
+        x
+    } else {
+        flip_x
+            .checked_sub(width)
+            .filter(|x| *x >= bounds.x)
+            .unwrap_or(bounds.right() - width)
+    };
+    let y = y.min(bounds.bottom() - height);
+    let area = Rect {
+        x,
+        y,
+        width,
+        height,
+    };
 
-            // Render the menu tab items
-            for (i, menu_tab_item) in self.menu_tab_items.as_ref().unwrap().iter().enumerate() {
-                menu_tab_item.render(layout[i], buf);
-            }
-        }
+    // Hide what is below, the items do not cover the whole width
+    Clear.render(area, buf);
+    let rows = Layout::new(
+        Direction::Vertical,
+        vec![Constraint::Length(1); items.len()],
+    )
+    .split(area);
+    for (menu_tab_item, row) in items.iter().zip(rows.iter()) {
+        menu_tab_item.render(*row, buf);
     }
 }
 
@@ -297,16 +321,15 @@ impl Widget for &MenuTabItem<'_> {
         };
         let label = format!(" {label}{hotkey} ", label = self.label, hotkey = hotkey);
         // Define the style
-        let style: Style;
-        if self.state.borrow().is_disabled {
-            style = self.menu_bar_style.disabled_style;
+        let style = if self.state.borrow().is_disabled {
+            self.menu_bar_style.disabled_style
         } else if self.state.borrow().is_selected {
-            style = self.menu_bar_style.selected_style;
+            self.menu_bar_style.selected_style
         } else if self.state.borrow().is_focused {
-            style = self.menu_bar_style.focused_style;
+            self.menu_bar_style.focused_style
         } else {
-            style = self.menu_bar_style.default_style;
-        }
+            self.menu_bar_style.default_style
+        };
 
         // Calculate the layout
         let constraints = if self.sub_menu_tab_items.is_none() {
@@ -315,18 +338,24 @@ impl Widget for &MenuTabItem<'_> {
             vec![
                 Constraint::Length(label.len() as u16),
                 Constraint::Min(0),
-                Constraint::Length(1),
+                Constraint::Length(2),
             ]
         };
         let layout = Layout::new(Direction::Horizontal, constraints).split(area);
+        self.state.borrow_mut().area = area;
 
         Span::styled(label, style).render(layout[0], buf);
         Block::default()
             .borders(Borders::NONE)
             .style(style)
             .render(layout[1], buf);
-        // [ ] if there are sub items, add a right arrow
 
-        // [ ] if there are sub items and the menu tab item is selected, render the sub items
+        if let Some(sub_menu_tab_items) = &self.sub_menu_tab_items {
+            Span::styled("▸ ", style).render(layout[2], buf);
+            // An open sub menu pops out to the right, or to the left at the edge
+            if self.state.borrow().is_selected {
+                render_drop_down(sub_menu_tab_items, area.right(), area.y, area.x, buf);
+            }
+        }
     }
 }
@@ -1 +1,4 @@
+pub mod input;
 pub mod menu_bar;
+pub mod popup;
+pub mod text_area;
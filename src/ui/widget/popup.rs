@@ -0,0 +1,123 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Margin, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Widget},
+};
+
+/// Popup widget
+///
+/// A bordered box which clears whatever was rendered below it
+pub struct Popup<'a> {
+    title: &'a str,
+    style: Style,
+    border_style: Style,
+}
+
+impl<'a> Popup<'a> {
+    pub fn new(title: &'a str, style: Style, border_style: Style) -> Self {
+        Self {
+            title,
+            style,
+            border_style,
+        }
+    }
+
+    /// A `width` x `height` area in the center of `area`
+    pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+        let width = width.min(area.width);
+        let height = height.min(area.height);
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// The area inside the borders, with one column of padding
+    pub fn inner(area: Rect) -> Rect {
+        area.inner(&Margin {
+            horizontal: 2,
+            vertical: 1,
+        })
+    }
+}
+
+impl Widget for &Popup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.border_style)
+            .title(format!(" {} ", self.title))
+            .style(self.style)
+            .render(area, buf);
+    }
+}
+
+/// ButtonBar widget
+///
+/// A row of buttons like `[ OK ]  [ Cancel ]`, aligned to the right
+pub struct ButtonBar<'a> {
+    labels: &'a [&'a str],
+    /// Index of the focused button, if any
+    focused: Option<usize>,
+    style: Style,
+    focused_style: Style,
+}
+
+impl<'a> ButtonBar<'a> {
+    pub fn new(
+        labels: &'a [&'a str],
+        focused: Option<usize>,
+        style: Style,
+        focused_style: Style,
+    ) -> Self {
+        Self {
+            labels,
+            focused,
+            style,
+            focused_style,
+        }
+    }
+
+    /// The area every button occupies when rendered into `area`
+    pub fn button_areas(&self, area: Rect) -> Vec<Rect> {
+        let widths: Vec<u16> = self
+            .labels
+            .iter()
+            .map(|label| label.chars().count() as u16 + 4)
+            .collect();
+        let total: u16 = widths.iter().sum::<u16>() + 2 * widths.len().saturating_sub(1) as u16;
+        let mut x = area.x + area.width.saturating_sub(total);
+        widths
+            .iter()
+            .map(|width| {
+                let button = Rect {
+                    x,
+                    y: area.y,
+                    width: *width,
+                    height: 1,
+                }
+                .intersection(area);
+                x += width + 2;
+                button
+            })
+            .collect()
+    }
+}
+
+impl Widget for &ButtonBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (i, (label, button)) in self.labels.iter().zip(self.button_areas(area)).enumerate() {
+            let style = if self.focused == Some(i) {
+                self.focused_style
+            } else {
+                self.style
+            };
+            Line::from(Span::styled(format!("[ {label} ]"), style)).render(button, buf);
+        }
+    }
+}
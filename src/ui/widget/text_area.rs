@@ -0,0 +1,200 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// TextArea UI State
+///
+/// Several lines of editable text, e.g. a pasted ASCII armored key
+#[derive(Clone, PartialEq, Eq)]
+pub struct TextAreaUiState {
+    pub lines: Vec<String>,
+    /// Cursor line
+    pub row: usize,
+    /// Cursor position in characters
+    pub col: usize,
+}
+
+impl Default for TextAreaUiState {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+        }
+    }
+}
+
+impl TextAreaUiState {
+    /// The whole text, lines joined by `\n`
+    pub fn value(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.trim().is_empty())
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        *self = Self::default();
+        self.insert_str(value);
+    }
+
+    /// Insert text at the cursor, e.g. from a bracketed paste
+    ///
+    /// Terminals often send pasted line breaks as `\r`
+    pub fn insert_str(&mut self, text: &str) {
+        for ch in text.replace("\r\n", "\n").chars() {
+            match ch {
+                '\n' | '\r' => self.new_line(),
+                ch if ch.is_control() => (),
+                ch => self.insert(ch),
+            }
+        }
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self, col: usize) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(col)
+            .map(|(index, _)| index)
+            .unwrap_or(line.len())
+    }
+
+    fn insert(&mut self, ch: char) {
+        let index = self.byte_index(self.col);
+        self.lines[self.row].insert(index, ch);
+        self.col += 1;
+    }
+
+    fn new_line(&mut self) {
+        let index = self.byte_index(self.col);
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    /// Apply an editing key, returning whether it was consumed
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                *self = Self::default();
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => self.insert(ch),
+            KeyCode::Enter => self.new_line(),
+            KeyCode::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let index = self.byte_index(self.col);
+                self.lines[self.row].remove(index);
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len();
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.col < self.line_len() => {
+                let index = self.byte_index(self.col);
+                self.lines[self.row].remove(index);
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let line = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Left if self.col > 0 => self.col -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len();
+            }
+            KeyCode::Right if self.col < self.line_len() => self.col += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            KeyCode::Backspace | KeyCode::Delete | KeyCode::Left | KeyCode::Right => (),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// TextArea widget
+pub struct TextArea<'a> {
+    state: &'a TextAreaUiState,
+    is_focused: bool,
+    style: Style,
+    focused_style: Style,
+}
+
+impl<'a> TextArea<'a> {
+    pub fn new(
+        state: &'a TextAreaUiState,
+        is_focused: bool,
+        style: Style,
+        focused_style: Style,
+    ) -> Self {
+        Self {
+            state,
+            is_focused,
+            style,
+            focused_style,
+        }
+    }
+}
+
+impl Widget for &TextArea<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let style = if self.is_focused {
+            self.focused_style
+        } else {
+            self.style
+        };
+        buf.set_style(area, style);
+        let width = area.width as usize;
+        let height = area.height as usize;
+        // Scroll so the cursor stays visible
+        let top = (self.state.row + 1).saturating_sub(height);
+        let left = (self.state.col + 1).saturating_sub(width);
+        for (i, line) in self.state.lines.iter().skip(top).take(height).enumerate() {
+            let row = Rect {
+                y: area.y + i as u16,
+                height: 1,
+                ..area
+            };
+            let chars: Vec<char> = line.chars().skip(left).take(width).collect();
+            if self.is_focused && top + i == self.state.row {
+                let cursor = self.state.col - left;
+                let before: String = chars.iter().take(cursor).collect();
+                let at = chars.get(cursor).copied().unwrap_or(' ').to_string();
+                let after: String = chars.iter().skip(cursor + 1).collect();
+                Line::from(vec![
+                    Span::styled(before, style),
+                    Span::styled(at, style.add_modifier(Modifier::REVERSED)),
+                    Span::styled(after, style),
+                ])
+                .render(row, buf);
+            } else {
+                Span::styled(chars.into_iter().collect::<String>(), style).render(row, buf);
+            }
+        }
+    }
+}
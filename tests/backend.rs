@@ -0,0 +1,250 @@
+mod common;
+
+use gpg4terminal::backend::{
+    colons,
+    generate::{normalize_expiry, KeyAlgorithm, KeyParams},
+    import::parse_import,
+    key::{Algorithm, SignatureCheck, Validity},
+    status::parse_status,
+    Gpg,
+};
+
+use common::TestHome;
+
+const LIST_KEYS: &str = include_str!("fixtures/list-keys.colons");
+const LIST_SECRET_KEYS: &str = include_str!("fixtures/list-secret-keys.colons");
+
+#[test]
+fn parse_public_listing() {
+    let keys = colons::parse_keys(LIST_KEYS);
+    assert_eq!(keys.len(), 3);
+
+    let alice = &keys[0];
+    assert_eq!(
+        alice.fingerprint(),
+        "4C2A739A8CA6AAF49B42D07943079B3772E1A1AB"
+    );
+    assert_eq!(alice.primary.key_id, "43079B3772E1A1AB");
+    assert_eq!(alice.primary.validity, Validity::Ultimate);
+    assert_eq!(alice.primary.algorithm, Algorithm::EdDsa);
+    assert_eq!(alice.primary.algorithm_name(), "ed25519");
+    assert_eq!(alice.primary.capabilities.to_string(), "SC");
+    assert_eq!(alice.capabilities.to_string(), "SEAC");
+    assert_eq!(
+        alice.primary.keygrip.as_deref(),
+        Some("B34AE0CFEDA898AAED2FE8B35AE3B9FE7423B64D")
+    );
+    assert_eq!(alice.primary.created.unwrap().timestamp(), 1792204336);
+    assert_eq!(alice.primary.expires.unwrap().timestamp(), 1855276336);
+    assert!(!alice.has_secret());
+
+    assert_eq!(alice.user_ids.len(), 2);
+    let work = &alice.user_ids[1];
+    assert_eq!(work.name(), "Alice Example");
+    assert_eq!(work.comment(), Some("work"));
+    assert_eq!(work.email(), Some("alice@example.org"));
+
+    assert_eq!(alice.subkeys.len(), 2);
+    assert_eq!(alice.subkeys[0].algorithm_name(), "cv25519");
+    assert_eq!(alice.subkeys[0].capabilities.to_string(), "E");
+    assert_eq!(
+        alice.subkeys[0].fingerprint,
+        "89850D87F7CD1EC7D4C2B062C36B63554F14CB5D"
+    );
+    assert_eq!(alice.subkeys[1].algorithm_name(), "rsa3072");
+    assert_eq!(alice.subkeys[1].capabilities.to_string(), "A");
+    assert_eq!(alice.subkeys[1].expires, None);
+}
+
+#[test]
+fn parse_revoked_key_with_attribute() {
+    let keys = colons::parse_keys(LIST_KEYS);
+    let carol = &keys[2];
+    assert_eq!(carol.primary.validity, Validity::Revoked);
+    assert_eq!(carol.primary.algorithm_name(), "dsa2048");
+    assert_eq!(
+        carol.user_ids[0].uid,
+        "Carol Old : Legacy <carol@example.com>"
+    );
+    assert!(carol.user_ids[1].is_attribute);
+    assert_eq!(carol.subkeys[0].validity, Validity::Expired);
+    assert!(carol.subkeys[0].is_expired());
+}
+
+#[test]
+fn merge_secret_listing() {
+    let mut keys = colons::parse_keys(LIST_KEYS);
+    let secret = colons::parse_keys(LIST_SECRET_KEYS);
+    assert_eq!(secret.len(), 2);
+    assert!(secret.iter().all(|key| key.primary.has_secret));
+
+    colons::merge_secret(&mut keys, &secret);
+    assert!(keys[0].primary.has_secret);
+    assert!(keys[0].subkeys.iter().all(|subkey| subkey.has_secret));
+    assert!(keys[1].has_secret());
+    assert!(!keys[2].has_secret());
+}
+
+#[test]
+fn parse_dates() {
+    assert_eq!(colons::parse_date(""), None);
+    assert_eq!(colons::parse_date("0"), None);
+    assert_eq!(
+        colons::parse_date("20240102T030405").unwrap().timestamp(),
+        1704164645
+    );
+}
+
+#[test]
+fn list_keys_in_throwaway_home() {
+    let home = TestHome::new();
+    assert!(home.gpg.list_keys().unwrap().is_empty());
+
+    let fingerprint = home.generate("Test User <test@example.org>");
+    home.gpg(&["--import", "tests/fixtures/public.asc"]);
+
+    let keys = home.gpg.list_keys().unwrap();
+    assert_eq!(keys.len(), 2);
+    let own = keys
+        .iter()
+        .find(|key| key.fingerprint() == fingerprint)
+        .unwrap();
+    assert!(own.has_secret());
+    assert_eq!(own.primary_uid().unwrap().email(), Some("test@example.org"));
+    assert!(own.primary.keygrip.is_some());
+    assert!(keys.iter().any(|key| !key.has_secret()));
+
+    assert_eq!(home.gpg.list_secret_keys().unwrap().len(), 1);
+}
+
+#[test]
+fn parse_signatures() {
+    let keys = colons::parse_keys(include_str!("fixtures/check-sigs.colons"));
+    let alice = &keys[0];
+
+    let work = &alice.user_ids[1];
+    assert_eq!(work.signatures.len(), 2);
+    assert!(work.signatures[0].is_self_signature(&alice.primary.key_id));
+    assert_eq!(work.signatures[0].class, 0x13);
+    assert_eq!(work.signatures[0].check, SignatureCheck::Good);
+    assert_eq!(work.signatures[1].signer, "Bob Tester <bob@example.net>");
+    assert_eq!(work.signatures[1].class, 0x10);
+
+    let old = &alice.user_ids[2];
+    assert_eq!(old.validity, Validity::Revoked);
+    assert!(old.signatures[0].is_revocation);
+    assert_eq!(old.signatures[0].revocation_reason, Some(0x20));
+
+    // Binding signatures belong to the subkeys
+    assert_eq!(alice.subkeys[0].signatures[0].class, 0x18);
+}
+
+#[test]
+fn normalize_expiry_inputs() {
+    assert_eq!(normalize_expiry("").as_deref(), Some("never"));
+    assert_eq!(normalize_expiry("never").as_deref(), Some("never"));
+    assert_eq!(normalize_expiry("2y").as_deref(), Some("2y"));
+    assert_eq!(
+        normalize_expiry("2030-01-31").as_deref(),
+        Some("2030-01-31")
+    );
+    assert_eq!(normalize_expiry("0d"), None);
+    assert_eq!(normalize_expiry("soon"), None);
+}
+
+#[test]
+fn generate_key_pair() {
+    let home = TestHome::new();
+    let params = KeyParams {
+        name: "Generated".to_string(),
+        email: "gen@example.org".to_string(),
+        algorithm: KeyAlgorithm::Ed25519,
+        expires: "1y".to_string(),
+        ..Default::default()
+    };
+    let fingerprint = home.gpg.generate_key(&params, |_| ()).unwrap();
+
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+    assert_eq!(key.user_ids[0].uid, "Generated <gen@example.org>");
+    assert_eq!(key.primary.algorithm_name(), "ed25519");
+    assert!(key.primary.expires.is_some());
+    assert_eq!(key.subkeys.len(), 1);
+    assert_eq!(key.subkeys[0].algorithm_name(), "cv25519");
+    assert!(key.subkeys[0].capabilities.encrypt);
+    assert!(key.has_secret());
+}
+
+#[test]
+fn parse_import_status() {
+    let status = parse_status(
+        "[GNUPG:] IMPORT_OK 1 AAAA\n\
+         [GNUPG:] IMPORT_OK 0 BBBB\n\
+         [GNUPG:] IMPORT_OK 17 CCCC\n\
+         [GNUPG:] IMPORT_OK 16 CCCC\n\
+         [GNUPG:] IMPORT_PROBLEM 1 DDDD\n\
+         [GNUPG:] IMPORT_RES 4 0 2 0 1 0 0 3 1 1 1 0 0 1 0\n",
+    );
+    let result = parse_import(&status);
+    assert_eq!(result.keys.len(), 3);
+    assert!(result.keys[0].1.new_key);
+    assert!(result.keys[1].1.is_unchanged());
+    assert!(result.keys[2].1.new_key && result.keys[2].1.secret);
+    assert_eq!(result.problems[0].reason, "invalid certificate");
+    assert_eq!(result.problems[0].fingerprint.as_deref(), Some("DDDD"));
+    assert_eq!(result.counts.considered, 4);
+    assert_eq!(result.counts.imported, 2);
+    assert_eq!(result.counts.unchanged, 1);
+    assert_eq!(result.counts.new_signatures, 3);
+    assert_eq!(result.counts.new_revocations, 1);
+    assert_eq!(result.counts.secret_read, 1);
+    assert_eq!(result.counts.not_imported, 1);
+    assert_eq!(result.first_changed(), Some("AAAA"));
+}
+
+#[test]
+fn import_armored_key() {
+    let home = TestHome::new();
+    let armor = std::fs::read("tests/fixtures/public.asc").unwrap();
+
+    let result = home.gpg.import(&armor).unwrap();
+    assert_eq!(result.counts.considered, 1);
+    assert_eq!(result.counts.imported, 1);
+    assert_eq!(
+        result.first_changed(),
+        Some("5DA09291CD5722A6C2A88BB6160E72203A43A272")
+    );
+
+    // Importing the same key again changes nothing
+    let result = home.gpg.import(&armor).unwrap();
+    assert_eq!(result.counts.unchanged, 1);
+    assert!(result.keys[0].1.is_unchanged());
+
+    // Garbage is no error, there is just nothing to import
+    let result = home.gpg.import(b"not a key").unwrap();
+    assert_eq!(result.counts.considered, 0);
+    assert!(result.keys.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_with_status_reads_non_utf8_messages() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Stands in for a gpg printing its messages in a latin1 locale
+    let home = TestHome::new();
+    let program = home.path().join("fake-gpg");
+    std::fs::write(
+        &program,
+        "#!/bin/sh\nprintf 'gpg: Schl\\374ssel\\n[GNUPG:] NEWSIG\\n' >&2\necho done\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = Gpg::new(&program, None)
+        .run_with_status(&[], b"", |_| ())
+        .unwrap();
+    assert!(output.success);
+    assert_eq!(output.log, "gpg: Schl\u{fffd}ssel\n");
+    assert_eq!(output.status.len(), 1);
+    assert_eq!(output.stdout, b"done\n");
+}
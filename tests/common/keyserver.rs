@@ -0,0 +1,163 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use gpg4terminal::backend::keyserver::{url_decode, url_encode};
+
+/// A certificate published on the [`MockKeyserver`]
+#[derive(Debug, Clone)]
+pub struct Cert {
+    pub fingerprint: String,
+    pub user_ids: Vec<String>,
+    pub armor: String,
+}
+
+/// An in-process HKP and VKS keyserver on a loopback port
+///
+/// Understands `op=index`, `op=get` and the VKS `by-email`, `by-fingerprint`
+/// and `by-keyid` lookups, which is enough for gpg's dirmngr as well.
+pub struct MockKeyserver {
+    /// `http://127.0.0.1:<port>`
+    pub url: String,
+    certs: Arc<Mutex<Vec<Cert>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockKeyserver {
+    pub fn start(certs: Vec<Cert>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let certs = Arc::new(Mutex::new(certs));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (shared_certs, shared_requests) = (certs.clone(), requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                serve(stream, &shared_certs, &shared_requests);
+            }
+        });
+        Self {
+            url,
+            certs,
+            requests,
+        }
+    }
+
+    /// Replace the published certificates, e.g. with an updated version
+    pub fn publish(&self, certs: Vec<Cert>) {
+        *self.certs.lock().unwrap() = certs;
+    }
+
+    /// Paths requested so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The address in the `hkp://` form gpg expects
+    pub fn hkp_url(&self) -> String {
+        self.url.replacen("http://", "hkp://", 1)
+    }
+}
+
+fn serve(stream: TcpStream, certs: &Mutex<Vec<Cert>>, requests: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Skip the headers
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|len| len > 2) {
+        header.clear();
+    }
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+    requests.lock().unwrap().push(path.clone());
+
+    let (status, body) = respond(&certs.lock().unwrap(), &path);
+    let reason = if status == 200 { "OK" } else { "Not Found" };
+    let _ = write!(
+        &stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+}
+
+fn respond(certs: &[Cert], path: &str) -> (u16, String) {
+    let not_found = (404, "Not found".to_string());
+    let ends_with_id = |cert: &&Cert, id: &str| {
+        let id = id.trim_start_matches("0x").to_uppercase();
+        !id.is_empty() && cert.fingerprint.ends_with(&id)
+    };
+
+    if let Some(query) = path.strip_prefix("/pks/lookup?") {
+        let params: Vec<(&str, String)> = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .map(|(name, value)| (name, url_decode(&value.replace('+', " "))))
+            .collect();
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        let search = param("search");
+        return match param("op").as_str() {
+            "index" => {
+                let term = search.trim_start_matches("0x").to_lowercase();
+                let found: Vec<&Cert> = certs
+                    .iter()
+                    .filter(|cert| {
+                        cert.fingerprint.to_lowercase().contains(&term)
+                            || cert
+                                .user_ids
+                                .iter()
+                                .any(|uid| uid.to_lowercase().contains(&term))
+                    })
+                    .collect();
+                if found.is_empty() {
+                    return not_found;
+                }
+                let mut body = format!("info:1:{}\n", found.len());
+                for cert in found {
+                    body.push_str(&format!("pub:{}:22:255:1700000000::\n", cert.fingerprint));
+                    for uid in cert.user_ids.iter() {
+                        body.push_str(&format!("uid:{}:1700000000::\n", url_encode(uid)));
+                    }
+                }
+                (200, body)
+            }
+            "get" => match certs.iter().find(|cert| ends_with_id(cert, &search)) {
+                Some(cert) => (200, cert.armor.clone()),
+                None => not_found,
+            },
+            _ => (501, "Not implemented".to_string()),
+        };
+    }
+
+    let found = if let Some(email) = path.strip_prefix("/vks/v1/by-email/") {
+        let email = format!("<{}>", url_decode(email).to_lowercase());
+        certs.iter().find(|cert| {
+            cert.user_ids
+                .iter()
+                .any(|uid| uid.to_lowercase().contains(&email))
+        })
+    } else if let Some(fingerprint) = path.strip_prefix("/vks/v1/by-fingerprint/") {
+        certs.iter().find(|cert| cert.fingerprint == fingerprint)
+    } else if let Some(key_id) = path.strip_prefix("/vks/v1/by-keyid/") {
+        certs.iter().find(|cert| ends_with_id(cert, key_id))
+    } else {
+        None
+    };
+    match found {
+        Some(cert) => (200, cert.armor.clone()),
+        None => not_found,
+    }
+}
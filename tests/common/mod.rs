@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+pub mod keyserver;
+
+use std::{path::Path, process::Command};
+
+use gpg4terminal::backend::Gpg;
+use tempfile::TempDir;
+
+use keyserver::Cert;
+
+/// A throwaway `GNUPGHOME` which stops its agent when dropped
+pub struct TestHome {
+    pub dir: TempDir,
+    pub gpg: Gpg,
+}
+
+impl TestHome {
+    pub fn new() -> Self {
+        // Keep the path short, agent sockets have a small length limit
+        let dir = tempfile::Builder::new()
+            .prefix("g4t")
+            .tempdir_in("/tmp")
+            .unwrap();
+        let gpg = Gpg::new("gpg", Some(dir.path().to_path_buf()));
+        Self { dir, gpg }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Run gpg in this home with `args`, panicking on failure
+    pub fn gpg(&self, args: &[&str]) -> String {
+        let output = self
+            .gpg
+            .command()
+            .args(["--pinentry-mode", "loopback", "--passphrase", ""])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "gpg {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Generate an unprotected ed25519/cv25519 key and return its fingerprint
+    pub fn generate(&self, uid: &str) -> String {
+        self.gpg(&["--quick-generate-key", uid, "ed25519", "default", "never"]);
+        let listing = self.gpg(&["--with-colons", "--list-keys", uid]);
+        listing
+            .lines()
+            .find(|line| line.starts_with("fpr:"))
+            .and_then(|line| line.split(':').nth(9))
+            .unwrap()
+            .to_string()
+    }
+
+    /// Export the public key `fingerprint` for publishing on a mock keyserver
+    pub fn cert(&self, fingerprint: &str) -> Cert {
+        let key = self
+            .gpg
+            .list_keys()
+            .unwrap()
+            .into_iter()
+            .find(|key| key.fingerprint() == fingerprint)
+            .unwrap();
+        Cert {
+            fingerprint: fingerprint.to_string(),
+            user_ids: key.user_ids.iter().map(|uid| uid.uid.clone()).collect(),
+            armor: self.gpg(&["--armor", "--export", fingerprint]),
+        }
+    }
+}
+
+impl Drop for TestHome {
+    fn drop(&mut self) {
+        let _ = Command::new("gpgconf")
+            .arg("--homedir")
+            .arg(self.dir.path())
+            .args(["--kill", "all"])
+            .output();
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use gpg4terminal::backend::component::{parse_gpgconf_list, ComponentState};
+
+use common::TestHome;
+
+#[test]
+fn parse_gpgconf_output() {
+    let output = "gpg:OpenPGP:/usr/bin/gpg\n\
+                  gpg-agent:Private Keys:/usr/bin/gpg-agent\n\
+                  \n\
+                  agent-socket:/run/user/1000/gnupg/S.gpg-agent\n\
+                  homedir:C%3a\\gnupg\n";
+    let fields = parse_gpgconf_list(output);
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0], ["gpg", "OpenPGP", "/usr/bin/gpg"]);
+    assert_eq!(fields[1][1], "Private Keys");
+    assert_eq!(fields[2][1], "/run/user/1000/gnupg/S.gpg-agent");
+    assert_eq!(fields[3], ["homedir", "C:\\gnupg"]);
+}
+
+#[test]
+fn kill_and_launch_agent() {
+    let home = TestHome::new();
+    let gpg = &home.gpg;
+    let agent_state = || {
+        gpg.list_components()
+            .unwrap()
+            .into_iter()
+            .find(|component| component.name == "gpg-agent")
+            .unwrap()
+    };
+
+    gpg.launch_component("gpg-agent").unwrap();
+    let agent = agent_state();
+    assert_eq!(agent.state, ComponentState::Running);
+    assert!(agent
+        .sockets
+        .iter()
+        .any(|(name, path)| name == "agent-socket" && path.exists()));
+
+    gpg.kill_component("gpg-agent").unwrap();
+    assert_eq!(agent_state().state, ComponentState::Stopped);
+
+    gpg.restart_all().unwrap();
+    let components = gpg.list_components().unwrap();
+    let state = |name: &str| {
+        components
+            .iter()
+            .find(|component| component.name == name)
+            .map(|component| component.state)
+    };
+    assert_eq!(state("gpg-agent"), Some(ComponentState::Running));
+    assert_eq!(state("gpg"), Some(ComponentState::NotDaemon));
+    assert!(gpg
+        .list_dirs()
+        .unwrap()
+        .iter()
+        .any(|(name, value)| name == "homedir" && value == &home.path().to_string_lossy()));
+}
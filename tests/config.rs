@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use gpg4terminal::config::{Config, ConfirmDelete};
+
+#[test]
+fn parse_config_with_unknown_and_invalid_keys() {
+    let text = r#"
+armor = false
+confirm_delete = "secret"
+date_format = "%d.%m.%Y"
+expiry_warning_days = -1
+gnupghome = "/tmp/gnupg"
+colour = "purple"
+theme = 42
+keyservers = ["hkps://keys.example", "ftp://files.example"]
+"#;
+    let (config, warnings) = Config::parse(text);
+    assert!(!config.armor);
+    assert_eq!(config.confirm_delete, ConfirmDelete::SecretKeys);
+    assert_eq!(config.date_format, "%d.%m.%Y");
+    assert_eq!(config.gnupghome, Some(PathBuf::from("/tmp/gnupg")));
+    // Unknown keys and bad values fall back to the defaults with a warning
+    assert_eq!(config.theme, Config::default().theme);
+    assert!(config.keyservers.is_empty());
+    assert_eq!(config.expiry_warning_days, 30);
+    assert_eq!(warnings.len(), 4, "{warnings:?}");
+    assert!(warnings.iter().any(|warning| warning.contains("`colour`")));
+    assert!(warnings.iter().any(|warning| warning.contains("`theme`")));
+
+    let (config, warnings) = Config::parse("armor = ");
+    assert_eq!(config, Config::default());
+    assert_eq!(warnings.len(), 1);
+    assert!(Config::load(&PathBuf::from("/nonexistent/config.toml"))
+        .1
+        .is_empty());
+}
+
+#[test]
+fn save_and_load_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("gpg4terminal").join("config.toml");
+    let config = Config {
+        default_signing_key: Some("4C2A739A8CA6AAF49B42D07943079B3772E1A1AB".to_string()),
+        armor: false,
+        confirm_delete: ConfirmDelete::Never,
+        gnupghome: Some(dir.path().to_path_buf()),
+        date_format: "%Y/%m/%d %H:%M".to_string(),
+        expiry_warning_days: 0,
+        keymap: "vim".to_string(),
+        keyservers: vec![
+            "hkps://keys.example".to_string(),
+            "ldap://ldap.example".to_string(),
+        ],
+        update_check: false,
+        update_feed: "file:///srv/releases.json".to_string(),
+        update_key: Some("4C2A739A8CA6AAF49B42D07943079B3772E1A1AB".to_string()),
+        ..Config::default()
+    };
+    assert_eq!(config.validate(), Ok(()));
+    config.save(&path).unwrap();
+    assert_eq!(Config::load(&path), (config, vec![]));
+
+    let invalid = [
+        Config {
+            gpg_path: PathBuf::from("/nonexistent/gpg"),
+            ..Config::default()
+        },
+        Config {
+            gnupghome: Some(path),
+            ..Config::default()
+        },
+        Config {
+            date_format: "%Y-%Q".to_string(),
+            ..Config::default()
+        },
+        Config {
+            theme: "../dark".to_string(),
+            ..Config::default()
+        },
+        Config {
+            keymap: "nano".to_string(),
+            ..Config::default()
+        },
+        Config {
+            update_feed: "ftp://releases.example".to_string(),
+            ..Config::default()
+        },
+        Config {
+            update_key: Some("0x1234".to_string()),
+            ..Config::default()
+        },
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err(), "{config:?}");
+    }
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use std::fs;
+
+use gpg4terminal::backend::{
+    decrypt::{detached_pair, parse_decrypt, Input, Integrity, SignatureStatus},
+    status::parse_status,
+};
+
+use common::TestHome;
+
+#[test]
+fn parse_decrypt_status() {
+    let status = parse_status(
+        "[GNUPG:] ENC_TO 1122334455667788 18 0\n\
+         [GNUPG:] DECRYPTION_KEY AAAA BBBB u\n\
+         [GNUPG:] BEGIN_DECRYPTION\n\
+         [GNUPG:] DECRYPTION_INFO 2 9 0\n\
+         [GNUPG:] PLAINTEXT 62 1782767153 notes%20v1.txt\n\
+         [GNUPG:] GOODSIG 43079B3772E1A1AB Alice <alice@example.net>\n\
+         [GNUPG:] VALIDSIG CCCC 2026-06-29 1782767153 0 4 0 22 10 00 DDDD\n\
+         [GNUPG:] TRUST_ULTIMATE 0 pgp\n\
+         [GNUPG:] ERRSIG 1111222233334444 1 10 00 1782767153 9 EEEE\n\
+         [GNUPG:] NO_PUBKEY 1111222233334444\n\
+         [GNUPG:] DECRYPTION_OKAY\n\
+         [GNUPG:] GOODMDC\n\
+         [GNUPG:] END_DECRYPTION\n",
+    );
+    let result = parse_decrypt(&status);
+    assert_eq!(result.recipients, ["1122334455667788"]);
+    assert_eq!(result.decryption_key.as_deref(), Some("AAAA"));
+    assert_eq!(result.integrity, Integrity::Mdc);
+    assert_eq!(result.filename.as_deref(), Some("notes v1.txt"));
+    assert!(!result.symmetric);
+
+    assert_eq!(result.signatures.len(), 2);
+    let good = &result.signatures[0];
+    assert_eq!(good.status, SignatureStatus::Good);
+    assert_eq!(good.user_id, "Alice <alice@example.net>");
+    assert_eq!(good.fingerprint.as_deref(), Some("CCCC"));
+    assert_eq!(good.primary_fingerprint.as_deref(), Some("DDDD"));
+    assert_eq!(good.trust.as_deref(), Some("ultimate"));
+    assert!(good.created.is_some());
+    let missing = &result.signatures[1];
+    assert_eq!(missing.status, SignatureStatus::MissingKey);
+    assert_eq!(missing.fingerprint.as_deref(), Some("EEEE"));
+
+    // Old messages without MDC are flagged, AEAD counts as protected
+    let unprotected = parse_decrypt(&parse_status(
+        "[GNUPG:] BEGIN_DECRYPTION\n[GNUPG:] DECRYPTION_OKAY\n",
+    ));
+    assert_eq!(unprotected.integrity, Integrity::Unprotected);
+    let aead = parse_decrypt(&parse_status(
+        "[GNUPG:] BEGIN_DECRYPTION\n\
+         [GNUPG:] DECRYPTION_INFO 0 9 2\n\
+         [GNUPG:] DECRYPTION_OKAY\n",
+    ));
+    assert_eq!(aead.integrity, Integrity::Aead);
+}
+
+#[test]
+fn decrypt_and_verify_round_trip() {
+    let home = TestHome::new();
+    let fingerprint = home.generate("Alice <alice@example.net>");
+    home.gpg(&["--quick-add-key", &fingerprint, "cv25519", "encr", "never"]);
+    let message = home.path().join("message.txt");
+    fs::write(&message, "Hello Bob\n").unwrap();
+    let encrypted = home.path().join("message.txt.asc");
+    home.gpg(&[
+        "--armor",
+        "--sign",
+        "--encrypt",
+        "--recipient",
+        &fingerprint,
+        "--output",
+        &encrypted.to_string_lossy(),
+        &message.to_string_lossy(),
+    ]);
+
+    // From a file
+    let result = home
+        .gpg
+        .decrypt(&Input::File(encrypted.clone()), None)
+        .unwrap();
+    assert!(result.success, "{}", result.log);
+    assert_eq!(result.plaintext, b"Hello Bob\n");
+    assert!(result.is_text());
+    assert!(matches!(result.integrity, Integrity::Mdc | Integrity::Aead));
+    assert_eq!(result.recipients.len(), 1);
+    assert_eq!(result.signatures.len(), 1);
+    assert!(result.signatures[0].status.is_good());
+    assert_eq!(
+        result.signatures[0].primary_fingerprint.as_deref(),
+        Some(fingerprint.as_str())
+    );
+
+    // From pasted armor, once through stdin and once with a passphrase
+    let armor = fs::read(&encrypted).unwrap();
+    let result = home.gpg.decrypt(&Input::Data(armor.clone()), None).unwrap();
+    assert_eq!(result.plaintext, b"Hello Bob\n");
+    let result = home.gpg.decrypt(&Input::Data(armor), Some("")).unwrap();
+    assert_eq!(result.plaintext, b"Hello Bob\n");
+
+    // Garbage fails without an error
+    let result = home
+        .gpg
+        .decrypt(&Input::Data(b"not a message".to_vec()), None)
+        .unwrap();
+    assert!(!result.success);
+    assert!(result.plaintext.is_empty());
+}
+
+#[test]
+fn verify_detached_signature() {
+    let home = TestHome::new();
+    home.generate("Alice <alice@example.net>");
+    let data = home.path().join("release.tar");
+    fs::write(&data, "release contents").unwrap();
+    let signature = home.path().join("release.tar.sig");
+    home.gpg(&[
+        "--detach-sign",
+        "--output",
+        &signature.to_string_lossy(),
+        &data.to_string_lossy(),
+    ]);
+
+    // Either file of the pair finds the other one
+    let pair = (signature.clone(), data.clone());
+    assert_eq!(detached_pair(&data), Some(pair.clone()));
+    assert_eq!(detached_pair(&signature), Some(pair));
+    // An armored encrypted message is no detached signature
+    let message = home.path().join("note.txt");
+    fs::write(&message, "note").unwrap();
+    fs::write(
+        home.path().join("note.txt.asc"),
+        "-----BEGIN PGP MESSAGE-----\n",
+    )
+    .unwrap();
+    assert_eq!(detached_pair(&message), None);
+
+    let result = home.gpg.verify_detached(&signature, &data).unwrap();
+    assert!(result.success);
+    assert!(!result.is_encrypted());
+    assert_eq!(result.signatures[0].status, SignatureStatus::Good);
+
+    fs::write(&data, "tampered contents").unwrap();
+    let result = home.gpg.verify_detached(&signature, &data).unwrap();
+    assert!(!result.success);
+    assert_eq!(result.signatures[0].status, SignatureStatus::Bad);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use gpg4terminal::{
+    backend::delete::DeleteKind,
+    config::ConfirmDelete,
+    ui::dialog::{delete_key::DeleteKeyDialog, DialogResult, DialogState},
+};
+
+use common::TestHome;
+
+#[test]
+fn delete_kinds() {
+    assert_eq!(DeleteKind::choices(false), [DeleteKind::Public]);
+    let secret = DeleteKind::choices(true);
+    assert!(secret.iter().all(DeleteKind::is_secret));
+    // Both parts go unless chosen otherwise
+    assert_eq!(secret[0], DeleteKind::SecretAndPublic);
+}
+
+#[test]
+fn delete_public_and_secret_keys() {
+    let home = TestHome::new();
+    let alice = home.generate("Alice <alice@example.net>");
+    let bob = home.generate("Bob <bob@example.net>");
+
+    // gpg refuses to delete a public key while its secret key is there
+    assert!(home.gpg.delete_key(&alice, DeleteKind::Public).is_err());
+
+    home.gpg.delete_key(&alice, DeleteKind::Secret).unwrap();
+    assert!(home
+        .gpg
+        .list_secret_keys()
+        .unwrap()
+        .iter()
+        .all(|key| key.fingerprint() != alice));
+    home.gpg.delete_key(&alice, DeleteKind::Public).unwrap();
+
+    home.gpg
+        .delete_key(&bob, DeleteKind::SecretAndPublic)
+        .unwrap();
+    assert!(home.gpg.list_keys().unwrap().is_empty());
+    assert!(home.gpg.delete_key(&bob, DeleteKind::Public).is_err());
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+#[test]
+fn secret_key_needs_typed_fingerprint() {
+    let home = TestHome::new();
+    let alice = home.generate("Alice <alice@example.net>");
+    let has_secret = || {
+        home.gpg
+            .list_secret_keys()
+            .unwrap()
+            .iter()
+            .any(|key| key.fingerprint() == alice)
+    };
+    let details = home.gpg.key_details(&alice).unwrap();
+
+    // Even with confirmation turned off
+    let mut dialog = DeleteKeyDialog::new(details, ConfirmDelete::Never);
+    // No backup, it would land in the working directory
+    dialog.handle_key(key(KeyCode::Tab), &home.gpg);
+    dialog.handle_key(key(KeyCode::Char(' ')), &home.gpg);
+    for _ in 0..3 {
+        dialog.handle_key(key(KeyCode::Enter), &home.gpg);
+        dialog.handle_key(key(KeyCode::Delete), &home.gpg);
+    }
+    // The Delete button of the confirmation
+    dialog.focus_button(1);
+    dialog.handle_key(key(KeyCode::Enter), &home.gpg);
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(dialog.tick(), DialogResult::Continue);
+    assert!(has_secret());
+
+    // Back to the fingerprint input
+    dialog.handle_key(key(KeyCode::BackTab), &home.gpg);
+    dialog.handle_key(key(KeyCode::BackTab), &home.gpg);
+    dialog.handle_paste(&alice);
+    dialog.handle_key(key(KeyCode::Enter), &home.gpg);
+    let result = (0..100)
+        .map(|_| {
+            thread::sleep(Duration::from_millis(50));
+            dialog.tick()
+        })
+        .find(|result| *result != DialogResult::Continue);
+    assert_eq!(result, Some(DialogResult::Reload(None)));
+    assert!(!has_secret());
+}
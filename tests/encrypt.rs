@@ -0,0 +1,148 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use gpg4terminal::backend::{
+    decrypt::{Input, Integrity},
+    encrypt::{recipient_issue, EncryptOptions, Encryption, RecipientIssue, Signing},
+    key::Validity,
+};
+
+use common::TestHome;
+
+#[test]
+fn validate_options() {
+    let options = EncryptOptions {
+        recipients: vec!["AAAA".to_string()],
+        armor: true,
+        ..Default::default()
+    };
+    assert!(options.validate().is_ok());
+    assert_eq!(
+        options.output_path(Path::new("/tmp/notes.txt")),
+        Path::new("/tmp/notes.txt.asc")
+    );
+
+    let nothing = EncryptOptions {
+        encryption: Encryption::None,
+        ..Default::default()
+    };
+    assert!(nothing.validate().is_err());
+    let no_recipients = EncryptOptions::default();
+    assert!(no_recipients.validate().is_err());
+    let encrypted_detached = EncryptOptions {
+        signing: Signing::Detached,
+        ..options.clone()
+    };
+    assert!(encrypted_detached.validate().is_err());
+    let symmetric_without_passphrase = EncryptOptions {
+        encryption: Encryption::Symmetric,
+        ..Default::default()
+    };
+    assert!(symmetric_without_passphrase.validate().is_err());
+
+    let detached = EncryptOptions {
+        encryption: Encryption::None,
+        signing: Signing::Detached,
+        ..Default::default()
+    };
+    assert!(detached.validate().is_ok());
+    assert_eq!(
+        detached.output_path(Path::new("release.tar")),
+        Path::new("release.tar.sig")
+    );
+}
+
+/// A key with an encryption subkey from another home, which is not valid
+/// after importing it as it is not certified
+fn foreign_key() -> (String, String) {
+    let other = TestHome::new();
+    let fingerprint = other.generate("Dave <dave@example.net>");
+    other.gpg(&["--quick-add-key", &fingerprint, "cv25519", "encr", "never"]);
+    let armor = other.cert(&fingerprint).armor;
+    (fingerprint, armor)
+}
+
+#[test]
+fn flag_unfit_recipients() {
+    let home = TestHome::new();
+    let own = home.generate("Alice <alice@example.net>");
+    home.gpg(&["--quick-add-key", &own, "cv25519", "encr", "never"]);
+    let sign_only = home.generate("Carol <carol@example.net>");
+    let (foreign, armor) = foreign_key();
+    home.gpg.import(armor.as_bytes()).unwrap();
+
+    let keys = home.gpg.list_keys().unwrap();
+    let issue = |fingerprint: &str| {
+        let key = keys
+            .iter()
+            .find(|key| key.fingerprint() == fingerprint)
+            .unwrap();
+        recipient_issue(key)
+    };
+    assert_eq!(issue(&own), None);
+    assert_eq!(issue(&sign_only), Some(RecipientIssue::NoEncryptionKey));
+    let foreign = issue(&foreign).unwrap();
+    assert_eq!(foreign, RecipientIssue::Untrusted(Validity::Unknown));
+    assert!(!foreign.is_fatal());
+}
+
+#[test]
+fn encrypt_and_sign_round_trip() {
+    let home = TestHome::new();
+    let own = home.generate("Alice <alice@example.net>");
+    home.gpg(&["--quick-add-key", &own, "cv25519", "encr", "never"]);
+    let (foreign, armor) = foreign_key();
+    home.gpg.import(armor.as_bytes()).unwrap();
+    let input = home.path().join("notes.txt");
+    fs::write(&input, "meeting at noon\n").unwrap();
+
+    // The untrusted foreign key is only accepted when confirmed
+    let mut options = EncryptOptions {
+        recipients: vec![foreign],
+        encrypt_to_self: true,
+        signing: Signing::Inline,
+        signer: Some(own.clone()),
+        armor: true,
+        ..Default::default()
+    };
+    let output = options.output_path(&input);
+    assert!(home.gpg.encrypt_file(&input, &output, &options).is_err());
+    options.always_trust = true;
+    home.gpg.encrypt_file(&input, &output, &options).unwrap();
+
+    let result = home.gpg.decrypt(&Input::File(output), None).unwrap();
+    assert!(result.success, "{}", result.log);
+    assert_eq!(result.plaintext, b"meeting at noon\n");
+    assert_eq!(result.recipients.len(), 2);
+    assert!(matches!(result.integrity, Integrity::Mdc | Integrity::Aead));
+    assert!(result.signatures[0].status.is_good());
+
+    // Passphrase only
+    let options = EncryptOptions {
+        encryption: Encryption::Symmetric,
+        passphrase: Some("correct horse".to_string()),
+        ..Default::default()
+    };
+    let output = home.path().join("notes.txt.gpg");
+    home.gpg.encrypt_file(&input, &output, &options).unwrap();
+    let result = home
+        .gpg
+        .decrypt(&Input::File(output), Some("correct horse"))
+        .unwrap();
+    assert!(result.symmetric);
+    assert_eq!(result.plaintext, b"meeting at noon\n");
+
+    // Clear text signature
+    let options = EncryptOptions {
+        encryption: Encryption::None,
+        signing: Signing::Clearsign,
+        signer: Some(own),
+        ..Default::default()
+    };
+    let output = options.output_path(&input);
+    home.gpg.encrypt_file(&input, &output, &options).unwrap();
+    let signed = fs::read_to_string(&output).unwrap();
+    assert!(signed.starts_with("-----BEGIN PGP SIGNED MESSAGE-----"));
+    assert!(signed.contains("meeting at noon"));
+}
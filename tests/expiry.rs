@@ -0,0 +1,75 @@
+mod common;
+
+use std::slice;
+
+use chrono::{NaiveDate, TimeDelta, Utc};
+use gpg4terminal::backend::{expiry::expiry_date, generate::normalize_expiry};
+
+use common::TestHome;
+
+#[test]
+fn expiry_dates() {
+    let now = Utc::now();
+    assert_eq!(expiry_date("never", now), None);
+    assert_eq!(expiry_date("10d", now), Some(now + TimeDelta::days(10)));
+    assert_eq!(expiry_date("2w", now), Some(now + TimeDelta::days(14)));
+    // Like gpg, months and years have a fixed number of days
+    assert_eq!(expiry_date("6m", now), Some(now + TimeDelta::days(180)));
+    assert_eq!(expiry_date("1y", now), Some(now + TimeDelta::days(365)));
+    let noon = NaiveDate::from_ymd_opt(2030, 12, 31)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+        .and_utc();
+    assert_eq!(expiry_date("2030-12-31", now), Some(noon));
+}
+
+#[test]
+fn set_expiry_of_primary_and_subkeys() {
+    let home = TestHome::new();
+    let alice = home.generate("Alice <alice@example.net>");
+    home.gpg(&["--quick-add-key", &alice, "cv25519", "encr", "never"]);
+    home.gpg(&["--quick-add-key", &alice, "ed25519", "sign", "never"]);
+    let key = home.gpg.key_details(&alice).unwrap();
+    assert!(key.primary.expires.is_none());
+    assert_eq!(key.next_expiry(), None);
+    let signing = key.subkeys[1].fingerprint.clone();
+
+    // Only the subkey, gpg rounds to whole seconds
+    let expiry = normalize_expiry("6m").unwrap();
+    let expected = expiry_date(&expiry, Utc::now()).unwrap();
+    home.gpg
+        .set_expiry(&alice, slice::from_ref(&signing), &expiry, None)
+        .unwrap();
+    let key = home.gpg.key_details(&alice).unwrap();
+    assert!(key.primary.expires.is_none());
+    assert!(key.subkeys[0].expires.is_none());
+    let expires = key.subkeys[1].expires.unwrap();
+    assert!(
+        (expires - expected).abs() < TimeDelta::minutes(1),
+        "{expires}"
+    );
+    assert_eq!(key.next_expiry(), Some(expires));
+
+    // The primary key, with the passphrase given
+    home.gpg
+        .set_expiry(&alice, &[], "2030-12-31", Some(""))
+        .unwrap();
+    let key = home.gpg.key_details(&alice).unwrap();
+    assert_eq!(key.primary.expires, expiry_date("2030-12-31", Utc::now()));
+    assert_eq!(key.subkeys[1].expires, Some(expires));
+
+    home.gpg.set_expiry(&alice, &[], "never", None).unwrap();
+    assert!(home
+        .gpg
+        .key_details(&alice)
+        .unwrap()
+        .primary
+        .expires
+        .is_none());
+    // gpg refuses dates in the past
+    assert!(home
+        .gpg
+        .set_expiry(&alice, &[], "2020-01-01", None)
+        .is_err());
+}
@@ -0,0 +1,73 @@
+use gpg4terminal::{
+    backend::colons,
+    config::Config,
+    event_handler::key_board_handler::EventResult,
+    ui::{
+        focus::FocusOn,
+        key_detail::{handle_event, init_key_detail_state, render_key_detail},
+        key_list::init_key_list_state,
+        menu_bar::{init_menu_bar_state, menu_tree},
+        UiState,
+    },
+};
+use ratatui::{buffer::Buffer, layout::Rect};
+
+fn shown(buf: &Buffer) -> String {
+    let area = buf.area;
+    (0..area.height)
+        .map(|y| {
+            (0..area.width)
+                .map(|x| buf.get(x, y).symbol().to_string())
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[test]
+fn scroll_to_signatures_below_the_last_item() {
+    let ui_state = UiState {
+        focus_on: FocusOn::KeyDetail,
+        menu_bar_state: init_menu_bar_state(&menu_tree()),
+        key_list_state: init_key_list_state(),
+        key_detail_state: init_key_detail_state(),
+        dialog_buttons: Default::default(),
+        date_format: Config::default().date_format,
+        expiry_warning_days: Config::default().expiry_warning_days,
+    };
+    let keys = colons::parse_keys(include_str!("fixtures/check-sigs.colons"));
+    ui_state.key_detail_state.borrow_mut().key = Some(keys[0].clone());
+    let state = || ui_state.key_detail_state.clone();
+    // 6 lines fit between the borders
+    let area = Rect::new(0, 0, 100, 8);
+    let render = || {
+        let mut buf = Buffer::empty(area);
+        render_key_detail(&ui_state, area, &mut buf);
+        shown(&buf)
+    };
+
+    render();
+    handle_event(state(), EventResult::KeyDetailLastItem);
+    let view = render();
+    assert!(view.contains("Old Mail"));
+    // The revocation of the last user ID comes after it
+    assert!(!view.contains("rev !"));
+
+    handle_event(state(), EventResult::KeyDetailScrollDown);
+    assert!(render().contains("rev !"));
+    // Not past the last line
+    handle_event(state(), EventResult::KeyDetailNextPage);
+    render();
+    let line_count = state().borrow().line_count;
+    assert_eq!(state().borrow().scroll, line_count - 6);
+
+    handle_event(state(), EventResult::KeyDetailPrevPage);
+    handle_event(state(), EventResult::KeyDetailPrevPage);
+    handle_event(state(), EventResult::KeyDetailScrollUp);
+    assert!(render().contains("Fingerprint"));
+    assert_eq!(state().borrow().scroll, 0);
+
+    // Selecting scrolls back to the selected item
+    handle_event(state(), EventResult::KeyDetailPrevItem);
+    assert!(render().contains("Alice Example (work)"));
+}
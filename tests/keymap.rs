@@ -0,0 +1,182 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use gpg4terminal::{
+    event_handler::keymap::{KeyAction, KeyChord, KeyContext, KeyLookup, Keymap, KEYMAP_PRESETS},
+    ui::{
+        focus::{FocusOn, Menu},
+        key_detail::DetailAction,
+        menu_bar::{menu_tree, Action, MenuNode},
+    },
+};
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+}
+
+#[test]
+fn parse_key_chords() {
+    let chord = |code, modifiers| Some(KeyChord::new(code, modifiers));
+    assert_eq!(
+        KeyChord::parse("q"),
+        chord(KeyCode::Char('q'), KeyModifiers::NONE)
+    );
+    assert_eq!(
+        KeyChord::parse("ctrl+n"),
+        chord(KeyCode::Char('n'), KeyModifiers::CONTROL)
+    );
+    assert_eq!(
+        KeyChord::parse("alt++"),
+        chord(KeyCode::Char('+'), KeyModifiers::ALT)
+    );
+    assert_eq!(
+        KeyChord::parse("PageDown"),
+        chord(KeyCode::PageDown, KeyModifiers::NONE)
+    );
+    assert_eq!(
+        KeyChord::parse("f10"),
+        chord(KeyCode::F(10), KeyModifiers::NONE)
+    );
+    // Shift is folded into the key, however the terminal reports it
+    assert_eq!(KeyChord::parse("shift+g"), KeyChord::parse("G"));
+    assert_eq!(
+        KeyChord::from(key(KeyCode::Char('g'), KeyModifiers::SHIFT)),
+        KeyChord::parse("G").unwrap()
+    );
+    assert_eq!(
+        KeyChord::from(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+        KeyChord::parse("shift+tab").unwrap()
+    );
+    assert_eq!(KeyChord::parse("hyper+q"), None);
+    assert_eq!(KeyChord::parse("pgup"), None);
+    assert_eq!(KeyChord::parse("ctrl+x").unwrap().label(), "Ctrl+x");
+}
+
+#[test]
+fn presets_and_keymap_file() {
+    let plain = |ch| key(KeyCode::Char(ch), KeyModifiers::NONE);
+    for name in KEYMAP_PRESETS {
+        let mut keymap = Keymap::preset(name);
+        assert_eq!(
+            keymap.lookup(KeyContext::MainPanel, plain('q')),
+            KeyLookup::Action(KeyAction::Quit),
+            "{name}"
+        );
+        // Letters in the menus are left to the hotkeys
+        fn hotkeys(nodes: &[MenuNode], letters: &mut Vec<char>) {
+            for node in nodes {
+                letters.extend(node.hotkey.and_then(|hotkey| hotkey.chars().next()));
+                hotkeys(&node.children, letters);
+            }
+        }
+        let mut letters = Vec::new();
+        hotkeys(&menu_tree(), &mut letters);
+        for letter in letters {
+            for ch in [letter.to_ascii_lowercase(), letter.to_ascii_uppercase()] {
+                for context in [KeyContext::MenuTab, KeyContext::MenuItem] {
+                    assert_eq!(keymap.lookup(context, plain(ch)), KeyLookup::Unbound);
+                }
+            }
+        }
+    }
+
+    let mut vim = Keymap::preset("vim");
+    assert_eq!(
+        vim.lookup(KeyContext::MainPanel, plain('g')),
+        KeyLookup::Pending
+    );
+    assert_eq!(
+        vim.lookup(KeyContext::MainPanel, plain('g')),
+        KeyLookup::Action(KeyAction::First)
+    );
+    // A key which does not continue the sequence counts on its own
+    vim.lookup(KeyContext::MainPanel, plain('g'));
+    assert_eq!(
+        vim.lookup(KeyContext::MainPanel, plain('j')),
+        KeyLookup::Action(KeyAction::Down)
+    );
+    assert_eq!(
+        vim.lookup(KeyContext::MainPanel, plain('/')),
+        KeyLookup::Action(KeyAction::Find)
+    );
+    assert_eq!(
+        Keymap::preset("emacs").lookup(
+            KeyContext::MainPanel,
+            key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+        ),
+        KeyLookup::Action(KeyAction::Find)
+    );
+    assert_eq!(
+        vim.hints(FocusOn::MainPanel)[2],
+        ("k/j".to_string(), "Select")
+    );
+
+    let mut keymap = Keymap::default();
+    let warnings = keymap.apply(
+        r#"
+[main_panel]
+quit = ["ctrl+q"]
+new_key_pair = "n"
+choose = "hyper+x"
+expire = "e"
+
+[key_detail]
+quit_menu = "x"
+
+[dialog]
+"#,
+    );
+    assert_eq!(warnings.len(), 4, "{warnings:?}");
+    // Changes of a key are only bound in the key detail view
+    assert_eq!(
+        keymap.lookup(KeyContext::MainPanel, plain('e')),
+        KeyLookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(KeyContext::KeyDetail, plain('e')),
+        KeyLookup::Action(KeyAction::Detail(DetailAction::Expire))
+    );
+    assert_eq!(
+        keymap.lookup(KeyContext::KeyDetail, plain('r')),
+        KeyLookup::Action(KeyAction::Detail(DetailAction::Revoke))
+    );
+    assert_eq!(
+        keymap.lookup(KeyContext::KeyDetail, plain('s')),
+        KeyLookup::Action(KeyAction::Detail(DetailAction::AddSubkey))
+    );
+    assert_eq!(
+        keymap.lookup(KeyContext::MainPanel, plain('q')),
+        KeyLookup::Unbound
+    );
+    assert_eq!(
+        keymap.lookup(
+            KeyContext::MainPanel,
+            key(KeyCode::Char('q'), KeyModifiers::CONTROL)
+        ),
+        KeyLookup::Action(KeyAction::Quit)
+    );
+    assert_eq!(
+        keymap.lookup(KeyContext::MainPanel, plain('n')),
+        KeyLookup::Action(KeyAction::Run(Action::NewKeyPair))
+    );
+    // Invalid keys keep the old bindings
+    assert_eq!(
+        keymap.lookup(
+            KeyContext::MainPanel,
+            key(KeyCode::Enter, KeyModifiers::NONE)
+        ),
+        KeyLookup::Action(KeyAction::Choose)
+    );
+    assert_eq!(
+        keymap.hints(FocusOn::MenuBar(Menu::TabItem(1)))[2],
+        ("Esc/Left".to_string(), "Back")
+    );
+    assert_eq!(
+        keymap.hints(FocusOn::MainPanel)[0],
+        ("Ctrl+q".to_string(), "Quit")
+    );
+
+    let (_, warnings) = Keymap::load(
+        "emacs",
+        Some(std::path::Path::new("/nonexistent/keymap.toml")),
+    );
+    assert!(warnings.is_empty());
+}
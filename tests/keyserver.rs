@@ -0,0 +1,213 @@
+mod common;
+
+use gpg4terminal::backend::{
+    key::Algorithm,
+    keyserver::{base_url, parse_index, url_decode, url_encode, Protocol},
+    network::{Proxy, ServerSettings},
+};
+
+use common::{keyserver::MockKeyserver, TestHome};
+
+#[test]
+fn parse_machine_readable_index() {
+    let results = parse_index(
+        "info:1:2\n\
+         pub:5DA09291CD5722A6C2A88BB6160E72203A43A272:1:3072:1782767153::\n\
+         uid:Bob%20Tester%20%3Cbob@example.net%3E:1782767153::\n\
+         pub:0123456789ABCDEF:22:255:1600000000:1700000000:re\n\
+         uid:Old:::r\n\
+         uid:Older:::\n",
+    );
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0].key_id,
+        "5DA09291CD5722A6C2A88BB6160E72203A43A272"
+    );
+    assert_eq!(results[0].algorithm, Some(Algorithm::Rsa));
+    assert_eq!(results[0].length, 3072);
+    assert_eq!(results[0].user_ids, ["Bob Tester <bob@example.net>"]);
+    assert!(!results[0].is_revoked);
+    assert!(results[1].is_revoked && results[1].is_expired);
+    assert!(results[1].expires.is_some());
+    assert_eq!(results[1].user_ids.len(), 2);
+}
+
+#[test]
+fn keyserver_urls() {
+    assert_eq!(
+        base_url("hkps://keys.openpgp.org/").unwrap(),
+        "https://keys.openpgp.org"
+    );
+    assert_eq!(
+        base_url("hkp://pgp.mit.edu").unwrap(),
+        "http://pgp.mit.edu:11371"
+    );
+    assert_eq!(
+        base_url("hkp://127.0.0.1:8080").unwrap(),
+        "http://127.0.0.1:8080"
+    );
+    assert_eq!(
+        base_url("keyserver.ubuntu.com").unwrap(),
+        "https://keyserver.ubuntu.com"
+    );
+    assert!(base_url("ldap://keys.example.com").is_err());
+    assert_eq!(url_encode("a b<c@d.e>"), "a%20b%3Cc%40d.e%3E");
+    assert_eq!(url_decode("a%20b%3Cc%40d.e%3E"), "a b<c@d.e>");
+}
+
+#[test]
+fn hkp_search_and_fetch() {
+    let publisher = TestHome::new();
+    let fingerprint = publisher.generate("Carol Server <carol@example.org>");
+    let server = MockKeyserver::start(vec![publisher.cert(&fingerprint)]);
+    let home = TestHome::new();
+    let keyserver = home.gpg.keyserver(&server.url).unwrap();
+    let results = home
+        .gpg
+        .search_keyserver(&keyserver, Protocol::Hkp, "carol")
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].key_id, fingerprint);
+    assert_eq!(results[0].user_ids, ["Carol Server <carol@example.org>"]);
+    assert!(results[0].data.is_none());
+
+    let imported = home
+        .gpg
+        .fetch_from_keyserver(&keyserver, &results[0])
+        .unwrap();
+    assert_eq!(imported.counts.imported, 1);
+    assert_eq!(imported.first_changed(), Some(fingerprint.as_str()));
+    assert!(server
+        .requests()
+        .iter()
+        .any(|path| path.contains("op=get") && path.contains(&fingerprint)));
+
+    // Nothing found is an empty list, not an error
+    let results = home
+        .gpg
+        .search_keyserver(&keyserver, Protocol::Hkp, "nobody")
+        .unwrap();
+    assert!(results.is_empty());
+}
+
+#[test]
+fn vks_lookup_by_email_and_fingerprint() {
+    let publisher = TestHome::new();
+    let fingerprint = publisher.generate("Dave Vks <dave@example.org>");
+    let server = MockKeyserver::start(vec![publisher.cert(&fingerprint)]);
+    let home = TestHome::new();
+    let keyserver = home.gpg.keyserver(&server.url).unwrap();
+    let results = home
+        .gpg
+        .search_keyserver(&keyserver, Protocol::Vks, "dave@example.org")
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].key_id, fingerprint);
+    assert_eq!(results[0].algorithm, Some(Algorithm::EdDsa));
+    // VKS returns the certificate right away, fetching needs no request
+    assert!(results[0].data.is_some());
+    let requests = server.requests().len();
+    let imported = home
+        .gpg
+        .fetch_from_keyserver(&keyserver, &results[0])
+        .unwrap();
+    assert_eq!(imported.counts.imported, 1);
+    assert_eq!(server.requests().len(), requests);
+
+    let results = home
+        .gpg
+        .search_keyserver(&keyserver, Protocol::Vks, &fingerprint.to_lowercase())
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(server
+        .requests()
+        .contains(&format!("/vks/v1/by-fingerprint/{fingerprint}")));
+
+    let results = home
+        .gpg
+        .search_keyserver(&keyserver, Protocol::Vks, "nobody@example.org")
+        .unwrap();
+    assert!(results.is_empty());
+}
+
+#[test]
+fn tor_refuses_direct_connections() {
+    let publisher = TestHome::new();
+    let fingerprint = publisher.generate("Erin Tor <erin@example.org>");
+    let server = MockKeyserver::start(vec![publisher.cert(&fingerprint)]);
+
+    let home = TestHome::new();
+    let settings = ServerSettings {
+        proxy: Proxy::Tor,
+        ..home.gpg.server_settings().unwrap()
+    };
+    home.gpg.save_server_settings(&settings).unwrap();
+    assert!(home.gpg.keyserver(&server.url).is_err());
+    assert!(home.gpg.check_update(&server.url, None).is_err());
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn fetch_imports_only_the_requested_key() {
+    let publisher = TestHome::new();
+    let carol = publisher.generate("Carol Server <carol@example.org>");
+    let mallory = publisher.generate("Mallory <mallory@example.org>");
+    let mut cert = publisher.cert(&carol);
+    // A server sending an unrelated key along
+    cert.armor += &publisher.cert(&mallory).armor;
+    let server = MockKeyserver::start(vec![cert]);
+    let fingerprints = |home: &TestHome| -> Vec<String> {
+        let keys = home.gpg.list_keys().unwrap();
+        keys.iter()
+            .map(|key| key.fingerprint().to_string())
+            .collect()
+    };
+
+    for protocol in Protocol::ALL {
+        let home = TestHome::new();
+        let keyserver = home.gpg.keyserver(&server.url).unwrap();
+        let query = match protocol {
+            Protocol::Hkp => "carol",
+            Protocol::Vks => "carol@example.org",
+        };
+        let results = home
+            .gpg
+            .search_keyserver(&keyserver, protocol, query)
+            .unwrap();
+        let result = results
+            .iter()
+            .find(|result| result.key_id == carol)
+            .unwrap();
+        let imported = home.gpg.fetch_from_keyserver(&keyserver, result).unwrap();
+        assert_eq!(imported.counts.imported, 1);
+        assert_eq!(fingerprints(&home), [carol.as_str()]);
+    }
+}
+
+#[test]
+fn fetch_refuses_secret_and_wrong_keys() {
+    let publisher = TestHome::new();
+    let dave = publisher.generate("Dave Secret <dave@example.org>");
+    let eve = publisher.generate("Eve Wrong <eve@example.org>");
+    let mallory = publisher.generate("Mallory <mallory@example.org>");
+    let mut secret = publisher.cert(&dave);
+    secret.armor = publisher.gpg(&["--armor", "--export-secret-keys", &dave]);
+    let mut wrong = publisher.cert(&eve);
+    wrong.armor = publisher.cert(&mallory).armor;
+    let server = MockKeyserver::start(vec![secret, wrong]);
+
+    let home = TestHome::new();
+    let keyserver = home.gpg.keyserver(&server.url).unwrap();
+    for query in ["dave", "eve"] {
+        let results = home
+            .gpg
+            .search_keyserver(&keyserver, Protocol::Hkp, query)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(home
+            .gpg
+            .fetch_from_keyserver(&keyserver, &results[0])
+            .is_err());
+    }
+    assert!(home.gpg.list_keys().unwrap().is_empty());
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use gpg4terminal::{
+    config::Config,
+    event_handler::key_board_handler::EventResult,
+    ui::{
+        focus::{FocusOn, Menu},
+        key_detail::init_key_detail_state,
+        key_list::init_key_list_state,
+        menu_bar::{
+            action_at, handle_event, init_menu_bar_state, menu_tree, render_menu_bar,
+            update_enabled, Action, MenuContext, MenuNode,
+        },
+        UiState,
+    },
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+};
+
+#[test]
+fn menu_tree_is_well_formed() {
+    fn check(nodes: &[MenuNode], actions: &mut HashSet<Action>) {
+        let mut labels = HashSet::new();
+        for node in nodes {
+            assert!(labels.insert(node.label), "duplicate item {}", node.label);
+            // Either a leaf with an action or a sub menu
+            assert_eq!(
+                node.action.is_some(),
+                node.children.is_empty(),
+                "{}",
+                node.label
+            );
+            if let Some(action) = node.action {
+                assert!(actions.insert(action), "{action:?} is in the menu twice");
+            }
+            check(&node.children, actions);
+        }
+    }
+    let menu = menu_tree();
+    let mut actions = HashSet::new();
+    for tab in menu.iter() {
+        assert!(tab.action.is_none(), "tab {} has no items", tab.label);
+    }
+    check(&menu, &mut actions);
+
+    assert_eq!(action_at(&menu, &[0, 0]), Some(Action::NewKeyPair));
+    assert_eq!(action_at(&menu, &[0, 2, 1]), Some(Action::ExportSecret));
+    assert_eq!(action_at(&menu, &[3, 1]), Some(Action::About));
+    assert_eq!(action_at(&menu, &[0, 2]), None);
+    assert_eq!(action_at(&menu, &[9]), None);
+}
+
+#[test]
+fn menu_hotkeys_do_not_conflict() {
+    // Hotkeys are matched ignoring case among the tabs, or among the items of one menu
+    fn check(nodes: &[MenuNode], menu: &str) {
+        let mut hotkeys = HashSet::new();
+        for node in nodes {
+            if let Some(hotkey) = node.hotkey {
+                assert!(
+                    hotkey.chars().count() == 1 && hotkey.chars().all(char::is_alphanumeric),
+                    "hotkey {hotkey:?} of {} is not a single letter",
+                    node.label
+                );
+                assert!(
+                    hotkeys.insert(hotkey.to_ascii_lowercase()),
+                    "hotkey {hotkey} of {} is taken in {menu}",
+                    node.label
+                );
+            }
+            check(&node.children, node.label);
+        }
+    }
+    check(&menu_tree(), "the menu bar");
+}
+
+#[test]
+fn menu_state_follows_the_tree() {
+    let menu = menu_tree();
+    let state = init_menu_bar_state(&menu);
+    assert_eq!(state.borrow().tab_state.len(), menu.len());
+    let help = menu.iter().position(|tab| tab.label == "Help").unwrap();
+    let check_update = || {
+        state.borrow().tab_state[help]
+            .borrow()
+            .sub_item_state
+            .as_ref()
+            .unwrap()[0]
+            .borrow()
+            .is_disabled
+    };
+
+    let enabled = Config::default();
+    let disabled = Config {
+        update_check: false,
+        ..Config::default()
+    };
+    for (config, is_disabled) in [(&enabled, false), (&disabled, true)] {
+        let context = MenuContext {
+            config,
+            selected_key: None,
+        };
+        update_enabled(&state, &menu, &context);
+        assert_eq!(check_update(), is_disabled);
+    }
+}
+
+#[test]
+fn click_menu_tabs_and_items() {
+    let menu = menu_tree();
+    let mut ui_state = UiState {
+        focus_on: FocusOn::MainPanel,
+        menu_bar_state: init_menu_bar_state(&menu),
+        key_list_state: init_key_list_state(),
+        key_detail_state: init_key_detail_state(),
+        dialog_buttons: Default::default(),
+        date_format: Config::default().date_format,
+        expiry_warning_days: Config::default().expiry_warning_days,
+    };
+    let config = Config::default();
+    let context = MenuContext {
+        config: &config,
+        selected_key: None,
+    };
+    update_enabled(&ui_state.menu_bar_state, &menu, &context);
+    let area = Rect::new(0, 0, 80, 12);
+    let mut buf = Buffer::empty(area);
+    // Find a label in the rendered menu
+    let find = |buf: &Buffer, label: &str| {
+        (0..area.height)
+            .find_map(|y| {
+                let row: String = (0..area.width)
+                    .map(|x| buf.get(x, y).symbol().to_string())
+                    .collect();
+                row.find(label)
+                    .map(|x| Position::new(row[..x].chars().count() as u16, y))
+            })
+            .unwrap()
+    };
+    let click = |ui_state: &mut UiState, buf: &mut Buffer, label: &str| {
+        render_menu_bar(ui_state, &menu, Rect::new(0, 0, 80, 1), buf);
+        let position = find(buf, label);
+        let action = handle_event(
+            &mut ui_state.focus_on,
+            ui_state.menu_bar_state.clone(),
+            &menu,
+            EventResult::MouseClick(position),
+        );
+        buf.reset();
+        action
+    };
+
+    assert_eq!(click(&mut ui_state, &mut buf, "File"), None);
+    assert!(ui_state.focus_on == FocusOn::MenuBar(Menu::TabItem(0)));
+    assert_eq!(click(&mut ui_state, &mut buf, "Export"), None);
+    assert!(ui_state.focus_on == FocusOn::MenuBar(Menu::TabItem(1)));
+    // Disabled items ignore clicks, a click on the parent menu closes the sub menu
+    assert_eq!(click(&mut ui_state, &mut buf, "Public Key"), None);
+    assert_eq!(click(&mut ui_state, &mut buf, "Quit"), Some(Action::Quit));
+    assert!(ui_state.focus_on == FocusOn::MainPanel);
+
+    assert_eq!(click(&mut ui_state, &mut buf, "Help"), None);
+    assert_eq!(click(&mut ui_state, &mut buf, "About"), Some(Action::About));
+    // A second click on an open tab closes it
+    click(&mut ui_state, &mut buf, "Tool");
+    click(&mut ui_state, &mut buf, "Tool");
+    assert!(ui_state.focus_on == FocusOn::MainPanel);
+}
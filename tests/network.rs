@@ -0,0 +1,63 @@
+mod common;
+
+use gpg4terminal::backend::{
+    network::{Proxy, ServerSettings},
+    options::{change_line, parse_option_values, OptionValue},
+};
+
+use common::TestHome;
+
+#[test]
+fn option_values_round_trip() {
+    assert_eq!(
+        parse_option_values("\"hkps%3a//a.example,\"ldap%3a//b%2cc"),
+        ["hkps://a.example", "ldap://b,c"]
+    );
+    assert_eq!(parse_option_values("1"), ["1"]);
+    assert!(parse_option_values("").is_empty());
+    assert_eq!(
+        change_line(
+            "keyserver",
+            &OptionValue::Strings(vec!["hkp://a:11371".to_string()])
+        ),
+        "keyserver:0:\"hkp%3a//a%3a11371"
+    );
+    assert_eq!(change_line("use-tor", &OptionValue::Enabled), "use-tor:0:1");
+    assert_eq!(change_line("use-tor", &OptionValue::Default), "use-tor:16:");
+}
+
+#[test]
+fn save_server_settings() {
+    let home = TestHome::new();
+    let gpg = &home.gpg;
+    let defaults = gpg.server_settings().unwrap();
+    assert_eq!(defaults.keyservers, ["hkps://keys.openpgp.org"]);
+    assert_eq!(defaults.auto_key_locate, ["local", "wkd"]);
+    assert_eq!(defaults.proxy, Proxy::Direct);
+
+    let mut settings = ServerSettings {
+        keyservers: vec!["hkp://keys.example:8080".to_string()],
+        proxy: Proxy::Http("http://proxy.example:3128".to_string()),
+        ..defaults.clone()
+    };
+    settings.set_locate("dane", true);
+    settings.set_locate("local", false);
+    assert!(!settings.locates_with("local"));
+    assert!(gpg.save_server_settings(&settings).unwrap());
+    assert_eq!(gpg.server_settings().unwrap(), settings);
+    let gpg_conf = std::fs::read_to_string(home.path().join("gpg.conf")).unwrap();
+    assert!(
+        gpg_conf.contains("auto-key-locate wkd,dane,nodefault"),
+        "{gpg_conf}"
+    );
+
+    settings.proxy = Proxy::Tor;
+    settings.set_locate("local", true);
+    gpg.save_server_settings(&settings).unwrap();
+    let saved = gpg.server_settings().unwrap();
+    assert_eq!(saved.proxy, Proxy::Tor);
+    assert_eq!(saved.auto_key_locate, ["local", "wkd", "dane"]);
+
+    settings.keyservers.push("ftp://keys.example".to_string());
+    assert!(gpg.save_server_settings(&settings).is_err());
+}
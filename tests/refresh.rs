@@ -0,0 +1,69 @@
+mod common;
+
+use gpg4terminal::backend::refresh::{CertChange, RefreshSource};
+
+use common::{keyserver::MockKeyserver, TestHome};
+
+#[test]
+fn refresh_from_keyserver_reports_changes() {
+    // The owner publishes a key, a teammate imports it
+    let owner = TestHome::new();
+    let fingerprint = owner.generate("Alice <alice@example.net>");
+    owner.gpg(&["--quick-add-uid", &fingerprint, "Alice <alice@old.example>"]);
+    let server = MockKeyserver::start(vec![owner.cert(&fingerprint)]);
+    let teammate = TestHome::new();
+    teammate
+        .gpg
+        .import(owner.cert(&fingerprint).armor.as_bytes())
+        .unwrap();
+    let key = teammate.gpg.find_key(&fingerprint).unwrap();
+    let source = RefreshSource::Keyserver(server.hkp_url());
+
+    // Nothing changed yet
+    assert_eq!(teammate.gpg.refresh_key(&key, &source).unwrap(), vec![]);
+
+    // The owner adds a subkey, sets an expiry and retires a user ID
+    owner.gpg(&["--quick-add-key", &fingerprint, "cv25519", "encr", "2y"]);
+    owner.gpg(&["--quick-set-expire", &fingerprint, "5y"]);
+    owner.gpg(&[
+        "--quick-revoke-uid",
+        &fingerprint,
+        "Alice <alice@old.example>",
+    ]);
+    server.publish(vec![owner.cert(&fingerprint)]);
+
+    let mut progress = Vec::new();
+    let outcomes = teammate
+        .gpg
+        .refresh_keys(std::slice::from_ref(&key), &source, |i, key| {
+            progress.push((i, key.fingerprint().to_string()))
+        });
+    assert_eq!(progress, [(0, fingerprint.clone())]);
+    assert_eq!(outcomes[0].fingerprint, fingerprint);
+    let changes = outcomes[0].result.clone().unwrap();
+    assert_eq!(changes.len(), 3, "{changes:?}");
+    assert!(
+        matches!(&changes[0], CertChange::Expiry { key_id, old: None, new: Some(_) }
+        if *key_id == key.primary.key_id)
+    );
+    assert!(matches!(&changes[1], CertChange::NewSubkey(_)));
+    assert_eq!(
+        changes[2],
+        CertChange::UserIdRevoked("Alice <alice@old.example>".to_string())
+    );
+    assert!(changes[0].describe().contains("shortened"));
+    assert!(server
+        .requests()
+        .iter()
+        .any(|path| path.contains(&fingerprint)));
+
+    // A key the server does not know fails on its own
+    let unknown = teammate.generate("Bob <bob@example.net>");
+    let unknown = teammate.gpg.find_key(&unknown).unwrap();
+    let key = teammate.gpg.find_key(&fingerprint).unwrap();
+    let outcomes = teammate
+        .gpg
+        .refresh_keys(&[unknown, key], &source, |_, _| ());
+    assert!(outcomes[0].result.is_err());
+    assert_eq!(outcomes[1].result, Ok(vec![]));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use gpg4terminal::backend::{
+    colons,
+    generate::KeyAlgorithm,
+    key::{RevocationReason, Validity},
+    subkey::SubkeyUsage,
+};
+
+use common::TestHome;
+
+#[test]
+fn subkey_algorithms() {
+    assert_eq!(SubkeyUsage::Encrypt.algo(KeyAlgorithm::Ed25519), "cv25519");
+    assert_eq!(SubkeyUsage::Sign.algo(KeyAlgorithm::Ed25519), "ed25519");
+    assert_eq!(
+        SubkeyUsage::Authenticate.algo(KeyAlgorithm::Rsa4096),
+        "rsa4096"
+    );
+
+    let keys = colons::parse_keys(include_str!("fixtures/list-keys.colons"));
+    let alice = &keys[0];
+    assert_eq!(
+        KeyAlgorithm::of(&alice.primary),
+        Some(KeyAlgorithm::Ed25519)
+    );
+    assert_eq!(
+        KeyAlgorithm::of(&alice.subkeys[0]),
+        Some(KeyAlgorithm::Ed25519)
+    );
+    assert_eq!(
+        SubkeyUsage::of(&alice.subkeys[0].capabilities),
+        SubkeyUsage::Encrypt
+    );
+}
+
+#[test]
+fn parse_stubs_and_cards() {
+    let listing = "\
+sec:u:255:22:43079B3772E1A1AB:1792204336:::u:::scSC:::#:::ed25519:::0:
+fpr:::::::::4C2A739A8CA6AAF49B42D07943079B3772E1A1AB:
+ssb:u:255:18:C36B63554F14CB5D:1792204336::::::e:::D2760001240100000006123456780000:::cv25519::
+fpr:::::::::89850D87F7CD1EC7D4C2B062C36B63554F14CB5D:
+ssb:u:3072:1:DA79BC1A77A3BBB9:1792204336::::::a:::+::::23:
+fpr:::::::::F124FAA678F394AD478B0B13DA79BC1A77A3BBB9:
+";
+    let key = &colons::parse_keys(listing)[0];
+    assert!(key.primary.is_stub);
+    assert!(!key.primary.has_secret);
+    let card = &key.subkeys[0];
+    assert!(card.has_secret);
+    assert_eq!(
+        card.card_serial.as_deref(),
+        Some("D2760001240100000006123456780000")
+    );
+    let local = &key.subkeys[1];
+    assert!(local.has_secret && !local.is_stub && local.card_serial.is_none());
+}
+
+#[test]
+fn rotate_encryption_subkey() {
+    let home = TestHome::new();
+    let fingerprint = home.generate("Alice <alice@example.net>");
+    let usage = SubkeyUsage::Encrypt;
+    for _ in 0..2 {
+        home.gpg
+            .add_subkey(
+                &fingerprint,
+                usage.algo(KeyAlgorithm::Ed25519),
+                usage.usage(),
+                "1y",
+                Some(""),
+                |_| (),
+            )
+            .unwrap();
+    }
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+    assert_eq!(key.subkeys.len(), 2);
+    assert!(key.subkeys.iter().all(|subkey| subkey.capabilities.encrypt));
+
+    let old = key.subkeys[0].fingerprint.clone();
+    home.gpg
+        .revoke_subkey(
+            &fingerprint,
+            &old,
+            RevocationReason::Superseded,
+            "Yearly rotation",
+            Some(""),
+        )
+        .unwrap();
+
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+    let old = key
+        .subkeys
+        .iter()
+        .find(|subkey| subkey.fingerprint == old)
+        .unwrap();
+    assert_eq!(old.validity, Validity::Revoked);
+    let revocation = old.signatures.iter().find(|sig| sig.is_revocation).unwrap();
+    assert_eq!(revocation.reason(), Some(RevocationReason::Superseded));
+    assert_eq!(
+        revocation.revocation_comment.as_deref(),
+        Some("Yearly rotation")
+    );
+    assert!(key
+        .subkeys
+        .iter()
+        .any(|subkey| subkey.capabilities.encrypt && subkey.validity.is_usable()));
+}
+
+#[test]
+fn offline_primary_key_is_a_stub() {
+    let home = TestHome::new();
+    let fingerprint = home.generate("Alice <alice@example.net>");
+    home.gpg(&["--quick-add-key", &fingerprint, "cv25519", "encr", "1y"]);
+    let subkeys = home.path().join("subkeys.gpg");
+    home.gpg(&[
+        "--output",
+        subkeys.to_str().unwrap(),
+        "--export-secret-subkeys",
+        &fingerprint,
+    ]);
+
+    let laptop = TestHome::new();
+    laptop.gpg(&["--import", subkeys.to_str().unwrap()]);
+    let key = laptop.gpg.key_details(&fingerprint).unwrap();
+    assert!(key.primary.is_stub);
+    assert!(!key.primary.has_secret);
+    assert!(key.subkeys[0].has_secret && !key.subkeys[0].is_stub);
+    assert!(key.has_secret());
+
+    // Nothing can be certified without the primary key
+    assert!(laptop
+        .gpg
+        .add_subkey(&fingerprint, "ed25519", "sign", "1y", Some(""), |_| ())
+        .is_err());
+}
@@ -0,0 +1,94 @@
+use std::{env, fs};
+
+use gpg4terminal::{
+    config::Config,
+    theme::{is_theme_name, set_theme, theme, Palette, Theme, LIGHT, THEME_PRESETS},
+};
+use ratatui::style::{Color, Modifier};
+
+#[test]
+fn presets_and_theme_files() {
+    let presets: Vec<Theme> = THEME_PRESETS
+        .iter()
+        .map(|name| {
+            let (theme, warnings) = Theme::load(name, None);
+            assert!(warnings.is_empty(), "{name}: {warnings:?}");
+            theme
+        })
+        .collect();
+    for (i, theme) in presets.iter().enumerate() {
+        assert!(!presets[..i].contains(theme), "{}", THEME_PRESETS[i]);
+    }
+    assert_eq!(Theme::preset(&Config::default().theme), Some(presets[0]));
+    // The name of the only theme before there were presets
+    assert_eq!(Theme::preset("default"), Some(presets[0]));
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("solarized.toml"),
+        "base = \"light\"\ntext = \"#586e75\"\nhighlight = \"33\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("broken.toml"),
+        "text = \"plaid\"\nfont = \"mono\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("light.toml"), "").unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+    assert_eq!(
+        Theme::names(Some(dir.path()))[THEME_PRESETS.len()..],
+        ["broken", "solarized"]
+    );
+    assert_eq!(Theme::names(None), THEME_PRESETS);
+
+    let (solarized, warnings) = Theme::load("solarized", Some(dir.path()));
+    assert!(warnings.is_empty(), "{warnings:?}");
+    let palette = Palette {
+        text: Color::Rgb(0x58, 0x6e, 0x75),
+        highlight: Color::Indexed(33),
+        ..LIGHT
+    };
+    assert_eq!(solarized, Theme::from_palette(&palette));
+
+    // What is wrong is skipped with a warning
+    let (broken, warnings) = Theme::load("broken", Some(dir.path()));
+    assert_eq!(broken, presets[0]);
+    assert_eq!(warnings.len(), 2, "{warnings:?}");
+    assert!(warnings.iter().any(|warning| warning.contains("`text`")));
+    assert!(warnings.iter().any(|warning| warning.contains("`font`")));
+    assert_eq!(Theme::parse("base = \"sepia\"").1.len(), 1);
+    assert_eq!(Theme::parse("text = ").1.len(), 1);
+    for name in ["missing", "../solarized"] {
+        let (theme, warnings) = Theme::load(name, Some(dir.path()));
+        assert_eq!(theme, presets[0]);
+        assert_eq!(warnings.len(), 1, "{name}");
+    }
+    assert!(!is_theme_name("../solarized"));
+    assert!(!is_theme_name(""));
+}
+
+#[test]
+fn switch_theme_and_no_color() {
+    let light = Theme::preset("light").unwrap();
+    set_theme(light);
+    assert_eq!(*theme(), light);
+
+    // Selection stays visible without colors
+    let plain = Theme::no_color();
+    assert_eq!(plain.dialog.default_style.fg, None);
+    assert_eq!(plain.key_list.selected_style.bg, None);
+    assert!(plain
+        .key_list
+        .selected_style
+        .add_modifier
+        .contains(Modifier::REVERSED));
+
+    env::set_var("NO_COLOR", "1");
+    set_theme(light);
+    assert_eq!(*theme(), plain);
+    env::set_var("NO_COLOR", "");
+    set_theme(light);
+    assert_eq!(*theme(), light);
+    env::remove_var("NO_COLOR");
+}
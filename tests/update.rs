@@ -0,0 +1,154 @@
+mod common;
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+use gpg4terminal::backend::update::{newer_releases, parse_feed, parse_version_tag};
+use semver::Version;
+
+use common::TestHome;
+
+const JSON_FEED: &str = r#"[
+  {"tag_name": "v0.3.0-rc.1", "name": "v0.3.0-rc.1", "body": "Preview", "draft": false,
+   "html_url": "https://example.org/v0.3.0-rc.1", "published_at": "2026-09-01T10:00:00Z"},
+  {"tag_name": "v0.2.0", "name": "Keymaps", "body": "- vim keys\n- themes",
+   "html_url": "https://example.org/v0.2.0", "published_at": "2026-06-01T10:00:00Z"},
+  {"tag_name": "v0.4.0", "name": "Unreleased", "draft": true},
+  {"tag_name": "nightly", "name": "Nightly"},
+  {"tag_name": "v0.1.0", "name": "First release", "body": "Hello"}
+]"#;
+
+const ATOM_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>tag:github.com,2008:https://github.com/Oct-autumn/gpg4terminal/releases</id>
+  <title>Release notes from gpg4terminal</title>
+  <entry>
+    <id>tag:github.com,2008:Repository/1/v0.2.0</id>
+    <updated>2026-06-01T10:00:00Z</updated>
+    <link rel="alternate" type="text/html" href="https://github.com/Oct-autumn/gpg4terminal/releases/tag/v0.2.0"/>
+    <title>Keymaps &amp; themes</title>
+    <content type="html">&lt;ul&gt;&lt;li&gt;vim keys&lt;/li&gt;&lt;li&gt;themes &amp;amp; colors&lt;/li&gt;&lt;/ul&gt;</content>
+  </entry>
+  <entry>
+    <id>tag:github.com,2008:Repository/1/v0.1.0</id>
+    <updated>2026-01-01T10:00:00Z</updated>
+    <title>v0.1.0</title>
+    <content type="html"><![CDATA[<p>First release</p>]]></content>
+  </entry>
+</feed>"#;
+
+#[test]
+fn parse_json_feed_and_compare() {
+    let releases = parse_feed(JSON_FEED).unwrap();
+    // The draft and the tag without a version are skipped
+    assert_eq!(releases.len(), 3);
+    assert_eq!(releases[1].title, "Keymaps");
+    assert_eq!(releases[1].notes, "- vim keys\n- themes");
+    assert_eq!(
+        releases[1].link.as_deref(),
+        Some("https://example.org/v0.2.0")
+    );
+    assert!(releases[1].published.is_some());
+
+    let newer = newer_releases(releases, &Version::new(0, 1, 0));
+    let versions: Vec<String> = newer.iter().map(|r| r.version.to_string()).collect();
+    assert_eq!(versions, ["0.3.0-rc.1", "0.2.0"]);
+
+    let object = r#"{"releases": [{"version": "1.0.0", "notes": "Stable", "date": "2026-10-01T00:00:00+02:00"}]}"#;
+    let releases = parse_feed(object).unwrap();
+    assert_eq!(releases[0].version, Version::new(1, 0, 0));
+    assert_eq!(releases[0].notes, "Stable");
+    assert!(parse_feed("not a feed").is_err());
+    assert_eq!(
+        parse_version_tag("gpg4terminal v1.2.3"),
+        Some(Version::new(1, 2, 3))
+    );
+}
+
+#[test]
+fn parse_atom_feed() {
+    let releases = parse_feed(ATOM_FEED).unwrap();
+    assert_eq!(releases.len(), 2);
+    // The version comes from the id if the title has none
+    assert_eq!(releases[0].version, Version::new(0, 2, 0));
+    assert_eq!(releases[0].title, "Keymaps & themes");
+    assert_eq!(releases[0].notes, "- vim keys\n- themes & colors");
+    assert_eq!(
+        releases[0].link.as_deref(),
+        Some("https://github.com/Oct-autumn/gpg4terminal/releases/tag/v0.2.0")
+    );
+    assert_eq!(releases[1].version, Version::new(0, 1, 0));
+    assert_eq!(releases[1].notes, "First release");
+}
+
+#[test]
+fn signed_file_feed() {
+    let home = TestHome::new();
+    let release_key = home.generate("Release <release@example.org>");
+    let other_key = home.generate("Other <other@example.org>");
+    let feed = home.path().join("feed.json");
+    fs::write(&feed, JSON_FEED).unwrap();
+    let feed_path = feed.to_string_lossy().into_owned();
+    let url = format!("file://{feed_path}");
+
+    // Without a release key the signature is not looked at
+    let check = home.gpg.check_update(&url, None).unwrap();
+    assert_eq!(check.signed_by, None);
+    assert_eq!(check.newer[0].version.to_string(), "0.3.0-rc.1");
+    // A release key requires a signature
+    assert!(home.gpg.check_update(&url, Some(&release_key)).is_err());
+
+    home.gpg(&[
+        "--local-user",
+        &release_key,
+        "--output",
+        &format!("{feed_path}.sig"),
+        "--detach-sign",
+        &feed_path,
+    ]);
+    let check = home.gpg.check_update(&url, Some(&release_key)).unwrap();
+    assert_eq!(check.signed_by.as_deref(), Some(release_key.as_str()));
+    let err = home
+        .gpg
+        .check_update(&url, Some(&other_key))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not by the release key"), "{err}");
+
+    // A modified feed is rejected
+    fs::write(&feed, JSON_FEED.replace("v0.2.0", "v9.9.9")).unwrap();
+    let err = home
+        .gpg
+        .check_update(&url, Some(&release_key))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("BAD"), "{err}");
+}
+
+#[test]
+fn loopback_http_feed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/releases.atom", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let stream = listener.incoming().next().unwrap().unwrap();
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|len| len > 2) {
+            line.clear();
+        }
+        write!(
+            &stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/atom+xml\r\nContent-Length: {}\r\n\r\n{ATOM_FEED}",
+            ATOM_FEED.len()
+        )
+        .unwrap();
+    });
+    let home = TestHome::new();
+    let check = home.gpg.check_update(&url, None).unwrap();
+    assert_eq!(check.newer.len(), 1);
+    assert_eq!(check.newer[0].version, Version::new(0, 2, 0));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use gpg4terminal::backend::{
+    edit::revocation_answers,
+    key::{Key, RevocationReason, Validity},
+};
+
+use common::TestHome;
+
+#[test]
+fn revocation_reasons() {
+    for reason in [
+        RevocationReason::Unspecified,
+        RevocationReason::Superseded,
+        RevocationReason::Compromised,
+        RevocationReason::Retired,
+        RevocationReason::UserIdInvalid,
+    ] {
+        assert_eq!(RevocationReason::from_code(reason.code()), reason);
+    }
+    assert_eq!(RevocationReason::UserIdInvalid.code(), 0x20);
+
+    // gpg numbers the user ID reason 4, the description ends with an empty line
+    let answers = revocation_answers(RevocationReason::UserIdInvalid, "Left the company");
+    assert_eq!(answers[0], ("ask_revocation_reason.code", "4".to_string()));
+    assert_eq!(
+        answers[1..3],
+        [
+            ("ask_revocation_reason.text", "Left the company".to_string()),
+            ("ask_revocation_reason.text", String::new()),
+        ]
+    );
+    let answers = revocation_answers(RevocationReason::Compromised, "");
+    assert_eq!(answers[0].1, "1");
+    assert_eq!(answers.len(), 3);
+}
+
+#[test]
+fn add_revoke_and_promote_user_ids() {
+    let home = TestHome::new();
+    let fingerprint = home.generate("Alice <alice@example.net>");
+
+    for uid in [
+        "Alice Work <alice@work.example>",
+        "Alice Old <alice@old.example>",
+        "Alice Moved <alice@moved.example>",
+    ] {
+        home.gpg.add_user_id(&fingerprint, uid, Some("")).unwrap();
+    }
+    // The same user ID is not added twice
+    assert!(home
+        .gpg
+        .add_user_id(&fingerprint, "Alice Work <alice@work.example>", Some(""))
+        .is_err());
+
+    home.gpg
+        .set_primary_user_id(&fingerprint, "Alice Work <alice@work.example>", Some(""))
+        .unwrap();
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+    assert_eq!(key.user_ids.len(), 4);
+    assert_eq!(
+        key.primary_uid().unwrap().uid,
+        "Alice Work <alice@work.example>"
+    );
+
+    let uid = |key: &Key, text: &str| {
+        key.user_ids
+            .iter()
+            .find(|uid| uid.uid == text)
+            .unwrap()
+            .clone()
+    };
+    let old = uid(&key, "Alice Old <alice@old.example>");
+    home.gpg
+        .revoke_user_id(
+            &fingerprint,
+            &old,
+            RevocationReason::UserIdInvalid,
+            "",
+            Some(""),
+        )
+        .unwrap();
+    let moved = uid(&key, "Alice Moved <alice@moved.example>");
+    home.gpg
+        .revoke_user_id(
+            &fingerprint,
+            &moved,
+            RevocationReason::Unspecified,
+            "Moved away",
+            Some(""),
+        )
+        .unwrap();
+
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+    let old = uid(&key, "Alice Old <alice@old.example>");
+    assert_eq!(old.validity, Validity::Revoked);
+    let revocation = old.revocation(&key.primary.key_id).unwrap();
+    assert_eq!(revocation.reason(), Some(RevocationReason::UserIdInvalid));
+    assert_eq!(revocation.revocation_comment, None);
+
+    let moved = uid(&key, "Alice Moved <alice@moved.example>");
+    let revocation = moved.revocation(&key.primary.key_id).unwrap();
+    assert_eq!(revocation.reason(), Some(RevocationReason::Unspecified));
+    assert_eq!(revocation.revocation_comment.as_deref(), Some("Moved away"));
+
+    let work = uid(&key, "Alice Work <alice@work.example>");
+    assert!(work.revocation(&key.primary.key_id).is_none());
+    assert!(work.self_signature(&key.primary.key_id).is_some());
+    assert_eq!(key.primary_uid().unwrap().uid, work.uid);
+}
+
+#[test]
+fn edit_key_stops_at_unexpected_prompts() {
+    let home = TestHome::new();
+    let fingerprint = home.generate("Alice <alice@example.net>");
+    let key = home.gpg.key_details(&fingerprint).unwrap();
+
+    // The script leaves out the reason, so gpg asks for it where `save` is expected
+    let script = [
+        ("keyedit.prompt", "uid 1".to_string()),
+        ("keyedit.prompt", "revuid".to_string()),
+        ("keyedit.revoke.uid.okay", "y".to_string()),
+        ("keyedit.prompt", "save".to_string()),
+    ];
+    let err = home
+        .gpg
+        .edit_key(&fingerprint, &script, Some(""))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("ask_revocation_reason.code"),
+        "{err}"
+    );
+    assert_eq!(home.gpg.key_details(&fingerprint).unwrap(), key);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use gpg4terminal::{backend::version::parse_version, clipboard::base64};
+
+use common::TestHome;
+
+#[test]
+fn parse_version_output() {
+    let output = "gpg (GnuPG) 2.2.40\n\
+                  libgcrypt 1.10.1\n\
+                  Copyright (C) 2022 g10 Code GmbH\n\
+                  \n\
+                  Home: /home/alice/.gnupg\n\
+                  Supported algorithms:\n\
+                  Pubkey: RSA, ELG, DSA, ECDH, ECDSA, EDDSA\n\
+                  Cipher: IDEA, 3DES, CAST5, BLOWFISH, AES, AES192, AES256, TWOFISH,\n        \
+                  CAMELLIA128, CAMELLIA192, CAMELLIA256\n\
+                  Hash: SHA1, RIPEMD160, SHA256, SHA384, SHA512, SHA224\n\
+                  Compression: Uncompressed, ZIP, ZLIB, BZIP2\n";
+    let version = parse_version(output);
+    assert_eq!(version.version, "2.2.40");
+    assert_eq!(version.libraries, ["libgcrypt 1.10.1"]);
+    assert_eq!(version.home, "/home/alice/.gnupg");
+    assert_eq!(version.algorithms.len(), 4);
+    assert_eq!(version.algorithms[0].0, "Pubkey");
+    assert_eq!(
+        version.algorithms[1].1,
+        "IDEA, 3DES, CAST5, BLOWFISH, AES, AES192, AES256, TWOFISH, \
+         CAMELLIA128, CAMELLIA192, CAMELLIA256"
+    );
+    assert_eq!(version.algorithms[3].1, "Uncompressed, ZIP, ZLIB, BZIP2");
+}
+
+#[test]
+fn version_reports_home() {
+    let home = TestHome::new();
+    let version = home.gpg.version().unwrap();
+    assert!(version.version.starts_with('2'));
+    assert_eq!(version.home, home.path().to_string_lossy());
+    assert!(version.algorithms.iter().any(|(kind, _)| kind == "Hash"));
+}
+
+#[test]
+fn base64_encoding() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foo"), "Zm9v");
+    assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+}